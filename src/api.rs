@@ -0,0 +1,506 @@
+// Library-facing API: SQLcord's storage engine without slash commands or embeds
+//
+// Every function takes the database explicitly instead of reading a user's `/sql use` selection,
+// and returns plain Rust values or a `SqlError`. The `/sql` commands are wrappers around these.
+
+use serenity::builder::{CreateChannel, CreateMessage};
+use serenity::model::channel::{ChannelType, GuildChannel};
+use serenity::model::id::{ChannelId, GuildId};
+use crate::commands::sql::insert::{assign_auto_increment_values, check_foreign_keys, check_row_size, check_unique_constraints, format_sql_values_for_storage};
+use crate::commands::sql::select::{check_where_columns, evaluate_where_condition, indexed_rows_from_messages, parse_schema_from_topic, rows_from_messages};
+use crate::error::SqlError;
+use crate::guards::require_table;
+use crate::services::discord_fs::max_row_messages;
+use crate::services::throttle::{with_retry, Throttle};
+use crate::services::shards::{fetch_table_messages, shard_channel_name, shard_for_row, MAX_SHARDS};
+use crate::services::topic_meta::{update_table_topic, TopicMeta};
+use crate::services::{find_database_category, find_table_channel, get_channels_cached, invalidate_channels, Backend, BotContext};
+use crate::sql_parser::{coerce_values_to_schema, format_sql_literal, parse_column_definitions, quote_identifier, validate_values_against_schema, ColumnDefinition, SqlValue};
+use crate::utils::{validate_identifier, DB_PREFIX, TABLE_PREFIX};
+
+/// Create database `name` (a `db_<name>` category) and return its sanitized name.
+///
+/// ```no_run
+/// # async fn example(ctx: &serenity::prelude::Context, guild_id: serenity::model::id::GuildId) -> Result<(), sqlcord::error::SqlError> {
+/// let name = sqlcord::api::create_database(ctx, guild_id, "Shop").await?;
+/// assert_eq!(name, "shop");
+/// # Ok(())
+/// # }
+/// ```
+pub async fn create_database(ctx: &impl BotContext, guild_id: GuildId, name: &str) -> Result<String, SqlError> {
+    let (sanitized_name, _) = validate_identifier(name, DB_PREFIX)
+        .map_err(|e| SqlError::InvalidName { kind: "Database", reason: e.describe("Database") })?;
+
+    let channels = match get_channels_cached(ctx, guild_id).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            tracing::error!("Failed to get channels: {e}");
+            return Err(SqlError::DiscordRequest { operation: "list channels" });
+        }
+    };
+    if find_database_category(&channels, &sanitized_name).is_some() {
+        return Err(SqlError::DatabaseExists { requested: collided_name(name, &sanitized_name), database: sanitized_name });
+    }
+
+    let builder = CreateChannel::new(format!("{}{}", DB_PREFIX, sanitized_name)).kind(ChannelType::Category);
+    if let Err(e) = ctx.backend().create_channel(guild_id, builder).await {
+        tracing::error!("Failed to create category: {e}");
+        return Err(SqlError::DiscordRequest { operation: "create database" });
+    }
+    invalidate_channels(ctx, guild_id).await;
+    Ok(sanitized_name)
+}
+
+/// Create table `name` in `database`, storing `schema` (e.g. `"id INT PRIMARY KEY, name VARCHAR(50)"`)
+/// and an optional one-line `comment` describing the table in the channel topic.
+/// Returns the sanitized table name and the parsed columns.
+///
+/// ```no_run
+/// # async fn example(ctx: &serenity::prelude::Context, guild_id: serenity::model::id::GuildId) -> Result<(), sqlcord::error::SqlError> {
+/// let (table, columns) = sqlcord::api::create_table(ctx, guild_id, "shop", "users", Some("id INT PRIMARY KEY, name VARCHAR(50)"), Some("Customer records")).await?;
+/// assert_eq!((table.as_str(), columns.len()), ("users", 2));
+/// # Ok(())
+/// # }
+/// ```
+pub async fn create_table(
+    ctx: &impl BotContext,
+    guild_id: GuildId,
+    database: &str,
+    name: &str,
+    schema: Option<&str>,
+    comment: Option<&str>,
+) -> Result<(String, Vec<ColumnDefinition>), SqlError> {
+    create_sharded_table(ctx, guild_id, database, name, schema, comment, 1).await
+}
+
+/// Like `create_table`, but with the rows spread over `shards` channels (at most `MAX_SHARDS`): the table's
+/// own `table_<name>` channel plus `table_<name>_1` and up. Each row is stored in the shard its primary key
+/// hashes to, and reads merge every shard, so large tables stay quick to page. One shard is a plain table.
+///
+/// ```no_run
+/// # async fn example(ctx: &serenity::prelude::Context, guild_id: serenity::model::id::GuildId) -> Result<(), sqlcord::error::SqlError> {
+/// sqlcord::api::create_sharded_table(ctx, guild_id, "shop", "orders", Some("id INT PRIMARY KEY, total DECIMAL(10,2)"), None, 4).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn create_sharded_table(
+    ctx: &impl BotContext,
+    guild_id: GuildId,
+    database: &str,
+    name: &str,
+    schema: Option<&str>,
+    comment: Option<&str>,
+    shards: usize,
+) -> Result<(String, Vec<ColumnDefinition>), SqlError> {
+    if !(1..=MAX_SHARDS).contains(&shards) {
+        return Err(SqlError::InvalidShardCount(shards));
+    }
+    let columns = match schema {
+        Some(schema) => parse_column_definitions(schema).map_err(SqlError::InvalidSchema)?,
+        None => Vec::new(),
+    };
+    // A new table starts with a cached row count of zero, which inserts then keep up to date
+    let meta = TopicMeta {
+        schema: (!columns.is_empty()).then(|| schema_topic(&columns)),
+        comment: table_comment(comment)?,
+        rows: Some(0),
+        modified: Some(chrono::Utc::now()),
+        shards: (shards > 1).then_some(shards),
+        ..TopicMeta::default()
+    };
+    let (sanitized_name, _) = create_table_channel(ctx, guild_id, database, name, &meta, &columns).await?;
+    Ok((sanitized_name, columns))
+}
+
+/// Create channel `table_<name>` in `database` with topic `meta`, checking it stored `columns`, plus the
+/// shard channels `meta.shards` asks for. Returns the sanitized table name and the table's own channel.
+async fn create_table_channel(
+    ctx: &impl BotContext,
+    guild_id: GuildId,
+    database: &str,
+    name: &str,
+    meta: &TopicMeta,
+    columns: &[ColumnDefinition],
+) -> Result<(String, GuildChannel), SqlError> {
+    let (sanitized_name, _) = validate_identifier(name, TABLE_PREFIX)
+        .map_err(|e| SqlError::InvalidName { kind: "Table", reason: e.describe("Table") })?;
+
+    let channels = match get_channels_cached(ctx, guild_id).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            tracing::error!("Failed to get channels: {e}");
+            return Err(SqlError::DiscordRequest { operation: "list channels" });
+        }
+    };
+    let category = find_database_category(&channels, database)
+        .ok_or_else(|| SqlError::DatabaseNotFound(database.to_string()))?;
+    if find_table_channel(&channels, category.id, &sanitized_name).is_some() {
+        return Err(SqlError::TableExists { requested: collided_name(name, &sanitized_name), table: sanitized_name, database: database.to_string() });
+    }
+    let shards = meta.shards.unwrap_or(1);
+    // Every shard's name must be free too, or a table named like a shard would take its place
+    for shard in 1..shards {
+        let shard_table = shard_channel_name(&sanitized_name, shard);
+        if find_table_channel(&channels, category.id, &shard_table).is_some() {
+            return Err(SqlError::TableExists { requested: None, table: shard_table, database: database.to_string() });
+        }
+    }
+
+    let builder = CreateChannel::new(format!("{}{}", TABLE_PREFIX, sanitized_name))
+        .kind(ChannelType::Text)
+        .category(category.id)
+        .topic(meta.serialize());
+    let channel = match ctx.backend().create_channel(guild_id, builder).await {
+        Ok(channel) => channel,
+        Err(e) => {
+            tracing::error!("Failed to create table channel: {e}");
+            return Err(SqlError::DiscordRequest { operation: "create table" });
+        }
+    };
+    let verified = verify_table_schema(ctx, &channel, columns).await;
+    let created = match verified {
+        Ok(()) => create_shard_channels(ctx, guild_id, category.id, &channel, shards).await,
+        Err(e) => Err(e),
+    };
+    invalidate_channels(ctx, guild_id).await;
+    created.map(|_| (sanitized_name, channel))
+}
+
+/// Create shards 1 up to `shards` of the new `table` channel. When one fails, the table and the shards
+/// made so far are deleted again, so no table is left with rows it can't place.
+async fn create_shard_channels(ctx: &impl BotContext, guild_id: GuildId, category_id: ChannelId, table: &GuildChannel, shards: usize) -> Result<(), SqlError> {
+    let mut created = vec![table.id];
+    for shard in 1..shards {
+        let meta = TopicMeta { shard: Some(shard), ..TopicMeta::default() };
+        let builder = CreateChannel::new(shard_channel_name(&table.name, shard))
+            .kind(ChannelType::Text)
+            .category(category_id)
+            .topic(meta.serialize());
+        match ctx.backend().create_channel(guild_id, builder).await {
+            Ok(channel) => created.push(channel.id),
+            Err(e) => {
+                tracing::error!("Failed to create shard {} of {}: {e}", shard, table.name);
+                for channel_id in created {
+                    if let Err(e) = ctx.backend().delete_channel(channel_id).await {
+                        tracing::error!("Failed to delete half-created table channel: {e}");
+                    }
+                }
+                return Err(SqlError::DiscordRequest { operation: "create table shards" });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A table made by `copy_table`: its sanitized name and the rows copied into it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopiedTable {
+    pub table: String,
+    pub rows: usize,
+}
+
+/// Create table `destination` in `database` with the schema and comment of `source`. With `with_data`,
+/// every stored row of `source` is re-posted into it, oldest first; a source with more than
+/// `SQLCORD_MAX_ROWS` rows is an error rather than a partial copy. An existing `destination` is an
+/// error, as with `create_table`.
+///
+/// ```no_run
+/// # async fn example(ctx: &serenity::prelude::Context, guild_id: serenity::model::id::GuildId) -> Result<(), sqlcord::error::SqlError> {
+/// let copied = sqlcord::api::copy_table(ctx, guild_id, "shop", "users", "users_backup", true).await?;
+/// println!("{} rows copied into {}", copied.rows, copied.table);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn copy_table(ctx: &impl BotContext, guild_id: GuildId, database: &str, source: &str, destination: &str, with_data: bool) -> Result<CopiedTable, SqlError> {
+    let source = require_table(ctx, guild_id, database, source).await?;
+    let columns = table_schema(&source)?;
+    let rows = if with_data { stored_row_contents(ctx, &source, &columns).await? } else { Vec::new() };
+
+    let mut meta = TopicMeta::parse(source.topic.as_deref());
+    // The copy holds every row in its one channel
+    meta.shards = None;
+    meta.rows = Some(rows.len());
+    meta.modified = Some(chrono::Utc::now());
+    let (table, channel) = create_table_channel(ctx, guild_id, database, destination, &meta, &columns).await?;
+
+    let throttle = Throttle::default();
+    for (copied, content) in rows.iter().enumerate() {
+        let sent = with_retry(&throttle, || ctx.backend().send_message(channel.id, CreateMessage::new().content(content.as_str()))).await;
+        if let Err(e) = sent {
+            tracing::error!("Failed to copy row {} of {} into {}: {e}", copied + 1, rows.len(), channel.name);
+            // The topic announced every row; correct it to the ones that made it
+            update_table_topic(ctx, guild_id, channel.id, |meta| meta.rows = Some(copied)).await;
+            return Err(SqlError::DiscordRequest { operation: "copy rows" });
+        }
+    }
+    Ok(CopiedTable { table, rows: rows.len() })
+}
+
+/// Contents of the messages in `channel` (and its other shards) that hold rows, oldest first.
+/// A table the scan couldn't read to the end is an error, so a copy is never silently partial.
+async fn stored_row_contents(ctx: &impl BotContext, channel: &GuildChannel, schema: &[ColumnDefinition]) -> Result<Vec<String>, SqlError> {
+    let limit = max_row_messages();
+    let fetched = match fetch_table_messages(ctx, channel, limit).await {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            tracing::error!("Failed to fetch messages: {e}");
+            return Err(SqlError::DiscordRequest { operation: "read table messages" });
+        }
+    };
+    if fetched.truncated {
+        let table = channel.name.strip_prefix(TABLE_PREFIX).unwrap_or(&channel.name);
+        return Err(SqlError::TableTooLarge { table: table.to_string(), limit });
+    }
+    let contents: Vec<&str> = fetched.messages.iter().rev().map(|message| message.content.as_str()).collect();
+    let (_, rows) = indexed_rows_from_messages(&contents, schema.to_vec());
+    Ok(rows.into_iter().map(|(index, _)| contents[index].to_string()).collect())
+}
+
+/// A table comment as stored in the topic: trimmed, and `None` when blank. Topic entries are one line each.
+pub(crate) fn table_comment(comment: Option<&str>) -> Result<Option<String>, SqlError> {
+    let Some(comment) = comment.map(str::trim).filter(|comment| !comment.is_empty()) else {
+        return Ok(None);
+    };
+    if comment.contains(['\n', '\r']) {
+        return Err(SqlError::InvalidComment("A table comment must fit on one line.".to_string()));
+    }
+    Ok(Some(comment.to_string()))
+}
+
+/// The name as typed, when it differs from the sanitized name it collided with (`My-Table` vs `my_table`)
+fn collided_name(requested: &str, sanitized: &str) -> Option<String> {
+    let requested = requested.trim();
+    (requested != sanitized).then(|| requested.to_string())
+}
+
+/// Read a new table channel back and check its topic holds `columns`; otherwise delete the channel,
+/// so a table never exists without its schema
+async fn verify_table_schema(ctx: &impl BotContext, channel: &GuildChannel, columns: &[ColumnDefinition]) -> Result<(), SqlError> {
+    let stored = match ctx.backend().get_channel(channel.id).await {
+        Ok(stored) => stored.topic,
+        Err(e) => {
+            tracing::error!("Failed to read back table channel: {e}");
+            None
+        }
+    };
+    if topic_holds_schema(stored.as_deref(), columns) {
+        return Ok(());
+    }
+
+    tracing::error!("Table channel {} was created without its schema; deleting it", channel.name);
+    if let Err(e) = ctx.backend().delete_channel(channel.id).await {
+        tracing::error!("Failed to delete half-created table channel: {e}");
+    }
+    let table = channel.name.strip_prefix(TABLE_PREFIX).unwrap_or(&channel.name);
+    Err(SqlError::SchemaNotStored(table.to_string()))
+}
+
+/// Whether a table channel's topic stores exactly `columns`. Schemaless tables accept any topic.
+fn topic_holds_schema(topic: Option<&str>, columns: &[ColumnDefinition]) -> bool {
+    if columns.is_empty() {
+        return true;
+    }
+    topic
+        .and_then(|topic| parse_schema_from_topic(topic).ok())
+        .is_some_and(|stored| schema_topic(&stored) == schema_topic(columns))
+}
+
+/// Insert one row into `table`, with values in schema order. AUTO_INCREMENT columns given as
+/// NULL are filled in, and the row is checked against the schema, PRIMARY KEY/UNIQUE constraints
+/// and REFERENCES targets, which must already hold the value.
+///
+/// ```no_run
+/// # async fn example(ctx: &serenity::prelude::Context, guild_id: serenity::model::id::GuildId) -> Result<(), sqlcord::error::SqlError> {
+/// use sqlcord::sql_parser::SqlValue;
+///
+/// sqlcord::api::insert_row(ctx, guild_id, "shop", "users", vec![SqlValue::Integer(1), SqlValue::String("Ann".into())]).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn insert_row(ctx: &impl BotContext, guild_id: GuildId, database: &str, table: &str, mut values: Vec<SqlValue>) -> Result<(), SqlError> {
+    let channel = require_table(ctx, guild_id, database, table).await?;
+    let schema = table_schema(&channel)?;
+
+    assign_auto_increment_values(ctx, &channel, &mut values, &schema).await?;
+    validate_values_against_schema(&values, &schema).map_err(SqlError::InvalidData)?;
+    coerce_values_to_schema(&mut values, &schema);
+    check_unique_constraints(ctx, &channel, &values, &schema).await?;
+    check_foreign_keys(ctx, guild_id, database, &values, &schema).await?;
+
+    let formatted = format_sql_values_for_storage(&values, &schema);
+    check_row_size(&formatted)?;
+    let shard = shard_for_row(ctx, &channel, &values, &schema).await?;
+    let message = CreateMessage::new().content(formatted);
+    if let Err(e) = ctx.backend().send_message(shard.id, message).await {
+        tracing::error!("Failed to insert data into table channel: {e}");
+        return Err(SqlError::DiscordRequest { operation: "insert data" });
+    }
+    // Keep the cached row count current without waiting on Discord's topic edit rate limit
+    let (ctx, channel_id) = (ctx.clone(), channel.id);
+    tokio::spawn(async move {
+        update_table_topic(&ctx, guild_id, channel_id, |meta| meta.record_change(1, chrono::Utc::now())).await;
+    });
+    Ok(())
+}
+
+/// Read every row of `table` (oldest first, in schema order), optionally filtered with the
+/// same WHERE syntax as `/sql select`; naming a column the table lacks is an error. Reads at most `SQLCORD_MAX_ROWS` stored rows.
+///
+/// ```no_run
+/// # async fn example(ctx: &serenity::prelude::Context, guild_id: serenity::model::id::GuildId) -> Result<(), sqlcord::error::SqlError> {
+/// let adults = sqlcord::api::query(ctx, guild_id, "shop", "users", Some("active=true AND NOT age=17")).await?;
+/// for row in &adults {
+///     println!("{:?}", row);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn query(ctx: &impl BotContext, guild_id: GuildId, database: &str, table: &str, where_clause: Option<&str>) -> Result<Vec<Vec<SqlValue>>, SqlError> {
+    let channel = require_table(ctx, guild_id, database, table).await?;
+    let schema = table_schema(&channel)?;
+
+    let fetched = match fetch_table_messages(ctx, &channel, max_row_messages()).await {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            tracing::error!("Failed to fetch messages: {e}");
+            return Err(SqlError::DiscordRequest { operation: "read table messages" });
+        }
+    };
+    let contents: Vec<&str> = fetched.messages.iter().rev().map(|message| message.content.as_str()).collect();
+    let (schema, rows) = rows_from_messages(&contents, schema);
+    filter_rows(rows, &schema, where_clause)
+}
+
+/// Keep the rows matching a WHERE clause (all rows when there is none), using the same syntax as `/sql select`.
+/// Naming a column `schema` lacks is an error. Works on rows already in memory, so it needs no Discord connection.
+///
+/// ```
+/// use sqlcord::sql_parser::{parse_column_definitions, SqlValue};
+///
+/// let schema = parse_column_definitions("name VARCHAR(20), age INT").unwrap();
+/// let rows = vec![
+///     vec![SqlValue::String("Ann".into()), SqlValue::Integer(31)],
+///     vec![SqlValue::String("Bo".into()), SqlValue::Integer(17)],
+/// ];
+/// let adults = sqlcord::api::filter_rows(rows, &schema, Some("age>=18")).unwrap();
+/// assert_eq!(adults.len(), 1);
+/// assert!(sqlcord::api::filter_rows(adults, &schema, Some("height>2")).is_err());
+/// ```
+pub fn filter_rows(rows: Vec<Vec<SqlValue>>, schema: &[ColumnDefinition], where_clause: Option<&str>) -> Result<Vec<Vec<SqlValue>>, SqlError> {
+    let Some(condition) = where_clause else {
+        return Ok(rows);
+    };
+    check_where_columns(condition, schema)?;
+    Ok(rows.into_iter().filter(|row| evaluate_where_condition(row, schema, condition)).collect())
+}
+
+/// Parsed schema from a table channel's topic; empty for schemaless tables
+pub(crate) fn table_schema(channel: &GuildChannel) -> Result<Vec<ColumnDefinition>, SqlError> {
+    match &channel.topic {
+        Some(topic) => parse_schema_from_topic(topic),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Column definitions as stored after `Schema: ` in a table channel's topic
+pub(crate) fn schema_topic(columns: &[ColumnDefinition]) -> String {
+    columns.iter()
+        .map(|col| {
+            // Type includes size/precision if specified (e.g., VARCHAR(10), DECIMAL(10,2))
+            let mut col_def = format!("{} {}", quote_identifier(&col.name), col.type_display());
+            if col.primary_key {
+                col_def.push_str(" PRIMARY KEY");
+            }
+            if col.unique {
+                col_def.push_str(" UNIQUE");
+            }
+            if col.auto_increment {
+                col_def.push_str(" AUTO_INCREMENT");
+            }
+            if let Some(default) = &col.default {
+                col_def.push_str(&format!(" DEFAULT {}", format_sql_literal(default)));
+            }
+            if let Some(references) = &col.references {
+                col_def.push_str(&format!(" REFERENCES {}", references));
+            }
+            if let Some(comment) = &col.comment {
+                col_def.push_str(&format!(" COMMENT {}", format_sql_literal(&SqlValue::String(comment.clone()))));
+            }
+            col_def
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_comment() {
+        assert_eq!(table_comment(Some("  Customer records ")).unwrap().as_deref(), Some("Customer records"));
+        assert_eq!(table_comment(Some("   ")).unwrap(), None);
+        assert_eq!(table_comment(None).unwrap(), None);
+        assert!(matches!(table_comment(Some("two\nlines")), Err(SqlError::InvalidComment(_))));
+    }
+
+    #[test]
+    fn test_schema_topic_round_trips() {
+        let columns = parse_column_definitions("id INT PRIMARY KEY AUTO_INCREMENT, email VARCHAR(50) UNIQUE, active BOOLEAN DEFAULT true").unwrap();
+        let topic = schema_topic(&columns);
+        assert_eq!(topic, "id INT PRIMARY KEY AUTO_INCREMENT, email VARCHAR(50) UNIQUE, active BOOLEAN DEFAULT true");
+
+        let reparsed = parse_schema_from_topic(&format!("Schema: {}", topic)).unwrap();
+        assert_eq!(reparsed.len(), 3);
+        assert!(reparsed[0].auto_increment && reparsed[1].unique);
+
+        let columns = parse_column_definitions("`order date` DATE, \"select\" INT").unwrap();
+        let topic = schema_topic(&columns);
+        assert_eq!(topic, "`order date` DATE, select INT");
+        let reparsed = parse_schema_from_topic(&format!("Schema: {}", topic)).unwrap();
+        assert_eq!(reparsed.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["order date", "select"]);
+    }
+
+    #[test]
+    fn test_sanitization_collision_message() {
+        let channels: std::collections::HashMap<_, _> = [
+            crate::services::discord_fs::test_channel(10, "db_shop", 4, None),
+            crate::services::discord_fs::test_channel(11, "table_my_table", 0, Some(10)),
+        ]
+        .into_iter()
+        .map(|c| (c.id, c))
+        .collect();
+
+        // Both spellings land on the channel that already exists
+        let (first, _) = validate_identifier("My_Table", TABLE_PREFIX).unwrap();
+        let (second, _) = validate_identifier("My-Table", TABLE_PREFIX).unwrap();
+        assert_eq!(first, second);
+        assert!(find_table_channel(&channels, serenity::model::id::ChannelId::new(10), &second).is_some());
+
+        let error = SqlError::TableExists { requested: collided_name("My-Table", &second), table: second, database: "shop".to_string() };
+        assert_eq!(error.to_string(), "The name `My-Table` sanitizes to **my_table**, which already exists in database **shop**. Choose a name that differs after sanitization.");
+
+        // Typing the existing name exactly keeps the plain message
+        assert_eq!(collided_name("my_table", "my_table"), None);
+        let error = SqlError::DatabaseExists { requested: collided_name(" Shop ", "shop"), database: "shop".to_string() };
+        assert!(error.to_string().starts_with("The name `Shop` sanitizes to **shop**"));
+    }
+
+    #[test]
+    fn test_topic_holds_schema() {
+        let columns = parse_column_definitions("id INT PRIMARY KEY, name VARCHAR(20) DEFAULT 'x'").unwrap();
+        let topic = format!("Schema: {}", schema_topic(&columns));
+        assert!(topic_holds_schema(Some(&topic), &columns));
+
+        // Missing, truncated or altered topics fail verification
+        assert!(!topic_holds_schema(None, &columns));
+        assert!(!topic_holds_schema(Some(&topic[..topic.len() - 12]), &columns));
+        assert!(!topic_holds_schema(Some("Schema: id INT, name VARCHAR(20) DEFAULT 'x'"), &columns));
+        assert!(!topic_holds_schema(Some("Schema: id INT PRIMARY KEY, name VARCHAR(20) DEFAULT 'x', extra INT"), &columns));
+
+        assert!(topic_holds_schema(None, &[]));
+        // The cached row count on its own line doesn't disturb the schema
+        assert!(topic_holds_schema(Some(&format!("{}\nRows: 0", topic)), &columns));
+        assert!(topic_holds_schema(Some("Rows: 0"), &[]));
+    }
+}
@@ -0,0 +1,162 @@
+// Offline self-test: runs schema, value and WHERE cases through the parser and exits nonzero if any fails.
+// No Discord connection needed: `cargo run --bin selftest`
+
+use sqlcord::api::filter_rows;
+use sqlcord::logging::{init, log_error, log_info};
+use sqlcord::sql_parser::{parse_column_definitions, parse_sql_values, validate_values_against_schema, ColumnDefinition, SqlValue};
+
+type CaseResult = Result<(), String>;
+
+const SCHEMA: &str = "id INT PRIMARY KEY, name VARCHAR(20) NOT NULL, age TINYINT, active BOOLEAN, score FLOAT";
+
+fn main() {
+    init();
+    let cases: &[(&str, fn() -> CaseResult)] = &[
+        ("valid schemas parse", valid_schemas),
+        ("invalid schemas are rejected", invalid_schemas),
+        ("values parse to their types", value_parsing),
+        ("malformed values are rejected", invalid_values),
+        ("values are checked against the schema", schema_validation),
+        ("WHERE filters synthetic rows", where_filtering),
+        ("WHERE rejects unknown columns", where_unknown_column),
+    ];
+
+    let failed = cases
+        .iter()
+        .filter(|(name, case)| match case() {
+            Ok(()) => {
+                log_info(&format!("PASS {}", name));
+                false
+            }
+            Err(e) => {
+                log_error(&format!("FAIL {}: {}", name, e));
+                true
+            }
+        })
+        .count();
+
+    if failed > 0 {
+        log_error(&format!("{} of {} self-test cases failed", failed, cases.len()));
+        std::process::exit(1);
+    }
+    log_info(&format!("All {} self-test cases passed", cases.len()));
+}
+
+fn schema() -> Result<Vec<ColumnDefinition>, String> {
+    parse_column_definitions(SCHEMA).map_err(|e| format!("test schema didn't parse: {}", e))
+}
+
+fn rows() -> Vec<Vec<SqlValue>> {
+    let row = |id, name: &str, age, active, score| {
+        vec![SqlValue::Integer(id), SqlValue::String(name.to_string()), SqlValue::Integer(age), SqlValue::Boolean(active), SqlValue::Float(score)]
+    };
+    vec![
+        row(1, "Ann", 31, true, 9.5),
+        row(2, "Bo", 17, false, 4.0),
+        row(3, "Cy", 45, true, 7.25),
+        vec![SqlValue::Integer(4), SqlValue::String("Di".to_string()), SqlValue::Null, SqlValue::Null, SqlValue::Null],
+    ]
+}
+
+fn valid_schemas() -> CaseResult {
+    let cases = [
+        (SCHEMA, 5),
+        ("email VARCHAR(255) UNIQUE, created DATETIME DEFAULT NULL", 2),
+        ("a INT, b INT, PRIMARY KEY (a, b)", 2),
+        ("`first name` VARCHAR(30), \"order\" INT", 2),
+    ];
+    for (schema, expected) in cases {
+        match parse_column_definitions(schema) {
+            Ok(columns) if columns.len() == expected => {}
+            Ok(columns) => return Err(format!("`{}` gave {} columns, expected {}", schema, columns.len(), expected)),
+            Err(e) => return Err(format!("`{}` was rejected: {}", schema, e)),
+        }
+    }
+    Ok(())
+}
+
+fn invalid_schemas() -> CaseResult {
+    for schema in ["id", "id BLOB", "name VARCHAR", "a INT, PRIMARY KEY (missing)"] {
+        if parse_column_definitions(schema).is_ok() {
+            return Err(format!("`{}` was accepted", schema));
+        }
+    }
+    Ok(())
+}
+
+fn value_parsing() -> CaseResult {
+    let cases = [
+        ("1, 'Ann', true, 2.5", vec![SqlValue::Integer(1), SqlValue::String("Ann".to_string()), SqlValue::Boolean(true), SqlValue::Float(2.5)]),
+        ("NULL, 'NULL'", vec![SqlValue::Null, SqlValue::String("NULL".to_string())]),
+        ("'it''s, fine'", vec![SqlValue::String("it's, fine".to_string())]),
+        ("-7", vec![SqlValue::Integer(-7)]),
+    ];
+    for (values, expected) in cases {
+        match parse_sql_values(values) {
+            Ok(parsed) if parsed == expected => {}
+            Ok(parsed) => return Err(format!("`{}` parsed as {:?}, expected {:?}", values, parsed, expected)),
+            Err(e) => return Err(format!("`{}` was rejected: {}", values, e)),
+        }
+    }
+    Ok(())
+}
+
+fn invalid_values() -> CaseResult {
+    for values in ["'unterminated", "not_a_value"] {
+        if parse_sql_values(values).is_ok() {
+            return Err(format!("`{}` was accepted", values));
+        }
+    }
+    Ok(())
+}
+
+fn schema_validation() -> CaseResult {
+    let schema = schema()?;
+    let check = |values: &str| parse_sql_values(values).and_then(|values| validate_values_against_schema(&values, &schema));
+    for values in ["1, 'Ann', 31, true, 9.5", "2, 'Bo', NULL, NULL, NULL"] {
+        check(values).map_err(|e| format!("`{}` was rejected: {}", values, e))?;
+    }
+    for values in ["1, 'Ann'", "'one', 'Ann', 31, true, 9.5", "1, NULL, 31, true, 9.5", "1, 'Ann', 300, true, 9.5", "1, 'a name well over twenty', 31, true, 9.5"] {
+        if check(values).is_ok() {
+            return Err(format!("`{}` was accepted", values));
+        }
+    }
+    Ok(())
+}
+
+fn where_filtering() -> CaseResult {
+    let schema = schema()?;
+    let cases = [
+        ("age > 20", vec![1, 3]),
+        ("name = 'Bo' OR score >= 7.25", vec![1, 2, 3]),
+        ("active = true AND age < 40", vec![1]),
+        ("age = null", vec![4]),
+        ("name <> 'Ann' AND active = true", vec![3]),
+    ];
+    for (condition, expected) in cases {
+        let ids: Vec<i64> = filter_rows(rows(), &schema, Some(condition))
+            .map_err(|e| format!("`{}` failed: {}", condition, e))?
+            .iter()
+            .filter_map(|row| match row.first() {
+                Some(SqlValue::Integer(id)) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        if ids != expected {
+            return Err(format!("`{}` kept ids {:?}, expected {:?}", condition, ids, expected));
+        }
+    }
+    let all = filter_rows(rows(), &schema, None).map_err(|e| e.to_string())?;
+    if all.len() != rows().len() {
+        return Err(format!("no WHERE kept {} of {} rows", all.len(), rows().len()));
+    }
+    Ok(())
+}
+
+fn where_unknown_column() -> CaseResult {
+    let schema = schema()?;
+    match filter_rows(rows(), &schema, Some("height > 2")) {
+        Ok(_) => Err("`height > 2` was accepted".to_string()),
+        Err(_) => Ok(()),
+    }
+}
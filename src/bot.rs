@@ -3,10 +3,13 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use tokio::sync::Mutex;
 use serenity::Client;
+use serenity::model::application::Command;
+use serenity::model::id::GuildId;
 use serenity::model::gateway::GatewayIntents;
-use crate::state::CurrentDB;
+use crate::state::{read_only_enabled, AuditLog, CurrentDB, DestructiveRoles, GuildChannelCache, PendingConfirmations, ReadOnlyMode, TopicLocks};
+use crate::services::channel_cache::ChannelCache;
 use crate::handler::Handler;
-use crate::logging::{log_info, log_error};
+use crate::logging::{audit_log_path, log_info, log_warn, log_error};
 
 pub async fn create_client_from_env() -> Result<Client, Box<dyn std::error::Error>> {
     let token = env::var("DISCORD_TOKEN")?;
@@ -18,10 +21,21 @@ pub async fn create_client_from_env() -> Result<Client, Box<dyn std::error::Erro
 
     let client = Client::builder(&token, intents).event_handler(Handler).await?;
 
-    // initialize shared data: CurrentDB map
+    let read_only = read_only_enabled(env::var("SQLCORD_READ_ONLY").ok().as_deref());
+    if read_only {
+        log_warn("SQLCORD_READ_ONLY is set: create, drop, insert, update and delete commands will not change anything");
+    }
+
+    // initialize shared data: CurrentDB map, destructive-command role allowlist, channel cache, pending drop confirmations, topic locks, audit log path and read-only mode
     {
         let mut data = client.data.write().await;
         data.insert::<CurrentDB>(Arc::new(Mutex::new(HashMap::new())));
+        data.insert::<DestructiveRoles>(Arc::new(Mutex::new(HashMap::new())));
+        data.insert::<GuildChannelCache>(Arc::new(Mutex::new(ChannelCache::default())));
+        data.insert::<PendingConfirmations>(Arc::new(Mutex::new(HashMap::new())));
+        data.insert::<TopicLocks>(Arc::new(Mutex::new(HashMap::new())));
+        data.insert::<AuditLog>(audit_log_path());
+        data.insert::<ReadOnlyMode>(read_only);
     }
 
     // command registration is performed after the client is ready (in handler.rs)
@@ -29,6 +43,43 @@ pub async fn create_client_from_env() -> Result<Client, Box<dyn std::error::Erro
     Ok(client)
 }
 
+/// Where the slash commands are registered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationMode {
+    /// Once for every guild; new commands can take up to an hour to propagate
+    Global,
+    /// Only to the `DEV_GUILD_ID` guild, for development
+    DevGuild(GuildId),
+    /// To each guild as it becomes available (at startup and when the bot joins), which shows up immediately
+    PerGuild,
+}
+
+/// The registration mode from `DEV_GUILD_ID` and `SQLCORD_COMMAND_SCOPE` (`global`, the default, or `guild`).
+/// A parseable `DEV_GUILD_ID` wins over the scope.
+pub fn registration_mode(scope: Option<&str>, dev_guild: Option<&str>) -> RegistrationMode {
+    if let Some(guild_id) = dev_guild.and_then(|id| id.trim().parse::<u64>().ok()).filter(|id| *id != 0) {
+        return RegistrationMode::DevGuild(GuildId::new(guild_id));
+    }
+    match scope.map(|scope| scope.trim().to_ascii_lowercase()).as_deref() {
+        Some("guild" | "per-guild" | "per_guild") => RegistrationMode::PerGuild,
+        _ => RegistrationMode::Global,
+    }
+}
+
+/// The registration mode configured in the environment (see `registration_mode`)
+pub fn command_registration_mode() -> RegistrationMode {
+    registration_mode(env::var("SQLCORD_COMMAND_SCOPE").ok().as_deref(), env::var("DEV_GUILD_ID").ok().as_deref())
+}
+
+/// Register the whole command set to one guild, replacing whatever SQLcord registered there before
+pub async fn register_guild_commands(http: &serenity::http::Http, guild_id: GuildId) -> serenity::Result<()> {
+    let builders = crate::commands::register_all();
+    tracing::info!("Registering {} commands to guild {}", builders.len(), guild_id);
+    let cmds = http.create_guild_commands(guild_id, &builders).await?;
+    tracing::info!("Created {} guild commands", cmds.len());
+    Ok(())
+}
+
 pub async fn register_commands(http: &serenity::http::Http) -> Result<(), Box<dyn std::error::Error>> {
     use serenity::builder::CreateCommand;
     use std::time::Duration;
@@ -63,34 +114,102 @@ pub async fn register_commands(http: &serenity::http::Http) -> Result<(), Box<dy
 
     // Optional fast-path: register to a single guild for development to get immediate updates.
     // Set DEV_GUILD_ID environment variable to a guild value (as integer) to enable.
-    if let Ok(guild_str) = std::env::var("DEV_GUILD_ID") {
-        match guild_str.parse::<u64>() {
-            Ok(gid) => {
-                tracing::info!("Registering {} commands to guild {} (DEV_GUILD_ID)", builders.len(), gid);
-                // The Http client exposes create_guild_commands to register multiple commands for a guild.
-                let clone_builders = builders.clone();
-                let guild_id = serenity::model::id::GuildId::from(gid);
-                match http.create_guild_commands(guild_id, &clone_builders).await {
-                    Ok(cmds) => tracing::info!("Created {} guild commands", cmds.len()),
-                    Err(e) => tracing::error!("Failed to create guild commands: {e}")
-                }
-                return Ok(());
+    match command_registration_mode() {
+        RegistrationMode::DevGuild(guild_id) => {
+            if let Err(e) = register_guild_commands(http, guild_id).await {
+                tracing::error!("Failed to create guild commands: {e}");
             }
-            Err(_) => tracing::warn!("DEV_GUILD_ID is set but couldn't parse as u64: {}", guild_str),
+            return Ok(());
+        }
+        // Each guild registers in `guild_create`, which fires for every guild after ready
+        RegistrationMode::PerGuild => {
+            tracing::info!("SQLCORD_COMMAND_SCOPE=guild: registering commands per guild as they become available");
+            return Ok(());
         }
+        RegistrationMode::Global => {}
+    }
+    if let Ok(guild_str) = std::env::var("DEV_GUILD_ID") {
+        tracing::warn!("DEV_GUILD_ID is set but couldn't parse as u64: {}", guild_str);
     }
 
-    // Default: create global commands. Note that global commands can take up to an hour to propagate.
+    // Default: overwrite the global commands in one request, so restarts replace them instead of adding
+    // duplicates. Note that global commands can take up to an hour to propagate.
     tracing::info!("Registering {} global commands (this can be slow to propagate)", builders.len());
-    for builder in builders {
-        match serenity::model::application::Command::create_global_command(http, builder).await {
-            Ok(cmd) => tracing::info!("Created global command: {} (id={})", cmd.name, cmd.id),
-            Err(e) => tracing::error!("Failed to create global command: {e}")
+    let existing = match Command::get_global_commands(http).await {
+        Ok(commands) => commands.into_iter().map(|cmd| cmd.name).collect(),
+        Err(e) => {
+            tracing::warn!("Couldn't list the current global commands: {e}");
+            Vec::new()
         }
+    };
+    match Command::set_global_commands(http, builders).await {
+        Ok(cmds) => {
+            let registered: Vec<String> = cmds.iter().map(|cmd| cmd.name.clone()).collect();
+            let diff = CommandDiff::between(&existing, &registered);
+            tracing::info!("Set {} global commands (added: [{}], removed: [{}], updated: [{}])",
+                cmds.len(), diff.added.join(", "), diff.removed.join(", "), diff.kept.join(", "));
+        }
+        Err(e) => tracing::error!("Failed to set global commands: {e}")
     }
 
-    // Ensure application info is fetched (some serenity versions require this to populate app id)
-    let _ = http.get_current_application_info().await;
-
     Ok(())
 }
+
+/// How a set of command names changes when registration overwrites it
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CommandDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Present before and after; overwritten in place
+    pub kept: Vec<String>,
+}
+
+impl CommandDiff {
+    pub fn between(existing: &[String], registered: &[String]) -> Self {
+        let (kept, added) = registered.iter().cloned().partition(|name| existing.contains(name));
+        let removed = existing.iter().filter(|name| !registered.contains(name)).cloned().collect();
+        CommandDiff { added, removed, kept }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_command_names_are_unique() {
+        let names: Vec<String> = crate::commands::register_all().into_iter()
+            .map(|builder| serde_json::to_value(builder).unwrap()["name"].as_str().unwrap().to_string())
+            .collect();
+        assert!(names.contains(&crate::commands::sql::sql_command_name().to_string()));
+        let mut unique = names.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), names.len(), "{names:?}");
+    }
+
+    #[test]
+    fn test_command_diff() {
+        let names = |list: &[&str]| list.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        let diff = CommandDiff::between(&names(&["sql", "old"]), &names(&["sql", "admin"]));
+        assert_eq!(diff, CommandDiff { added: names(&["admin"]), removed: names(&["old"]), kept: names(&["sql"]) });
+        assert_eq!(CommandDiff::between(&[], &[]), CommandDiff::default());
+    }
+
+    #[test]
+    fn test_registration_mode() {
+        assert_eq!(registration_mode(None, None), RegistrationMode::Global);
+        assert_eq!(registration_mode(Some("global"), None), RegistrationMode::Global);
+        assert_eq!(registration_mode(Some("nonsense"), None), RegistrationMode::Global);
+        for scope in ["guild", " Guild ", "per-guild", "PER_GUILD"] {
+            assert_eq!(registration_mode(Some(scope), None), RegistrationMode::PerGuild, "{scope}");
+        }
+
+        // A valid DEV_GUILD_ID wins; an invalid one is ignored
+        let dev = RegistrationMode::DevGuild(GuildId::new(42));
+        assert_eq!(registration_mode(None, Some("42")), dev);
+        assert_eq!(registration_mode(Some("guild"), Some(" 42 ")), dev);
+        assert_eq!(registration_mode(Some("guild"), Some("not-a-guild")), RegistrationMode::PerGuild);
+        assert_eq!(registration_mode(None, Some("0")), RegistrationMode::Global);
+    }
+}
@@ -1,11 +1,116 @@
-// /admin debug
+// /admin debug table <name>
 
 use std::error::Error;
+use serenity::builder::CreateEmbed;
+use serenity::prelude::Context;
+use serenity::model::id::{GuildId, UserId};
+use crate::commands::sql::select::{extract_values_from_message, parse_schema_from_topic};
+use crate::error::SqlError;
+use crate::guards::{require_selected_db, require_table};
+use crate::logging::{log_debug, log_info};
+use crate::services::discord_fs::fetch_all_messages;
+use crate::services::topic_meta::TopicMeta;
+use crate::sql_parser::ColumnDefinition;
+use crate::utils::create_info_embed;
+
+/// Stored messages dumped per inspection; each becomes one embed field
+pub const DEBUG_RAW_ROWS: usize = 5;
+
+/// Characters of a raw message kept in a dump (embed field values are limited to 1024)
+const RAW_CONTENT_CHARS: usize = 900;
 
 pub fn register() -> Result<(), Box<dyn Error>> {
+	log_debug("Registering ADMIN DEBUG command");
 	Ok(())
 }
 
-pub async fn run() -> Result<(), Box<dyn Error>> {
-	Ok(())
+/// Dump a table's topic, parsed schema and the latest raw row messages, with whether each decodes
+pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, table_name: &str) -> Result<CreateEmbed, SqlError> {
+	log_info(&format!("ADMIN DEBUG TABLE executed for table: {}", table_name));
+
+	let current_db = require_selected_db(ctx, guild_id, user_id).await?;
+	let channel = require_table(ctx, guild_id, &current_db, table_name).await?;
+
+	let topic = channel.topic.clone().unwrap_or_default();
+	let (schema, schema_summary) = match parse_schema_from_topic(&topic) {
+		Ok(schema) if schema.is_empty() => (schema, "*No schema (columns are inferred from stored keys)*".to_string()),
+		Ok(schema) => {
+			let summary = schema.iter().map(|column| format!("• {}", column)).collect::<Vec<_>>().join("\n");
+			(schema, summary)
+		}
+		Err(e) => (Vec::new(), format!("✖️ {}", e)),
+	};
+
+	let fetched = match fetch_all_messages(&*ctx.http, channel.id, DEBUG_RAW_ROWS).await {
+		Ok(fetched) => fetched,
+		Err(e) => {
+			tracing::error!("Failed to fetch messages: {e}");
+			return Err(SqlError::DiscordRequest { operation: "read table messages" });
+		}
+	};
+
+	let mut description = format!(
+		"Table **{}** in database **{}** (<#{}>)\n\n**Raw topic:**\n```\n{}\n```\n**Parsed schema:**\n{}",
+		table_name,
+		current_db,
+		channel.id,
+		if topic.is_empty() { "(empty)" } else { &topic },
+		schema_summary
+	);
+	if let Some(comment) = TopicMeta::parse(Some(&topic)).comment {
+		description.push_str(&format!("\n**Comment:** {}", comment));
+	}
+	let mut embed = create_info_embed("🔍 Table Debug", &description);
+
+	if fetched.messages.is_empty() {
+		return Ok(embed.field("Stored Rows", "*No messages in this channel.*", false));
+	}
+	for (i, message) in fetched.messages.iter().enumerate() {
+		embed = embed.field(format!("Message {} (id {})", i + 1, message.id), describe_raw_row(&message.content, &schema), false);
+	}
+	if fetched.truncated {
+		embed = embed.footer(serenity::builder::CreateEmbedFooter::new(format!("Showing the latest {} messages", DEBUG_RAW_ROWS)));
+	}
+	Ok(embed)
+}
+
+/// Raw message content in a code block plus whether it decodes against `schema`, the way SELECT reads it
+fn describe_raw_row(content: &str, schema: &[ColumnDefinition]) -> String {
+	let mut raw: String = content.chars().take(RAW_CONTENT_CHARS).collect();
+	if raw.len() < content.len() {
+		raw.push_str("\n…");
+	}
+	// Keep user data from closing the code block early
+	let raw = raw.replace("```", "`\u{200b}``");
+
+	let status = match extract_values_from_message(content, schema) {
+		Some(values) if schema.is_empty() && values.is_empty() => "⚠️ No `DATA:` values found".to_string(),
+		Some(values) => format!("✔️ Decodes to {} value(s)", values.len()),
+		None if !content.contains("DATA:\n") => "✖️ No `DATA:` section".to_string(),
+		None => "✖️ Missing a schema column, so SELECT skips this row".to_string(),
+	};
+	format!("```\n{}\n```{}", raw, status)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sql_parser::parse_column_definitions;
+
+	#[test]
+	fn test_describe_raw_row() {
+		let schema = parse_column_definitions("id INT, name VARCHAR(20)").unwrap();
+		let row = "TIMESTAMP: 2025-01-01 00:00:00 UTC\nDATA:\n  id: 1\n  name: 'Ann'";
+
+		let described = describe_raw_row(row, &schema);
+		assert!(described.starts_with("```\nTIMESTAMP:"));
+		assert!(described.ends_with("✔️ Decodes to 2 value(s)"));
+
+		let missing_column = "TIMESTAMP: 2025-01-01 00:00:00 UTC\nDATA:\n  id: 1";
+		assert!(describe_raw_row(missing_column, &schema).ends_with("SELECT skips this row"));
+		assert!(describe_raw_row("hello there", &schema).ends_with("✖️ No `DATA:` section"));
+
+		let long = format!("DATA:\n  note: '{}'", "x".repeat(2000));
+		assert!(describe_raw_row(&long, &[]).len() < 1024);
+	}
 }
@@ -3,6 +3,28 @@ pub mod perms;
 pub mod debug;
 
 pub fn register_admin_group() -> serenity::builder::CreateCommand {
-	use serenity::builder::CreateCommand;
+	use serenity::builder::{CreateCommand, CreateCommandOption};
+	use serenity::model::application::CommandOptionType;
+	use serenity::model::permissions::Permissions;
 	CreateCommand::new("admin").description("Admin helpers")
+		// Only administrators see and run /admin
+		.default_member_permissions(Permissions::ADMINISTRATOR)
+		// perms group: /admin perms allow|deny <role>
+		.add_option(
+			CreateCommandOption::new(CommandOptionType::SubCommandGroup, "perms", "Control who may run destructive commands")
+				.set_sub_options(vec![
+					CreateCommandOption::new(CommandOptionType::SubCommand, "allow", "Allow a role to run DROP and DELETE")
+						.add_sub_option(CreateCommandOption::new(CommandOptionType::Role, "role", "Role to allow").required(true)),
+					CreateCommandOption::new(CommandOptionType::SubCommand, "deny", "Remove a role from the DROP/DELETE allowlist")
+						.add_sub_option(CreateCommandOption::new(CommandOptionType::Role, "role", "Role to remove").required(true)),
+				])
+		)
+		// debug group: /admin debug table <name>
+		.add_option(
+			CreateCommandOption::new(CommandOptionType::SubCommandGroup, "debug", "Inspect how data is stored")
+				.set_sub_options(vec![
+					CreateCommandOption::new(CommandOptionType::SubCommand, "table", "Dump a table's schema and raw row messages")
+						.add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "Table name").required(true)),
+				])
+		)
 }
@@ -1,11 +1,75 @@
-// /admin perms
+// /admin perms allow|deny <role>
 
+use std::collections::HashSet;
 use std::error::Error;
+use serenity::prelude::Context;
+use serenity::model::id::{GuildId, RoleId};
+use crate::state::DestructiveRoles;
+use crate::logging::{log_debug, log_info};
+use crate::utils::{create_success_embed, create_info_embed, create_error_embed};
 
 pub fn register() -> Result<(), Box<dyn Error>> {
+	log_debug("Registering ADMIN PERMS command");
 	Ok(())
 }
 
-pub async fn run() -> Result<(), Box<dyn Error>> {
-	Ok(())
+/// Add (`allow`) or remove (`deny`) a role from the guild's destructive-command allowlist
+pub async fn run(ctx: &Context, guild_id: GuildId, allow: bool, role_id: RoleId) -> Result<serenity::builder::CreateEmbed, serenity::builder::CreateEmbed> {
+	log_info(&format!("ADMIN PERMS {} executed for role: {}", if allow { "allow" } else { "deny" }, role_id));
+
+	let data = ctx.data.read().await;
+	let Some(store) = data.get::<DestructiveRoles>() else {
+		return Err(create_error_embed(
+			"Internal Error",
+			"Permission settings are not initialized."
+		));
+	};
+	let mut roles_by_guild = store.lock().await;
+	let allowed_roles = roles_by_guild.entry(guild_id).or_default();
+
+	let changed = apply_role_change(allowed_roles, allow, role_id);
+	let summary = if allowed_roles.is_empty() {
+		"No roles are configured, so everyone can run destructive commands.".to_string()
+	} else {
+		format!("**Allowed roles:** {}", allowed_roles.iter().map(|role| format!("<@&{}>", role)).collect::<Vec<_>>().join(", "))
+	};
+
+	if !changed {
+		let state = if allow { "already allowed" } else { "not in the allowlist" };
+		return Ok(create_info_embed("ℹ️ No Change", &format!("Role <@&{}> is {}.\n\n{}", role_id, state, summary)));
+	}
+
+	let title = if allow { "✔️ Role Allowed" } else { "✔️ Role Removed" };
+	Ok(create_success_embed(
+		title,
+		&format!("Role <@&{}> {} run DROP DATABASE, DROP TABLE and DELETE.\n\n{}", role_id, if allow { "can now" } else { "can no longer" }, summary)
+	))
+}
+
+/// Apply an allow/deny change; returns whether the allowlist changed
+fn apply_role_change(allowed_roles: &mut HashSet<RoleId>, allow: bool, role_id: RoleId) -> bool {
+	if allow {
+		allowed_roles.insert(role_id)
+	} else {
+		allowed_roles.remove(&role_id)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_apply_role_change() {
+		let mut roles = HashSet::new();
+		let role = RoleId::new(42);
+
+		assert!(apply_role_change(&mut roles, true, role));
+		assert!(!apply_role_change(&mut roles, true, role));
+		assert!(roles.contains(&role));
+
+		assert!(apply_role_change(&mut roles, false, role));
+		assert!(!apply_role_change(&mut roles, false, role));
+		assert!(roles.is_empty());
+	}
 }
@@ -1,6 +1,7 @@
 // Top-level commands module
 pub mod sql;
 pub mod admin;
+pub mod options;
 use serenity::builder::CreateCommand;
 
 /// Build and return the top-level command builders for registration.
@@ -0,0 +1,418 @@
+// Typed extraction of slash command options
+
+use serenity::builder::CreateEmbed;
+use serenity::model::application::{CommandDataOption, CommandDataOptionValue};
+use serenity::model::id::{AttachmentId, RoleId};
+use crate::commands::sql::insert::OnConflict;
+use crate::commands::sql::join::JoinKind;
+use crate::commands::sql::select::OutputFormat;
+use crate::utils::create_error_embed;
+
+/// Arguments for `/sql select`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectArgs<'a> {
+    pub columns: &'a str,
+    pub from: &'a str,
+    pub distinct: Option<bool>,
+    pub where_clause: Option<&'a str>,
+    /// Only show the result to the user who ran the query
+    pub ephemeral: bool,
+    pub format: OutputFormat,
+    /// Post the complete result in a thread off the response
+    pub to_thread: bool,
+    /// Rows shown in the result table; defaults to `SQLCORD_DISPLAY_ROWS` or 20
+    pub max_rows: Option<usize>,
+    /// Database to query instead of the `/sql use` selection
+    pub db: Option<&'a str>,
+}
+
+/// Required options that were not supplied for a command
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingOptions {
+    pub command: &'static str,
+    pub missing: Vec<&'static str>,
+}
+
+impl MissingOptions {
+    /// Build the "Missing required parameters" error embed
+    pub fn to_embed(&self) -> CreateEmbed {
+        let list = self.missing.iter().map(|name| format!("`{}`", name)).collect::<Vec<_>>().join(", ");
+        create_error_embed(
+            "Missing Required Parameters",
+            &format!("`/sql {}` requires: {}", self.command, list)
+        )
+    }
+}
+
+/// Parse `/sql select` options, reporting every missing required option at once
+pub fn parse_select_args(options: &[CommandDataOption]) -> Result<SelectArgs<'_>, MissingOptions> {
+    let columns = get_string_option(options, "columns");
+    let from = get_string_option(options, "from");
+
+    match (columns, from) {
+        (Some(columns), Some(from)) => Ok(SelectArgs {
+            columns,
+            from,
+            distinct: get_bool_option(options, "distinct"),
+            where_clause: get_string_option(options, "where"),
+            ephemeral: get_bool_option(options, "ephemeral").unwrap_or(false),
+            format: OutputFormat::from_option(get_string_option(options, "format")),
+            to_thread: get_bool_option(options, "to_thread").unwrap_or(false),
+            max_rows: get_integer_option(options, "max_rows").and_then(|rows| usize::try_from(rows).ok()),
+            db: get_string_option(options, "db"),
+        }),
+        _ => Err(MissingOptions {
+            command: "select",
+            missing: [("columns", columns.is_none()), ("from", from.is_none())]
+                .into_iter()
+                .filter_map(|(name, is_missing)| is_missing.then_some(name))
+                .collect(),
+        }),
+    }
+}
+
+/// Arguments for `/sql count`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountArgs<'a> {
+    pub from: &'a str,
+    pub where_clause: Option<&'a str>,
+    /// Database to count in instead of the `/sql use` selection
+    pub db: Option<&'a str>,
+    /// Count the stored rows even when the table topic caches a row count
+    pub rescan: bool,
+}
+
+/// Parse `/sql count` options
+pub fn parse_count_args(options: &[CommandDataOption]) -> Result<CountArgs<'_>, MissingOptions> {
+    match get_string_option(options, "from") {
+        Some(from) => Ok(CountArgs {
+            from,
+            where_clause: get_string_option(options, "where"),
+            db: get_string_option(options, "db"),
+            rescan: get_bool_option(options, "rescan").unwrap_or(false),
+        }),
+        None => Err(MissingOptions { command: "count", missing: vec!["from"] }),
+    }
+}
+
+/// Arguments for `/sql join`
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinArgs<'a> {
+    pub left: &'a str,
+    pub right: &'a str,
+    /// `left_column=right_column`
+    pub on: &'a str,
+    pub kind: JoinKind,
+    /// Column selection as in `/sql select`; defaults to `*`
+    pub columns: Option<&'a str>,
+    pub where_clause: Option<&'a str>,
+}
+
+/// Parse `/sql join` options, reporting every missing required option at once
+pub fn parse_join_args(options: &[CommandDataOption]) -> Result<JoinArgs<'_>, MissingOptions> {
+    let left = get_string_option(options, "left");
+    let right = get_string_option(options, "right");
+    let on = get_string_option(options, "on");
+
+    match (left, right, on) {
+        (Some(left), Some(right), Some(on)) => Ok(JoinArgs {
+            left,
+            right,
+            on,
+            kind: JoinKind::from_option(get_string_option(options, "kind")),
+            columns: get_string_option(options, "columns"),
+            where_clause: get_string_option(options, "where"),
+        }),
+        _ => Err(MissingOptions {
+            command: "join",
+            missing: [("left", left.is_none()), ("right", right.is_none()), ("on", on.is_none())]
+                .into_iter()
+                .filter_map(|(name, is_missing)| is_missing.then_some(name))
+                .collect(),
+        }),
+    }
+}
+
+/// Arguments for `/sql create table`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateTableArgs<'a> {
+    pub name: &'a str,
+    pub schema: Option<&'a str>,
+    pub comment: Option<&'a str>,
+    /// Report an existing table instead of rejecting it
+    pub if_not_exists: bool,
+    /// Channels the rows are spread over; 1 keeps them in the table's own channel
+    pub shards: usize,
+}
+
+/// Parse `/sql create table` options
+pub fn parse_create_table_args(options: &[CommandDataOption]) -> Result<CreateTableArgs<'_>, MissingOptions> {
+    match get_string_option(options, "name") {
+        Some(name) => Ok(CreateTableArgs {
+            name,
+            schema: get_string_option(options, "schema"),
+            comment: get_string_option(options, "comment"),
+            if_not_exists: get_bool_option(options, "if_not_exists").unwrap_or(false),
+            // Out-of-range counts become 0, which creating the table reports
+            shards: get_integer_option(options, "shards").map_or(1, |shards| usize::try_from(shards).unwrap_or(0)),
+        }),
+        None => Err(MissingOptions { command: "create table", missing: vec!["name"] }),
+    }
+}
+
+/// Arguments for `/sql insert into`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsertArgs<'a> {
+    pub table: &'a str,
+    pub data: &'a str,
+    /// Columns the values map to, in order; omitted columns get their DEFAULT or NULL
+    pub columns: Option<&'a str>,
+    /// Run every check without writing the row
+    pub validate_only: bool,
+    pub on_conflict: OnConflict,
+    /// Database to insert into instead of the `/sql use` selection
+    pub db: Option<&'a str>,
+}
+
+/// Parse `/sql insert into` options, reporting every missing required option at once
+pub fn parse_insert_args(options: &[CommandDataOption]) -> Result<InsertArgs<'_>, MissingOptions> {
+    let table = get_string_option(options, "table");
+    let data = get_string_option(options, "data");
+
+    match (table, data) {
+        (Some(table), Some(data)) => Ok(InsertArgs {
+            table,
+            data,
+            columns: get_string_option(options, "columns"),
+            validate_only: get_bool_option(options, "validate_only").unwrap_or(false),
+            on_conflict: OnConflict::from_option(get_string_option(options, "on_conflict")),
+            db: get_string_option(options, "db"),
+        }),
+        _ => Err(MissingOptions {
+            command: "insert into",
+            missing: [("table", table.is_none()), ("data", data.is_none())]
+                .into_iter()
+                .filter_map(|(name, is_missing)| is_missing.then_some(name))
+                .collect(),
+        }),
+    }
+}
+
+/// Get a string option value by name
+pub fn get_string_option<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a str> {
+    options.iter().find(|opt| opt.name == name).and_then(|opt| match &opt.value {
+        CommandDataOptionValue::String(value) => Some(value.as_str()),
+        _ => None,
+    })
+}
+
+/// Get a boolean option value by name
+pub fn get_bool_option(options: &[CommandDataOption], name: &str) -> Option<bool> {
+    options.iter().find(|opt| opt.name == name).and_then(|opt| match &opt.value {
+        CommandDataOptionValue::Boolean(value) => Some(*value),
+        _ => None,
+    })
+}
+
+/// Get an integer option value by name
+pub fn get_integer_option(options: &[CommandDataOption], name: &str) -> Option<i64> {
+    options.iter().find(|opt| opt.name == name).and_then(|opt| match &opt.value {
+        CommandDataOptionValue::Integer(value) => Some(*value),
+        _ => None,
+    })
+}
+
+/// Get an attachment option by name; the file itself is in the interaction's resolved data
+pub fn get_attachment_option(options: &[CommandDataOption], name: &str) -> Option<AttachmentId> {
+    options.iter().find(|opt| opt.name == name).and_then(|opt| match &opt.value {
+        CommandDataOptionValue::Attachment(value) => Some(*value),
+        _ => None,
+    })
+}
+
+/// Get a role option value by name
+pub fn get_role_option(options: &[CommandDataOption], name: &str) -> Option<RoleId> {
+    options.iter().find(|opt| opt.name == name).and_then(|opt| match &opt.value {
+        CommandDataOptionValue::Role(value) => Some(*value),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build options the same way Discord sends them (CommandDataOption is non-exhaustive)
+    fn options_from_json(json: &str) -> Vec<CommandDataOption> {
+        serde_json::from_str(json).expect("valid option JSON")
+    }
+
+    fn embed_description(embed: &CreateEmbed) -> String {
+        serde_json::to_value(embed).unwrap()["description"].as_str().unwrap_or_default().to_string()
+    }
+
+    #[test]
+    fn test_get_string_option() {
+        let options = options_from_json(r#"[
+            {"name": "columns", "type": 3, "value": "id, name"},
+            {"name": "from", "type": 3, "value": "users"},
+            {"name": "distinct", "type": 5, "value": true}
+        ]"#);
+
+        assert_eq!(get_string_option(&options, "columns"), Some("id, name"));
+        assert_eq!(get_string_option(&options, "from"), Some("users"));
+        assert_eq!(get_string_option(&options, "where"), None);
+        // Wrong type is treated as missing
+        assert_eq!(get_string_option(&options, "distinct"), None);
+    }
+
+    #[test]
+    fn test_get_bool_option() {
+        let options = options_from_json(r#"[
+            {"name": "distinct", "type": 5, "value": false},
+            {"name": "from", "type": 3, "value": "users"}
+        ]"#);
+
+        assert_eq!(get_bool_option(&options, "distinct"), Some(false));
+        assert_eq!(get_bool_option(&options, "missing"), None);
+        assert_eq!(get_bool_option(&options, "from"), None);
+    }
+
+    #[test]
+    fn test_get_role_option() {
+        let options = options_from_json(r#"[
+            {"name": "role", "type": 8, "value": "123456789"}
+        ]"#);
+
+        assert_eq!(get_role_option(&options, "role"), Some(RoleId::new(123456789)));
+        assert_eq!(get_role_option(&options, "other"), None);
+    }
+
+    #[test]
+    fn test_parse_select_args() {
+        let options = options_from_json(r#"[
+            {"name": "columns", "type": 3, "value": "*"},
+            {"name": "from", "type": 3, "value": "users"},
+            {"name": "where", "type": 3, "value": "age=25"}
+        ]"#);
+
+        assert_eq!(parse_select_args(&options).unwrap(), SelectArgs {
+            columns: "*",
+            from: "users",
+            distinct: None,
+            where_clause: Some("age=25"),
+            ephemeral: false,
+            format: OutputFormat::Table,
+            to_thread: false,
+            max_rows: None,
+            db: None,
+        });
+
+        let options = options_from_json(r#"[
+            {"name": "columns", "type": 3, "value": "*"},
+            {"name": "from", "type": 3, "value": "users"},
+            {"name": "ephemeral", "type": 5, "value": true},
+            {"name": "format", "type": 3, "value": "json"},
+            {"name": "max_rows", "type": 4, "value": 50}
+        ]"#);
+
+        let args = parse_select_args(&options).unwrap();
+        assert!(args.ephemeral);
+        assert_eq!(args.format, OutputFormat::Json);
+        assert_eq!(args.max_rows, Some(50));
+    }
+
+    #[test]
+    fn test_parse_select_args_lists_missing_options() {
+        let options = options_from_json(r#"[
+            {"name": "distinct", "type": 5, "value": true}
+        ]"#);
+
+        let error = parse_select_args(&options).unwrap_err();
+        assert_eq!(error.missing, vec!["columns", "from"]);
+        assert_eq!(embed_description(&error.to_embed()), "`/sql select` requires: `columns`, `from`");
+
+        let options = options_from_json(r#"[
+            {"name": "columns", "type": 3, "value": "*"}
+        ]"#);
+
+        assert_eq!(parse_select_args(&options).unwrap_err().missing, vec!["from"]);
+    }
+
+    #[test]
+    fn test_parse_count_args() {
+        let options = options_from_json(r#"[
+            {"name": "from", "type": 3, "value": "users"},
+            {"name": "where", "type": 3, "value": "age=25"}
+        ]"#);
+
+        assert_eq!(parse_count_args(&options).unwrap(), CountArgs { from: "users", where_clause: Some("age=25"), db: None, rescan: false });
+        assert_eq!(parse_count_args(&[]).unwrap_err().missing, vec!["from"]);
+
+        let options = options_from_json(r#"[
+            {"name": "from", "type": 3, "value": "users"},
+            {"name": "db", "type": 3, "value": "sales"}
+        ]"#);
+        assert_eq!(parse_count_args(&options).unwrap().db, Some("sales"));
+
+        let options = options_from_json(r#"[
+            {"name": "from", "type": 3, "value": "users"},
+            {"name": "rescan", "type": 5, "value": true}
+        ]"#);
+        assert!(parse_count_args(&options).unwrap().rescan);
+    }
+
+    #[test]
+    fn test_parse_join_args() {
+        let options = options_from_json(r#"[
+            {"name": "left", "type": 3, "value": "users"},
+            {"name": "right", "type": 3, "value": "orders"},
+            {"name": "on", "type": 3, "value": "id=user_id"},
+            {"name": "where", "type": 3, "value": "total>5"}
+        ]"#);
+
+        assert_eq!(
+            parse_join_args(&options).unwrap(),
+            JoinArgs { left: "users", right: "orders", on: "id=user_id", kind: JoinKind::Inner, columns: None, where_clause: Some("total>5") }
+        );
+        let options = options_from_json(r#"[
+            {"name": "left", "type": 3, "value": "users"},
+            {"name": "right", "type": 3, "value": "orders"},
+            {"name": "on", "type": 3, "value": "id=user_id"},
+            {"name": "kind", "type": 3, "value": "LEFT"}
+        ]"#);
+        assert_eq!(parse_join_args(&options).unwrap().kind, JoinKind::Left);
+        assert_eq!(parse_join_args(&options[..1]).unwrap_err().missing, vec!["right", "on"]);
+    }
+
+    #[test]
+    fn test_parse_insert_args() {
+        let options = options_from_json(r#"[
+            {"name": "table", "type": 3, "value": "users"},
+            {"name": "data", "type": 3, "value": "1, 'Ann'"},
+            {"name": "on_conflict", "type": 3, "value": "update"}
+        ]"#);
+
+        let args = parse_insert_args(&options).unwrap();
+        assert_eq!((args.table, args.data, args.columns), ("users", "1, 'Ann'", None));
+        assert!(!args.validate_only);
+        assert_eq!(args.on_conflict, OnConflict::Update);
+        assert_eq!(args.db, None);
+        assert_eq!(parse_insert_args(&[]).unwrap_err().missing, vec!["table", "data"]);
+    }
+
+    #[test]
+    fn test_parse_create_table_args() {
+        let options = options_from_json(r#"[
+            {"name": "name", "type": 3, "value": "orders"},
+            {"name": "schema", "type": 3, "value": "id INT PRIMARY KEY"},
+            {"name": "shards", "type": 4, "value": 4}
+        ]"#);
+
+        let args = parse_create_table_args(&options).unwrap();
+        assert_eq!(args, CreateTableArgs { name: "orders", schema: Some("id INT PRIMARY KEY"), comment: None, if_not_exists: false, shards: 4 });
+        let unsharded = options_from_json(r#"[{"name": "name", "type": 3, "value": "orders"}]"#);
+        assert_eq!(parse_create_table_args(&unsharded).unwrap().shards, 1);
+        assert_eq!(parse_create_table_args(&[]).unwrap_err().missing, vec!["name"]);
+    }
+}
@@ -0,0 +1,236 @@
+// Autocomplete for database, table and column names in /sql options
+
+use serenity::builder::{CreateAutocompleteResponse, CreateInteractionResponse};
+use serenity::model::application::{CommandDataOption, CommandDataOptionValue, CommandInteraction};
+use serenity::model::id::{GuildId, UserId};
+use serenity::prelude::Context;
+use crate::commands::options::get_string_option;
+use crate::guards::require_selected_db;
+use crate::services::{find_database_category, find_table_channel, get_channels_cached, list_databases, list_tables};
+use crate::utils::{canonical_name, sanitize_channel_name, DB_PREFIX, TABLE_PREFIX};
+use super::select::{parse_schema_from_topic, split_qualified_table};
+
+/// Most choices Discord accepts in one autocomplete response
+const MAX_CHOICES: usize = 25;
+/// Longest choice value Discord accepts
+const MAX_CHOICE_LENGTH: usize = 100;
+
+/// Answer an autocomplete request for a `/sql` option: database names for `use`, table names for `from`/`table`,
+/// schema column names for `columns`. Anything that can't be looked up (no database selected, unknown table)
+/// answers with no choices.
+pub async fn respond(ctx: &Context, interaction: &CommandInteraction) {
+    let Some(focused) = interaction.data.autocomplete() else {
+        return;
+    };
+    let (Some(guild_id), Some(params)) = (interaction.guild_id, subcommand_params(&interaction.data.options)) else {
+        return;
+    };
+    let user_id = interaction.user.id;
+
+    let subcommand = interaction.data.options.first().map(|option| option.name.as_str());
+    let choices = match focused.name {
+        "name" if subcommand == Some("use") => database_choices(ctx, guild_id, focused.value).await,
+        "from" | "table" => table_choices(ctx, guild_id, user_id, params, focused.value).await,
+        "columns" => column_choices(ctx, guild_id, user_id, params, focused.value).await,
+        _ => Vec::new(),
+    };
+    let response = choices
+        .into_iter()
+        .fold(CreateAutocompleteResponse::new(), |response, choice| response.add_string_choice(choice.clone(), choice));
+    if let Err(e) = interaction.create_response(&ctx.http, CreateInteractionResponse::Autocomplete(response)).await {
+        tracing::warn!("Failed to send autocomplete choices: {e}");
+    }
+}
+
+/// Options of the innermost subcommand (`/sql select ...`, `/sql insert into ...`)
+fn subcommand_params(options: &[CommandDataOption]) -> Option<&[CommandDataOption]> {
+    match &options.first()?.value {
+        CommandDataOptionValue::SubCommand(params) => Some(params),
+        CommandDataOptionValue::SubCommandGroup(options) => subcommand_params(options),
+        _ => None,
+    }
+}
+
+/// The database a subcommand works in: its `db` option, otherwise the user's `/sql use` selection
+async fn database_for(ctx: &Context, guild_id: GuildId, user_id: UserId, db: Option<&str>) -> Option<String> {
+    match db {
+        Some(db) => Some(canonical_name(db, DB_PREFIX).0),
+        None => require_selected_db(ctx, guild_id, user_id).await.ok(),
+    }
+}
+
+async fn database_choices(ctx: &Context, guild_id: GuildId, typed: &str) -> Vec<String> {
+    let Ok(channels) = get_channels_cached(ctx, guild_id).await else {
+        return Vec::new();
+    };
+    let databases: Vec<String> = list_databases(&channels)
+        .iter()
+        .map(|category| category.name.trim_start_matches(DB_PREFIX).to_string())
+        .collect();
+    database_suggestions(&databases, typed)
+}
+
+async fn table_choices(ctx: &Context, guild_id: GuildId, user_id: UserId, params: &[CommandDataOption], typed: &str) -> Vec<String> {
+    // `sales.us` completes the tables of database `sales`
+    let db = typed.split_once('.').map(|(db, _)| db).or(get_string_option(params, "db"));
+    let Some(database) = database_for(ctx, guild_id, user_id, db).await else {
+        return Vec::new();
+    };
+    let Ok(channels) = get_channels_cached(ctx, guild_id).await else {
+        return Vec::new();
+    };
+    let Some(category) = find_database_category(&channels, &database) else {
+        return Vec::new();
+    };
+    let tables: Vec<String> = list_tables(&channels, category.id)
+        .iter()
+        .map(|table| table.name.trim_start_matches(TABLE_PREFIX).to_string())
+        .collect();
+    table_suggestions(&tables, typed)
+}
+
+async fn column_choices(ctx: &Context, guild_id: GuildId, user_id: UserId, params: &[CommandDataOption], typed: &str) -> Vec<String> {
+    let Some(table) = get_string_option(params, "from").or(get_string_option(params, "table")) else {
+        return Vec::new();
+    };
+    let Ok((db, table)) = split_qualified_table(table, get_string_option(params, "db")) else {
+        return Vec::new();
+    };
+    let Some(database) = database_for(ctx, guild_id, user_id, db).await else {
+        return Vec::new();
+    };
+    let Ok(channels) = get_channels_cached(ctx, guild_id).await else {
+        return Vec::new();
+    };
+    let Some(channel) = find_database_category(&channels, &database)
+        .and_then(|category| find_table_channel(&channels, category.id, &sanitize_channel_name(table).0))
+    else {
+        return Vec::new();
+    };
+    let schema = channel.topic.as_deref().and_then(|topic| parse_schema_from_topic(topic).ok()).unwrap_or_default();
+    let columns: Vec<String> = schema.into_iter().map(|column| column.name).collect();
+    column_suggestions(&columns, typed)
+}
+
+/// `candidates` containing `partial` (ignoring case), those starting with it first
+fn matching<'a>(candidates: &'a [String], partial: &str) -> Vec<&'a str> {
+    let partial = partial.trim().to_lowercase();
+    let mut matches: Vec<&str> = candidates
+        .iter()
+        .map(String::as_str)
+        .filter(|candidate| candidate.to_lowercase().contains(&partial))
+        .collect();
+    matches.sort_by_key(|candidate| (!candidate.to_lowercase().starts_with(&partial), candidate.to_string()));
+    matches
+}
+
+/// Database names matching what was typed, ignoring a typed `db_` prefix
+fn database_suggestions(databases: &[String], typed: &str) -> Vec<String> {
+    let typed = typed.trim();
+    let partial = typed.strip_prefix(DB_PREFIX).unwrap_or(typed);
+    matching(databases, partial)
+        .into_iter()
+        .filter(|database| database.len() <= MAX_CHOICE_LENGTH)
+        .take(MAX_CHOICES)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Table names matching what was typed; `sales.us` suggests `sales.users`, keeping the database qualifier
+fn table_suggestions(tables: &[String], typed: &str) -> Vec<String> {
+    let (qualifier, partial) = match typed.split_once('.') {
+        Some((db, partial)) => (Some(db.trim()), partial),
+        None => (None, typed),
+    };
+    matching(tables, partial)
+        .into_iter()
+        .map(|table| match qualifier {
+            Some(db) => format!("{}.{}", db, table),
+            None => table.to_string(),
+        })
+        .filter(|choice| choice.len() <= MAX_CHOICE_LENGTH)
+        .take(MAX_CHOICES)
+        .collect()
+}
+
+/// Complete the last entry of a comma-separated column list. Columns already listed aren't suggested again,
+/// and an empty list also offers `*`.
+fn column_suggestions(columns: &[String], typed: &str) -> Vec<String> {
+    let (listed, partial) = match typed.rsplit_once(',') {
+        Some((listed, partial)) => (Some(listed.trim()), partial),
+        None => (None, typed),
+    };
+    let chosen: Vec<String> = listed
+        .into_iter()
+        .flat_map(|listed| listed.split(','))
+        .filter_map(|entry| entry.split_whitespace().next())
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut suggestions = Vec::new();
+    if listed.is_none() && partial.trim().is_empty() {
+        suggestions.push("*".to_string());
+    }
+    suggestions.extend(
+        matching(columns, partial)
+            .into_iter()
+            .filter(|column| !chosen.contains(&column.to_lowercase()))
+            .map(|column| match listed {
+                Some(listed) => format!("{}, {}", listed, column),
+                None => column.to_string(),
+            }),
+    );
+    suggestions.retain(|choice| choice.len() <= MAX_CHOICE_LENGTH);
+    suggestions.truncate(MAX_CHOICES);
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_database_suggestions() {
+        let databases = names(&["shop", "blog", "shop_archive", "workshop"]);
+        assert_eq!(database_suggestions(&databases, ""), names(&["blog", "shop", "shop_archive", "workshop"]));
+        assert_eq!(database_suggestions(&databases, "SHOP"), names(&["shop", "shop_archive", "workshop"]));
+        // Typing the internal prefix still matches
+        assert_eq!(database_suggestions(&databases, "db_bl"), names(&["blog"]));
+        assert!(database_suggestions(&databases, "missing").is_empty());
+
+        let many: Vec<String> = (0..40).map(|i| format!("db{:02}", i)).collect();
+        assert_eq!(database_suggestions(&many, "").len(), MAX_CHOICES);
+    }
+
+    #[test]
+    fn test_table_suggestions() {
+        let tables = names(&["orders", "users", "user_roles", "audit_users"]);
+        assert_eq!(table_suggestions(&tables, ""), names(&["audit_users", "orders", "user_roles", "users"]));
+        // Prefix matches come before other matches, ignoring case
+        assert_eq!(table_suggestions(&tables, "Us"), names(&["user_roles", "users", "audit_users"]));
+        assert_eq!(table_suggestions(&tables, "sales.ord"), names(&["sales.orders"]));
+        assert!(table_suggestions(&tables, "missing").is_empty());
+
+        let many: Vec<String> = (0..40).map(|i| format!("table{:02}", i)).collect();
+        assert_eq!(table_suggestions(&many, "table").len(), MAX_CHOICES);
+    }
+
+    #[test]
+    fn test_column_suggestions() {
+        let columns = names(&["id", "name", "email", "nickname"]);
+        assert_eq!(column_suggestions(&columns, ""), names(&["*", "email", "id", "name", "nickname"]));
+        assert_eq!(column_suggestions(&columns, "na"), names(&["name", "nickname"]));
+        // Later entries extend the list and skip columns already in it
+        assert_eq!(column_suggestions(&columns, "id, name,"), names(&["id, name, email", "id, name, nickname"]));
+        assert_eq!(column_suggestions(&columns, "ID AS key, e"), names(&["ID AS key, email", "ID AS key, name", "ID AS key, nickname"]));
+        assert_eq!(column_suggestions(&[], ""), names(&["*"]));
+
+        // Choices longer than Discord allows are dropped
+        let long_list = format!("{},", "x".repeat(MAX_CHOICE_LENGTH));
+        assert!(column_suggestions(&columns, &long_list).is_empty());
+    }
+}
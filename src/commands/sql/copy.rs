@@ -0,0 +1,48 @@
+// /sql copy from:<table> to:<table> [with_data]
+
+use std::error::Error;
+use serenity::builder::CreateEmbed;
+use serenity::model::id::{GuildId, UserId};
+use crate::api::copy_table;
+use crate::error::SqlError;
+use crate::guards::{require_selected_db, require_writable};
+use crate::logging::{audit, log_debug, log_info, AuditEntry};
+use crate::services::BotContext;
+use crate::utils::{canonical_name, create_success_embed, TABLE_PREFIX};
+
+pub fn register() -> Result<(), Box<dyn Error>> {
+    log_debug("Registering COPY command");
+    Ok(())
+}
+
+/// Create table `to` in the current database with the schema and comment of `from`,
+/// re-posting every row of `from` into it when `with_data` is set.
+/// Returns Ok(embed) or Err(SqlError). The attempt is written to the audit log.
+pub async fn run(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId, from: &str, to: &str, with_data: bool) -> Result<CreateEmbed, SqlError> {
+    log_info(&format!("COPY command executed from table: {} to table: {} (with_data: {})", from, to, with_data));
+    let result = match require_writable(ctx).await {
+        Ok(()) => copy(ctx, guild_id, user_id, from, to, with_data).await,
+        Err(e) => Err(e),
+    };
+    audit(ctx, AuditEntry {
+        database: require_selected_db(ctx, guild_id, user_id).await.ok(),
+        table: Some(canonical_name(to, TABLE_PREFIX).0),
+        ..AuditEntry::new(guild_id, user_id, "COPY", result.is_ok())
+    }).await;
+    result
+}
+
+async fn copy(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId, from: &str, to: &str, with_data: bool) -> Result<CreateEmbed, SqlError> {
+    let current_db = require_selected_db(ctx, guild_id, user_id).await?;
+    let copied = copy_table(ctx, guild_id, &current_db, from, to, with_data).await?;
+
+    let source = canonical_name(from, TABLE_PREFIX).0;
+    let mut description = format!("Table **{}** created in database **{}** with the schema of **{}**", copied.table, current_db, source);
+    if with_data {
+        description.push_str(&format!("\n\n**Rows copied:** {}", copied.rows));
+    } else {
+        description.push_str("\n\n*Schema only: no rows were copied.*");
+    }
+    log_info(&format!("SUCCESS: Copied {}{} to {}{} ({} rows)", TABLE_PREFIX, source, TABLE_PREFIX, copied.table, copied.rows));
+    Ok(create_success_embed("✔️ Table Copied", &description))
+}
@@ -0,0 +1,138 @@
+// /sql count from <table> [where] [rescan]
+
+use std::error::Error;
+use serenity::builder::CreateEmbedFooter;
+use serenity::prelude::Context;
+use serenity::model::channel::GuildChannel;
+use serenity::model::id::{GuildId, UserId};
+use crate::commands::options::CountArgs;
+use crate::guards::{require_table, resolve_database};
+use crate::logging::{log_debug, log_info};
+use crate::utils::{create_error_embed, create_info_embed};
+use crate::services::discord_fs::max_row_messages;
+use crate::services::shards::fetch_table_messages;
+use crate::services::{Backend, BotContext};
+use crate::services::topic_meta::TopicMeta;
+use crate::sql_parser::{ColumnDefinition, SqlValue};
+use super::select::{check_where_columns, evaluate_where_condition, parse_schema_from_topic, rows_from_messages};
+
+pub fn register() -> Result<(), Box<dyn Error>> {
+    log_debug("Registering COUNT command");
+    Ok(())
+}
+
+/// COUNT the rows of a table (Discord channel), optionally filtered by a WHERE clause.
+/// Lighter than SELECT: no column selection or result table is built.
+/// Without a WHERE clause or `rescan` the `Rows:` count cached in the table topic is used when present, so no messages
+/// are fetched. The counter can drift (rows deleted by hand aren't counted off), so the reply says where it came from.
+pub async fn run(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+    args: &CountArgs<'_>
+) -> Result<serenity::builder::CreateEmbed, serenity::builder::CreateEmbed> {
+    let CountArgs { from: table_name, where_clause, db, rescan } = *args;
+    log_info(&format!("COUNT command executed: table={}, where={:?}", table_name, where_clause));
+
+    let current_db = resolve_database(ctx, guild_id, user_id, db).await?;
+    let table_channel = require_table(ctx, guild_id, &current_db, table_name).await?;
+
+    let schema = match &table_channel.topic {
+        Some(topic) => parse_schema_from_topic(topic)?,
+        None => Vec::new(),
+    };
+
+    if where_clause.is_none()
+        && !rescan
+        && let Some(count) = cached_row_count(ctx, &table_channel).await
+    {
+        return Ok(create_info_embed("🔢 COUNT Result", &count_description(table_name, None, count))
+            .footer(CreateEmbedFooter::new("From the row count cached in the table topic, which may be stale. Use rescan:true to count the stored rows.")));
+    }
+
+    let max_rows = max_row_messages();
+    let fetched = match fetch_table_messages(ctx, &table_channel, max_rows).await {
+        Ok(fetched) => fetched,
+        Err(_) => {
+            return Err(create_error_embed(
+                "Table Access Error",
+                "Could not read messages from table. Please check bot permissions."
+            ));
+        }
+    };
+
+    let contents: Vec<&str> = fetched.messages.iter().map(|message| message.content.as_str()).collect();
+    let (schema, rows) = rows_from_messages(&contents, schema);
+    if let Some(where_cond) = where_clause {
+        check_where_columns(where_cond, &schema)?;
+    }
+    let count = count_matching_rows(&rows, &schema, where_clause);
+
+    let mut embed = create_info_embed("🔢 COUNT Result", &count_description(table_name, where_clause, count));
+    if fetched.truncated {
+        embed = embed.footer(CreateEmbedFooter::new(format!(
+            "⚠️ Scan limit reached: only the newest {} rows were counted",
+            max_rows
+        )));
+    }
+    Ok(embed)
+}
+
+/// The `Rows:` counter of the table's topic as Discord has it now, rather than the channel cache's copy
+async fn cached_row_count(ctx: &impl BotContext, table_channel: &GuildChannel) -> Option<usize> {
+    match ctx.backend().get_channel(table_channel.id).await {
+        Ok(channel) => TopicMeta::parse(channel.topic.as_deref()).rows,
+        Err(e) => {
+            tracing::warn!("Failed to read the topic of {}: {e}", table_channel.name);
+            None
+        }
+    }
+}
+
+/// Body of the COUNT embed: the table, the filter if any, and the count
+fn count_description(table_name: &str, where_clause: Option<&str>, count: usize) -> String {
+    let mut description = format!("**Table:** {}\n", table_name);
+    if let Some(where_cond) = where_clause {
+        description.push_str(&format!("**Filter:** WHERE {}\n", where_cond));
+    }
+    description.push_str(&format!(
+        "\n**{}** {} {}.",
+        count,
+        if count == 1 { "row" } else { "rows" },
+        if where_clause.is_some() { if count == 1 { "matches" } else { "match" } } else { "in table" }
+    ));
+    description
+}
+
+/// Count rows that satisfy the WHERE clause (all rows when there is none)
+fn count_matching_rows(rows: &[Vec<SqlValue>], schema: &[ColumnDefinition], where_clause: Option<&str>) -> usize {
+    match where_clause {
+        Some(condition) => rows.iter().filter(|row| evaluate_where_condition(row, schema, condition)).count(),
+        None => rows.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_parser::parse_column_definitions;
+
+    fn sample_rows() -> Vec<Vec<SqlValue>> {
+        vec![
+            vec![SqlValue::String("John".to_string()), SqlValue::Integer(25)],
+            vec![SqlValue::String("Jane".to_string()), SqlValue::Integer(30)],
+            vec![SqlValue::String("Bob".to_string()), SqlValue::Integer(25)],
+        ]
+    }
+
+    #[test]
+    fn test_count_matching_rows() {
+        let schema = parse_column_definitions("name VARCHAR(50), age INT").unwrap();
+        let rows = sample_rows();
+
+        assert_eq!(count_matching_rows(&rows, &schema, None), 3);
+        assert_eq!(count_matching_rows(&rows, &schema, Some("age=25")), 2);
+        assert_eq!(count_matching_rows(&rows, &schema, Some("age=25 AND NOT name='Bob'")), 1);
+        assert_eq!(count_matching_rows(&rows, &schema, Some("name='Nobody'")), 0);
+    }
+}
@@ -2,54 +2,49 @@
 
 use std::error::Error;
 use serenity::prelude::Context;
-use serenity::model::id::GuildId;
-use serenity::model::channel::ChannelType;
-use crate::logging::{log_info, log_error};
-use crate::utils::{sanitize_channel_name, create_success_embed, create_error_embed};
+use serenity::model::id::{GuildId, UserId};
+use serenity::builder::CreateEmbed;
+use crate::api::create_database;
+use crate::error::SqlError;
+use crate::guards::require_writable;
+use crate::logging::{audit, log_debug, log_info, log_error, AuditEntry};
+use crate::utils::{canonical_name, DB_PREFIX, create_success_embed};
 
 pub fn register() -> Result<(), Box<dyn Error>> {
-    log_info("Registering CREATE DB command");
+    log_debug("Registering CREATE DB command");
     Ok(())
 }
 
 /// Create a category named `db_<db_name>` in the given guild.
-/// Returns Ok(embed) or Err(embed).
-pub async fn run(ctx: &Context, guild_id: GuildId, db_name: &str) -> Result<serenity::builder::CreateEmbed, serenity::builder::CreateEmbed> {
+/// Returns Ok(embed) or Err(SqlError). The attempt is written to the audit log.
+pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, db_name: &str) -> Result<CreateEmbed, SqlError> {
     log_info(&format!("CREATE DB command executed for database: {}", db_name));
-    
-    // Sanitize the database name
-    let (sanitized_name, was_changed) = sanitize_channel_name(db_name);
-    
-    if sanitized_name.is_empty() {
-        let embed = create_error_embed(
-            "✖️ Invalid Database Name",
-            "Database name cannot be empty after sanitization. Please provide a valid name with alphanumeric characters."
-        );
-        return Err(embed);
-    }
-    
-    let channel_name = format!("db_{}", sanitized_name);
-    let builder = serenity::builder::CreateChannel::new(&channel_name).kind(ChannelType::Category);
-    
-    match guild_id.create_channel(&ctx.http, builder).await {
-        Ok(_) => {
-            let mut description = format!("Database **{}** has been created successfully!", channel_name);
-            if was_changed {
-                description.push_str(&format!("\n\n*Name sanitized from `{}` to `{}`*", db_name, sanitized_name));
-            }
-            
-            let embed = create_success_embed("✔️ Database Created", &description);
-            log_info(&format!("SUCCESS: Database {} created", channel_name));
-            Ok(embed)
-        },
+    let result = match require_writable(ctx).await {
+        Ok(()) => create(ctx, guild_id, db_name).await,
+        Err(e) => Err(e),
+    };
+    audit(ctx, AuditEntry {
+        database: Some(canonical_name(db_name, DB_PREFIX).0),
+        ..AuditEntry::new(guild_id, user_id, "CREATE DB", result.is_ok())
+    }).await;
+    result
+}
+
+async fn create(ctx: &Context, guild_id: GuildId, db_name: &str) -> Result<CreateEmbed, SqlError> {
+    let sanitized_name = match create_database(ctx, guild_id, db_name).await {
+        Ok(name) => name,
         Err(e) => {
-            tracing::error!("Failed to create category: {e}");
-            let embed = create_error_embed(
-                "✖️ Database Creation Failed",
-                "Failed to create database. Please check bot permissions or try again."
-            );
             log_error("Failed to create database");
-            Err(embed)
+            return Err(e);
         }
+    };
+    
+    let channel_name = format!("{}{}", DB_PREFIX, sanitized_name);
+    let mut description = format!("Database **{}** has been created successfully!", sanitized_name);
+    if sanitized_name != db_name {
+        description.push_str(&format!("\n\n*Name sanitized from `{}` to `{}`*", db_name, sanitized_name));
     }
+    
+    log_info(&format!("SUCCESS: Database {} created", channel_name));
+    Ok(create_success_embed("✔️ Database Created", &description))
 }
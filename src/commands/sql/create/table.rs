@@ -1,166 +1,122 @@
-// /sql create table <name> [schema]
+// /sql create table <name> [schema] [comment] [shards]
 
 use std::error::Error;
-use serenity::prelude::Context;
 use serenity::model::id::{GuildId, UserId};
-use serenity::model::channel::ChannelType;
-use crate::state::CurrentDB;
-use crate::logging::{log_info, log_error};
-use crate::utils::{sanitize_channel_name, create_success_embed, create_error_embed};
-use crate::sql_parser::parse_column_definitions;
+use serenity::builder::CreateEmbed;
+use crate::api::{create_sharded_table, schema_topic, table_schema};
+use crate::commands::options::CreateTableArgs;
+use crate::error::SqlError;
+use crate::guards::{require_selected_db, require_table, require_writable};
+use crate::services::BotContext;
+use crate::logging::{audit, log_debug, log_info, log_error, AuditEntry};
+use crate::sql_parser::{parse_column_definitions, ColumnDefinition};
+use crate::utils::{canonical_name, TABLE_PREFIX, create_info_embed, create_success_embed, create_warning_embed};
 
 pub fn register() -> Result<(), Box<dyn Error>> {
-    log_info("Registering CREATE TABLE command");
+    log_debug("Registering CREATE TABLE command");
     Ok(())
 }
 
 /// Create a text channel named `table_<table_name>` under the current database category.
-/// If schema is provided, parse and store the column definitions.
-/// Returns Ok(embed) or Err(embed).
-pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, table_name: &str, schema: Option<&str>) -> Result<serenity::builder::CreateEmbed, serenity::builder::CreateEmbed> {
-    log_info(&format!("CREATE TABLE command executed for table: {} with schema: {:?}", table_name, schema));
-    
-    // Parse schema if provided
-    let parsed_schema = if let Some(schema_str) = schema {
-        match parse_column_definitions(schema_str) {
-            Ok(columns) => Some(columns),
-            Err(e) => {
-                let embed = create_error_embed(
-                    "✖️ Invalid Table Schema",
-                    &format!("**Schema Error:**\n{}\n\n💡 **Tip:** Use formats like `id INT`, `name VARCHAR(255)`, `active BOOLEAN`", e)
-                );
-                return Err(embed);
-            }
+/// If schema is provided, parse and store the column definitions; a comment is stored with them.
+/// With `if_not_exists`, an existing table is reported instead of rejected, with a warning if its schema differs.
+/// With `shards` above 1, the rows are spread over that many channels.
+/// Returns Ok(embed) or Err(SqlError). The attempt is written to the audit log.
+pub async fn run(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId, args: &CreateTableArgs<'_>) -> Result<CreateEmbed, SqlError> {
+    log_info(&format!("CREATE TABLE command executed for table: {} with schema: {:?}, shards: {}", args.name, args.schema, args.shards));
+    let result = match require_writable(ctx).await {
+        Ok(()) => create(ctx, guild_id, user_id, args).await,
+        Err(e) => Err(e),
+    };
+    audit(ctx, AuditEntry {
+        database: require_selected_db(ctx, guild_id, user_id).await.ok(),
+        table: Some(canonical_name(args.name, TABLE_PREFIX).0),
+        ..AuditEntry::new(guild_id, user_id, "CREATE TABLE", result.is_ok())
+    }).await;
+    result
+}
+
+async fn create(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId, args: &CreateTableArgs<'_>) -> Result<CreateEmbed, SqlError> {
+    let CreateTableArgs { name: table_name, schema, comment, if_not_exists, shards } = *args;
+    let current_db = require_selected_db(ctx, guild_id, user_id).await?;
+    let (sanitized_name, columns) = match create_sharded_table(ctx, guild_id, &current_db, table_name, schema, comment, shards).await {
+        Ok(created) => created,
+        Err(SqlError::TableExists { table, database, .. }) if if_not_exists => {
+            // create_table parsed the schema before finding the table, so this can't fail
+            let requested = schema.map(parse_column_definitions).transpose().map_err(SqlError::InvalidSchema)?.unwrap_or_default();
+            let existing = table_schema(&require_table(ctx, guild_id, &database, &table).await?)?;
+            log_info(&format!("Table {}{} already exists (if_not_exists); nothing created", TABLE_PREFIX, table));
+            return Ok(existing_table_embed(&table, &database, &existing, &requested));
+        }
+        Err(e) => {
+            log_error("Failed to create table");
+            return Err(e);
         }
-    } else {
-        None
     };
     
-    // Sanitize the table name
-    let (sanitized_name, was_changed) = sanitize_channel_name(table_name);
+    let mut description = format!("Table **{}** created in database **{}**", sanitized_name, current_db);
+    if sanitized_name != table_name {
+        description.push_str(&format!("\n\n*Name sanitized from `{}` to `{}`*", table_name, sanitized_name));
+    }
+    if let Some(comment) = comment.map(str::trim).filter(|comment| !comment.is_empty()) {
+        description.push_str(&format!("\n\n**Comment:** {}", comment));
+    }
+    if shards > 1 {
+        description.push_str(&format!("\n\n**Shards:** rows are spread over {} channels", shards));
+    }
     
-    if sanitized_name.is_empty() {
-        let embed = create_error_embed(
-            "✖️ Invalid Table Name",
-            "Table name cannot be empty after sanitization. Please provide a valid name with alphanumeric characters."
-        );
-        return Err(embed);
+    // Add schema information to success message
+    if !columns.is_empty() {
+        description.push_str("\n\n**Schema:**\n");
+        for column in &columns {
+            description.push_str(&format!("• {}\n", column));
+        }
     }
     
-    // Get the current database for this user
-    let data_read = ctx.data.read().await;
-    let current_db = if let Some(map_arc) = data_read.get::<CurrentDB>() {
-        let map = map_arc.lock().await;
-        map.get(&(guild_id, user_id)).cloned()
-    } else {
-        None
-    };
-    drop(data_read);
+    log_info(&format!("SUCCESS: Table {}{} created with {} columns", TABLE_PREFIX, sanitized_name, columns.len()));
+    Ok(create_success_embed("✔️ Table Created", &description))
+}
 
-    let current_db = match current_db {
-        Some(db_name) => db_name,
-        None => {
-            let embed = create_error_embed(
-                "✖️ No Database Selected",
-                "No database selected. Use `/sql use <db_name>` first to select a database."
-            );
-            return Err(embed);
-        }
+/// Reply for `if_not_exists` when the table is already there: informational if its schema is the one
+/// requested, a warning listing both schemas if not. The existing table is never changed.
+fn existing_table_embed(table: &str, database: &str, existing: &[ColumnDefinition], requested: &[ColumnDefinition]) -> CreateEmbed {
+    let description = format!("Table **{}** already exists in database **{}**; no changes were made.", table, database);
+    if schema_topic(existing) == schema_topic(requested) {
+        return create_info_embed("ℹ️ Table Already Exists", &description);
+    }
+    let describe = |columns: &[ColumnDefinition]| {
+        if columns.is_empty() { "*(no schema)*".to_string() } else { format!("`{}`", schema_topic(columns)) }
     };
+    create_warning_embed(
+        "⚠️ Table Exists With a Different Schema",
+        &format!("{}\n\n**Existing schema:** {}\n**Requested schema:** {}", description, describe(existing), describe(requested)),
+    )
+}
 
-    // Find the database category
-    match guild_id.channels(&ctx.http).await {
-        Ok(channels) => {
-            let db_category_name = format!("db_{}", current_db);
-            let db_category = channels.values()
-                .find(|c| c.name == db_category_name && c.kind == ChannelType::Category);
-            
-            if let Some(category) = db_category {
-                // Check if table already exists
-                let table_channel_name = format!("table_{}", sanitized_name);
-                let existing_table = channels.values()
-                    .find(|c| c.name == table_channel_name && c.parent_id == Some(category.id));
-                
-                if existing_table.is_some() {
-                    let embed = create_error_embed(
-                        "✖️ Table Already Exists",
-                        &format!("Table **{}** already exists in database **{}**", sanitized_name, current_db)
-                    );
-                    return Err(embed);
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                // Create the table channel
-                let mut builder = serenity::builder::CreateChannel::new(&table_channel_name)
-                    .kind(ChannelType::Text)
-                    .category(category.id);
-                
-                // Add schema to channel topic if provided
-                if let Some(columns) = &parsed_schema {
-                    let schema_description = columns.iter()
-                        .map(|col| {
-                            let mut col_def = format!("{} {}", col.name, col.data_type);
-                            
-                            // Add size if specified (e.g., VARCHAR(10))
-                            if let Some(size) = col.size {
-                                col_def = format!("{}({})", col_def, size);
-                            }
-                            
-                            // Add PRIMARY KEY constraint
-                            if col.primary_key {
-                                col_def = format!("{} PRIMARY KEY", col_def);
-                            }
-                            
-                            col_def
-                        })
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    builder = builder.topic(&format!("Schema: {}", schema_description));
-                }
-                
-                match guild_id.create_channel(&ctx.http, builder).await {
-                    Ok(_channel) => {
-                        let mut description = format!("Table **{}** created in database **{}**", sanitized_name, current_db);
-                        if was_changed {
-                            description.push_str(&format!("\n\n*Name sanitized from `{}` to `{}`*", table_name, sanitized_name));
-                        }
-                        
-                        // Add schema information to success message
-                        if let Some(columns) = &parsed_schema {
-                            description.push_str("\n\n**Schema:**\n");
-                            for column in columns {
-                                description.push_str(&format!("• {}\n", column));
-                            }
-                        }
-                        
-                        let embed = create_success_embed("✔️ Table Created", &description);
-                        log_info(&format!("SUCCESS: Table {} created with {} columns", table_channel_name, parsed_schema.as_ref().map_or(0, |s| s.len())));
-                        Ok(embed)
-                    },
-                    Err(e) => {
-                        tracing::error!("Failed to create table channel: {e}");
-                        let embed = create_error_embed(
-                            "✖️ Table Creation Failed",
-                            "Failed to create table. Please check bot permissions or try again."
-                        );
-                        log_error("Failed to create table");
-                        Err(embed)
-                    }
-                }
-            } else {
-                let embed = create_error_embed(
-                    "✖️ Database Not Found",
-                    &format!("Database **{}** not found. Create it first with `/sql create db {}`", current_db, current_db)
-                );
-                Err(embed)
-            }
-        },
-        Err(e) => {
-            tracing::error!("Failed to get channels: {e}");
-            let embed = create_error_embed(
-                "✖️ Permission Error",
-                "Failed to list channels. Please check bot permissions."
-            );
-            Err(embed)
-        }
+    fn embed_json(existing: &str, requested: &str) -> serde_json::Value {
+        let parse = |schema: &str| if schema.is_empty() { Vec::new() } else { parse_column_definitions(schema).unwrap() };
+        serde_json::to_value(existing_table_embed("users", "shop", &parse(existing), &parse(requested))).unwrap()
+    }
+
+    #[test]
+    fn test_existing_table_embed() {
+        // Same schema, even written differently: nothing to warn about
+        let embed = embed_json("id INT PRIMARY KEY, name VARCHAR(50)", "id int primary key, name varchar(50)");
+        assert_eq!(embed["title"], "ℹ️ Table Already Exists");
+        assert!(embed["description"].as_str().unwrap().contains("no changes were made"));
+
+        let embed = embed_json("id INT PRIMARY KEY, name VARCHAR(50)", "id INT PRIMARY KEY, name VARCHAR(100)");
+        assert_eq!(embed["title"], "⚠️ Table Exists With a Different Schema");
+        let description = embed["description"].as_str().unwrap();
+        assert!(description.contains("**Existing schema:** `id INT PRIMARY KEY, name VARCHAR(50)`"));
+        assert!(description.contains("**Requested schema:** `id INT PRIMARY KEY, name VARCHAR(100)`"));
+
+        let embed = embed_json("", "id INT");
+        assert!(embed["description"].as_str().unwrap().contains("**Existing schema:** *(no schema)*"));
+        assert_eq!(embed_json("", "")["title"], "ℹ️ Table Already Exists");
     }
 }
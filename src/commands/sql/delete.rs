@@ -1,14 +1,28 @@
 // /sql delete <table> [where]
 
 use std::error::Error;
-use crate::logging::log_info;
+use serenity::prelude::Context;
+use serenity::model::id::{GuildId, UserId};
+use crate::guards::{require_selected_db, require_writable};
+use crate::logging::{audit, log_debug, log_info, AuditEntry};
+use crate::utils::{canonical_name, TABLE_PREFIX};
 
 pub fn register() -> Result<(), Box<dyn Error>> {
-    log_info("Registering DELETE command");
+    log_debug("Registering DELETE command");
     Ok(())
 }
 
-pub async fn run(table_name: &str) -> Result<String, String> {
+/// The attempt is written to the audit log, including one refused in read-only mode.
+pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, table_name: &str) -> Result<String, String> {
     log_info(&format!("DELETE command executed for table: {}", table_name));
-    Ok(format!("Would delete rows from table `{}` (placeholder)", table_name))
+    let result = match require_writable(ctx).await {
+        Ok(()) => Ok(format!("Would delete rows from table `{}` (placeholder)", table_name)),
+        Err(e) => Err(e.to_string()),
+    };
+    audit(ctx, AuditEntry {
+        database: require_selected_db(ctx, guild_id, user_id).await.ok(),
+        table: Some(canonical_name(table_name, TABLE_PREFIX).0),
+        ..AuditEntry::new(guild_id, user_id, "DELETE", result.is_ok())
+    }).await;
+    result
 }
@@ -1,82 +1,283 @@
 // /sql drop db <name>
 
+use std::collections::HashMap;
 use std::error::Error;
+use std::time::Instant;
 use serenity::prelude::Context;
-use serenity::model::id::GuildId;
-use serenity::model::channel::ChannelType;
-use crate::logging::{log_info, log_error};
-use crate::utils::{sanitize_channel_name, create_success_embed, create_error_embed, create_warning_embed};
+use serenity::builder::{CreateEmbed, EditMessage};
+use serenity::model::application::CommandInteraction;
+use serenity::model::channel::{ChannelType, Reaction, ReactionType};
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+use crate::guards::require_writable;
+use crate::logging::{audit, log_debug, log_info, log_error, AuditEntry};
+use crate::services::invalidate_channels;
+use crate::state::{clear_database_selections, CurrentDB, PendingConfirmations, PendingDrop, CONFIRMATION_TIMEOUT};
+use crate::utils::{canonical_name, DB_PREFIX, create_success_embed, create_error_embed, create_info_embed, create_warning_embed};
+
+/// Reaction that performs a pending drop
+pub const CONFIRM_EMOJI: &str = "✅";
+/// Reaction that cancels a pending drop
+pub const CANCEL_EMOJI: &str = "⚠️";
 
 pub fn register() -> Result<(), Box<dyn Error>> {
-    log_info("Registering DROP DB command");
+    log_debug("Registering DROP DB command");
     Ok(())
 }
 
 /// Attempt to drop the category named `db_<db_name>` in the guild.
-/// Returns Ok(embed) or Err(embed).
-pub async fn run(ctx: &Context, guild_id: GuildId, db_name: &str) -> Result<serenity::builder::CreateEmbed, serenity::builder::CreateEmbed> {
+/// Returns Ok(embed) or Err(embed). The attempt is written to the audit log.
+pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, db_name: &str) -> Result<CreateEmbed, CreateEmbed> {
     log_info(&format!("DROP DB command executed for database: {}", db_name));
-    
+    let result = match require_writable(ctx).await {
+        Ok(()) => drop_database(ctx, guild_id, db_name).await,
+        Err(e) => Err(e.into()),
+    };
+    audit(ctx, AuditEntry {
+        database: Some(canonical_name(db_name, DB_PREFIX).0),
+        ..AuditEntry::new(guild_id, user_id, "DROP DB", result.is_ok())
+    }).await;
+    result
+}
+
+async fn drop_database(ctx: &Context, guild_id: GuildId, db_name: &str) -> Result<CreateEmbed, CreateEmbed> {
+    let target = find_empty_database(ctx, guild_id, db_name).await?;
+    match target.category.delete(&ctx.http).await {
+        Ok(_) => {
+            invalidate_channels(ctx, guild_id).await;
+            // Users still on this database get "No Database Selected" instead of a confusing lookup failure
+            if let Some(selections) = ctx.data.read().await.get::<CurrentDB>() {
+                clear_database_selections(&mut *selections.lock().await, guild_id, &target.name);
+            }
+            let mut description = format!("Database **{}** has been deleted successfully!", target.name);
+            if target.was_changed {
+                description.push_str(&format!("\n\n*Name sanitized from `{}` to `{}`*", db_name, target.name));
+            }
+            Ok(create_success_embed("✔️ Database Deleted", &description))
+        },
+        Err(e) => {
+            tracing::error!("Failed to delete category: {e}");
+            log_error(&format!("{}", e));
+            Err(create_error_embed(
+                "Database Deletion Failed",
+                "Failed to delete database. Please check bot permissions or try again."
+            ))
+        }
+    }
+}
+
+/// Check that the database can be dropped and build the warning shown before asking for confirmation.
+/// Returns the prompt and the canonical database name to store with the pending drop.
+pub async fn confirmation_prompt(ctx: &Context, guild_id: GuildId, db_name: &str) -> Result<(CreateEmbed, String), CreateEmbed> {
+    log_info(&format!("DROP DB confirmation requested for database: {}", db_name));
+    // Refuse before prompting, rather than after the user confirmed
+    require_writable(ctx).await?;
+
+    let target = find_empty_database(ctx, guild_id, db_name).await?;
+    let description = format!(
+        "Database **{}** will be deleted permanently.\n\nReact with {} to drop it or {} to cancel. This request expires in {} seconds.",
+        target.name,
+        CONFIRM_EMOJI,
+        CANCEL_EMOJI,
+        CONFIRMATION_TIMEOUT.as_secs()
+    );
+    Ok((create_warning_embed("⚠️ Confirm Database Drop", &description), target.name))
+}
+
+/// Add the confirmation reactions to the command's response and remember the pending drop.
+/// If nobody answers within `CONFIRMATION_TIMEOUT`, the prompt is marked as expired.
+pub async fn request_confirmation(ctx: &Context, command: &CommandInteraction, guild_id: GuildId, db_name: String) {
+    let message = match command.get_response(&ctx.http).await {
+        Ok(message) => message,
+        Err(e) => {
+            tracing::error!("Failed to read drop confirmation prompt: {e}");
+            return;
+        }
+    };
+    for emoji in [CONFIRM_EMOJI, CANCEL_EMOJI] {
+        if let Err(e) = message.react(&ctx.http, ReactionType::Unicode(emoji.to_string())).await {
+            tracing::warn!("Failed to add {emoji} reaction to drop confirmation: {e}");
+        }
+    }
+
+    let Some(pending) = ctx.data.read().await.get::<PendingConfirmations>().cloned() else {
+        tracing::error!("Pending confirmation map missing; drop of {db_name} cannot be confirmed");
+        return;
+    };
+    let key = (message.id, command.user.id);
+    pending.lock().await.insert(key, PendingDrop { guild_id, db_name: db_name.clone(), requested_at: Instant::now() });
+
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(CONFIRMATION_TIMEOUT).await;
+        if pending.lock().await.remove(&key).is_some() {
+            update_prompt(&ctx, message.channel_id, message.id, expired_embed(&db_name)).await;
+        }
+    });
+}
+
+/// Handle a reaction on a drop confirmation prompt; reactions on other messages are ignored
+pub async fn handle_reaction(ctx: &Context, reaction: &Reaction) {
+    let (Some(user_id), ReactionType::Unicode(emoji)) = (reaction.user_id, &reaction.emoji) else {
+        return;
+    };
+    let Some(pending) = ctx.data.read().await.get::<PendingConfirmations>().cloned() else {
+        return;
+    };
+    let decision = resolve_confirmation(&mut *pending.lock().await, (reaction.message_id, user_id), emoji, Instant::now());
+
+    let embed = match decision {
+        Confirmation::Ignored => return,
+        Confirmation::Expired(db_name) => expired_embed(&db_name),
+        Confirmation::Cancelled(db_name) => create_info_embed("Drop Cancelled", &format!("Database **{}** was not dropped.", db_name)),
+        // The database is checked again: tables may have been added while the prompt was open
+        Confirmation::Confirmed(drop) => match run(ctx, drop.guild_id, user_id, &drop.db_name).await {
+            Ok(embed) | Err(embed) => embed,
+        },
+    };
+    update_prompt(ctx, reaction.channel_id, reaction.message_id, embed).await;
+}
+
+/// What a reaction on a confirmation prompt means
+#[derive(Debug, PartialEq)]
+pub enum Confirmation {
+    /// The invoking user reacted ✅ in time
+    Confirmed(PendingDrop),
+    /// The invoking user reacted ⚠️
+    Cancelled(String),
+    /// The invoking user answered after the timeout
+    Expired(String),
+    /// Not an answer to a pending prompt (another user, another message or another emoji)
+    Ignored,
+}
+
+/// Decide what a reaction does, removing the pending drop it answers
+pub fn resolve_confirmation(
+    pending: &mut HashMap<(MessageId, UserId), PendingDrop>,
+    key: (MessageId, UserId),
+    emoji: &str,
+    now: Instant,
+) -> Confirmation {
+    // Clients send ⚠ with or without the emoji variation selector
+    let emoji = emoji.trim_end_matches('\u{fe0f}');
+    let confirm = emoji == CONFIRM_EMOJI;
+    if !confirm && emoji != CANCEL_EMOJI.trim_end_matches('\u{fe0f}') {
+        return Confirmation::Ignored;
+    }
+    let Some(drop) = pending.remove(&key) else {
+        return Confirmation::Ignored;
+    };
+
+    if now.duration_since(drop.requested_at) > CONFIRMATION_TIMEOUT {
+        Confirmation::Expired(drop.db_name)
+    } else if confirm {
+        Confirmation::Confirmed(drop)
+    } else {
+        Confirmation::Cancelled(drop.db_name)
+    }
+}
+
+fn expired_embed(db_name: &str) -> CreateEmbed {
+    create_info_embed(
+        "Drop Request Expired",
+        &format!("Database **{}** was not dropped. Run `/sql drop db {}` again to retry.", db_name, db_name)
+    )
+}
+
+/// Replace the prompt with the outcome of the confirmation
+async fn update_prompt(ctx: &Context, channel_id: ChannelId, message_id: MessageId, embed: CreateEmbed) {
+    if let Err(e) = channel_id.edit_message(&ctx.http, message_id, EditMessage::new().embed(embed)).await {
+        tracing::error!("Failed to update drop confirmation prompt: {e}");
+    }
+}
+
+/// A database category that exists and has no tables
+struct DroppableDatabase {
+    category: ChannelId,
+    /// Canonical name, without the `db_` prefix
+    name: String,
+    was_changed: bool,
+}
+
+/// Find the database's category and make sure it has no tables left
+async fn find_empty_database(ctx: &Context, guild_id: GuildId, db_name: &str) -> Result<DroppableDatabase, CreateEmbed> {
     // Sanitize the database name
-    let (sanitized_name, was_changed) = sanitize_channel_name(db_name);
-    
+    let (sanitized_name, was_changed) = canonical_name(db_name, DB_PREFIX);
+
     if sanitized_name.is_empty() {
-        let embed = create_error_embed(
-            "✖️ Invalid Database Name",
+        return Err(create_error_embed(
+            "Invalid Database Name",
             "Database name cannot be empty after sanitization. Please provide a valid name."
-        );
-        return Err(embed);
+        ));
     }
-    
-    match guild_id.channels(&ctx.http).await {
-        Ok(chans) => {
-            let target = format!("db_{}", sanitized_name);
-            let found = chans.values().find(|c| c.name == target && c.kind == ChannelType::Category);
-            if let Some(cat) = found {
-                // ensure category has no child channels
-                let child_count = chans.values().filter(|c| c.parent_id == Some(cat.id)).count();
-                if child_count > 0 {
-                    let embed = create_warning_embed(
-                        "⚠️ Cannot Drop Database",
-                        &format!("Refusing to drop **{}**: category is not empty ({} tables). Remove all tables first.", target, child_count)
-                    );
-                    Err(embed)
-                } else {
-                    match cat.id.delete(&ctx.http).await {
-                        Ok(_) => {
-                            let mut description = format!("Database **{}** has been deleted successfully!", target);
-                            if was_changed {
-                                description.push_str(&format!("\n\n*Name sanitized from `{}` to `{}`*", db_name, sanitized_name));
-                            }
-                            let embed = create_success_embed("✔️ Database Deleted", &description);
-                            Ok(embed)
-                        },
-                        Err(e) => {
-                            tracing::error!("Failed to delete category: {e}");
-                            let embed = create_error_embed(
-                                "✖️ Database Deletion Failed",
-                                "Failed to delete database. Please check bot permissions or try again."
-                            );
-                            log_error(&format!("{}", e));
-                            Err(embed)
-                        }
-                    }
-                }
-            } else {
-                let embed = create_error_embed(
-                    "✖️ Database Not Found",
-                    &format!("Database **{}** was not found in this server.", target)
-                );
-                Err(embed)
-            }
-        },
+
+    // Always read fresh channels here: the emptiness check guards a destructive operation
+    let chans = match guild_id.channels(&ctx.http).await {
+        Ok(chans) => chans,
         Err(e) => {
             tracing::error!("Failed to get channels: {e}");
-            let embed = create_error_embed(
-                "✖️ Permission Error",
+            return Err(create_error_embed(
+                "Permission Error",
                 "Failed to list channels. Please check bot permissions."
-            );
-            Err(embed)
+            ));
         }
+    };
+
+    let target = format!("{}{}", DB_PREFIX, sanitized_name);
+    let Some(cat) = chans.values().find(|c| c.name == target && c.kind == ChannelType::Category) else {
+        return Err(create_error_embed(
+            "Database Not Found",
+            &format!("Database **{}** was not found in this server.", sanitized_name)
+        ));
+    };
+
+    // ensure category has no child channels
+    let child_count = chans.values().filter(|c| c.parent_id == Some(cat.id)).count();
+    if child_count > 0 {
+        return Err(create_warning_embed(
+            "⚠️ Cannot Drop Database",
+            &format!("Refusing to drop **{}**: database is not empty ({} tables). Remove all tables first.", sanitized_name, child_count)
+        ));
+    }
+
+    Ok(DroppableDatabase { category: cat.id, name: sanitized_name, was_changed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending_drop(requested_at: Instant) -> PendingDrop {
+        PendingDrop { guild_id: GuildId::new(1), db_name: "shop".to_string(), requested_at }
+    }
+
+    #[test]
+    fn test_resolve_confirmation() {
+        let now = Instant::now();
+        let key = (MessageId::new(100), UserId::new(10));
+        let mut pending = HashMap::new();
+
+        // Other emoji and other users leave the prompt waiting
+        pending.insert(key, pending_drop(now));
+        assert_eq!(resolve_confirmation(&mut pending, key, "👍", now), Confirmation::Ignored);
+        assert_eq!(resolve_confirmation(&mut pending, (MessageId::new(100), UserId::new(11)), CONFIRM_EMOJI, now), Confirmation::Ignored);
+        assert_eq!(pending.len(), 1);
+
+        assert_eq!(resolve_confirmation(&mut pending, key, CONFIRM_EMOJI, now), Confirmation::Confirmed(pending_drop(now)));
+        // Each prompt is answered once
+        assert_eq!(resolve_confirmation(&mut pending, key, CONFIRM_EMOJI, now), Confirmation::Ignored);
+
+        pending.insert(key, pending_drop(now));
+        assert_eq!(resolve_confirmation(&mut pending, key, "⚠", now), Confirmation::Cancelled("shop".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_confirmation_expires() {
+        let requested_at = Instant::now();
+        let key = (MessageId::new(100), UserId::new(10));
+        let mut pending = HashMap::from([(key, pending_drop(requested_at))]);
+
+        let late = requested_at + CONFIRMATION_TIMEOUT + std::time::Duration::from_secs(1);
+        assert_eq!(resolve_confirmation(&mut pending, key, CONFIRM_EMOJI, late), Confirmation::Expired("shop".to_string()));
+        assert!(pending.is_empty());
     }
 }
@@ -4,28 +4,28 @@ pub mod db;
 pub mod table;
 
 use std::error::Error;
-use crate::logging::log_info;
+use crate::logging::{log_debug, log_warn};
 
 /// Register all drop subcommands dynamically
 pub fn register_drop_subcommands() -> Result<(), Box<dyn Error>> {
-    log_info("Starting dynamic registration of DROP subcommands...");
+    log_debug("Starting dynamic registration of DROP subcommands...");
     
     // Register db command
-    log_info("Registering DROP DB command...");
+    log_debug("Registering DROP DB command...");
     if let Err(e) = db::register() {
-        log_info(&format!("DROP DB command registration failed: {}", e));
+        log_warn(&format!("DROP DB command registration failed: {}", e));
         return Err(e);
     }
-    log_info("DROP DB command registered successfully");
+    log_debug("DROP DB command registered successfully");
     
     // Register table command  
-    log_info("Registering DROP TABLE command...");
+    log_debug("Registering DROP TABLE command...");
     if let Err(e) = table::register() {
-        log_info(&format!("DROP TABLE command registration failed: {}", e));
+        log_warn(&format!("DROP TABLE command registration failed: {}", e));
         return Err(e);
     }
-    log_info("DROP TABLE command registered successfully");
+    log_debug("DROP TABLE command registered successfully");
     
-    log_info("All DROP subcommands registered successfully!");
+    log_debug("All DROP subcommands registered successfully!");
     Ok(())
 }
@@ -0,0 +1,259 @@
+// /sql dump schema | data
+
+use std::error::Error;
+use serenity::builder::{CreateAttachment, CreateEmbed};
+use serenity::model::channel::GuildChannel;
+use serenity::model::id::{GuildId, UserId};
+use crate::api::table_schema;
+use crate::error::SqlError;
+use crate::guards::require_selected_db;
+use crate::logging::{log_debug, log_info};
+use crate::render::archive::ZipArchive;
+use crate::render::pages::EMBED_DESCRIPTION_LIMIT;
+use crate::services::discord_fs::max_row_messages;
+use crate::services::shards::fetch_table_messages;
+use crate::services::{find_database_category, get_channels_cached, list_tables, BotContext};
+use crate::sql_parser::{ColumnDefinition, SqlValue, TableSchema};
+use crate::utils::{create_info_embed, create_success_embed, TABLE_PREFIX};
+use super::select::{csv_field, row_to_csv, rows_from_messages};
+
+pub fn register() -> Result<(), Box<dyn Error>> {
+    log_debug("Registering DUMP SCHEMA and DUMP DATA commands");
+    Ok(())
+}
+
+/// The dump as an embed, plus the file it was written to when it doesn't fit in one
+pub struct DumpOutput {
+    pub embed: CreateEmbed,
+    pub attachment: Option<CreateAttachment>,
+}
+
+/// Rebuild a `CREATE TABLE` statement for every table of the current database from the schemas in their topics
+pub async fn run_schema(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId) -> Result<DumpOutput, SqlError> {
+    log_info(&format!("DUMP SCHEMA command executed by user: {}", user_id));
+    let current_db = require_selected_db(ctx, guild_id, user_id).await?;
+    let tables: Vec<TableSchema> = database_tables(ctx, guild_id, &current_db).await?
+        .iter()
+        .map(stored_table_schema)
+        .collect();
+
+    let dump = schema_dump(&current_db, &tables);
+    log_info(&format!("SUCCESS: Dumped the schema of {} tables in database {}", tables.len(), current_db));
+    Ok(dump_output(&current_db, &tables, dump))
+}
+
+/// Export every table of the current database as `<table>.csv`, with `schema.sql` from `/sql dump schema`,
+/// in one `<db>_data.zip`. Tables are read one at a time and written to the archive straight away,
+/// so only one table's rows are held at once. Each table reads at most `SQLCORD_MAX_ROWS` rows.
+pub async fn run_data(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId) -> Result<DumpOutput, SqlError> {
+    log_info(&format!("DUMP DATA command executed by user: {}", user_id));
+    let current_db = require_selected_db(ctx, guild_id, user_id).await?;
+    let channels = database_tables(ctx, guild_id, &current_db).await?;
+
+    let mut archive = ZipArchive::new(chrono::Utc::now());
+    let mut tables = Vec::new();
+    let mut summary = Vec::new();
+    for channel in &channels {
+        let table = stored_table_schema(channel);
+        let fetched = match fetch_table_messages(ctx, channel, max_row_messages()).await {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                tracing::error!("Failed to fetch messages of {}: {e}", channel.name);
+                return Err(SqlError::DiscordRequest { operation: "read table messages" });
+            }
+        };
+        let contents: Vec<&str> = fetched.messages.iter().rev().map(|message| message.content.as_str()).collect();
+        // Schemaless tables get the columns inferred from their rows, as in SELECT
+        let (columns, rows) = rows_from_messages(&contents, table.columns.clone());
+        let (file_name, csv) = table_csv(&table.name, &columns, &rows);
+        archive.add_file(&file_name, csv.as_bytes());
+
+        let mut line = format!("• **{}**: {} {}", table.name, rows.len(), if rows.len() == 1 { "row" } else { "rows" });
+        if fetched.truncated {
+            line.push_str(&format!(" *(only the newest {} messages were read)*", max_row_messages()));
+        }
+        summary.push(line);
+        tables.push(table);
+    }
+    archive.add_file("schema.sql", schema_dump(&current_db, &tables).as_bytes());
+
+    let file_name = format!("{}_data.zip", current_db);
+    let mut description = format!("Exported **{}** tables of database **{}** to `{}`:\n{}", tables.len(), current_db, file_name, summary.join("\n"));
+    if description.chars().count() > EMBED_DESCRIPTION_LIMIT {
+        description = format!("Exported **{}** tables of database **{}** to `{}`.", tables.len(), current_db, file_name);
+    }
+    log_info(&format!("SUCCESS: Dumped the data of {} tables in database {}", tables.len(), current_db));
+    Ok(DumpOutput {
+        embed: create_success_embed("📦 Data Dump", &description),
+        attachment: Some(CreateAttachment::bytes(archive.finish(), file_name)),
+    })
+}
+
+/// The table channels of `database`, sorted by name
+async fn database_tables(ctx: &impl BotContext, guild_id: GuildId, database: &str) -> Result<Vec<GuildChannel>, SqlError> {
+    let channels = match get_channels_cached(ctx, guild_id).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            tracing::error!("Failed to get channels: {e}");
+            return Err(SqlError::DiscordRequest { operation: "list channels" });
+        }
+    };
+    let category = find_database_category(&channels, database)
+        .ok_or_else(|| SqlError::DatabaseNotFound(database.to_string()))?;
+    Ok(list_tables(&channels, category.id).into_iter().cloned().collect())
+}
+
+/// A table's name and the columns in its topic. A topic that no longer parses gives no columns
+/// rather than failing the whole dump.
+fn stored_table_schema(channel: &GuildChannel) -> TableSchema {
+    let name = channel.name.strip_prefix(TABLE_PREFIX).unwrap_or(&channel.name).to_string();
+    let columns = table_schema(channel).unwrap_or_else(|e| {
+        tracing::warn!("Skipping the unreadable schema of table {}: {e}", channel.name);
+        Vec::new()
+    });
+    TableSchema { name, columns }
+}
+
+/// The file name and contents of a table's CSV export: a header of column names, then one line per row
+pub(crate) fn table_csv(table: &str, columns: &[ColumnDefinition], rows: &[Vec<SqlValue>]) -> (String, String) {
+    let mut csv = columns.iter().map(|column| csv_field(&column.name)).collect::<Vec<_>>().join(",");
+    csv.push('\n');
+    for row in rows {
+        csv.push_str(&row_to_csv(row));
+        csv.push('\n');
+    }
+    (format!("{}.csv", table), csv)
+}
+
+/// `CREATE TABLE` statements for `tables` in the order given, each ending with `;`. A table without
+/// a stored schema becomes a `--` comment, since CREATE TABLE needs at least one column.
+pub(crate) fn schema_dump(database: &str, tables: &[TableSchema]) -> String {
+    let mut statements = vec![format!("-- Schema of database {}", database)];
+    for table in tables {
+        if table.columns.is_empty() {
+            statements.push(format!("-- Table {} has no stored schema", table.name));
+        } else {
+            statements.push(format!("{};", table));
+        }
+    }
+    statements.join("\n\n")
+}
+
+/// The tables of a `schema_dump`, each with the column definitions between its CREATE TABLE parentheses
+/// (`None` for a table dumped without a schema). Statements are read back exactly as `schema_dump` writes them.
+pub(crate) fn parse_schema_dump(sql: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    let mut tables = Vec::new();
+    for statement in sql.replace("\r\n", "\n").split("\n\n").map(str::trim).filter(|statement| !statement.is_empty()) {
+        if let Some(name) = statement.strip_prefix("-- Table ").and_then(|rest| rest.strip_suffix(" has no stored schema")) {
+            tables.push((name.to_string(), None));
+        } else if let Some(rest) = statement.strip_prefix("CREATE TABLE ") {
+            let (name, columns) = rest.split_once(" (")
+                .and_then(|(name, columns)| Some((name, columns.strip_suffix(");")?)))
+                .ok_or_else(|| format!("Unreadable statement in schema.sql:\n```sql\n{}\n```", statement))?;
+            tables.push((name.trim().to_string(), Some(columns.trim().to_string())));
+        } else if !statement.starts_with("--") {
+            return Err(format!("Unexpected text in schema.sql:\n```sql\n{}\n```", statement));
+        }
+    }
+    Ok(tables)
+}
+
+/// Show the dump in a code block if it fits the embed; otherwise list the tables and attach the dump
+fn dump_output(database: &str, tables: &[TableSchema], dump: String) -> DumpOutput {
+    let summary = format!("**{}** tables in database **{}**", tables.len(), database);
+    let description = format!("{}\n```sql\n{}\n```", summary, dump);
+    if description.chars().count() <= EMBED_DESCRIPTION_LIMIT {
+        return DumpOutput { embed: create_info_embed("🗂️ Schema Dump", &description), attachment: None };
+    }
+
+    let file_name = format!("{}_schema.sql", database);
+    let note = format!("\n\n📎 The statements are attached as `{}`.", file_name);
+    let mut listing = summary;
+    for table in tables {
+        listing.push_str(&format!("\n• {}", table.name));
+    }
+    let room = EMBED_DESCRIPTION_LIMIT.saturating_sub(note.chars().count());
+    let mut description: String = listing.chars().take(room).collect();
+    description.push_str(&note);
+    DumpOutput {
+        embed: create_info_embed("🗂️ Schema Dump", &description),
+        attachment: Some(CreateAttachment::bytes(dump, file_name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_parser::parse_column_definitions;
+
+    fn table(name: &str, schema: &str) -> TableSchema {
+        TableSchema { name: name.to_string(), columns: parse_column_definitions(schema).unwrap() }
+    }
+
+    #[test]
+    fn test_schema_dump_reparses() {
+        let tables = vec![
+            table("customers", "id INT PRIMARY KEY AUTO_INCREMENT, `full name` VARCHAR(50) NOT NULL COMMENT 'as printed', vip BOOLEAN DEFAULT false"),
+            table("orders", "id INT PRIMARY KEY, customer_id INT REFERENCES customers(id), total DECIMAL(10,2), quantity SMALLINT UNSIGNED"),
+            table("logs", ""),
+        ];
+        let dump = schema_dump("shop", &tables);
+        assert!(dump.starts_with("-- Schema of database shop"));
+        assert!(dump.contains("-- Table logs has no stored schema"));
+
+        // Every statement gives back the columns it was built from
+        let statements: Vec<&str> = dump.split("\n\n").filter(|statement| !statement.starts_with("--")).collect();
+        assert_eq!(statements.len(), 2);
+        for (statement, table) in statements.iter().zip(&tables) {
+            let body = statement.strip_prefix(&format!("CREATE TABLE {} (", table.name))
+                .and_then(|rest| rest.strip_suffix(");"))
+                .unwrap_or_else(|| panic!("unexpected statement: {}", statement));
+            let reparsed: Vec<String> = parse_column_definitions(body).unwrap().iter().map(ToString::to_string).collect();
+            assert_eq!(reparsed, table.columns.iter().map(ToString::to_string).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_parse_schema_dump() {
+        let tables = vec![table("customers", "id INT PRIMARY KEY, note VARCHAR(20) COMMENT 'a; b'"), table("logs", "")];
+        let parsed = parse_schema_dump(&schema_dump("shop", &tables)).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].0, "customers");
+        let columns = parse_column_definitions(parsed[0].1.as_deref().unwrap()).unwrap();
+        assert_eq!(columns.iter().map(ToString::to_string).collect::<Vec<_>>(), ["id INT PRIMARY KEY", "note VARCHAR(20) COMMENT 'a; b'"]);
+        assert_eq!(parsed[1], ("logs".to_string(), None));
+
+        assert!(parse_schema_dump("DROP TABLE users;").is_err());
+        assert!(parse_schema_dump("CREATE TABLE users id INT").is_err());
+    }
+
+    #[test]
+    fn test_table_csv() {
+        let columns = parse_column_definitions("id INT, `full name` VARCHAR(30), \"a,b\" BOOLEAN").unwrap();
+        let rows = vec![
+            vec![SqlValue::Integer(1), SqlValue::String("Doe, Jo".to_string()), SqlValue::Boolean(true)],
+            vec![SqlValue::Integer(2), SqlValue::Null, SqlValue::Boolean(false)],
+        ];
+        let (file_name, csv) = table_csv("users", &columns, &rows);
+        assert_eq!(file_name, "users.csv");
+        assert_eq!(csv, "id,full name,\"a,b\"\n1,\"Doe, Jo\",true\n2,,false\n");
+        assert_eq!(table_csv("empty", &columns, &[]).1, "id,full name,\"a,b\"\n");
+    }
+
+    #[test]
+    fn test_large_dump_is_attached() {
+        let tables: Vec<TableSchema> = (0..60)
+            .map(|index| table(&format!("table_number_{}", index), "id INT PRIMARY KEY, description VARCHAR(255) COMMENT 'a fairly long column comment'"))
+            .collect();
+        let dump = schema_dump("big", &tables);
+        let output = dump_output("big", &tables, dump.clone());
+        let description = serde_json::to_value(&output.embed).unwrap()["description"].as_str().unwrap().to_string();
+        assert!(description.chars().count() <= EMBED_DESCRIPTION_LIMIT);
+        assert!(description.contains("• table_number_0"));
+
+        let attachment = output.attachment.expect("oversized dump is attached");
+        assert_eq!(attachment.filename, "big_schema.sql");
+        assert_eq!(attachment.data, dump.into_bytes());
+        assert!(dump_output("small", &tables[..1], schema_dump("small", &tables[..1])).attachment.is_none());
+    }
+}
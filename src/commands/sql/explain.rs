@@ -2,11 +2,11 @@
 
 use std::error::Error;
 use serenity::builder::CreateEmbed;
-use crate::logging::log_info;
+use crate::logging::{log_debug, log_info};
 use crate::utils::create_info_embed;
 
 pub fn register() -> Result<(), Box<dyn Error>> {
-    log_info("Registering EXPLAIN command");
+    log_debug("Registering EXPLAIN command");
     Ok(())
 }
 
@@ -32,8 +32,11 @@ pub async fn run(operation: &str) -> Result<CreateEmbed, CreateEmbed> {
             **Process**:\n\
             • Locates category with name format: `db_<database_name>`\n\
             • Checks if database contains tables (prevents deletion)\n\
-            • Removes empty database categories only\n\n\
-            **Safety**: Non-empty databases cannot be deleted"
+            • Removes empty database categories only\n\
+            • Waits for the invoking user to react ✅ to the warning (⚠️ cancels, expires after 60 seconds)\n\
+            • Clears the database from every user's `USE` selection in the server\n\n\
+            **Safety**: Non-empty databases cannot be deleted\n\
+            **Permissions**: Limited to roles allowed with `/admin perms allow <role>` (administrators always may)"
         ),
         "create table" | "create_table" => (
             "📋 CREATE TABLE",
@@ -44,21 +47,26 @@ pub async fn run(operation: &str) -> Result<CreateEmbed, CreateEmbed> {
             • Places channel inside the current database category\n\
             • Accepts SQL-like column definitions with constraints\n\
             • Stores complete schema information in channel topic\n\
-            • Prevents duplicate table creation\n\n\
-            **Syntax**: `/sql create table name:<table_name> [schema:<column_definitions>]`\n\n\
+            • Prevents duplicate table creation, unless `if_not_exists:true` (then an existing table is left as is, with a warning if its schema differs)\n\
+            • With `shards:N` (up to 8), rows are spread over `table_<name>` plus `table_<name>_1` .. `table_<name>_{N-1}` by a hash of their primary key; SELECT and COUNT read every shard\n\n\
+            **Syntax**: `/sql create table name:<table_name> [schema:<column_definitions>] [if_not_exists:true] [shards:<1-8>]`\n\n\
             **Examples**:\n\
             • Basic: `/sql create table customers`\n\
             • With schema: `/sql create table users id INT PRIMARY KEY, name VARCHAR(50), active BOOLEAN`\n\
             • Complex: `/sql create table products id INT, name VARCHAR(100), price DECIMAL, description VARCHAR(255)`\n\n\
             **Supported Data Types**:\n\
             • **INT**, **INTEGER** - Integer numbers\n\
+            • **TINYINT**, **SMALLINT** - Range-checked integers; add **UNSIGNED** to reject negatives\n\
             • **VARCHAR(size)**, **CHAR(size)** - Text with size limits (size validation enforced)\n\
             • **BOOLEAN**, **BOOL** - True/false values\n\
             • **FLOAT**, **DOUBLE**, **DECIMAL** - Decimal numbers\n\
             • **DATE**, **TIME**, **DATETIME** - Date and time values (stored as strings)\n\n\
             **Constraints**:\n\
             • **PRIMARY KEY** - Enforces uniqueness, prevents duplicate insertions\n\
+            • **PRIMARY KEY (col_a, col_b)** - Table-level composite key; only the full tuple must be unique\n\
+            • **UNIQUE** - Rejects repeated non-NULL values in the column\n\
             • **VARCHAR(n)/CHAR(n)** - String length validation (rejects strings longer than n)\n\
+            • **CHAR(n)** - Fixed length: shorter values are padded with spaces (or rejected with `SQLCORD_CHAR_MODE=strict`); comparisons ignore the padding\n\
             • **NOT NULL** - Prevents null values (planned feature)\n\n\
             **Schema Storage**: Complete schema including constraints stored in Discord channel topic:\n\
             • Format: `Schema: id INT PRIMARY KEY, name VARCHAR(50), active BOOLEAN`\n\
@@ -74,7 +82,8 @@ pub async fn run(operation: &str) -> Result<CreateEmbed, CreateEmbed> {
             • Requires active database selection (`USE <db>`)\n\
             • Locates text channel with name format: `table_<table_name>`\n\
             • Removes the channel and any stored data\n\n\
-            **Warning**: This permanently deletes the table and all data"
+            **Warning**: This permanently deletes the table and all data\n\
+            **Permissions**: Limited to roles allowed with `/admin perms allow <role>` (administrators always may)"
         ),
         "use" | "use database" => (
             "🎯 USE DATABASE",
@@ -94,23 +103,37 @@ pub async fn run(operation: &str) -> Result<CreateEmbed, CreateEmbed> {
             • Supports column selection, filtering, and DISTINCT\n\
             • Validates column names against table schema\n\
             • Returns formatted results in embed tables\n\n\
-            **Syntax**: `/sql select columns:<cols> from:<table> [distinct:true] [where:<condition>] [params:<values>]`\n\n\
+            **Syntax**: `/sql select columns:<cols> from:<table> [distinct:true] [where:<condition>] [ephemeral:true] [format:table|json|csv] [to_thread:true] [max_rows:<1-100>]`\n\n\
             **Column Selection**:\n\
             • All columns: `columns:*`\n\
             • Specific columns: `columns:id, name, email`\n\
+            • All but some: `columns:* EXCEPT (password, secret)`\n\
+            • Aliases: `columns:name AS full_name, id AS user_id` (renames result headers)\n\
+            • Computed: `columns:item, price*quantity AS total, price / 2` (`+ - * /` over numeric columns and numbers; NULL operands and division by zero give NULL)\n\
+            • String functions: `columns:CONCAT(first_name, ' ', last_name) AS name, UPPER(city), LOWER(email), LENGTH(name)`\n\
+            • Repeated columns (`columns:name, name`) are shown once, with a note\n\
+            • Quoted names: ``columns:`first name` AS name`` (backticks or double quotes, also in WHERE)\n\
             • Must match schema column names (if schema exists)\n\n\
             **Examples**:\n\
             • All data: `/sql select columns:* from:users`\n\
+            • Another database: `/sql select columns:* from:sales.users`\n\
             • Specific columns: `/sql select columns:name, email from:customers`\n\
-            • Single condition: `/sql select columns:* from:products where:price='100'`\n\
-            • AND logic: `/sql select columns:* from:users where:name='John' AND age='25'`\n\
+            • Single condition: `/sql select columns:* from:products where:price=100`\n\
+            • AND logic: `/sql select columns:* from:users where:name='John' AND age=25`\n\
             • OR logic: `/sql select columns:* from:users where:name='John' OR name='Jane'`\n\
-            • Parentheses grouping: `/sql select columns:* from:users where:(name='John' OR name='Jane') AND age='25'`\n\
-            • Complex logic: `/sql select columns:* from:products where:category='Electronics' AND (price='100' OR price='200')`\n\
+            • Parentheses grouping: `/sql select columns:* from:users where:(name='John' OR name='Jane') AND age=25`\n\
+            • Complex logic: `/sql select columns:* from:products where:category='Electronics' AND (price=100 OR price=200)`\n\
             • Nested grouping: `/sql select columns:* from:users where:(role='Admin' OR role='Manager') AND (department='IT' OR department='Sales')`\n\
-            • Distinct values: `/sql select columns:category from:products distinct:true`\n\n\
+            • Distinct values: `/sql select columns:category from:products distinct:true`\n\
+            • Private query: `/sql select columns:* from:users ephemeral:true` (only you see the result)\n\
+            • JSON output: `/sql select columns:* from:users format:json` (also `format:csv`)\n\
+            • Large result: `/sql select columns:* from:logs to_thread:true` (every row, posted in a thread)\n\n\
             **Enhanced WHERE Conditions**:\n\
             • Single condition: `column_name='value'`\n\
+            • Typed literals: `price=100`, `active=true` (numbers and booleans unquoted; quoted values are text)\n\
+            • Comparisons: `age>=18`, `price<100`, `status!='done'` (also `<>`, `<=`, `>`)\n\
+            • Row creation time: `_created_at>'2024-01-01 00:00:00'` (select it by name; `*` leaves it out)\n\
+            • One exact row: `_rowid='123456789012345678'` (the id of the row's message)\n\
             • AND logic: `col1='value1' AND col2='value2'` (both must be true)\n\
             • OR logic: `col1='value1' OR col2='value2'` (either can be true)\n\
             • **Parentheses grouping**: `(col1='value1' OR col2='value2') AND col3='value3'`\n\
@@ -118,14 +141,86 @@ pub async fn run(operation: &str) -> Result<CreateEmbed, CreateEmbed> {
             • **Operator Precedence**: Parentheses > AND > OR\n\
             • **Example Logic**: `A OR B AND C` evaluates as `A OR (B AND C)`, but `(A OR B) AND C` forces different grouping\n\n\
             **Features**:\n\
-            • Schema validation for column names\n\
+            • Schema validation for column names (including those in WHERE)\n\
             • DISTINCT filtering to remove duplicates\n\
             • Dynamic table formatting (adapts column widths to content)\n\
-            • Supports up to 20 rows in display (larger results truncated)\n\
+            • Reads up to 1000 stored rows per query (configurable with `SQLCORD_MAX_ROWS`)\n\
+            • Shows 20 rows by default, up to 100 with `max_rows` (rows that don't fit the embed are left out)\n\
             • Proper NULL, string, number, and boolean formatting\n\
             • Full AND/OR/parentheses logic support in WHERE clauses\n\n\
             **Result**: Formatted table showing selected data with query statistics"
         ),
+        "count" => (
+            "🔢 COUNT",
+            "**Discord Mapping**: Counts row messages in a table channel without building a result table\n\n\
+            **Process**:\n\
+            • Requires active database selection (`USE <db>`)\n\
+            • Reads stored rows from the table channel\n\
+            • Applies the optional WHERE clause (same syntax as SELECT)\n\
+            • Without WHERE, uses the row count cached in the table topic; `rescan:true` counts the rows instead\n\
+            • Returns only the number of matching rows\n\n\
+            **Syntax**: `/sql count from:<table> [where:<condition>] [rescan:true]`\n\n\
+            **Examples**:\n\
+            • All rows: `/sql count from:users`\n\
+            • Filtered: `/sql count from:users where:age=25 AND active=true`\n\n\
+            **Result**: A short embed such as \"**42** rows match.\""
+        ),
+        "copy" | "copy table" => (
+            "📋 COPY",
+            "**Discord Mapping**: Creates a new table channel with the topic of an existing one\n\n\
+            **Process**:\n\
+            • Requires active database selection (`USE <db>`)\n\
+            • Rejects the copy if the destination table already exists\n\
+            • Creates the destination channel with the source's schema and comment\n\
+            • With `with_data:true`, re-posts every row message of the source, oldest first\n\n\
+            **Syntax**: `/sql copy from:<table> to:<table> [with_data:true]`\n\n\
+            **Examples**:\n\
+            • Schema only: `/sql copy from:users to:users_staging`\n\
+            • With rows: `/sql copy from:users to:users_backup with_data:true`\n\n\
+            **Result**: Success embed with the number of rows copied"
+        ),
+        "restore" => (
+            "♻️ RESTORE",
+            "**Discord Mapping**: Creates one table channel per file of a `/sql dump data` archive and posts its rows as messages\n\n\
+            **Process**:\n\
+            • Requires active database selection (`USE <db>`)\n\
+            • Reads `schema.sql` and creates each table with its stored schema\n\
+            • Skips tables that already exist, or with `overwrite:true` deletes and recreates their channels\n\
+            • Checks every CSV row against the schema, PRIMARY KEY and UNIQUE before posting it\n\n\
+            **Syntax**: `/sql restore archive:<shop_data.zip> [overwrite:true]`\n\n\
+            **Examples**:\n\
+            • Into a new database: `/sql restore archive:shop_data.zip`\n\
+            • Replacing tables: `/sql restore archive:shop_data.zip overwrite:true`\n\n\
+            **Result**: Embed with the rows restored per table and any rows that were skipped"
+        ),
+        "join" | "inner join" => (
+            "🔗 JOIN",
+            "**Discord Mapping**: Reads the row messages of two table channels and combines them in memory\n\n\
+            **Process**:\n\
+            • Reads both tables (each up to the scan limit)\n\
+            • Indexes the right table by its `on` column (hash join)\n\
+            • Pairs every left row with each right row holding an equal value; NULL never matches\n\
+            • With `kind:left`, keeps unmatched left rows with NULL right columns (LEFT JOIN)\n\
+            • Prefixes column names both tables use with the table name, e.g. `users.id`\n\
+            • Applies the optional WHERE clause and column selection to the combined rows\n\n\
+            **Syntax**: `/sql join left:<table> right:<table> on:<left_col>=<right_col> [kind:inner|left] [columns:<cols>] [where:<condition>]`\n\n\
+            **Example**: `/sql join left:users right:orders on:id=user_id columns:name, total`\n\n\
+            **Result**: Formatted table of the joined rows, like SELECT"
+        ),
+        "run" | "script" => (
+            "📜 RUN",
+            "**Discord Mapping**: Runs several statements through the same code as their slash commands\n\n\
+            **Process**:\n\
+            • Splits the script at `;` (semicolons inside quoted values are kept)\n\
+            • Parses every statement before running any of them\n\
+            • Runs the statements in order and stops at the first failure\n\
+            • Statements that already ran stay applied\n\n\
+            **Supported statements** (up to 10):\n\
+            • `INSERT INTO <table> [(<columns>)] VALUES (<values>)`\n\
+            • `SELECT [DISTINCT] <columns> FROM <table> [WHERE <conditions>]`\n\n\
+            **Example**: `/sql run script:INSERT INTO users VALUES (1, 'Ann'); SELECT * FROM users`\n\n\
+            **Result**: One embed with a field per statement"
+        ),
         "insert" => (
             "➕ INSERT INTO",
             "**Discord Mapping**: Adds validated data to table channels as formatted messages\n\n\
@@ -136,8 +231,10 @@ pub async fn run(operation: &str) -> Result<CreateEmbed, CreateEmbed> {
             • Enforces PRIMARY KEY uniqueness constraints\n\
             • Validates VARCHAR/CHAR length limits\n\
             • Checks data types and value formats\n\
-            • Stores data as timestamped message in table channel\n\n\
-            **Syntax**: `/sql insert into:<table_name> data:<values>`\n\n\
+            • Stores data as timestamped message in table channel\n\
+            • With `validate_only:true`, runs every check and reports the result without writing\n\
+            • With `on_conflict:update`, replaces the row with the same primary key instead of failing\n\n\
+            **Syntax**: `/sql insert into:<table_name> data:<values> [validate_only:<true/false>] [on_conflict:<error/update>]`\n\n\
             **Data Format**: SQL VALUES format with proper type validation\n\n\
             **Examples**:\n\
             • Basic types: `/sql insert users 1, 'John Doe', 25, true`\n\
@@ -145,12 +242,12 @@ pub async fn run(operation: &str) -> Result<CreateEmbed, CreateEmbed> {
             • Primary key table: `/sql insert customers 1, 'Alice'` (prevents duplicates)\n\n\
             **Supported Value Types**:\n\
             • Numbers: `42`, `3.14`, `-5` (validated as INT/FLOAT)\n\
-            • Strings: `'John Doe'`, `'Hello World'` (single quotes, SQL standard)\n\
+            • Strings: `'John Doe'`, `\"Hello World\"` (single or double quotes)\n\
             • Booleans: `true`, `false`\n\
             • NULL: `NULL`\n\
             • Escaped quotes: `'It''s working!'`\n\n\
             **Schema Validation**:\n\
-            • **Type checking**: INT, VARCHAR, CHAR, BOOLEAN, FLOAT, DOUBLE, DECIMAL, DATE, TIME, DATETIME\n\
+            • **Type checking**: INT, TINYINT, SMALLINT, VARCHAR, CHAR, BOOLEAN, FLOAT, DOUBLE, DECIMAL, DATE, TIME, DATETIME\n\
             • **String length limits**: VARCHAR(50) rejects strings longer than 50 characters\n\
             • **Primary key constraints**: Prevents duplicate primary key values across all rows\n\
             • **Value count matching**: Must provide exactly the right number of values for schema columns\n\
@@ -173,18 +270,56 @@ pub async fn run(operation: &str) -> Result<CreateEmbed, CreateEmbed> {
             "✏️ UPDATE",
             "**Discord Mapping**: Will modify existing data in table channels\n\n\
             **Planned Process**:\n\
-            • Locate and modify specific records\n\
-            • Support conditional updates\n\
-            • Maintain data history if needed\n\n\
+            • Locate row messages matching a WHERE clause (same syntax as SELECT)\n\
+            • Edit the matching messages in place with the new values\n\
+            • Re-validate updated values against the table schema and constraints\n\n\
+            **Registered Syntax**: `/sql update table:<table_name>`\n\n\
             **Status**: Not yet implemented"
         ),
         "delete" => (
             "✖️ DELETE",
             "**Discord Mapping**: Will remove data from table channels\n\n\
             **Planned Process**:\n\
-            • Remove specific records from tables\n\
-            • Support conditional deletion\n\
-            • Maintain referential integrity\n\n\
+            • Locate row messages matching a WHERE clause (same syntax as SELECT)\n\
+            • Delete the matching messages from the table channel\n\
+            • Require confirmation before deleting every row\n\n\
+            **Registered Syntax**: `/sql delete table:<table_name>`\n\n\
+            **Status**: Not yet implemented"
+        ),
+        "show" | "show tables" | "show databases" => (
+            "📜 SHOW",
+            "**Discord Mapping**: Will list databases and tables from the guild channel list\n\n\
+            **Planned Process**:\n\
+            • `SHOW DATABASES` - list categories named `db_<name>`\n\
+            • `SHOW TABLES` - list `table_<name>` channels in the current database category\n\n\
+            **Today**: Databases and tables are visible directly in the Discord channel list\n\n\
+            **Status**: Not yet implemented"
+        ),
+        "describe" | "desc" => (
+            "🔎 DESCRIBE",
+            "**Discord Mapping**: Will show a table's schema from its channel topic\n\n\
+            **Planned Process**:\n\
+            • Read the `Schema: ...` line stored in the table channel topic\n\
+            • List each column with its type, size, and constraints\n\n\
+            **Today**: Open the table channel and read its topic, e.g. `Schema: id INT PRIMARY KEY, name VARCHAR(50)`\n\n\
+            **Status**: Not yet implemented"
+        ),
+        "alter" | "alter table" => (
+            "🛠️ ALTER TABLE",
+            "**Discord Mapping**: Will rewrite the schema stored in the table channel topic\n\n\
+            **Planned Process**:\n\
+            • Add, drop, or rename columns in the topic schema\n\
+            • Validate existing row messages against the new schema\n\n\
+            **Today**: Create a new table with the desired schema (`/sql create table`) and insert the data again\n\n\
+            **Status**: Not yet implemented"
+        ),
+        "truncate" | "truncate table" => (
+            "🧹 TRUNCATE",
+            "**Discord Mapping**: Will delete every row message while keeping the channel and its schema\n\n\
+            **Planned Process**:\n\
+            • Bulk-delete all row messages in the table channel\n\
+            • Keep the channel topic (schema) unchanged\n\n\
+            **Today**: `/sql drop table` followed by `/sql create table` with the same schema\n\n\
             **Status**: Not yet implemented"
         ),
         _ => (
@@ -196,9 +331,14 @@ pub async fn run(operation: &str) -> Result<CreateEmbed, CreateEmbed> {
             • `CREATE TABLE` - Create tables with full schema support and constraints\n\
             • `DROP TABLE` - Delete tables and all their data permanently\n\
             • `SELECT` - Query data with column selection, filtering, and DISTINCT\n\
+            • `COUNT` - Count rows, optionally filtered with WHERE\n\
+            • `SAMPLE` - Preview the first few rows of a table\n\
+            • `JOIN` - Combine rows of two tables that match on a column\n\
             • `INSERT` - Add validated data with comprehensive constraint checking\n\
+            • `RUN` - Run several INSERT/SELECT statements separated by `;`\n\
             • `UPDATE` - Modify existing data (🚧 planned feature)\n\
-            • `DELETE` - Remove data with conditions (🚧 planned feature)\n\n\
+            • `DELETE` - Remove data with conditions (🚧 planned feature)\n\
+            • `SHOW`, `DESCRIBE`, `ALTER`, `TRUNCATE` - Schema and maintenance commands (🚧 planned features)\n\n\
             💡 **Quick Help**:\n\n\
             • `/sql explain create table` - Schema and constraint details\n\
             • `/sql explain insert` - Data validation and constraint enforcement\n\
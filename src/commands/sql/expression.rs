@@ -0,0 +1,512 @@
+// Computed SELECT columns: `+ - * /` over numeric columns and number literals, e.g. `price*quantity AS total`,
+// and the string functions CONCAT, UPPER, LOWER and LENGTH, e.g. `CONCAT(first_name, ' ', last_name) AS name`
+
+use crate::sql_parser::{format_float, ColumnDefinition, SqlValue};
+use super::select::find_column_index;
+
+/// A parsed expression, with columns resolved to their index in the row and their data type
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expression {
+    Column(usize, String),
+    Literal(SqlValue),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Function {
+    Concat,
+    Upper,
+    Lower,
+    Length,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "CONCAT" => Some(Function::Concat),
+            "UPPER" => Some(Function::Upper),
+            "LOWER" => Some(Function::Lower),
+            "LENGTH" => Some(Function::Length),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Function::Concat => "CONCAT",
+            Function::Upper => "UPPER",
+            Function::Lower => "LOWER",
+            Function::Length => "LENGTH",
+        }
+    }
+}
+
+/// What an expression produces, checked while parsing so a bad argument is reported before any row is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Text,
+    /// BOOLEAN columns: only CONCAT takes them
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(SqlValue),
+    Identifier(String),
+    Operator(Operator),
+    Open,
+    Close,
+    Comma,
+}
+
+/// Whether a selection item is an expression rather than a column name: it has an operator, parenthesis
+/// or string literal outside quoted identifiers
+pub(crate) fn looks_like_expression(item: &str) -> bool {
+    let mut quote: Option<char> = None;
+    item.chars().any(|c| match quote {
+        Some(q) => {
+            if c == q {
+                quote = None;
+            }
+            false
+        }
+        None if c == '`' || c == '"' => {
+            quote = Some(c);
+            false
+        }
+        None => matches!(c, '+' | '-' | '*' | '/' | '(' | ')' | '\''),
+    })
+}
+
+/// Parse `text` against `schema`. Arithmetic takes numeric columns (INT, FLOAT, DECIMAL and their variants)
+/// and numbers; UPPER, LOWER and LENGTH take text. The error says what's wrong, for the "Invalid Expression" reply.
+pub(crate) fn parse_expression(text: &str, schema: &[ColumnDefinition]) -> Result<Expression, String> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser { tokens: &tokens, position: 0, schema };
+    let (expression, _) = parser.sum()?;
+    match parser.tokens.get(parser.position) {
+        None => Ok(expression),
+        Some(Token::Close) => Err("`)` has no matching `(`.".to_string()),
+        Some(Token::Comma) => Err("`,` is only allowed between function arguments.".to_string()),
+        Some(_) => Err("Expected an operator (`+ - * /`) between values.".to_string()),
+    }
+}
+
+impl Expression {
+    /// The value for one row. NULL operands give NULL, as does dividing by zero or overflowing a float.
+    /// Integers stay integers under `+ - *` (becoming floats only on overflow); `/` always gives a float.
+    /// CHAR values lose the padding they are stored with, so `LENGTH` and `CONCAT` see the value as inserted.
+    pub(crate) fn evaluate(&self, row: &[SqlValue]) -> SqlValue {
+        match self {
+            Expression::Column(index, data_type) => match row.get(*index) {
+                Some(SqlValue::String(s)) if data_type == "CHAR" => SqlValue::String(s.trim_end_matches(' ').to_string()),
+                value => value.cloned().unwrap_or(SqlValue::Null),
+            },
+            Expression::Literal(value) => value.clone(),
+            Expression::Negate(operand) => match operand.evaluate(row) {
+                SqlValue::Integer(i) => i.checked_neg().map_or(SqlValue::Float(-(i as f64)), SqlValue::Integer),
+                SqlValue::Float(f) => SqlValue::Float(-f),
+                _ => SqlValue::Null,
+            },
+            Expression::Binary(left, operator, right) => apply(*operator, left.evaluate(row), right.evaluate(row)),
+            Expression::Call(function, arguments) => call(*function, arguments.iter().map(|argument| argument.evaluate(row)).collect()),
+        }
+    }
+}
+
+fn apply(operator: Operator, left: SqlValue, right: SqlValue) -> SqlValue {
+    if let (SqlValue::Integer(a), SqlValue::Integer(b)) = (&left, &right) {
+        let exact = match operator {
+            Operator::Add => a.checked_add(*b),
+            Operator::Subtract => a.checked_sub(*b),
+            Operator::Multiply => a.checked_mul(*b),
+            Operator::Divide => None,
+        };
+        if let Some(result) = exact {
+            return SqlValue::Integer(result);
+        }
+    }
+    let (a, b) = match (as_float(&left), as_float(&right)) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return SqlValue::Null,
+    };
+    let result = match operator {
+        Operator::Add => a + b,
+        Operator::Subtract => a - b,
+        Operator::Multiply => a * b,
+        Operator::Divide if b == 0.0 => return SqlValue::Null,
+        Operator::Divide => a / b,
+    };
+    if result.is_finite() { SqlValue::Float(result) } else { SqlValue::Null }
+}
+
+fn as_float(value: &SqlValue) -> Option<f64> {
+    match value {
+        SqlValue::Integer(i) => Some(*i as f64),
+        SqlValue::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+/// A function's result; as in SQL, any NULL argument makes it NULL. LENGTH counts characters.
+fn call(function: Function, arguments: Vec<SqlValue>) -> SqlValue {
+    let mut texts = Vec::with_capacity(arguments.len());
+    for argument in arguments {
+        texts.push(match argument {
+            SqlValue::Null => return SqlValue::Null,
+            SqlValue::String(s) => s,
+            SqlValue::Integer(i) => i.to_string(),
+            SqlValue::Float(f) => format_float(f),
+            SqlValue::Boolean(b) => b.to_string(),
+        });
+    }
+    match function {
+        Function::Concat => SqlValue::String(texts.concat()),
+        Function::Upper => SqlValue::String(texts[0].to_uppercase()),
+        Function::Lower => SqlValue::String(texts[0].to_lowercase()),
+        Function::Length => SqlValue::Integer(texts[0].chars().count() as i64),
+    }
+}
+
+/// Split an expression into numbers, strings (`'...'`, with `''` for a quote), identifiers (plain, or quoted
+/// with backticks or double quotes), operators, parentheses and commas
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' => {
+                chars.next();
+                tokens.push(Token::Operator(match c {
+                    '+' => Operator::Add,
+                    '-' => Operator::Subtract,
+                    '*' => Operator::Multiply,
+                    _ => Operator::Divide,
+                }));
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Comma,
+                });
+            }
+            '\'' => {
+                chars.next();
+                let mut literal = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) if chars.next_if(|&(_, c)| c == '\'').is_some() => literal.push('\''),
+                        Some((_, '\'')) => break,
+                        Some((_, c)) => literal.push(c),
+                        None => return Err(format!("The string starting at `{}` is missing its closing quote.", &text[start..])),
+                    }
+                }
+                tokens.push(Token::Literal(SqlValue::String(literal)));
+            }
+            '`' | '"' => {
+                chars.next();
+                let Some(end) = text[start + 1..].find(c) else {
+                    return Err(format!("The identifier starting at `{}` is missing its closing {}.", &text[start..], c));
+                };
+                tokens.push(Token::Identifier(text[start + 1..start + 1 + end].to_string()));
+                while chars.next_if(|&(i, _)| i <= start + 1 + end).is_some() {}
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = start;
+                while let Some((i, c)) = chars.next_if(|&(_, c)| c.is_ascii_alphanumeric() || c == '.') {
+                    end = i + c.len_utf8();
+                }
+                let literal = &text[start..end];
+                let number = match literal.parse::<i64>() {
+                    Ok(i) => SqlValue::Integer(i),
+                    Err(_) => match literal.parse::<f64>() {
+                        Ok(f) if f.is_finite() => SqlValue::Float(f),
+                        _ => return Err(format!("`{}` is not a number.", literal)),
+                    },
+                };
+                tokens.push(Token::Literal(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start;
+                while let Some((i, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_' || c == '.') {
+                    end = i + c.len_utf8();
+                }
+                tokens.push(Token::Identifier(text[start..end].to_string()));
+            }
+            _ => return Err(format!("Unexpected `{}`; expressions may only use columns, numbers, strings, `+ - * /`, parentheses and CONCAT, UPPER, LOWER or LENGTH.", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens: `*` and `/` bind tighter than `+` and `-`, all left-associative
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    schema: &'a [ColumnDefinition],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next_operator(&mut self, accepted: [Operator; 2]) -> Option<Operator> {
+        match self.peek() {
+            Some(Token::Operator(operator)) if accepted.contains(operator) => {
+                let operator = *operator;
+                self.position += 1;
+                Some(operator)
+            }
+            _ => None,
+        }
+    }
+
+    fn sum(&mut self) -> Result<(Expression, Kind), String> {
+        let mut left = self.product()?;
+        while let Some(operator) = self.next_operator([Operator::Add, Operator::Subtract]) {
+            let right = self.product()?;
+            left = (Expression::Binary(Box::new(self.number(left)?), operator, Box::new(self.number(right)?)), Kind::Number);
+        }
+        Ok(left)
+    }
+
+    fn product(&mut self) -> Result<(Expression, Kind), String> {
+        let mut left = self.operand()?;
+        while let Some(operator) = self.next_operator([Operator::Multiply, Operator::Divide]) {
+            let right = self.operand()?;
+            left = (Expression::Binary(Box::new(self.number(left)?), operator, Box::new(self.number(right)?)), Kind::Number);
+        }
+        Ok(left)
+    }
+
+    fn operand(&mut self) -> Result<(Expression, Kind), String> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        match token {
+            Some(Token::Literal(value)) => {
+                let kind = if matches!(value, SqlValue::String(_)) { Kind::Text } else { Kind::Number };
+                Ok((Expression::Literal(value), kind))
+            }
+            Some(Token::Identifier(name)) if self.peek() == Some(&Token::Open) => self.call(&name),
+            Some(Token::Identifier(name)) => self.column(&name),
+            Some(Token::Operator(Operator::Subtract)) => {
+                let operand = self.operand()?;
+                Ok((Expression::Negate(Box::new(self.number(operand)?)), Kind::Number))
+            }
+            Some(Token::Operator(Operator::Add)) => self.operand(),
+            Some(Token::Open) => {
+                let inner = self.sum()?;
+                self.close()?;
+                Ok(inner)
+            }
+            Some(Token::Close | Token::Comma | Token::Operator(_)) | None => Err("Expected a column, number or string.".to_string()),
+        }
+    }
+
+    fn close(&mut self) -> Result<(), String> {
+        match self.peek() {
+            Some(Token::Close) => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => Err("`(` is missing its closing `)`.".to_string()),
+        }
+    }
+
+    /// `name(arguments)`, with the position on the `(`
+    fn call(&mut self, name: &str) -> Result<(Expression, Kind), String> {
+        let Some(function) = Function::from_name(name) else {
+            return Err(format!("Unknown function **{}**. Available functions: CONCAT, UPPER, LOWER, LENGTH.", name));
+        };
+        self.position += 1;
+        let mut arguments = Vec::new();
+        if self.peek() != Some(&Token::Close) {
+            arguments.push(self.sum()?);
+            while self.peek() == Some(&Token::Comma) {
+                self.position += 1;
+                arguments.push(self.sum()?);
+            }
+        }
+        self.close()?;
+
+        let kind = match function {
+            Function::Concat if arguments.is_empty() => return Err("CONCAT needs at least one argument.".to_string()),
+            Function::Concat => Kind::Text,
+            _ if arguments.len() != 1 => {
+                return Err(format!("{} takes exactly one argument, but got {}.", function.name(), arguments.len()));
+            }
+            _ if arguments[0].1 != Kind::Text => {
+                return Err(format!("{} needs text, but {} is not text.", function.name(), self.describe(&arguments[0].0)));
+            }
+            Function::Length => Kind::Number,
+            Function::Upper | Function::Lower => Kind::Text,
+        };
+        Ok((Expression::Call(function, arguments.into_iter().map(|(argument, _)| argument).collect()), kind))
+    }
+
+    fn column(&self, name: &str) -> Result<(Expression, Kind), String> {
+        let Some(index) = find_column_index(self.schema, name) else {
+            return Err(format!(
+                "Column **{}** does not exist in table schema.\n\n**Available columns:** {}",
+                name,
+                self.schema.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+            ));
+        };
+        let kind = match self.schema[index].data_type.as_str() {
+            "INT" | "TINYINT" | "SMALLINT" | "FLOAT" | "DOUBLE" | "DECIMAL" => Kind::Number,
+            "BOOLEAN" => Kind::Other,
+            _ => Kind::Text,
+        };
+        Ok((Expression::Column(index, self.schema[index].data_type.clone()), kind))
+    }
+
+    /// An arithmetic operand, which must be numeric
+    fn number(&self, (expression, kind): (Expression, Kind)) -> Result<Expression, String> {
+        if kind == Kind::Number {
+            return Ok(expression);
+        }
+        match expression {
+            Expression::Column(index, _) => {
+                let column = &self.schema[index];
+                Err(format!("Column **{}** is {}, but arithmetic needs a numeric column.", column.name, column.data_type))
+            }
+            other => Err(format!("Arithmetic needs numbers, but {} is text.", self.describe(&other))),
+        }
+    }
+
+    /// How an error names an expression: the column, the literal, or the function producing it
+    fn describe(&self, expression: &Expression) -> String {
+        match expression {
+            Expression::Column(index, _) => format!("column **{}** ({})", self.schema[*index].name, self.schema[*index].data_type),
+            Expression::Literal(SqlValue::String(s)) => format!("'{}'", s),
+            Expression::Literal(value) => value.to_string(),
+            Expression::Call(function, _) => format!("the result of {}", function.name()),
+            Expression::Negate(_) | Expression::Binary(..) => "the arithmetic result".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_parser::parse_column_definitions;
+
+    fn schema() -> Vec<ColumnDefinition> {
+        parse_column_definitions("price DECIMAL(10,2), quantity INT, name VARCHAR(20), `unit cost` FLOAT, active BOOLEAN").unwrap()
+    }
+
+    fn evaluate(text: &str, row: &[SqlValue]) -> SqlValue {
+        parse_expression(text, &schema()).unwrap().evaluate(row)
+    }
+
+    fn row() -> Vec<SqlValue> {
+        vec![SqlValue::Float(2.5), SqlValue::Integer(4), SqlValue::String("Pen".into()), SqlValue::Float(1.0), SqlValue::Boolean(true)]
+    }
+
+    #[test]
+    fn test_expression_arithmetic() {
+        let row = row();
+        // Two columns multiplied, and a column plus a literal
+        assert_eq!(evaluate("price*quantity", &row), SqlValue::Float(10.0));
+        assert_eq!(evaluate("quantity + 10", &row), SqlValue::Integer(14));
+        assert_eq!(evaluate("quantity + 0.5", &row), SqlValue::Float(4.5));
+
+        // Precedence, parentheses, unary minus and quoted names
+        assert_eq!(evaluate("1 + quantity * 2", &row), SqlValue::Integer(9));
+        assert_eq!(evaluate("(1 + quantity) * 2", &row), SqlValue::Integer(10));
+        assert_eq!(evaluate("-quantity - -1", &row), SqlValue::Integer(-3));
+        assert_eq!(evaluate("`unit cost` * quantity", &row), SqlValue::Float(4.0));
+        assert_eq!(evaluate("10 - 4 - 3", &row), SqlValue::Integer(3));
+        assert_eq!(evaluate("quantity / 8", &row), SqlValue::Float(0.5));
+    }
+
+    #[test]
+    fn test_expression_nulls_and_overflow() {
+        let row = [SqlValue::Float(2.5), SqlValue::Integer(0), SqlValue::Null, SqlValue::Null, SqlValue::Null];
+        // Dividing by zero gives NULL rather than an error, as does any NULL operand
+        assert_eq!(evaluate("price / quantity", &row), SqlValue::Null);
+        assert_eq!(evaluate("price / (quantity * 3)", &row), SqlValue::Null);
+        assert_eq!(evaluate("`unit cost` + 1", &row), SqlValue::Null);
+        assert_eq!(evaluate("CONCAT(price, name)", &row), SqlValue::Null);
+        assert_eq!(evaluate("LENGTH(name)", &row), SqlValue::Null);
+
+        let large = [SqlValue::Null, SqlValue::Integer(i64::MAX), SqlValue::Null, SqlValue::Null, SqlValue::Null];
+        assert_eq!(evaluate("quantity + 1", &large), SqlValue::Float(i64::MAX as f64 + 1.0));
+    }
+
+    #[test]
+    fn test_string_functions() {
+        let row = row();
+        // CONCAT of two columns with a literal separator
+        assert_eq!(evaluate("CONCAT(name, ' x ', quantity)", &row), SqlValue::String("Pen x 4".into()));
+        assert_eq!(evaluate("concat(name, ': ', price, ', ', active)", &row), SqlValue::String("Pen: 2.5, true".into()));
+        assert_eq!(evaluate("CONCAT('it''s ', name)", &row), SqlValue::String("it's Pen".into()));
+        assert_eq!(evaluate("UPPER(name)", &row), SqlValue::String("PEN".into()));
+        assert_eq!(evaluate("LOWER(CONCAT(name, 'S'))", &row), SqlValue::String("pens".into()));
+        assert_eq!(evaluate("LENGTH('héllo')", &row), SqlValue::Integer(5));
+        assert_eq!(evaluate("LENGTH(name) * quantity", &row), SqlValue::Integer(12));
+    }
+
+    #[test]
+    fn test_char_columns_are_unpadded() {
+        let schema = parse_column_definitions("code CHAR(10), name VARCHAR(20)").unwrap();
+        let row = [SqlValue::String("AB        ".into()), SqlValue::String("Pen ".into())];
+        let evaluate = |text: &str| parse_expression(text, &schema).unwrap().evaluate(&row);
+        // CHAR(10) values are stored padded to ten characters; VARCHAR keeps trailing spaces as inserted
+        assert_eq!(evaluate("LENGTH(code)"), SqlValue::Integer(2));
+        assert_eq!(evaluate("CONCAT(code, '-', name)"), SqlValue::String("AB-Pen ".into()));
+        assert_eq!(evaluate("LOWER(code)"), SqlValue::String("ab".into()));
+        assert_eq!(evaluate("LENGTH(name)"), SqlValue::Integer(4));
+    }
+
+    #[test]
+    fn test_expression_errors() {
+        let error = |text: &str| parse_expression(text, &schema()).unwrap_err();
+        assert!(error("name * 2").contains("Column **name** is VARCHAR, but arithmetic needs a numeric column"));
+        assert!(error("'a' + 1").contains("Arithmetic needs numbers, but 'a' is text"));
+        assert!(error("UPPER(name) + 1").contains("the result of UPPER is text"));
+        assert!(error("missing + 1").contains("does not exist"));
+        assert!(error("(price + 1").contains("closing `)`"));
+        assert!(error("price + 1)").contains("no matching `(`"));
+        assert!(error("price quantity").contains("Expected an operator"));
+        assert!(error("price +").contains("Expected a column, number or string"));
+        assert!(error("price % 2").contains("Unexpected `%`"));
+        assert!(error("1.2.3 * price").contains("not a number"));
+
+        // Function arguments are checked by count and type
+        assert!(error("UPPER(quantity)").contains("UPPER needs text, but column **quantity** (INT) is not text"));
+        assert!(error("LENGTH(active)").contains("LENGTH needs text"));
+        assert!(error("LOWER(name, name)").contains("exactly one argument, but got 2"));
+        assert!(error("CONCAT()").contains("at least one argument"));
+        assert!(error("TRIM(name)").contains("Unknown function **TRIM**"));
+        assert!(error("CONCAT(name, 'x)").contains("missing its closing quote"));
+        assert!(error("name, 1").contains("only allowed between function arguments"));
+    }
+
+    #[test]
+    fn test_looks_like_expression() {
+        assert!(looks_like_expression("price*quantity"));
+        assert!(looks_like_expression("UPPER(name)"));
+        assert!(looks_like_expression("'literal'"));
+        assert!(!looks_like_expression("price"));
+        assert!(!looks_like_expression("`a-b`"));
+        assert!(looks_like_expression("`a-b` - 1"));
+    }
+}
@@ -1,31 +1,66 @@
 // /sql insert into <table> <data>
 
 use std::error::Error;
-use serenity::prelude::*;
-use serenity::model::id::{GuildId, UserId};
-use serenity::model::channel::ChannelType;
-use serenity::builder::CreateMessage;
-use crate::state::CurrentDB;
-use crate::logging::{log_info, log_error};
-use crate::utils::{sanitize_channel_name, create_success_embed, create_error_embed};
-use crate::sql_parser::{parse_column_definitions, ColumnDefinition, parse_sql_values, validate_values_against_schema, SqlValue};
+use serenity::model::id::{GuildId, MessageId, UserId};
+use serenity::builder::{CreateMessage, EditMessage};
+use crate::commands::options::InsertArgs;
+use super::select::split_qualified_table;
+use crate::guards::{require_selected_db, require_table, require_writable, resolve_database};
+use crate::error::SqlError;
+use crate::logging::{audit, log_debug, log_info, log_error, AuditEntry};
+use crate::utils::{canonical_name, sanitize_channel_name, DB_PREFIX, create_success_embed, create_error_embed, create_info_embed, create_warning_embed};
+use crate::services::{find_database_category, find_table_channel, get_channels_cached, Backend, BotContext};
+use crate::services::discord_fs::max_row_messages;
+use crate::services::shards::{fetch_table_messages, is_shard_channel, shard_for_row};
+use crate::render::pages::MESSAGE_CHAR_LIMIT;
+use crate::services::topic_meta::{update_table_topic, TopicMeta};
+use crate::services::encode::{decode_stored_string, encode_stored_string};
+use crate::sql_parser::{parse_column_definitions, ColumnDefinition, ForeignKey, parse_sql_values, validate_values_against_schema, coerce_values_to_schema, char_values_equal, format_float, format_float_for_column, format_sql_literal, pad_char_value, SqlValue};
 
 pub fn register() -> Result<(), Box<dyn Error>> {
-    log_info("Registering INSERT command");
+    log_debug("Registering INSERT command");
     Ok(())
 }
 
 /// Insert data into a table (Discord channel)
-/// Validates data against table schema and stores as a message
-pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, table_name: &str, data: &str) -> Result<serenity::builder::CreateEmbed, serenity::builder::CreateEmbed> {
-    log_info(&format!("INSERT command executed for table: {} with data: {}", table_name, data));
+/// Validates data against table schema and stores as a message.
+/// An optional column list maps the values onto named columns; omitted columns become NULL.
+/// With `validate_only`, every check runs but nothing is written to the channel.
+/// With `on_conflict:update`, a row whose primary key already exists is replaced instead of rejected.
+/// Every attempt except a `validate_only` one is written to the audit log.
+pub async fn run(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId, args: &InsertArgs<'_>) -> Result<serenity::builder::CreateEmbed, serenity::builder::CreateEmbed> {
+    // A validate_only insert writes nothing, so it still runs in read-only mode
+    let writable = if args.validate_only { Ok(()) } else { require_writable(ctx).await };
+    let result = match writable {
+        Ok(()) => insert(ctx, guild_id, user_id, args).await,
+        Err(e) => Err(e.into()),
+    };
+    if !args.validate_only {
+        let (db, table_name) = insert_target(args).unwrap_or((args.db, args.table));
+        let database = match db {
+            Some(db) => Some(canonical_name(db, DB_PREFIX).0),
+            None => require_selected_db(ctx, guild_id, user_id).await.ok(),
+        };
+        audit(ctx, AuditEntry {
+            database,
+            table: Some(sanitize_channel_name(table_name).0),
+            ..AuditEntry::new(guild_id, user_id, "INSERT", result.is_ok())
+        }).await;
+    }
+    result
+}
+
+async fn insert(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId, args: &InsertArgs<'_>) -> Result<serenity::builder::CreateEmbed, serenity::builder::CreateEmbed> {
+    let InsertArgs { table, data, columns, validate_only, on_conflict, .. } = *args;
+    log_info(&format!("INSERT command executed for table: {} with data: {}, columns: {:?}, validate_only: {}, on_conflict: {:?}", table, data, columns, validate_only, on_conflict));
+    let (db, table_name) = insert_target(args)?;
     
     // Parse and validate SQL VALUES data
-    let parsed_values = match parse_sql_values(data) {
+    let mut parsed_values = match parse_sql_values(data) {
         Ok(values) => values,
         Err(e) => {
             let embed = create_error_embed(
-                "✖️ Invalid Data Format",
+                "Invalid Data Format",
                 &format!("**Data Error:**\n{}\n\n💡 **Tip:** Use SQL format like `1, 'John', true`", e)
             );
             return Err(embed);
@@ -37,121 +72,261 @@ pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, table_name:
     
     if sanitized_name.is_empty() {
         let embed = create_error_embed(
-            "✖️ Invalid Table Name",
+            "Invalid Table Name",
             "Table name cannot be empty after sanitization. Please provide a valid name with alphanumeric characters."
         );
         return Err(embed);
     }
     
-    // Get the current database for this user
-    let data_read = ctx.data.read().await;
-    let current_db = if let Some(map_arc) = data_read.get::<CurrentDB>() {
-        let map = map_arc.lock().await;
-        map.get(&(guild_id, user_id)).cloned()
-    } else {
-        None
-    };
-    drop(data_read);
+    let current_db = resolve_database(ctx, guild_id, user_id, db).await?;
 
-    let current_db = match current_db {
-        Some(db_name) => db_name,
-        None => {
+    let channels = match get_channels_cached(ctx, guild_id).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            tracing::error!("Failed to get channels: {e}");
             let embed = create_error_embed(
-                "✖️ No Database Selected",
-                "No database selected. Use `/sql use <db_name>` first to select a database."
+                "Permission Error",
+                "Failed to list channels. Please check bot permissions."
             );
             return Err(embed);
         }
     };
 
     // Find the table channel and validate data against schema
-    match guild_id.channels(&ctx.http).await {
-        Ok(channels) => {
-            let table_channel_name = format!("table_{}", sanitized_name);
-            let db_category_name = format!("db_{}", current_db);
-            
-            // Find the database category
-            let db_category = channels.values()
-                .find(|c| c.name == db_category_name && c.kind == ChannelType::Category);
-            
-            if let Some(category) = db_category {
-                // Find the table channel
-                let table_channel = channels.values()
-                    .find(|c| c.name == table_channel_name && c.parent_id == Some(category.id));
-                
-                if let Some(channel) = table_channel {
-                    // Get and parse table schema from channel topic
-                    let schema = if let Some(topic) = &channel.topic {
-                        parse_schema_from_topic(topic)?
-                    } else {
-                        Vec::new() // No schema defined
-                    };
-                    
-                    // Validate data against schema
-                    if let Err(validation_error) = validate_values_against_schema(&parsed_values, &schema) {
-                        return Err(create_error_embed(
-                            "✖️ Data Validation Failed",
-                            &format!("**Validation Error:**\n{}\n\n**Schema:** {}", validation_error, format_schema_info(&schema))
-                        ));
-                    }
-                    
-                    // Check for primary key duplicates
-                    if let Err(duplicate_error) = check_primary_key_duplicates(ctx, channel, &parsed_values, &schema).await {
-                        return Err(duplicate_error);
-                    }
-                    
-                    // Format data for storage
-                    let formatted_data = format_sql_values_for_storage(&parsed_values, &schema);
-                    
-                    // Insert data as a message in the table channel
-                    match channel.send_message(&ctx.http, CreateMessage::new().content(&formatted_data)).await {
-                        Ok(_message) => {
-                            let success_msg = format!(
-                                "Successfully inserted 1 row into table **{}**\n\n**Data:**\n{}",
-                                sanitized_name,
-                                format_sql_values_for_display(&parsed_values, &schema)
-                            );
-                            log_info(&format!("SUCCESS: Data inserted into table {}", table_channel_name));
-                            Ok(create_success_embed("✔️ Row Inserted", &success_msg))
-                        },
-                        Err(e) => {
-                            tracing::error!("Failed to insert data into table channel: {e}");
-                            let embed = create_error_embed(
-                                "✖️ Insert Failed",
-                                "Failed to insert data. Please check bot permissions or try again."
-                            );
-                            log_error("Failed to insert data");
-                            Err(embed)
-                        }
-                    }
-                } else {
-                    let embed = create_error_embed(
-                        "✖️ Table Not Found",
-                        &format!("Table **{}** not found in database **{}**. Create it first with `/sql create table {}`", sanitized_name, current_db, sanitized_name)
-                    );
-                    Err(embed)
-                }
-            } else {
-                let embed = create_error_embed(
-                    "✖️ Database Not Found",
-                    &format!("Database **{}** not found. Create it first with `/sql create db {}`", current_db, current_db)
+    let Some(category) = find_database_category(&channels, &current_db) else {
+        let embed = create_error_embed(
+            "Database Not Found",
+            &format!("Database **{}** not found. Create it first with `/sql create db {}`", current_db, current_db)
+        );
+        return Err(embed);
+    };
+    let Some(channel) = find_table_channel(&channels, category.id, &sanitized_name).filter(|channel| !is_shard_channel(channel)) else {
+        let embed = create_error_embed(
+            "Table Not Found",
+            &format!("Table **{}** not found in database **{}**. Create it first with `/sql create table {}`", sanitized_name, current_db, sanitized_name)
+        );
+        return Err(embed);
+    };
+
+    // Get and parse table schema from channel topic
+    let schema = if let Some(topic) = &channel.topic {
+        parse_schema_from_topic(topic)?
+    } else {
+        Vec::new() // No schema defined
+    };
+    
+    // Map an explicit column list onto schema order
+    if let Some(column_list) = columns {
+        parsed_values = match apply_column_list(column_list, parsed_values, &schema) {
+            Ok(values) => values,
+            Err(e) => {
+                return Err(create_error_embed(
+                    "Invalid Column List",
+                    &format!("**Column List Error:**\n{}\n\n**Schema:** {}", e, format_schema_info(&schema))
+                ));
+            }
+        };
+    }
+    
+    // Schemaless tables remember how many values their first row had, so later rows can be compared
+    let recorded_columns = if schema.is_empty() { implicit_column_count(channel.topic.as_deref()) } else { None };
+    let column_warning = if schema.is_empty() { column_count_warning(recorded_columns, parsed_values.len()) } else { None };
+    
+    // Fill AUTO_INCREMENT columns that were omitted or given as NULL
+    assign_auto_increment_values(ctx, channel, &mut parsed_values, &schema).await?;
+    
+    // Validate data against schema
+    if let Err(validation_error) = validate_values_against_schema(&parsed_values, &schema) {
+        return Err(create_error_embed(
+            "Data Validation Failed",
+            &format!("**Validation Error:**\n{}\n\n**Schema:** {}", validation_error, format_schema_info(&schema))
+        ));
+    }
+    let coercion_note = coercion_note(&coerce_values_to_schema(&mut parsed_values, &schema));
+    check_row_size(&format_sql_values_for_storage(&parsed_values, &schema))?;
+    
+    // Check for PRIMARY KEY and UNIQUE duplicates; with on_conflict:update a primary key match is the row to replace
+    let existing_row = match on_conflict {
+        OnConflict::Error => {
+            check_unique_constraints(ctx, channel, &parsed_values, &schema).await?;
+            None
+        }
+        OnConflict::Update => find_upsert_target(ctx, channel, &parsed_values, &schema).await?,
+    };
+    check_foreign_keys(ctx, guild_id, &current_db, &parsed_values, &schema).await?;
+    
+    let (message_id, formatted_data) = match insert_action(&parsed_values, &schema, validate_only, existing_row) {
+        InsertAction::Write(formatted_data) => (None, formatted_data),
+        InsertAction::Update(message_id, formatted_data) => (Some(message_id), formatted_data),
+        InsertAction::DryRun => {
+            let outcome = if existing_row.is_some() { "would replace the row with the same primary key" } else { "passed all checks" };
+            let mut preview = format!(
+                "Row for table **{}** {}. Nothing was written.\n\n**Data:**\n{}",
+                sanitized_name,
+                outcome,
+                format_sql_values_for_display(&parsed_values, &schema)
+            );
+            if let Some(warning) = &column_warning {
+                preview.push_str(&format!("\n\n⚠️ {}", warning));
+            }
+            log_info(&format!("SUCCESS: Row validated for table {} (validate only)", channel.name));
+            return Ok(create_info_embed("🧪 Insert Validated", &preview));
+        }
+    };
+    
+    // Rows with the same primary key share a shard, so a conflicting row is in this one too
+    let shard = shard_for_row(ctx, channel, &parsed_values, &schema).await?;
+
+    // Replace the conflicting row in place
+    if let Some(message_id) = message_id {
+        return match ctx.backend().edit_message(shard.id, message_id, EditMessage::new().content(&formatted_data)).await {
+            Ok(_message) => {
+                let mut success_msg = format!(
+                    "Primary key already existed, so 1 row in table **{}** was updated\n\n**Data:**\n{}",
+                    sanitized_name,
+                    format_sql_values_for_display(&parsed_values, &schema)
                 );
-                Err(embed)
+                if let Some(note) = &coercion_note {
+                    success_msg.push_str(&format!("\n\n{}", note));
+                }
+                // Same row count, new modification time
+                let (topic_ctx, channel_id) = (ctx.clone(), channel.id);
+                tokio::spawn(async move {
+                    update_table_topic(&topic_ctx, guild_id, channel_id, |meta| meta.record_change(0, chrono::Utc::now())).await;
+                });
+                log_info(&format!("SUCCESS: Row updated in table {} (on conflict)", channel.name));
+                Ok(create_success_embed("✔️ Row Updated", &success_msg))
+            }
+            Err(e) => {
+                tracing::error!("Failed to update row message in table channel: {e}");
+                log_error("Failed to update conflicting row");
+                Err(SqlError::DiscordRequest { operation: "update the existing row" }.into())
+            }
+        };
+    }
+    
+    // Insert data as a message in the table channel
+    match ctx.backend().send_message(shard.id, CreateMessage::new().content(&formatted_data)).await {
+        Ok(_message) => {
+            // Topic edits are heavily rate limited by Discord, so they run in the background instead of holding up the reply
+            let record_columns = (schema.is_empty() && recorded_columns.is_none()).then_some(parsed_values.len());
+            let (topic_ctx, channel_id) = (ctx.clone(), channel.id);
+            tokio::spawn(async move {
+                update_table_topic(&topic_ctx, guild_id, channel_id, |meta| record_insert(meta, record_columns, chrono::Utc::now())).await;
+            });
+            
+            let mut success_msg = format!(
+                "Successfully inserted 1 row into table **{}**\n\n**Data:**\n{}",
+                sanitized_name,
+                format_sql_values_for_display(&parsed_values, &schema)
+            );
+            if let Some(note) = &coercion_note {
+                success_msg.push_str(&format!("\n\n{}", note));
+            }
+            log_info(&format!("SUCCESS: Data inserted into table {}", channel.name));
+            match column_warning {
+                // Still inserted: schemaless tables accept any row shape, but mixed shapes break `columns:*`
+                Some(warning) => Ok(create_warning_embed("⚠️ Row Inserted With a Different Column Count", &format!("{}\n\n⚠️ {}", success_msg, warning))),
+                None => Ok(create_success_embed("✔️ Row Inserted", &success_msg)),
             }
         },
         Err(e) => {
-            tracing::error!("Failed to get channels: {e}");
+            tracing::error!("Failed to insert data into table channel: {e}");
             let embed = create_error_embed(
-                "✖️ Permission Error",
-                "Failed to list channels. Please check bot permissions."
+                "Insert Failed",
+                "Failed to insert data. Please check bot permissions or try again."
             );
+            log_error("Failed to insert data");
             Err(embed)
         }
     }
 }
 
+/// Database and table an insert writes to: `table:sales.users` targets database `sales` whatever the `db`
+/// option or the `/sql use` selection say, while an unqualified table keeps the `db` option
+fn insert_target<'a>(args: &InsertArgs<'a>) -> Result<(Option<&'a str>, &'a str), SqlError> {
+    split_qualified_table(args.table, args.db)
+}
+
+/// Note for the success embed listing columns whose integer values were stored as floats
+fn coercion_note(coerced: &[String]) -> Option<String> {
+    if coerced.is_empty() {
+        return None;
+    }
+    Some(format!("*Integer values stored as floats to match the column type: {}*", coerced.join(", ")))
+}
+
+/// Value count recorded in a schemaless table's topic (`Columns: 3`), if any
+fn implicit_column_count(topic: Option<&str>) -> Option<usize> {
+    TopicMeta::parse(topic).columns
+}
+
+/// Warning for a schemaless row whose value count differs from the recorded one
+fn column_count_warning(recorded: Option<usize>, given: usize) -> Option<String> {
+    match recorded {
+        Some(expected) if expected != given => Some(format!(
+            "This table's rows have **{}** values, but this row has **{}**. Rows with different column counts don't line up in `columns:*` results; consider giving the table a schema.",
+            expected,
+            given
+        )),
+        _ => None,
+    }
+}
+
+/// Topic metadata once a new row is stored: the cached row count goes up by one, and the first row of a
+/// schemaless table records its value count (`record_columns`). Failing to save it only loses the warning or
+/// the cached count, so the caller logs errors rather than returning them.
+fn record_insert(meta: &mut TopicMeta, record_columns: Option<usize>, now: chrono::DateTime<chrono::Utc>) {
+    meta.record_change(1, now);
+    if meta.columns.is_none() {
+        meta.columns = record_columns;
+    }
+}
+
+/// How `/sql insert into` handles a row whose primary key already exists
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OnConflict {
+    /// Reject the row (plain INSERT)
+    #[default]
+    Error,
+    /// Replace the existing row with the new values (upsert)
+    Update,
+}
+
+impl OnConflict {
+    /// Parse the `on_conflict` option; anything unrecognized keeps the rejection
+    pub fn from_option(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("update") => OnConflict::Update,
+            _ => OnConflict::Error,
+        }
+    }
+}
+
+/// What `run` does once a row has passed validation
+#[derive(Debug, PartialEq)]
+enum InsertAction {
+    /// Post the formatted row message to the table channel
+    Write(String),
+    /// Edit the stored row with the same primary key to hold the formatted row
+    Update(MessageId, String),
+    /// Validate-only mode: report success without posting anything
+    DryRun,
+}
+
+fn insert_action(values: &[SqlValue], schema: &[ColumnDefinition], validate_only: bool, existing_row: Option<MessageId>) -> InsertAction {
+    if validate_only {
+        return InsertAction::DryRun;
+    }
+    let formatted = format_sql_values_for_storage(values, schema);
+    match existing_row {
+        Some(message_id) => InsertAction::Update(message_id, formatted),
+        None => InsertAction::Write(formatted),
+    }
+}
+
 /// Format SQL values for storage in Discord message
-fn format_sql_values_for_storage(values: &[SqlValue], schema: &[ColumnDefinition]) -> String {
+pub(crate) fn format_sql_values_for_storage(values: &[SqlValue], schema: &[ColumnDefinition]) -> String {
     let mut parts = Vec::new();
     
     // Add timestamp
@@ -163,18 +338,23 @@ fn format_sql_values_for_storage(values: &[SqlValue], schema: &[ColumnDefinition
     if schema.is_empty() {
         // No schema - just format values by position
         for (i, value) in values.iter().enumerate() {
-            parts.push(format!("  column_{}: {}", i + 1, format_sql_value_for_display(value)));
+            parts.push(format!("  column_{}: {}", i + 1, format_sql_value_for_storage(value)));
         }
     } else {
         // Format according to schema order
+        // CHAR(n) values are stored padded to their fixed length
         for (column, value) in schema.iter().zip(values.iter()) {
-            parts.push(format!("  {}: {}", column.name, format_sql_value_for_display(value)));
+            let stored = match value {
+                SqlValue::String(s) => format_sql_value_for_storage(&SqlValue::String(pad_char_value(s, column))),
+                other => format_sql_value_for_storage(other),
+            };
+            parts.push(format!("  {}: {}", column.name, stored));
         }
         
         // Add any extra values beyond schema
         if values.len() > schema.len() {
             for (i, value) in values.iter().skip(schema.len()).enumerate() {
-                parts.push(format!("  extra_{}: {}", i + 1, format_sql_value_for_display(value)));
+                parts.push(format!("  extra_{}: {}", i + 1, format_sql_value_for_storage(value)));
             }
         }
     }
@@ -182,40 +362,60 @@ fn format_sql_values_for_storage(values: &[SqlValue], schema: &[ColumnDefinition
     parts.join("\n")
 }
 
+/// Reject a formatted row that wouldn't fit in one Discord message, instead of letting `send_message` fail
+pub(crate) fn check_row_size(formatted: &str) -> Result<(), SqlError> {
+    let length = formatted.chars().count();
+    if length > MESSAGE_CHAR_LIMIT {
+        return Err(SqlError::RowTooLong { length, limit: MESSAGE_CHAR_LIMIT });
+    }
+    Ok(())
+}
+
 /// Format SQL values for user-friendly display
 fn format_sql_values_for_display(values: &[SqlValue], schema: &[ColumnDefinition]) -> String {
     if schema.is_empty() {
         // No schema - just format values by position
         values.iter()
             .enumerate()
-            .map(|(i, value)| format!("• **Column {}:** {}", i + 1, format_sql_value_for_display(value)))
+            .map(|(i, value)| format!("• **Column {}:** {}", i + 1, format_sql_value_for_display(value, None)))
             .collect::<Vec<_>>()
             .join("\n")
     } else {
         // Use schema column names
         schema.iter()
             .zip(values.iter())
-            .map(|(column, value)| format!("• **{}:** {}", column.name, format_sql_value_for_display(value)))
+            .map(|(column, value)| format!("• **{}:** {}", column.name, format_sql_value_for_display(value, Some(column))))
             .collect::<Vec<_>>()
             .join("\n")
     }
 }
 
-/// Format a single SQL value for display
-fn format_sql_value_for_display(value: &SqlValue) -> String {
+/// Format a single SQL value for display; floats follow `column`'s DECIMAL scale or FLOAT precision
+fn format_sql_value_for_display(value: &SqlValue, column: Option<&ColumnDefinition>) -> String {
     match value {
         SqlValue::String(s) => format!("'{}'", s),
         SqlValue::Integer(n) => n.to_string(),
-        SqlValue::Float(f) => f.to_string(),
+        SqlValue::Float(f) => format_float_for_column(*f, column),
         SqlValue::Boolean(b) => b.to_string(),
         SqlValue::Null => "NULL".to_string(),
     }
 }
 
+/// Format a single SQL value for the stored row message (strings are escaped to stay on one line)
+fn format_sql_value_for_storage(value: &SqlValue) -> String {
+    match value {
+        SqlValue::String(s) => format!("'{}'", encode_stored_string(s)),
+        // Stored rows keep every digit; only the display is rounded
+        SqlValue::Float(f) => format_float(*f),
+        other => format_sql_value_for_display(other, None),
+    }
+}
+
 /// Parse table schema from channel topic
-fn parse_schema_from_topic(topic: &str) -> Result<Vec<ColumnDefinition>, serenity::builder::CreateEmbed> {
+fn parse_schema_from_topic(topic: &str) -> Result<Vec<ColumnDefinition>, SqlError> {
     if let Some(schema_start) = topic.find("Schema: ") {
-        let schema_str = &topic[schema_start + 8..];
+        // Only the schema line: the topic may also carry `Rows:` / `Columns:` lines
+        let schema_str = topic[schema_start + 8..].lines().next().unwrap_or_default();
         
         // Handle backward compatibility: if the schema contains colons (old format),
         // convert it to the new format before parsing
@@ -227,15 +427,7 @@ fn parse_schema_from_topic(topic: &str) -> Result<Vec<ColumnDefinition>, serenit
             schema_str.to_string()
         };
         
-        match parse_column_definitions(&normalized_schema) {
-            Ok(columns) => Ok(columns),
-            Err(e) => {
-                Err(create_error_embed(
-                    "✖️ Schema Parse Error",
-                    &format!("Failed to parse table schema: {}", e)
-                ))
-            }
-        }
+        parse_column_definitions(&normalized_schema).map_err(SqlError::SchemaParse)
     } else {
         Ok(Vec::new()) // No schema in topic
     }
@@ -248,16 +440,22 @@ fn format_schema_info(schema: &[ColumnDefinition]) -> String {
     } else {
         let column_info: Vec<String> = schema.iter()
             .map(|col| {
-                let mut info = format!("{} {}", col.name, col.data_type);
-                if let Some(size) = col.size {
-                    info += &format!("({})", size);
-                }
+                let mut info = format!("{} {}", col.name, col.type_display());
                 if !col.nullable {
                     info += " NOT NULL";
                 }
                 if col.primary_key {
                     info += " PRIMARY KEY";
                 }
+                if col.unique {
+                    info += " UNIQUE";
+                }
+                if col.auto_increment {
+                    info += " AUTO_INCREMENT";
+                }
+                if let Some(default) = &col.default {
+                    info += &format!(" DEFAULT {}", format_sql_literal(default));
+                }
                 info
             })
             .collect();
@@ -265,82 +463,367 @@ fn format_schema_info(schema: &[ColumnDefinition]) -> String {
     }
 }
 
-/// Check for primary key duplicates in existing messages
-async fn check_primary_key_duplicates(
-    ctx: &Context,
+/// Reorder values given for an explicit column list into schema order.
+/// Columns missing from the list get their DEFAULT value, or NULL if they have none.
+fn apply_column_list(column_list: &str, values: Vec<SqlValue>, schema: &[ColumnDefinition]) -> Result<Vec<SqlValue>, String> {
+    if schema.is_empty() {
+        return Err("❌ **Column lists require a schema** - This table has no schema, so values are stored by position".to_string());
+    }
+    
+    let names: Vec<&str> = column_list
+        .split(',')
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .collect();
+    
+    if names.len() != values.len() {
+        return Err(format!(
+            "❌ **Value count mismatch:** {} columns listed but {} values given",
+            names.len(),
+            values.len()
+        ));
+    }
+    
+    let mut ordered: Vec<SqlValue> = schema
+        .iter()
+        .map(|col| col.default.clone().unwrap_or(SqlValue::Null))
+        .collect();
+    let mut assigned = vec![false; schema.len()];
+    for (name, value) in names.iter().zip(values) {
+        let index = schema
+            .iter()
+            .position(|col| col.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!(
+                "❌ **Unknown column:** `{}`\n\n📋 **Available columns:** {}",
+                name,
+                schema.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+            ))?;
+        if assigned[index] {
+            return Err(format!("❌ **Duplicate column:** `{}` is listed more than once", name));
+        }
+        assigned[index] = true;
+        ordered[index] = value;
+    }
+    
+    Ok(ordered)
+}
+
+/// Compute the next AUTO_INCREMENT value for a column: the current maximum plus one
+fn next_auto_increment_value(existing_rows: &[Vec<SqlValue>], column_index: usize, column_name: &str) -> Result<i64, SqlError> {
+    let max = existing_rows
+        .iter()
+        .filter_map(|row| match row.get(column_index) {
+            Some(SqlValue::Integer(n)) => Some(*n),
+            _ => None,
+        })
+        .max();
+    match max {
+        None => Ok(1),
+        Some(max) => max.checked_add(1).ok_or_else(|| SqlError::InvalidData(format!(
+            "AUTO_INCREMENT column `{}` has reached the largest integer value ({}); no further value can be assigned.",
+            column_name, i64::MAX
+        ))),
+    }
+}
+
+/// Assign values to AUTO_INCREMENT columns that are NULL, scanning existing rows for the current maximum
+pub(crate) async fn assign_auto_increment_values(
+    ctx: &impl BotContext,
     channel: &serenity::model::channel::GuildChannel,
+    values: &mut [SqlValue],
+    schema: &[ColumnDefinition],
+) -> Result<(), SqlError> {
+    let pending: Vec<usize> = schema
+        .iter()
+        .enumerate()
+        .filter(|(index, col)| col.auto_increment && matches!(values.get(*index), Some(SqlValue::Null)))
+        .map(|(index, _)| index)
+        .collect();
+    
+    if pending.is_empty() {
+        return Ok(());
+    }
+    
+    let existing_rows = match fetch_existing_rows(ctx, channel, schema).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::error!("Failed to read rows for AUTO_INCREMENT: {e}");
+            return Err(SqlError::DiscordRequest { operation: "read existing rows to assign the next AUTO_INCREMENT value" });
+        }
+    };
+    
+    for index in pending {
+        values[index] = SqlValue::Integer(next_auto_increment_value(&existing_rows, index, &schema[index].name)?);
+    }
+    
+    Ok(())
+}
+
+/// A uniqueness violation found while checking a new row against existing rows
+#[derive(Debug, PartialEq)]
+pub(crate) enum ConstraintViolation {
+    /// The full primary-key tuple already exists
+    PrimaryKey { columns: Vec<String>, values: Vec<String> },
+    /// A UNIQUE column value already exists
+    Unique { column: String, value: String },
+}
+
+impl From<ConstraintViolation> for SqlError {
+    fn from(violation: ConstraintViolation) -> Self {
+        match violation {
+            ConstraintViolation::PrimaryKey { columns, values } => SqlError::ConstraintViolation {
+                kind: "Primary Key",
+                detail: format!(
+                    "**Duplicate primary key detected!**\n\nPrimary key column(s): **{}**\nValue(s): **{}**\n\n💡 **Tip:** Primary key values must be unique across all rows.",
+                    columns.join(", "),
+                    values.join(", ")
+                ),
+            },
+            ConstraintViolation::Unique { column, value } => SqlError::ConstraintViolation {
+                kind: "Unique Constraint",
+                detail: format!(
+                    "**Duplicate value detected!**\n\nUnique column: **{}**\nValue: **{}**\n\n💡 **Tip:** Values in UNIQUE columns must not repeat across rows.",
+                    column,
+                    value
+                ),
+            },
+        }
+    }
+}
+
+/// Find the first PRIMARY KEY or UNIQUE conflict between a new row and existing rows.
+/// Primary keys compare the full tuple; UNIQUE columns compare individually and ignore NULLs.
+pub(crate) fn find_constraint_violation(
     new_values: &[SqlValue],
+    existing_rows: &[Vec<SqlValue>],
     schema: &[ColumnDefinition],
-) -> Result<(), serenity::builder::CreateEmbed> {
-    // Find primary key column(s)
-    let primary_key_columns: Vec<(usize, &ColumnDefinition)> = schema
+) -> Option<ConstraintViolation> {
+    let primary_key_columns: Vec<usize> = schema
         .iter()
         .enumerate()
         .filter(|(_, col)| col.primary_key)
+        .map(|(index, _)| index)
         .collect();
     
-    // If no primary key defined, no need to check
-    if primary_key_columns.is_empty() {
-        return Ok(());
+    let unique_columns: Vec<usize> = schema
+        .iter()
+        .enumerate()
+        .filter(|(_, col)| col.unique && !col.primary_key)
+        .map(|(index, _)| index)
+        .collect();
+    
+    for existing in existing_rows {
+        if !primary_key_columns.is_empty() && primary_key_matches(new_values, existing, schema, &primary_key_columns) {
+            return Some(ConstraintViolation::PrimaryKey {
+                columns: primary_key_columns.iter().map(|&index| schema[index].name.clone()).collect(),
+                values: primary_key_columns
+                    .iter()
+                    .map(|&index| new_values.get(index).map_or_else(|| "NULL".to_string(), |value| format_sql_value_for_display(value, Some(&schema[index]))))
+                    .collect(),
+            });
+        }
+        
+        for &index in &unique_columns {
+            if let (Some(new_val), Some(existing_val)) = (new_values.get(index), existing.get(index))
+                && !matches!(new_val, SqlValue::Null)
+                && column_values_equal(new_val, existing_val, &schema[index])
+            {
+                return Some(ConstraintViolation::Unique {
+                    column: schema[index].name.clone(),
+                    value: format_sql_value_for_display(new_val, Some(&schema[index])),
+                });
+            }
+        }
     }
     
-    // Get primary key values from new data
-    let mut new_pk_values = Vec::new();
-    for (index, _column) in &primary_key_columns {
-        if let Some(value) = new_values.get(*index) {
-            new_pk_values.push(value);
-        } else {
-            return Err(create_error_embed(
-                "✖️ Primary Key Missing",
-                "Primary key value is required but not provided in the data."
-            ));
+    None
+}
+
+/// Whether two rows hold the same values in every primary key column
+fn primary_key_matches(new_values: &[SqlValue], existing: &[SqlValue], schema: &[ColumnDefinition], primary_key_columns: &[usize]) -> bool {
+    primary_key_columns.iter().all(|&index| {
+        match (new_values.get(index), existing.get(index)) {
+            (Some(new_val), Some(existing_val)) => column_values_equal(new_val, existing_val, &schema[index]),
+            _ => false,
+        }
+    })
+}
+
+/// The stored row an upsert replaces: the one sharing the new row's primary key, if any.
+/// UNIQUE columns are still checked against every other row.
+fn upsert_target(
+    new_values: &[SqlValue],
+    existing_rows: &[(MessageId, Vec<SqlValue>)],
+    schema: &[ColumnDefinition],
+) -> Result<Option<MessageId>, ConstraintViolation> {
+    let primary_key_columns: Vec<usize> = schema
+        .iter()
+        .enumerate()
+        .filter(|(_, col)| col.primary_key)
+        .map(|(index, _)| index)
+        .collect();
+    
+    let target = if primary_key_columns.is_empty() {
+        None
+    } else {
+        existing_rows
+            .iter()
+            .find(|(_, row)| primary_key_matches(new_values, row, schema, &primary_key_columns))
+            .map(|(message_id, _)| *message_id)
+    };
+    
+    let other_rows: Vec<Vec<SqlValue>> = existing_rows
+        .iter()
+        .filter(|(message_id, _)| Some(*message_id) != target)
+        .map(|(_, row)| row.clone())
+        .collect();
+    match find_constraint_violation(new_values, &other_rows, schema) {
+        Some(violation) => Err(violation),
+        None => Ok(target),
+    }
+}
+
+/// Check constraints for `on_conflict:update`, returning the message of the row to replace.
+/// Unlike plain inserts this fails closed: an unreadable table could otherwise gain a duplicate key.
+async fn find_upsert_target(
+    ctx: &impl BotContext,
+    channel: &serenity::model::channel::GuildChannel,
+    new_values: &[SqlValue],
+    schema: &[ColumnDefinition],
+) -> Result<Option<MessageId>, SqlError> {
+    if !schema.iter().any(|col| col.primary_key) {
+        check_unique_constraints(ctx, channel, new_values, schema).await?;
+        return Ok(None);
+    }
+    require_primary_key_values(new_values, schema)?;
+    
+    let existing_rows = match fetch_existing_row_messages(ctx, channel, schema).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::error!("Failed to read rows for upsert: {e}");
+            return Err(SqlError::DiscordRequest { operation: "read existing rows to find the row to update" });
+        }
+    };
+    upsert_target(new_values, &existing_rows, schema).map_err(SqlError::from)
+}
+
+/// Every primary key value must be provided
+fn require_primary_key_values(new_values: &[SqlValue], schema: &[ColumnDefinition]) -> Result<(), SqlError> {
+    for (index, column) in schema.iter().enumerate() {
+        if column.primary_key && new_values.get(index).is_none() {
+            return Err(SqlError::InvalidData("Primary key value is required but not provided in the data.".to_string()));
         }
     }
+    Ok(())
+}
+
+/// Check PRIMARY KEY and UNIQUE constraints against existing messages
+pub(crate) async fn check_unique_constraints(
+    ctx: &impl BotContext,
+    channel: &serenity::model::channel::GuildChannel,
+    new_values: &[SqlValue],
+    schema: &[ColumnDefinition],
+) -> Result<(), SqlError> {
+    // If no unique columns are defined, no need to check
+    if !schema.iter().any(|col| col.primary_key || col.unique) {
+        return Ok(());
+    }
     
-    // Fetch existing messages from the channel
-    let messages = match channel.messages(&ctx.http, serenity::builder::GetMessages::new().limit(100)).await {
-        Ok(messages) => messages,
+    require_primary_key_values(new_values, schema)?;
+    
+    // Fetch existing rows from the channel
+    let existing_rows = match fetch_existing_rows(ctx, channel, schema).await {
+        Ok(rows) => rows,
         Err(_) => {
             // If we can't read messages, allow the insert (fail-open for permissions issues)
             return Ok(());
         }
     };
     
-    // Check each existing message for primary key conflicts
-    for message in messages {
-        if let Some(existing_values) = extract_values_from_message(&message.content, schema) {
-            // Check if primary key values match
-            let mut matches = true;
-            for (i, (index, _column)) in primary_key_columns.iter().enumerate() {
-                if let (Some(new_val), Some(existing_val)) = (new_pk_values.get(i), existing_values.get(*index)) {
-                    if !sql_values_equal(new_val, existing_val) {
-                        matches = false;
-                        break;
-                    }
-                }
-            }
-            
-            if matches {
-                let pk_column_names: Vec<String> = primary_key_columns
-                    .iter()
-                    .map(|(_, col)| col.name.clone())
-                    .collect();
-                
-                return Err(create_error_embed(
-                    "✖️ Primary Key Violation",
-                    &format!(
-                        "**Duplicate primary key detected!**\n\nPrimary key column(s): **{}**\nValue(s): **{}**\n\n💡 **Tip:** Primary key values must be unique across all rows.",
-                        pk_column_names.join(", "),
-                        new_pk_values.iter().map(|v| format_sql_value_for_display(v)).collect::<Vec<_>>().join(", ")
-                    )
-                ));
+    match find_constraint_violation(new_values, &existing_rows, schema) {
+        Some(violation) => Err(violation.into()),
+        None => Ok(()),
+    }
+}
+
+/// Check REFERENCES columns: each non-NULL value must exist in the referenced column of its table.
+/// Unlike UNIQUE checks this fails closed, since an unreadable target could otherwise gain orphaned rows.
+pub(crate) async fn check_foreign_keys(
+    ctx: &impl BotContext,
+    guild_id: GuildId,
+    database: &str,
+    new_values: &[SqlValue],
+    schema: &[ColumnDefinition],
+) -> Result<(), SqlError> {
+    for (index, column) in schema.iter().enumerate() {
+        let (Some(references), Some(value)) = (&column.references, new_values.get(index)) else {
+            continue;
+        };
+        if matches!(value, SqlValue::Null) {
+            continue;
+        }
+        
+        let target = require_table(ctx, guild_id, database, &references.table).await?;
+        let target_schema = crate::api::table_schema(&target)?;
+        let Some(target_index) = target_schema.iter().position(|col| col.name.eq_ignore_ascii_case(&references.column)) else {
+            return Err(foreign_key_violation(&column.name, references, &format!("table **{}** has no column **{}**", references.table, references.column)));
+        };
+        let existing_rows = match fetch_existing_rows(ctx, &target, &target_schema).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                tracing::error!("Failed to read referenced table {}: {e}", target.name);
+                return Err(SqlError::DiscordRequest { operation: "read the referenced table" });
             }
+        };
+        let referenced_column = &target_schema[target_index];
+        let exists = existing_rows.iter().any(|row| row.get(target_index).is_some_and(|existing| column_values_equal(value, existing, referenced_column)));
+        if !exists {
+            let reason = format!("no row there has the value **{}**", format_sql_value_for_display(value, Some(column)));
+            return Err(foreign_key_violation(&column.name, references, &reason));
         }
     }
-    
     Ok(())
 }
 
+fn foreign_key_violation(column: &str, references: &ForeignKey, reason: &str) -> SqlError {
+    SqlError::ConstraintViolation {
+        kind: "Foreign Key",
+        detail: format!(
+            "**Foreign key violation!**\n\nColumn **{}** references **{}**, but {}.\n\n💡 **Tip:** Insert the referenced row first.",
+            column,
+            references,
+            reason
+        ),
+    }
+}
+
+/// Read the stored rows of a table channel (paged, up to the configured scan limit)
+async fn fetch_existing_rows(
+    ctx: &impl BotContext,
+    channel: &serenity::model::channel::GuildChannel,
+    schema: &[ColumnDefinition],
+) -> serenity::Result<Vec<Vec<SqlValue>>> {
+    let rows = fetch_existing_row_messages(ctx, channel, schema).await?;
+    Ok(rows.into_iter().map(|(_, row)| row).collect())
+}
+
+/// Stored rows of every shard with the ID of the message holding each one, newest first
+async fn fetch_existing_row_messages(
+    ctx: &impl BotContext,
+    channel: &serenity::model::channel::GuildChannel,
+    schema: &[ColumnDefinition],
+) -> serenity::Result<Vec<(MessageId, Vec<SqlValue>)>> {
+    let fetched = fetch_table_messages(ctx, channel, max_row_messages()).await?;
+    if fetched.truncated {
+        tracing::warn!("Table {} exceeds the scan limit; constraint checks only cover the newest rows", channel.name);
+    }
+    
+    Ok(fetched.messages
+        .iter()
+        .filter_map(|message| Some((message.id, extract_values_from_message(&message.content, schema)?)))
+        .collect())
+}
+
 /// Extract values from a stored message in schema order
 fn extract_values_from_message(content: &str, schema: &[ColumnDefinition]) -> Option<Vec<SqlValue>> {
     // Look for "DATA:" section
@@ -403,7 +886,7 @@ fn parse_stored_value(value_str: &str) -> Result<SqlValue, String> {
     if (trimmed.starts_with('\'') && trimmed.ends_with('\'')) || 
        (trimmed.starts_with('"') && trimmed.ends_with('"')) {
         let content = &trimmed[1..trimmed.len()-1];
-        return Ok(SqlValue::String(content.to_string()));
+        return Ok(SqlValue::String(decode_stored_string(content)));
     }
     
     // Check for integer
@@ -419,11 +902,21 @@ fn parse_stored_value(value_str: &str) -> Result<SqlValue, String> {
     Err(format!("Cannot parse stored value: {}", value_str))
 }
 
-/// Compare two SQL values for equality
+/// Compare two values of `column`; CHAR values ignore their trailing padding
+fn column_values_equal(a: &SqlValue, b: &SqlValue, column: &ColumnDefinition) -> bool {
+    match (a, b) {
+        (SqlValue::String(a), SqlValue::String(b)) if column.data_type == "CHAR" => char_values_equal(a, b),
+        _ => sql_values_equal(a, b),
+    }
+}
+
+/// Compare two SQL values for equality.
+/// FLOAT/DOUBLE/DECIMAL columns accept integer literals, so `Integer(5)` equals `Float(5.0)`.
 fn sql_values_equal(a: &SqlValue, b: &SqlValue) -> bool {
     match (a, b) {
         (SqlValue::Integer(a), SqlValue::Integer(b)) => a == b,
         (SqlValue::Float(a), SqlValue::Float(b)) => (a - b).abs() < f64::EPSILON,
+        (SqlValue::Integer(i), SqlValue::Float(f)) | (SqlValue::Float(f), SqlValue::Integer(i)) => *f == *i as f64,
         (SqlValue::String(a), SqlValue::String(b)) => a == b,
         (SqlValue::Boolean(a), SqlValue::Boolean(b)) => a == b,
         (SqlValue::Null, SqlValue::Null) => true,
@@ -431,4 +924,260 @@ fn sql_values_equal(a: &SqlValue, b: &SqlValue) -> bool {
     }
 }
 
-// Essential functionality only - no tests needed
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_parser::parse_column_definitions;
+
+    #[test]
+    fn test_insert_target() {
+        let args = |table, db| InsertArgs { table, data: "1", columns: None, validate_only: false, on_conflict: OnConflict::Error, db };
+        assert_eq!(insert_target(&args("sales.users", None)), Ok((Some("sales"), "users")));
+        assert_eq!(insert_target(&args("sales.users", Some("shop"))), Ok((Some("sales"), "users")));
+        // Unqualified tables use the db option, or the /sql use selection without one
+        assert_eq!(insert_target(&args("users", Some("shop"))), Ok((Some("shop"), "users")));
+        assert_eq!(insert_target(&args("users", None)), Ok((None, "users")));
+        assert!(insert_target(&args("sales.", None)).is_err());
+    }
+
+    #[test]
+    fn test_char_values_are_padded_and_compared_without_padding() {
+        let schema = parse_column_definitions("code CHAR(4) PRIMARY KEY, name VARCHAR(10)").unwrap();
+        let values = vec![SqlValue::String("AB".to_string()), SqlValue::String("x".to_string())];
+        let stored = format_sql_values_for_storage(&values, &schema);
+        assert!(stored.contains("  code: 'AB  '\n  name: 'x'"));
+
+        // The stored, padded key still conflicts with the unpadded one
+        let existing = vec![SqlValue::String("AB  ".to_string()), SqlValue::String("y".to_string())];
+        assert!(primary_key_matches(&values, &existing, &schema, &[0]));
+        assert!(!column_values_equal(&SqlValue::String("x ".to_string()), &SqlValue::String("x".to_string()), &schema[1]));
+    }
+
+    #[test]
+    fn test_coerced_floats_round_trip() {
+        let schema = parse_column_definitions("price FLOAT, qty INT").unwrap();
+        let mut values = vec![SqlValue::Integer(5), SqlValue::Integer(2)];
+        let note = coercion_note(&coerce_values_to_schema(&mut values, &schema)).unwrap();
+        assert!(note.contains("price") && !note.contains("qty"));
+
+        let stored = format_sql_values_for_storage(&values, &schema);
+        assert!(stored.contains("  price: 5.0\n  qty: 2"));
+        assert_eq!(parse_stored_value("5.0"), Ok(SqlValue::Float(5.0)));
+        assert_eq!(coercion_note(&[]), None);
+    }
+
+    #[test]
+    fn test_validate_only_posts_nothing() {
+        let schema = parse_column_definitions("id INT PRIMARY KEY, name VARCHAR(20)").unwrap();
+        let values = vec![SqlValue::Integer(1), SqlValue::String("Ann".to_string())];
+
+        assert_eq!(insert_action(&values, &schema, true, None), InsertAction::DryRun);
+        match insert_action(&values, &schema, false, None) {
+            InsertAction::Write(content) => assert!(content.contains("  id: 1\n  name: 'Ann'")),
+            other => panic!("a normal insert must write the row, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_upsert_updates_the_existing_row() {
+        let schema = parse_column_definitions("id INT PRIMARY KEY, name VARCHAR(20), email VARCHAR(30) UNIQUE").unwrap();
+        let mut messages: Vec<(MessageId, String)> = Vec::new();
+        let write = |values: Vec<SqlValue>, messages: &mut Vec<(MessageId, String)>| {
+            let existing: Vec<(MessageId, Vec<SqlValue>)> = messages
+                .iter()
+                .filter_map(|(id, content)| Some((*id, extract_values_from_message(content, &schema)?)))
+                .collect();
+            let target = upsert_target(&values, &existing, &schema)?;
+            match insert_action(&values, &schema, false, target) {
+                InsertAction::Write(content) => messages.push((MessageId::new(messages.len() as u64 + 1), content)),
+                InsertAction::Update(id, content) => messages.iter_mut().find(|(m, _)| *m == id).unwrap().1 = content,
+                InsertAction::DryRun => unreachable!(),
+            }
+            Ok::<(), ConstraintViolation>(())
+        };
+
+        let row = |id: i64, name: &str, email: &str| vec![SqlValue::Integer(id), SqlValue::String(name.to_string()), SqlValue::String(email.to_string())];
+        write(row(1, "Ann", "ann@x.io"), &mut messages).unwrap();
+        write(row(1, "Annie", "ann@x.io"), &mut messages).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].1.contains("  name: 'Annie'"));
+
+        // A new key inserts, and UNIQUE values still can't collide with other rows
+        write(row(2, "Bo", "bo@x.io"), &mut messages).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(write(row(2, "Bo", "ann@x.io"), &mut messages), Err(ConstraintViolation::Unique { .. })));
+    }
+
+    #[test]
+    fn test_schemaless_column_count_warning() {
+        assert_eq!(implicit_column_count(None), None);
+        assert_eq!(implicit_column_count(Some("Columns: 3")), Some(3));
+        assert_eq!(implicit_column_count(Some("Raw logs\nColumns: 2")), Some(2));
+        assert_eq!(implicit_column_count(Some("Schema: id INT")), None);
+
+        // The first row sets the count, matching rows pass, different counts only warn
+        assert_eq!(column_count_warning(None, 4), None);
+        assert_eq!(column_count_warning(Some(3), 3), None);
+        let warning = column_count_warning(Some(3), 2).unwrap();
+        assert!(warning.contains("**3** values") && warning.contains("has **2**"));
+    }
+
+    #[test]
+    fn test_check_row_size() {
+        let schema = parse_column_definitions("id INT, note VARCHAR(5000)").unwrap();
+        let row = |note: &str| format_sql_values_for_storage(&[SqlValue::Integer(1), SqlValue::String(note.to_string())], &schema);
+        assert_eq!(check_row_size(&row("short")), Ok(()));
+
+        let oversized = row(&"x".repeat(2500));
+        let error = check_row_size(&oversized).unwrap_err();
+        assert_eq!(error, SqlError::RowTooLong { length: oversized.chars().count(), limit: MESSAGE_CHAR_LIMIT });
+        let embed = serde_json::to_value(serenity::builder::CreateEmbed::from(error)).unwrap();
+        assert!(embed["title"].as_str().unwrap().contains("Row Too Large"));
+        assert!(embed["description"].as_str().unwrap().contains("fewer or shorter values"));
+
+        // The limit counts characters, not bytes
+        let overhead = row("").chars().count();
+        assert_eq!(check_row_size(&row(&"é".repeat(MESSAGE_CHAR_LIMIT - overhead))), Ok(()));
+    }
+
+    #[test]
+    fn test_record_insert() {
+        let now = chrono::Utc::now();
+        // The cached row count goes up by one; the schema is kept
+        let mut meta = TopicMeta::parse(Some("Schema: id INT\nRows: 4"));
+        record_insert(&mut meta, None, now);
+        assert_eq!((meta.schema.as_deref(), meta.rows, meta.modified, meta.columns), (Some("id INT"), Some(5), Some(now), None));
+        // The first schemaless row records its value count; later rows keep it
+        let mut meta = TopicMeta::parse(Some("Rows: 0"));
+        record_insert(&mut meta, Some(3), now);
+        assert_eq!((meta.rows, meta.columns), (Some(1), Some(3)));
+        let mut meta = TopicMeta::parse(Some("Rows: 1\nColumns: 2"));
+        record_insert(&mut meta, Some(3), now);
+        assert_eq!((meta.rows, meta.columns), (Some(2), Some(2)));
+    }
+
+    #[test]
+    fn test_on_conflict_from_option() {
+        assert_eq!(OnConflict::from_option(Some("UPDATE")), OnConflict::Update);
+        assert_eq!(OnConflict::from_option(Some("error")), OnConflict::Error);
+        assert_eq!(OnConflict::from_option(None), OnConflict::Error);
+    }
+
+    #[test]
+    fn test_sql_values_equal_across_numeric_types() {
+        assert!(sql_values_equal(&SqlValue::Integer(5), &SqlValue::Float(5.0)));
+        assert!(sql_values_equal(&SqlValue::Float(5.0), &SqlValue::Integer(5)));
+        assert!(!sql_values_equal(&SqlValue::Integer(5), &SqlValue::Float(5.5)));
+        assert!(!sql_values_equal(&SqlValue::Float(5.5), &SqlValue::Integer(5)));
+        assert!(sql_values_equal(&SqlValue::Integer(-3), &SqlValue::Float(-3.0)));
+        // Numbers never equal their text form
+        assert!(!sql_values_equal(&SqlValue::Integer(5), &SqlValue::String("5".to_string())));
+    }
+
+    #[test]
+    fn test_next_auto_increment_value() {
+        // Empty table starts at 1
+        assert_eq!(next_auto_increment_value(&[], 0, "id").unwrap(), 1);
+        
+        let rows = vec![
+            vec![SqlValue::Integer(3), SqlValue::String("a".to_string())],
+            vec![SqlValue::Integer(7), SqlValue::String("b".to_string())],
+            vec![SqlValue::Null, SqlValue::String("c".to_string())],
+            vec![SqlValue::Integer(5), SqlValue::String("d".to_string())],
+        ];
+        assert_eq!(next_auto_increment_value(&rows, 0, "id").unwrap(), 8);
+
+        // The maximum value has no successor
+        let rows = vec![vec![SqlValue::Integer(i64::MAX)]];
+        assert!(matches!(next_auto_increment_value(&rows, 0, "id"), Err(SqlError::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_apply_column_list() {
+        let schema = parse_column_definitions("id INT PRIMARY KEY AUTO_INCREMENT, name VARCHAR(50), age INT").unwrap();
+        
+        // Omitted columns without a default become NULL and values land in schema order
+        let values = vec![SqlValue::Integer(30), SqlValue::String("John".to_string())];
+        let ordered = apply_column_list("age, Name", values, &schema).unwrap();
+        assert!(matches!(ordered[0], SqlValue::Null));
+        assert!(matches!(ordered[1], SqlValue::String(ref s) if s == "John"));
+        assert!(matches!(ordered[2], SqlValue::Integer(30)));
+        
+        // Unknown, duplicate and miscounted columns are rejected
+        assert!(apply_column_list("nope", vec![SqlValue::Integer(1)], &schema).is_err());
+        assert!(apply_column_list("age, age", vec![SqlValue::Integer(1), SqlValue::Integer(2)], &schema).is_err());
+        assert!(apply_column_list("age, name", vec![SqlValue::Integer(1)], &schema).is_err());
+    }
+
+    #[test]
+    fn test_apply_column_list_uses_defaults() {
+        let schema = parse_column_definitions("name VARCHAR(50), status VARCHAR(20) DEFAULT 'active', note VARCHAR(20)").unwrap();
+        
+        let ordered = apply_column_list("name", vec![SqlValue::String("John".to_string())], &schema).unwrap();
+        assert!(matches!(ordered[1], SqlValue::String(ref s) if s == "active"));
+        assert!(matches!(ordered[2], SqlValue::Null));
+        
+        // An explicit value overrides the default
+        let values = vec![SqlValue::String("John".to_string()), SqlValue::String("banned".to_string())];
+        let ordered = apply_column_list("name, status", values, &schema).unwrap();
+        assert!(matches!(ordered[1], SqlValue::String(ref s) if s == "banned"));
+    }
+
+    #[test]
+    fn test_unique_violation() {
+        let schema = parse_column_definitions("id INT PRIMARY KEY, email VARCHAR(50) UNIQUE, name VARCHAR(50)").unwrap();
+        let existing = vec![
+            vec![SqlValue::Integer(1), SqlValue::String("a@x.io".to_string()), SqlValue::String("Alice".to_string())],
+        ];
+        
+        let new_row = vec![SqlValue::Integer(2), SqlValue::String("a@x.io".to_string()), SqlValue::String("Bob".to_string())];
+        assert_eq!(
+            find_constraint_violation(&new_row, &existing, &schema),
+            Some(ConstraintViolation::Unique { column: "email".to_string(), value: "'a@x.io'".to_string() })
+        );
+        
+        let new_row = vec![SqlValue::Integer(1), SqlValue::String("b@x.io".to_string()), SqlValue::String("Bob".to_string())];
+        assert!(matches!(
+            find_constraint_violation(&new_row, &existing, &schema),
+            Some(ConstraintViolation::PrimaryKey { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unique_allows_distinct_values_and_nulls() {
+        let schema = parse_column_definitions("id INT PRIMARY KEY, email VARCHAR(50) UNIQUE").unwrap();
+        let existing = vec![
+            vec![SqlValue::Integer(1), SqlValue::String("a@x.io".to_string())],
+            vec![SqlValue::Integer(2), SqlValue::Null],
+        ];
+        
+        let new_row = vec![SqlValue::Integer(3), SqlValue::String("b@x.io".to_string())];
+        assert_eq!(find_constraint_violation(&new_row, &existing, &schema), None);
+        
+        // Multiple NULLs don't conflict in a UNIQUE column
+        let new_row = vec![SqlValue::Integer(4), SqlValue::Null];
+        assert_eq!(find_constraint_violation(&new_row, &existing, &schema), None);
+    }
+
+    #[test]
+    fn test_composite_primary_key_requires_full_tuple() {
+        let schema = parse_column_definitions("order_id INT, product_id INT, qty INT, PRIMARY KEY (order_id, product_id)").unwrap();
+        let existing = vec![
+            vec![SqlValue::Integer(1), SqlValue::Integer(10), SqlValue::Integer(2)],
+        ];
+        
+        // Same tuple is a duplicate
+        let new_row = vec![SqlValue::Integer(1), SqlValue::Integer(10), SqlValue::Integer(5)];
+        assert_eq!(
+            find_constraint_violation(&new_row, &existing, &schema),
+            Some(ConstraintViolation::PrimaryKey {
+                columns: vec!["order_id".to_string(), "product_id".to_string()],
+                values: vec!["1".to_string(), "10".to_string()],
+            })
+        );
+        
+        // A partial match on one key column is allowed
+        let new_row = vec![SqlValue::Integer(1), SqlValue::Integer(11), SqlValue::Integer(5)];
+        assert_eq!(find_constraint_violation(&new_row, &existing, &schema), None);
+    }
+}
@@ -0,0 +1,355 @@
+// /sql join left:<table> right:<table> on:<left_col>=<right_col> [kind] [columns] [where]
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Instant;
+use serenity::prelude::Context;
+use serenity::model::id::{GuildId, UserId};
+use serenity::builder::CreateEmbed;
+use crate::commands::options::{JoinArgs, SelectArgs};
+use crate::error::SqlError;
+use crate::guards::resolve_database;
+use crate::logging::{log_debug, log_info};
+use crate::services::discord_fs::max_row_messages;
+use crate::sql_parser::{format_float, ColumnDefinition, SqlValue};
+use super::select::{
+    check_where_columns, find_column_index, load_table, parse_column_selection, select_from, split_qualified_table,
+    LoadedTable, OutputFormat, SelectOutput,
+};
+
+pub fn register() -> Result<(), Box<dyn Error>> {
+    log_debug("Registering JOIN command");
+    Ok(())
+}
+
+/// Join two tables on one column each and render the combined rows like `/sql select`.
+/// `kind:left` keeps left rows without a match, with NULL in the right table's columns.
+/// Either table may be qualified as `database.table`; unqualified tables come from the `/sql use` selection.
+/// Columns that exist in both tables are prefixed with their table name, e.g. `users.id` and `orders.id`.
+/// Each table is read up to `SQLCORD_MAX_ROWS` messages, and the join stops after as many combined rows.
+pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, args: &JoinArgs<'_>) -> Result<SelectOutput, CreateEmbed> {
+    log_info(&format!("JOIN command executed: left={}, right={}, on={}, kind={:?}, columns={:?}, where={:?}",
+                      args.left, args.right, args.on, args.kind, args.columns, args.where_clause));
+
+    let (left_db, left_table) = split_qualified_table(args.left, None)?;
+    let (right_db, right_table) = split_qualified_table(args.right, None)?;
+    let left_db = resolve_database(ctx, guild_id, user_id, left_db).await?;
+    let right_db = resolve_database(ctx, guild_id, user_id, right_db).await?;
+    if left_db == right_db && left_table.eq_ignore_ascii_case(right_table) {
+        return Err(SqlError::InvalidJoin(format!("**{}** can't be joined with itself.", left_table)).into());
+    }
+
+    let query_start = Instant::now();
+    let left = JoinSide::new(left_table, load_table(ctx, guild_id, &left_db, left_table).await?);
+    let right = JoinSide::new(right_table, load_table(ctx, guild_id, &right_db, right_table).await?);
+    let (left_key, right_key) = parse_join_condition(args.on, &left, &right)?;
+
+    let joined = hash_join(&left, left_key, &right, right_key, args.kind, max_row_messages());
+    if let Some(where_condition) = args.where_clause {
+        check_where_columns(where_condition, &joined.schema)?;
+    }
+    let selected_columns = parse_column_selection(args.columns.unwrap_or("*"), &joined.schema, joined.stored_columns)?;
+
+    let select_args = SelectArgs {
+        columns: args.columns.unwrap_or("*"),
+        from: args.left,
+        distinct: None,
+        where_clause: args.where_clause,
+        ephemeral: false,
+        format: OutputFormat::Table,
+        to_thread: false,
+        max_rows: None,
+        db: None,
+    };
+    Ok(select_from(joined, selected_columns, &select_args, query_start))
+}
+
+/// Which rows a join returns
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum JoinKind {
+    /// Only pairs of rows whose keys match
+    #[default]
+    Inner,
+    /// Also every unmatched left row, padded with NULLs
+    Left,
+}
+
+impl JoinKind {
+    /// Parse the `kind` option; anything unrecognized is an inner join
+    pub fn from_option(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("left") => JoinKind::Left,
+            _ => JoinKind::Inner,
+        }
+    }
+}
+
+/// One table of a join: its name, stored columns and rows
+struct JoinSide {
+    name: String,
+    schema: Vec<ColumnDefinition>,
+    rows: Vec<Vec<SqlValue>>,
+    messages_scanned: usize,
+    scan_truncated: bool,
+}
+
+impl JoinSide {
+    /// Keep only the stored columns: implicit ones like `_rowid` would collide in every join
+    fn new(name: &str, table: LoadedTable) -> Self {
+        let LoadedTable { mut schema, stored_columns, rows, messages_scanned, scan_truncated } = table;
+        schema.truncate(stored_columns);
+        let rows = rows
+            .into_iter()
+            .map(|mut row| {
+                row.resize(stored_columns, SqlValue::Null);
+                row
+            })
+            .collect();
+        JoinSide { name: name.to_string(), schema, rows, messages_scanned, scan_truncated }
+    }
+
+    /// Column `name` of this table, optionally qualified with the table name (`users.id`)
+    fn column_index(&self, name: &str) -> Option<usize> {
+        let unqualified = match name.split_once('.') {
+            Some((table, column)) if table.trim().eq_ignore_ascii_case(&self.name) => column.trim(),
+            Some(_) => return None,
+            None => name,
+        };
+        find_column_index(&self.schema, unqualified)
+    }
+
+    fn column_list(&self) -> String {
+        self.schema.iter().map(|col| col.name.as_str()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Resolve `on` (`left_col=right_col`) to a column index in each table.
+/// The sides may be written in either order when the column names tell them apart, e.g. `orders.user_id=users.id`.
+fn parse_join_condition(on: &str, left: &JoinSide, right: &JoinSide) -> Result<(usize, usize), SqlError> {
+    let Some((first, second)) = on.split_once('=') else {
+        return Err(SqlError::InvalidJoin(format!(
+            "`{}` is not a join condition. Use `left_column=right_column`, e.g. `id=user_id`.",
+            on
+        )));
+    };
+    let (first, second) = (first.trim(), second.trim());
+    if first.is_empty() || second.is_empty() || first.ends_with(['!', '<', '>']) || second.contains('=') {
+        return Err(SqlError::InvalidJoin(format!(
+            "`{}` is not a join condition. JOIN only matches equal values: `left_column=right_column`.",
+            on
+        )));
+    }
+
+    if let (Some(left_key), Some(right_key)) = (left.column_index(first), right.column_index(second)) {
+        return Ok((left_key, right_key));
+    }
+    if let (Some(left_key), Some(right_key)) = (left.column_index(second), right.column_index(first)) {
+        return Ok((left_key, right_key));
+    }
+    Err(SqlError::InvalidJoin(format!(
+        "`{}` must compare a column of **{}** with a column of **{}**.\n\n**{} columns:** {}\n**{} columns:** {}",
+        on,
+        left.name,
+        right.name,
+        left.name,
+        left.column_list(),
+        right.name,
+        right.column_list()
+    )))
+}
+
+/// Columns of the joined rows: the left table's, then the right table's.
+/// A name both tables use is prefixed with the table name on each side.
+fn merged_columns(left: &JoinSide, right: &JoinSide) -> Vec<ColumnDefinition> {
+    let prefixed = |side: &JoinSide, other: &JoinSide| -> Vec<ColumnDefinition> {
+        side.schema
+            .iter()
+            .map(|col| {
+                let mut col = col.clone();
+                if find_column_index(&other.schema, &col.name).is_some() {
+                    col.name = format!("{}.{}", side.name, col.name);
+                }
+                col
+            })
+            .collect()
+    };
+    let mut columns = prefixed(left, right);
+    columns.extend(prefixed(right, left));
+    columns
+}
+
+/// Hash join: index the right rows by key, then pair every left row with each right row of equal key.
+/// Rows keep the left table's order. NULL keys never match; with `JoinKind::Left` an unmatched left row
+/// appears once with NULL right columns. Stops after `limit` joined rows.
+fn hash_join(left: &JoinSide, left_key: usize, right: &JoinSide, right_key: usize, kind: JoinKind, limit: usize) -> LoadedTable {
+    let mut index: HashMap<String, Vec<&Vec<SqlValue>>> = HashMap::new();
+    for row in &right.rows {
+        if let Some(key) = join_key(&row[right_key], &right.schema[right_key]) {
+            index.entry(key).or_default().push(row);
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let null_row = vec![SqlValue::Null; right.schema.len()];
+    let unmatched = [&null_row];
+    'left_rows: for left_row in &left.rows {
+        let matches = match join_key(&left_row[left_key], &left.schema[left_key]).and_then(|key| index.get(&key)) {
+            Some(matches) => matches.as_slice(),
+            None if kind == JoinKind::Left => &unmatched[..],
+            None => continue,
+        };
+        for right_row in matches {
+            if rows.len() == limit {
+                truncated = true;
+                break 'left_rows;
+            }
+            rows.push(left_row.iter().chain(right_row.iter()).cloned().collect());
+        }
+    }
+
+    let schema = merged_columns(left, right);
+    LoadedTable {
+        stored_columns: schema.len(),
+        schema,
+        rows,
+        messages_scanned: left.messages_scanned + right.messages_scanned,
+        scan_truncated: truncated || left.scan_truncated || right.scan_truncated,
+    }
+}
+
+/// Value a join matches on: numbers compare by value across INT and FLOAT columns, and CHAR padding is ignored
+fn join_key(value: &SqlValue, column: &ColumnDefinition) -> Option<String> {
+    match value {
+        SqlValue::Null => None,
+        SqlValue::Integer(i) => Some(format!("n:{}", i)),
+        SqlValue::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => Some(format!("n:{}", *f as i64)),
+        SqlValue::Float(f) => Some(format!("n:{}", format_float(*f))),
+        SqlValue::String(s) if column.data_type == "CHAR" => Some(format!("s:{}", s.trim_end())),
+        SqlValue::String(s) => Some(format!("s:{}", s)),
+        SqlValue::Boolean(b) => Some(format!("b:{}", b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_parser::parse_column_definitions;
+    use super::super::select::evaluate_where_condition;
+
+    fn side(name: &str, schema: &str, rows: Vec<Vec<SqlValue>>) -> JoinSide {
+        JoinSide { name: name.to_string(), schema: parse_column_definitions(schema).unwrap(), rows, messages_scanned: 0, scan_truncated: false }
+    }
+
+    fn users() -> JoinSide {
+        side("users", "id INT, name VARCHAR(20)", vec![
+            vec![SqlValue::Integer(1), SqlValue::String("Ann".to_string())],
+            vec![SqlValue::Integer(2), SqlValue::String("Bob".to_string())],
+            vec![SqlValue::Null, SqlValue::String("Nobody".to_string())],
+        ])
+    }
+
+    fn orders(rows: Vec<(i64, Option<i64>, f64)>) -> JoinSide {
+        let rows = rows
+            .into_iter()
+            .map(|(id, user_id, total)| vec![SqlValue::Integer(id), user_id.map_or(SqlValue::Null, SqlValue::Integer), SqlValue::Float(total)])
+            .collect();
+        side("orders", "id INT, user_id INT, total FLOAT", rows)
+    }
+
+    fn names(schema: &[ColumnDefinition]) -> Vec<&str> {
+        schema.iter().map(|col| col.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_hash_join_matches() {
+        let (users, orders) = (users(), orders(vec![(10, Some(2), 5.0), (11, Some(1), 7.5), (12, Some(2), 1.25), (13, None, 9.0)]));
+        let (left_key, right_key) = parse_join_condition("id=user_id", &users, &orders).unwrap();
+        let joined = hash_join(&users, left_key, &orders, right_key, JoinKind::Inner, 100);
+
+        // Left table order, every matching right row; NULL keys and unmatched rows drop out
+        let order_ids: Vec<&SqlValue> = joined.rows.iter().map(|row| &row[2]).collect();
+        assert_eq!(order_ids, [&SqlValue::Integer(11), &SqlValue::Integer(10), &SqlValue::Integer(12)]);
+        assert_eq!(joined.rows[0][1], SqlValue::String("Ann".to_string()));
+        assert!(!joined.scan_truncated);
+
+        // The limit caps the combined rows and is reported like a scan limit
+        let joined = hash_join(&users, left_key, &orders, right_key, JoinKind::Inner, 2);
+        assert_eq!(joined.rows.len(), 2);
+        assert!(joined.scan_truncated);
+    }
+
+    #[test]
+    fn test_hash_join_empty() {
+        let users = users();
+        let joined = hash_join(&users, 0, &orders(vec![(10, Some(99), 5.0)]), 1, JoinKind::Inner, 100);
+        assert!(joined.rows.is_empty());
+        assert_eq!(joined.stored_columns, 5);
+
+        let joined = hash_join(&users, 0, &orders(Vec::new()), 1, JoinKind::Inner, 100);
+        assert!(joined.rows.is_empty());
+    }
+
+    #[test]
+    fn test_left_join_keeps_unmatched_rows() {
+        let no_orders = orders(Vec::new());
+        let (users, orders) = (users(), orders(vec![(10, Some(2), 5.0), (11, Some(2), 1.25)]));
+        let inner = hash_join(&users, 0, &orders, 1, JoinKind::Inner, 100);
+        let left = hash_join(&users, 0, &orders, 1, JoinKind::Left, 100);
+
+        let user_names = |joined: &LoadedTable| -> Vec<SqlValue> { joined.rows.iter().map(|row| row[1].clone()).collect() };
+        let name = |n: &str| SqlValue::String(n.to_string());
+        assert_eq!(user_names(&inner), [name("Bob"), name("Bob")]);
+        // Ann has no orders and Nobody's NULL key matches nothing; both still appear once, in left order
+        assert_eq!(user_names(&left), [name("Ann"), name("Bob"), name("Bob"), name("Nobody")]);
+        assert_eq!(&left.rows[0][2..], [SqlValue::Null, SqlValue::Null, SqlValue::Null]);
+        assert_eq!(left.rows[1][2], SqlValue::Integer(10));
+
+        // Nothing to match: a left join is the left table with NULL right columns
+        let left = hash_join(&users, 0, &no_orders, 1, JoinKind::Left, 100);
+        assert_eq!(left.rows.len(), 3);
+        assert_eq!(JoinKind::from_option(Some(" Left ")), JoinKind::Left);
+        assert_eq!(JoinKind::from_option(None), JoinKind::Inner);
+    }
+
+    #[test]
+    fn test_merged_columns_prefix_collisions() {
+        let (users, orders) = (users(), orders(Vec::new()));
+        let columns = merged_columns(&users, &orders);
+        assert_eq!(names(&columns), ["users.id", "name", "orders.id", "user_id", "total"]);
+
+        // Prefixed names work in WHERE and the column list
+        let row = vec![SqlValue::Integer(1), SqlValue::String("Ann".to_string()), SqlValue::Integer(10), SqlValue::Integer(1), SqlValue::Float(7.5)];
+        assert!(evaluate_where_condition(&row, &columns, "orders.id=10 AND name='Ann'"));
+        assert!(!evaluate_where_condition(&row, &columns, "users.id=10"));
+        assert!(parse_column_selection("users.id, total", &columns, columns.len()).is_ok());
+    }
+
+    #[test]
+    fn test_parse_join_condition() {
+        let (users, orders) = (users(), orders(Vec::new()));
+        assert_eq!(parse_join_condition("id = user_id", &users, &orders), Ok((0, 1)));
+        assert_eq!(parse_join_condition("users.id=orders.user_id", &users, &orders), Ok((0, 1)));
+        // Written right-to-left
+        assert_eq!(parse_join_condition("orders.user_id=users.id", &users, &orders), Ok((0, 1)));
+        assert_eq!(parse_join_condition("orders.id=users.id", &users, &orders), Ok((0, 0)));
+
+        for invalid in ["id", "id<>user_id", "id!=user_id", "=user_id"] {
+            assert!(matches!(parse_join_condition(invalid, &users, &orders), Err(SqlError::InvalidJoin(_))), "{invalid}");
+        }
+        let Err(SqlError::InvalidJoin(message)) = parse_join_condition("id=customer", &users, &orders) else {
+            panic!("unknown column accepted");
+        };
+        assert!(message.contains("**orders columns:** id, user_id, total"));
+    }
+
+    #[test]
+    fn test_join_key_normalizes_values() {
+        let int = parse_column_definitions("n INT").unwrap().remove(0);
+        let code = parse_column_definitions("code CHAR(4)").unwrap().remove(0);
+        assert_eq!(join_key(&SqlValue::Integer(3), &int), join_key(&SqlValue::Float(3.0), &int));
+        assert_ne!(join_key(&SqlValue::Integer(3), &int), join_key(&SqlValue::String("3".to_string()), &code));
+        assert_eq!(join_key(&SqlValue::String("ab  ".to_string()), &code), Some("s:ab".to_string()));
+        assert_eq!(join_key(&SqlValue::Null, &int), None);
+    }
+}
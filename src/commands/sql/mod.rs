@@ -4,15 +4,52 @@ pub mod drop;
 
 pub mod use_;
 pub mod select;
+pub mod expression;
+pub mod count;
+pub mod sample;
+pub mod copy;
+pub mod dump;
+pub mod restore;
+pub mod join;
+pub mod run;
 pub mod insert;
 pub mod update;
 pub mod delete;
 pub mod explain;
+pub mod status;
+pub mod autocomplete;
 
 use serenity::builder::{CreateCommand, CreateCommandOption};
 use serenity::model::application::CommandOptionType;
 use std::error::Error;
-use crate::logging::{log_info, log_error};
+use std::sync::OnceLock;
+use crate::logging::{log_debug, log_info, log_error, log_warn};
+
+/// Top-level command name when `SQLCORD_COMMAND_NAME` is unset or invalid
+pub const DEFAULT_COMMAND_NAME: &str = "sql";
+
+/// The top-level command name: `SQLCORD_COMMAND_NAME`, so SQLcord can register as e.g. `/sqlcord` next to
+/// another bot's `/sql`. Read once; registration and dispatch both use it.
+pub fn sql_command_name() -> &'static str {
+    static NAME: OnceLock<String> = OnceLock::new();
+    NAME.get_or_init(|| {
+        let value = std::env::var("SQLCORD_COMMAND_NAME").ok();
+        parse_command_name(value.as_deref()).unwrap_or_else(|| {
+            if let Some(value) = value {
+                log_warn(&format!("SQLCORD_COMMAND_NAME `{}` is not a valid command name; using /{}", value, DEFAULT_COMMAND_NAME));
+            }
+            DEFAULT_COMMAND_NAME.to_string()
+        })
+    })
+}
+
+/// A `SQLCORD_COMMAND_NAME` value as a Discord command name: 1-32 letters, digits, `-` or `_`, lowercased.
+/// `None` when unset, blank or invalid.
+pub fn parse_command_name(value: Option<&str>) -> Option<String> {
+    let name = value?.trim().to_ascii_lowercase();
+    let valid = (1..=32).contains(&name.len()) && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    valid.then_some(name)
+}
 
 /// Register all SQL commands dynamically
 pub fn register_all_sql_commands() -> Result<(), Box<dyn Error>> {
@@ -30,7 +67,7 @@ pub fn register_all_sql_commands() -> Result<(), Box<dyn Error>> {
     }
     
     // Register individual commands
-    log_info("Registering individual SQL commands...");
+    log_debug("Registering individual SQL commands...");
     
     if let Err(e) = use_::register() {
         log_error(&format!("Failed to register USE command: {}", e));
@@ -42,6 +79,41 @@ pub fn register_all_sql_commands() -> Result<(), Box<dyn Error>> {
         return Err(e);
     }
     
+    if let Err(e) = count::register() {
+        log_error(&format!("Failed to register COUNT command: {}", e));
+        return Err(e);
+    }
+    
+    if let Err(e) = sample::register() {
+        log_error(&format!("Failed to register SAMPLE command: {}", e));
+        return Err(e);
+    }
+    
+    if let Err(e) = copy::register() {
+        log_error(&format!("Failed to register COPY command: {}", e));
+        return Err(e);
+    }
+    
+    if let Err(e) = dump::register() {
+        log_error(&format!("Failed to register DUMP commands: {}", e));
+        return Err(e);
+    }
+    
+    if let Err(e) = restore::register() {
+        log_error(&format!("Failed to register RESTORE command: {}", e));
+        return Err(e);
+    }
+    
+    if let Err(e) = join::register() {
+        log_error(&format!("Failed to register JOIN command: {}", e));
+        return Err(e);
+    }
+    
+    if let Err(e) = run::register() {
+        log_error(&format!("Failed to register RUN command: {}", e));
+        return Err(e);
+    }
+    
     if let Err(e) = insert::register() {
         log_error(&format!("Failed to register INSERT command: {}", e));
         return Err(e);
@@ -62,13 +134,23 @@ pub fn register_all_sql_commands() -> Result<(), Box<dyn Error>> {
         return Err(e);
     }
     
+    if let Err(e) = status::register() {
+        log_error(&format!("Failed to register STATUS command: {}", e));
+        return Err(e);
+    }
+    
     log_info("All SQL commands registered successfully!");
     Ok(())
 }
 
-/// Build and return the `/sql` CreateCommand builder.
+/// Build and return the `/sql` CreateCommand builder, named by `sql_command_name`.
 pub fn register_sql_group() -> CreateCommand {
-    CreateCommand::new("sql").description("Run SQL-like operations mapped to Discord")
+    sql_group_named(sql_command_name())
+}
+
+/// The SQL command group registered as `/<name>`
+fn sql_group_named(name: &str) -> CreateCommand {
+    CreateCommand::new(name).description("Run SQL-like operations mapped to Discord")
         // create group: /sql create db <name>
         .add_option(
             CreateCommandOption::new(CommandOptionType::SubCommandGroup, "create", "Create resources")
@@ -78,6 +160,12 @@ pub fn register_sql_group() -> CreateCommand {
                     CreateCommandOption::new(CommandOptionType::SubCommand, "table", "Create a table (channel)")
                         .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "Table name").required(true))
                         .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "schema", "Table schema (e.g., 'id int, name varchar(255)')").required(false))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "comment", "Description of the table").required(false))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "if_not_exists", "Succeed without changes if the table already exists").required(false))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "shards", "Spread the rows over this many channels, for large tables (default 1)")
+                            .required(false)
+                            .min_int_value(1)
+                            .max_int_value(crate::services::shards::MAX_SHARDS as u64))
                 ])
         )
         // drop group: /sql drop db <name>
@@ -93,22 +181,107 @@ pub fn register_sql_group() -> CreateCommand {
         // use subcommand: /sql use <name>
         .add_option(
             CreateCommandOption::new(CommandOptionType::SubCommand, "use", "Select database to use")
-                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "Database name").required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "Database name").required(true).set_autocomplete(true))
         )
-        // select subcommand: /sql select <columns> from <table> [distinct] [where]
+        // select subcommand: /sql select <columns> from <table> [distinct] [where] [ephemeral]
         .add_option(
             CreateCommandOption::new(CommandOptionType::SubCommand, "select", "Read rows from a table")
-                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "columns", "Columns to select (e.g., 'id, name' or '*')").required(true))
-                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "from", "Table name").required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "columns", "Columns to select (e.g., 'id, name' or '*')").required(true).set_autocomplete(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "from", "Table name, or database.table to read another database").required(true).set_autocomplete(true))
                 .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "distinct", "Select distinct values only").required(false))
                 .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "where", "WHERE clause with AND/OR logic and parentheses (e.g., '(name=\"John\" OR name=\"Jane\") AND age=\"25\"')").required(false))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "ephemeral", "Only show the results to you").required(false))
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "format", "Result format (default: table)")
+                        .required(false)
+                        .add_string_choice("table", "table")
+                        .add_string_choice("json", "json")
+                        .add_string_choice("csv", "csv")
+                )
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "to_thread", "Post every matching row in a thread").required(false))
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "max_rows", "Rows to show in the result table (default: 20)")
+                        .required(false)
+                        .min_int_value(1)
+                        .max_int_value(select::MAX_DISPLAY_ROWS as u64)
+                )
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "db", "Database to query instead of the one selected with /sql use").required(false))
+        )
+        // count subcommand: /sql count from <table> [where] [rescan]
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "count", "Count rows in a table")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "from", "Table name").required(true).set_autocomplete(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "where", "WHERE clause to filter counted rows (e.g., 'age=25')").required(false))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "db", "Database to count in instead of the one selected with /sql use").required(false))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "rescan", "Count the stored rows instead of using the cached row count").required(false))
+        )
+        // sample subcommand: /sql sample <table> [n]
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "sample", "Preview the first rows of a table")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "table", "Table name").required(true))
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "n", "Rows to show (default: 5)")
+                        .required(false)
+                        .min_int_value(1)
+                        .max_int_value(sample::MAX_SAMPLE_ROWS as u64)
+                )
+        )
+        // copy subcommand: /sql copy from:<table> to:<table> [with_data]
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "copy", "Create a table with the schema of another, optionally copying its rows")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "from", "Table to copy").required(true).set_autocomplete(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "to", "Name of the new table").required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "with_data", "Also copy every row (default: schema only)").required(false))
+        )
+        // dump group: /sql dump schema | data
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommandGroup, "dump", "Export the structure of a database")
+                .set_sub_options(vec![
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "schema", "CREATE TABLE statements for every table in the current database"),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "data", "Every table of the current database as CSV files in one .zip")
+                ])
+        )
+        // restore subcommand: /sql restore archive:<file> [overwrite]
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "restore", "Recreate tables and rows from a /sql dump data archive")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Attachment, "archive", "The .zip made by /sql dump data").required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "overwrite", "Drop tables that already exist and restore them (default: skip them)").required(false))
+        )
+        // join subcommand: /sql join left:<table> right:<table> on:<left_col>=<right_col> [columns] [where]
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "join", "Combine rows of two tables that match on a column (INNER or LEFT JOIN)")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "left", "First table, or database.table").required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "right", "Second table, or database.table").required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "on", "Columns that must match (e.g., 'id=user_id')").required(true))
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "kind", "Join type (default: inner)")
+                        .required(false)
+                        .add_string_choice("inner", "inner")
+                        .add_string_choice("left", "left")
+                )
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "columns", "Columns to show (default: *); shared names are prefixed, e.g. 'users.id'").required(false))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "where", "WHERE clause on the joined rows (e.g., 'total>5')").required(false))
+        )
+        // run subcommand: /sql run script:<statements>
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "run", "Run several INSERT/SELECT statements separated by ;")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "script", "Statements, e.g. \"INSERT INTO users VALUES (1, 'Ann'); SELECT * FROM users\"").required(true))
         )
         .add_option(
             CreateCommandOption::new(CommandOptionType::SubCommandGroup, "insert", "Insert resources")
                 .set_sub_options(vec![
                     CreateCommandOption::new(CommandOptionType::SubCommand, "into", "Insert a row into a table")
-                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "table", "Table name (e.g., 'users')").required(true))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "table", "Table name (e.g., 'users'), or database.table (e.g., 'sales.users')").required(true).set_autocomplete(true))
                         .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "data", "Values in SQL format (e.g., '1, \"John\", true')").required(true))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "columns", "Columns the values map to (e.g., 'name, age'); omitted columns become NULL").required(false).set_autocomplete(true))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "validate_only", "Run all checks without writing the row").required(false))
+                        .add_sub_option(
+                            CreateCommandOption::new(CommandOptionType::String, "on_conflict", "What to do when the primary key already exists (default: error)")
+                                .required(false)
+                                .add_string_choice("error", "error")
+                                .add_string_choice("update", "update")
+                        )
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "db", "Database to insert into instead of the one selected with /sql use").required(false))
                 ])
         )
         .add_option(
@@ -123,4 +296,28 @@ pub fn register_sql_group() -> CreateCommand {
             CreateCommandOption::new(CommandOptionType::SubCommand, "explain", "Explain an operation")
                 .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "op", "Operation to explain").required(false))
         )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "status", "Show bot version, uptime and database counts")
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_command_name_round_trips() {
+        assert_eq!(parse_command_name(None), None);
+        assert_eq!(parse_command_name(Some("  ")), None);
+        assert_eq!(parse_command_name(Some("my sql")), None);
+        assert_eq!(parse_command_name(Some(&"x".repeat(33))), None);
+        let name = parse_command_name(Some(" SQLcord ")).unwrap();
+        assert_eq!(name, "sqlcord");
+
+        // The registered command carries the name that dispatch compares against
+        let command = serde_json::to_value(sql_group_named(&name)).unwrap();
+        assert_eq!(command["name"], "sqlcord");
+        assert_eq!(parse_command_name(command["name"].as_str()).as_deref(), Some(name.as_str()));
+        assert_eq!(serde_json::to_value(sql_group_named(DEFAULT_COMMAND_NAME)).unwrap()["name"], "sql");
+    }
 }
@@ -0,0 +1,312 @@
+// /sql restore archive:<file> [overwrite]
+
+use std::error::Error;
+use serenity::builder::{CreateEmbed, CreateMessage};
+use serenity::model::id::{GuildId, UserId};
+use crate::api::create_sharded_table;
+use crate::error::SqlError;
+use crate::guards::{require_selected_db, require_table, require_writable};
+use crate::logging::{audit, log_debug, log_info, AuditEntry};
+use crate::render::archive::read_zip;
+use crate::render::pages::EMBED_DESCRIPTION_LIMIT;
+use crate::services::topic_meta::update_table_topic;
+use crate::services::shards::{delete_table_channels, shard_count, shard_for_row};
+use crate::services::throttle::{with_retry, Throttle};
+use crate::services::{invalidate_channels, Backend, BotContext};
+use crate::sql_parser::{coerce_values_to_schema, parse_column_definitions, parse_sql_values, validate_values_against_schema, ColumnDefinition, SqlValue};
+use crate::utils::{create_success_embed, create_warning_embed};
+use super::dump::parse_schema_dump;
+use super::insert::{check_row_size, find_constraint_violation, ConstraintViolation, format_sql_values_for_storage};
+
+/// Failures listed per table in the reply; the rest are counted
+const MAX_LISTED_FAILURES: usize = 5;
+
+pub fn register() -> Result<(), Box<dyn Error>> {
+    log_debug("Registering RESTORE command");
+    Ok(())
+}
+
+/// One table as stored in the archive: its `schema.sql` columns (if any) and its CSV file
+struct ArchivedTable<'a> {
+    name: &'a str,
+    schema: Option<&'a str>,
+    csv: Option<&'a [u8]>,
+}
+
+/// What happened to one table of the archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestoredTable {
+    pub table: String,
+    pub rows: usize,
+    /// Rows that were skipped, or why the table as a whole wasn't restored
+    pub failures: Vec<String>,
+}
+
+/// Recreate the tables of a `/sql dump data` archive in the current database and import their rows.
+/// With `overwrite`, tables that already exist are dropped first and recreated with the same number of
+/// shards; otherwise they are left alone and reported.
+/// Returns Ok(embed) or Err(SqlError). The attempt is written to the audit log.
+pub async fn run(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId, archive: &[u8], overwrite: bool) -> Result<CreateEmbed, SqlError> {
+    log_info(&format!("RESTORE command executed with a {} byte archive (overwrite: {})", archive.len(), overwrite));
+    let database = require_selected_db(ctx, guild_id, user_id).await;
+    let result = match require_writable(ctx).await.and(database.clone()) {
+        Ok(database) => restore(ctx, guild_id, &database, archive, overwrite).await,
+        Err(e) => Err(e),
+    };
+    audit(ctx, AuditEntry {
+        database: database.as_ref().ok().cloned(),
+        ..AuditEntry::new(guild_id, user_id, "RESTORE", result.is_ok())
+    }).await;
+    let (database, tables) = (database?, result?);
+    log_info(&format!("SUCCESS: Restored {} tables into database {}", tables.len(), database));
+    Ok(restore_embed(&database, &tables))
+}
+
+/// Restore every table listed in the archive's `schema.sql`, in the order listed
+pub async fn restore(ctx: &impl BotContext, guild_id: GuildId, database: &str, archive: &[u8], overwrite: bool) -> Result<Vec<RestoredTable>, SqlError> {
+    let files = read_zip(archive).map_err(SqlError::InvalidArchive)?;
+    let file = |name: &str| files.iter().find(|(file_name, _)| file_name == name).map(|(_, contents)| contents.as_slice());
+    let schema = file("schema.sql")
+        .ok_or_else(|| SqlError::InvalidArchive("The archive has no `schema.sql`. Restore an archive made by `/sql dump data`.".to_string()))?;
+    let tables = parse_schema_dump(&String::from_utf8_lossy(schema)).map_err(SqlError::InvalidArchive)?;
+
+    let mut restored = Vec::new();
+    for (table, schema) in &tables {
+        let archived = ArchivedTable { name: table, schema: schema.as_deref(), csv: file(&format!("{}.csv", table)) };
+        let mut outcome = RestoredTable { table: table.clone(), rows: 0, failures: Vec::new() };
+        if let Err(e) = restore_table(ctx, guild_id, database, &archived, overwrite, &mut outcome).await {
+            outcome.failures.push(match e {
+                SqlError::TableExists { .. } => "The table already exists; restore with `overwrite:true` to replace it.".to_string(),
+                other => other.to_string(),
+            });
+        }
+        restored.push(outcome);
+    }
+    Ok(restored)
+}
+
+/// Create one table and import its CSV rows, recording imported rows and skipped ones in `outcome`.
+/// Rows are checked against the restored schema and PRIMARY KEY/UNIQUE constraints before anything is posted.
+/// REFERENCES targets aren't re-checked: tables come back in name order, which needn't be the order they depend on.
+async fn restore_table(
+    ctx: &impl BotContext,
+    guild_id: GuildId,
+    database: &str,
+    archived: &ArchivedTable<'_>,
+    overwrite: bool,
+    outcome: &mut RestoredTable,
+) -> Result<(), SqlError> {
+    let ArchivedTable { name: table, schema, csv } = *archived;
+    let csv = csv.ok_or_else(|| SqlError::InvalidArchive(format!("`{}.csv` is missing from the archive.", table)))?;
+    let records = parse_csv(&String::from_utf8_lossy(csv)).map_err(|e| SqlError::InvalidArchive(format!("`{}.csv`: {}", table, e)))?;
+    let Some((header, records)) = records.split_first() else {
+        return Err(SqlError::InvalidArchive(format!("`{}.csv` has no header row.", table)));
+    };
+    let columns = schema.map(parse_column_definitions).transpose().map_err(SqlError::InvalidSchema)?.unwrap_or_default();
+    let header: Vec<&str> = header.iter().map(|field| field.as_deref().unwrap_or_default()).collect();
+    if !columns.is_empty() && !header.iter().copied().eq(columns.iter().map(|column| column.name.as_str())) {
+        return Err(SqlError::InvalidArchive(format!("The header of `{}.csv` doesn't match the columns in `schema.sql`.", table)));
+    }
+
+    let mut shards = 1;
+    if overwrite {
+        match require_table(ctx, guild_id, database, table).await {
+            Ok(existing) => {
+                shards = shard_count(&existing);
+                if let Err(e) = delete_table_channels(ctx, &existing).await {
+                    tracing::error!("Failed to delete table channel before restoring it: {e}");
+                    return Err(SqlError::DiscordRequest { operation: "delete the existing table" });
+                }
+                invalidate_channels(ctx, guild_id).await;
+            }
+            Err(SqlError::TableNotFound { .. }) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    let (table, columns) = create_sharded_table(ctx, guild_id, database, table, schema, None, shards).await?;
+    let channel = require_table(ctx, guild_id, database, &table).await?;
+
+    // Validate every row up front, so a bad row is skipped rather than half-posted
+    let mut accepted: Vec<Vec<SqlValue>> = Vec::new();
+    let mut messages = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        match restored_row(record, &columns, &accepted) {
+            Ok(values) => {
+                messages.push(format_sql_values_for_storage(&values, &columns));
+                accepted.push(values);
+            }
+            Err(reason) => outcome.failures.push(format!("Row {}: {}", index + 1, reason)),
+        }
+    }
+
+    let throttle = Throttle::default();
+    for (values, message) in accepted.iter().zip(&messages) {
+        let shard = match shard_for_row(ctx, &channel, values, &columns).await {
+            Ok(shard) => shard,
+            Err(e) => {
+                outcome.failures.push(e.to_string());
+                break;
+            }
+        };
+        let sent = with_retry(&throttle, || ctx.backend().send_message(shard.id, CreateMessage::new().content(message.as_str()))).await;
+        if let Err(e) = sent {
+            tracing::error!("Failed to restore a row into {}: {e}", shard.name);
+            outcome.failures.push(SqlError::DiscordRequest { operation: "post the remaining rows" }.to_string());
+            break;
+        }
+        outcome.rows += 1;
+    }
+    let rows = outcome.rows as i64;
+    update_table_topic(ctx, guild_id, channel.id, |meta| meta.record_change(rows, chrono::Utc::now())).await;
+    Ok(())
+}
+
+/// One CSV record as a row ready to store, or why it can't be restored
+fn restored_row(record: &[Option<String>], columns: &[ColumnDefinition], accepted: &[Vec<SqlValue>]) -> Result<Vec<SqlValue>, String> {
+    let mut values = record.iter()
+        .enumerate()
+        .map(|(index, field)| csv_value(field.as_deref(), columns.get(index)))
+        .collect::<Result<Vec<_>, _>>()?;
+    validate_values_against_schema(&values, columns)?;
+    coerce_values_to_schema(&mut values, columns);
+    match find_constraint_violation(&values, accepted, columns) {
+        Some(ConstraintViolation::PrimaryKey { columns, values }) => {
+            return Err(format!("duplicate primary key ({}) = ({})", columns.join(", "), values.join(", ")));
+        }
+        Some(ConstraintViolation::Unique { column, value }) => return Err(format!("duplicate value {} in UNIQUE column **{}**", value, column)),
+        None => {}
+    }
+    check_row_size(&format_sql_values_for_storage(&values, columns)).map_err(|e| e.to_string())?;
+    Ok(values)
+}
+
+/// A CSV field as a value of `column`: empty is NULL, text columns keep the text as is, and other
+/// columns parse it as a literal. Without a schema, anything that isn't a number or boolean is text.
+fn csv_value(field: Option<&str>, column: Option<&ColumnDefinition>) -> Result<SqlValue, String> {
+    let Some(text) = field else {
+        return Ok(SqlValue::Null);
+    };
+    if column.is_some_and(|column| matches!(column.data_type.as_str(), "VARCHAR" | "CHAR" | "DATE" | "TIME" | "DATETIME")) {
+        return Ok(SqlValue::String(text.to_string()));
+    }
+    match (parse_sql_values(text).ok().as_deref(), column) {
+        (Some([value @ (SqlValue::Integer(_) | SqlValue::Float(_) | SqlValue::Boolean(_))]), _) => Ok(value.clone()),
+        (_, None) => Ok(SqlValue::String(text.to_string())),
+        (_, Some(column)) => Err(format!("`{}` is not a valid value for column **{}**", text, column.name)),
+    }
+}
+
+/// Records of a CSV file, as `/sql dump data` writes them. Unquoted empty fields are `None` (NULL),
+/// so `""` stays an empty string. Quoted fields may hold commas, `""` and line breaks.
+fn parse_csv(text: &str) -> Result<Vec<Vec<Option<String>>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut in_record = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        in_record = true;
+        match ch {
+            '"' if field.is_empty() && !quoted => {
+                quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => field.push(c),
+                        None => return Err(format!("a quoted field is never closed (record {})", records.len() + 1)),
+                    }
+                }
+            }
+            ',' => record.push(take_field(&mut field, &mut quoted)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(take_field(&mut field, &mut quoted));
+                records.push(std::mem::take(&mut record));
+                in_record = false;
+            }
+            c => field.push(c),
+        }
+    }
+    if in_record {
+        record.push(take_field(&mut field, &mut quoted));
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// The field just read: `None` when it was empty and unquoted
+fn take_field(field: &mut String, quoted: &mut bool) -> Option<String> {
+    let value = (*quoted || !field.is_empty()).then(|| std::mem::take(field));
+    *quoted = false;
+    value
+}
+
+/// Per-table row counts, then the rows or tables that couldn't be restored
+fn restore_embed(database: &str, tables: &[RestoredTable]) -> CreateEmbed {
+    let mut description = format!("Restored **{}** tables into database **{}**:", tables.len(), database);
+    for table in tables {
+        description.push_str(&format!("\n• **{}**: {} {}", table.table, table.rows, if table.rows == 1 { "row" } else { "rows" }));
+    }
+    let failed: Vec<&RestoredTable> = tables.iter().filter(|table| !table.failures.is_empty()).collect();
+    for table in &failed {
+        description.push_str(&format!("\n\n⚠️ **{}**:", table.table));
+        for failure in table.failures.iter().take(MAX_LISTED_FAILURES) {
+            description.push_str(&format!("\n{}", failure));
+        }
+        if table.failures.len() > MAX_LISTED_FAILURES {
+            description.push_str(&format!("\n*...and {} more*", table.failures.len() - MAX_LISTED_FAILURES));
+        }
+    }
+    let description: String = description.chars().take(EMBED_DESCRIPTION_LIMIT).collect();
+    if failed.is_empty() {
+        create_success_embed("✔️ Database Restored", &description)
+    } else {
+        create_warning_embed("⚠️ Database Restored With Problems", &description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(record: &[Option<&str>]) -> Vec<Option<String>> {
+        record.iter().map(|field| field.map(str::to_string)).collect()
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let records = parse_csv("id,name\n1,\"Doe, \"\"JD\"\"\nJr\"\n2,\n3,\"\"\n").unwrap();
+        assert_eq!(records, vec![
+            fields(&[Some("id"), Some("name")]),
+            fields(&[Some("1"), Some("Doe, \"JD\"\nJr")]),
+            fields(&[Some("2"), None]),
+            fields(&[Some("3"), Some("")]),
+        ]);
+        // A blank line is a record with one NULL field; a missing final newline is fine
+        assert_eq!(parse_csv("id\n\n4").unwrap(), vec![fields(&[Some("id")]), fields(&[None]), fields(&[Some("4")])]);
+        assert_eq!(parse_csv("a\r\nb\r\n").unwrap(), vec![fields(&[Some("a")]), fields(&[Some("b")])]);
+        assert!(parse_csv("id\n\"open").is_err());
+    }
+
+    #[test]
+    fn test_restored_row() {
+        let columns = parse_column_definitions("id INT PRIMARY KEY, name VARCHAR(10), active BOOLEAN").unwrap();
+        let row = restored_row(&fields(&[Some("1"), Some("123"), Some("true")]), &columns, &[]).unwrap();
+        assert_eq!(row, vec![SqlValue::Integer(1), SqlValue::String("123".to_string()), SqlValue::Boolean(true)]);
+
+        assert_eq!(restored_row(&fields(&[Some("1"), None, None]), &columns, &[row]).unwrap_err(), "duplicate primary key (id) = (1)");
+        assert!(restored_row(&fields(&[Some("one"), None, None]), &columns, &[]).unwrap_err().contains("`one` is not a valid value"));
+        assert!(restored_row(&fields(&[Some("2"), Some("far too long here"), None]), &columns, &[]).is_err());
+        assert!(restored_row(&fields(&[Some("2")]), &columns, &[]).is_err());
+
+        // Schemaless rows keep numbers and booleans, and everything else as text
+        let row = restored_row(&fields(&[Some("7"), Some("Ann"), Some("NULL"), None]), &[], &[]).unwrap();
+        assert_eq!(row, vec![SqlValue::Integer(7), SqlValue::String("Ann".to_string()), SqlValue::String("NULL".to_string()), SqlValue::Null]);
+    }
+}
@@ -0,0 +1,337 @@
+// /sql run script:<statements>
+
+use std::error::Error;
+use std::future::Future;
+use serenity::builder::CreateEmbed;
+use serenity::model::id::{GuildId, UserId};
+use serenity::prelude::Context;
+use crate::commands::options::{InsertArgs, SelectArgs};
+use crate::logging::{log_debug, log_info};
+use crate::utils::{create_error_embed, create_success_embed};
+use super::insert::OnConflict;
+use super::select::{unquoted_byte_positions, OutputFormat};
+
+/// Statements accepted in one script
+pub const MAX_STATEMENTS: usize = 10;
+/// Characters of each statement's result shown in the combined embed
+const SUMMARY_CHARS: usize = 450;
+
+pub fn register() -> Result<(), Box<dyn Error>> {
+    log_debug("Registering RUN command");
+    Ok(())
+}
+
+/// One statement of a script, as the arguments of the command that runs it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement<'a> {
+    Insert(InsertArgs<'a>),
+    Select(SelectArgs<'a>),
+}
+
+impl Statement<'_> {
+    /// Short label for the combined result, e.g. `INSERT INTO users`
+    fn label(&self) -> String {
+        match self {
+            Statement::Insert(args) => format!("INSERT INTO {}", args.table),
+            Statement::Select(args) => format!("SELECT FROM {}", args.from),
+        }
+    }
+}
+
+/// Title and text of the embed one statement produced
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementOutcome {
+    pub title: String,
+    pub summary: String,
+}
+
+impl From<CreateEmbed> for StatementOutcome {
+    fn from(embed: CreateEmbed) -> Self {
+        let embed = serde_json::to_value(embed).unwrap_or_default();
+        let text = |key: &str| embed[key].as_str().unwrap_or_default().to_string();
+        StatementOutcome { title: text("title"), summary: text("description") }
+    }
+}
+
+/// Run a semicolon-separated script of INSERT and SELECT statements in order, stopping at the first failure.
+/// Returns one embed summarizing every statement that ran.
+pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, script: &str) -> Result<CreateEmbed, CreateEmbed> {
+    log_info(&format!("RUN command executed with script: {}", script));
+
+    let statements = parse_script(script).map_err(|e| create_error_embed("Invalid Script", &e))?;
+    let outcomes = execute_statements(&statements, |statement| execute_statement(ctx, guild_id, user_id, statement)).await;
+    let report = script_report(&statements, &outcomes);
+    if outcomes.iter().any(Result::is_err) { Err(report) } else { Ok(report) }
+}
+
+/// Run one statement through the same code as its slash command
+async fn execute_statement(ctx: &Context, guild_id: GuildId, user_id: UserId, statement: &Statement<'_>) -> Result<StatementOutcome, StatementOutcome> {
+    let result = match statement {
+        Statement::Insert(args) => super::insert::run(ctx, guild_id, user_id, args).await,
+        Statement::Select(args) => super::select::run(ctx, guild_id, user_id, args).await.map(|output| output.embed),
+    };
+    result.map(StatementOutcome::from).map_err(StatementOutcome::from)
+}
+
+/// Execute statements one after another; the outcomes end at the first failure
+async fn execute_statements<'s, 'a, F, Fut>(statements: &'s [Statement<'a>], mut execute: F) -> Vec<Result<StatementOutcome, StatementOutcome>>
+where
+    F: FnMut(&'s Statement<'a>) -> Fut,
+    Fut: Future<Output = Result<StatementOutcome, StatementOutcome>>,
+{
+    let mut outcomes = Vec::new();
+    for statement in statements {
+        let outcome = execute(statement).await;
+        let failed = outcome.is_err();
+        outcomes.push(outcome);
+        if failed {
+            break;
+        }
+    }
+    outcomes
+}
+
+/// Combined embed: one field per executed statement; an error embed naming the statement that failed
+fn script_report(statements: &[Statement<'_>], outcomes: &[Result<StatementOutcome, StatementOutcome>]) -> CreateEmbed {
+    let failed_at = outcomes.iter().position(Result::is_err);
+    let embed = match failed_at {
+        Some(index) => {
+            let mut description = format!("Statement **{}** of {} failed, so the script stopped.", index + 1, statements.len());
+            if index > 0 {
+                description.push_str(&format!(" The {} statement(s) before it were already applied.", index));
+            }
+            create_error_embed("Script Failed", &description)
+        }
+        None => create_success_embed(
+            "✔️ Script Completed",
+            &format!("Ran {} {}.", statements.len(), if statements.len() == 1 { "statement" } else { "statements" })
+        ),
+    };
+
+    let fields = statements.iter().zip(outcomes).enumerate().map(|(index, (statement, outcome))| {
+        let (Ok(outcome) | Err(outcome)) = outcome;
+        let name = format!("{}. {} — {}", index + 1, statement.label(), outcome.title);
+        (name, truncate_summary(&outcome.summary, SUMMARY_CHARS), false)
+    });
+    embed.fields(fields)
+}
+
+/// Shorten a statement's result to whole lines within `limit` characters, closing a cut code block
+fn truncate_summary(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return if text.is_empty() { "\u{200b}".to_string() } else { text.to_string() };
+    }
+    // Leave room for the closing fence and the ellipsis
+    let budget = limit.saturating_sub(8);
+    let mut kept = String::new();
+    for line in text.lines() {
+        if kept.chars().count() + line.chars().count() + 1 > budget {
+            break;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+    }
+    if kept.matches("```").count() % 2 == 1 {
+        kept.push_str("```\n");
+    }
+    kept.push('…');
+    kept
+}
+
+/// Parse a script into statements; errors name the statement (1-based) that could not be parsed
+pub fn parse_script(script: &str) -> Result<Vec<Statement<'_>>, String> {
+    let texts = split_statements(script);
+    if texts.is_empty() {
+        return Err("The script has no statements. Separate statements with `;`, e.g. `INSERT INTO users VALUES (1, 'Ann'); SELECT * FROM users`".to_string());
+    }
+    if texts.len() > MAX_STATEMENTS {
+        return Err(format!("A script can have at most {} statements; this one has {}.", MAX_STATEMENTS, texts.len()));
+    }
+    texts.into_iter()
+        .enumerate()
+        .map(|(index, text)| parse_statement(text).map_err(|e| format!("**Statement {}:** `{}`\n{}", index + 1, text, e)))
+        .collect()
+}
+
+/// Split at `;` outside quoted values, dropping empty statements
+fn split_statements(script: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    for i in unquoted_byte_positions(script) {
+        if script.as_bytes()[i] == b';' {
+            statements.push(&script[start..i]);
+            start = i + 1;
+        }
+    }
+    statements.push(&script[start..]);
+    statements.into_iter().map(str::trim).filter(|statement| !statement.is_empty()).collect()
+}
+
+fn parse_statement(text: &str) -> Result<Statement<'_>, String> {
+    if let Some(rest) = strip_keyword(text, "INSERT").and_then(|rest| strip_keyword(rest, "INTO")) {
+        parse_insert(rest).map(Statement::Insert)
+    } else if let Some(rest) = strip_keyword(text, "SELECT") {
+        parse_select(rest).map(Statement::Select)
+    } else {
+        Err("Only `INSERT INTO` and `SELECT` statements are supported in scripts.".to_string())
+    }
+}
+
+/// `<table> [(<columns>)] VALUES (<values>)`
+fn parse_insert(rest: &str) -> Result<InsertArgs<'_>, String> {
+    const USAGE: &str = "Expected `INSERT INTO <table> [(<columns>)] VALUES (<values>)`.";
+    let values_at = find_keyword(rest, "VALUES").ok_or(USAGE)?;
+    let target = rest[..values_at].trim();
+    let data = strip_parens(rest[values_at + "VALUES".len()..].trim()).ok_or(USAGE)?;
+
+    let (table, columns) = match target.find('(') {
+        Some(open) => (target[..open].trim(), Some(strip_parens(&target[open..]).ok_or(USAGE)?)),
+        None => (target, None),
+    };
+    if table.is_empty() || table.contains(char::is_whitespace) {
+        return Err(USAGE.to_string());
+    }
+    Ok(InsertArgs { table, data, columns, validate_only: false, on_conflict: OnConflict::Error, db: None })
+}
+
+/// `[DISTINCT] <columns> FROM <table> [WHERE <conditions>]`
+fn parse_select(rest: &str) -> Result<SelectArgs<'_>, String> {
+    const USAGE: &str = "Expected `SELECT [DISTINCT] <columns> FROM <table> [WHERE <conditions>]`.";
+    let (distinct, rest) = match strip_keyword(rest, "DISTINCT") {
+        Some(rest) => (Some(true), rest),
+        None => (None, rest),
+    };
+    let from_at = find_keyword(rest, "FROM").ok_or(USAGE)?;
+    let columns = rest[..from_at].trim();
+    let after_from = rest[from_at + "FROM".len()..].trim_start();
+    let (from, where_clause) = match after_from.split_once(char::is_whitespace) {
+        Some((table, tail)) => {
+            let where_clause = strip_keyword(tail.trim_start(), "WHERE").ok_or(USAGE)?;
+            (table, Some(where_clause.trim()).filter(|condition| !condition.is_empty()))
+        }
+        None => (after_from, None),
+    };
+    if columns.is_empty() || from.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(SelectArgs {
+        columns,
+        from,
+        distinct,
+        where_clause,
+        ephemeral: false,
+        format: OutputFormat::Table,
+        to_thread: false,
+        max_rows: None,
+        db: None,
+    })
+}
+
+/// The text after a leading keyword (case-insensitive, followed by whitespace)
+fn strip_keyword<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    let rest = text.trim_start();
+    let word = rest.get(..keyword.len())?;
+    let after = &rest[keyword.len()..];
+    (word.eq_ignore_ascii_case(keyword) && after.starts_with(char::is_whitespace)).then(|| after.trim_start())
+}
+
+/// Byte offset of the first `keyword` outside quotes, as a whole word; parentheses count as
+/// separators so `VALUES(1, 2)` and `(a, b)VALUES` are found
+fn find_keyword(text: &str, keyword: &str) -> Option<usize> {
+    let separator = |c: char| c.is_whitespace() || c == '(' || c == ')';
+    unquoted_byte_positions(text).find(|&i| {
+        let Some(word) = text.get(i..i + keyword.len()) else {
+            return false;
+        };
+        word.eq_ignore_ascii_case(keyword)
+            && text[..i].chars().next_back().is_none_or(separator)
+            && text[i + keyword.len()..].chars().next().is_none_or(separator)
+    })
+}
+
+/// The inside of `(...)`
+fn strip_parens(text: &str) -> Option<&str> {
+    let inner = text.strip_prefix('(')?.strip_suffix(')')?.trim();
+    (!inner.is_empty()).then_some(inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(title: &str, summary: &str) -> StatementOutcome {
+        StatementOutcome { title: title.to_string(), summary: summary.to_string() }
+    }
+
+    fn embed_json(embed: CreateEmbed) -> serde_json::Value {
+        serde_json::to_value(embed).unwrap()
+    }
+
+    #[test]
+    fn test_parse_two_statement_script() {
+        let statements = parse_script("INSERT INTO users (name, age) VALUES ('A; B', 30);\n  select distinct name FROM users where age>=18 ;").unwrap();
+        assert_eq!(statements.len(), 2);
+
+        let Statement::Insert(insert) = &statements[0] else { panic!("expected INSERT") };
+        assert_eq!((insert.table, insert.columns, insert.data), ("users", Some("name, age"), "'A; B', 30"));
+
+        let Statement::Select(select) = &statements[1] else { panic!("expected SELECT") };
+        assert_eq!((select.columns, select.from, select.distinct, select.where_clause), ("name", "users", Some(true), Some("age>=18")));
+
+        let statements = parse_script("insert into logs values(1);SELECT * FROM logs").unwrap();
+        assert!(matches!(&statements[0], Statement::Insert(args) if args.table == "logs" && args.data == "1" && args.columns.is_none()));
+        assert!(matches!(&statements[1], Statement::Select(args) if args.columns == "*" && args.where_clause.is_none()));
+    }
+
+    #[test]
+    fn test_parse_script_errors() {
+        let error = parse_script("SELECT * FROM users; DELETE FROM users").unwrap_err();
+        assert!(error.starts_with("**Statement 2:** `DELETE FROM users`"));
+        assert!(parse_script("SELECT * users").unwrap_err().contains("FROM <table>"));
+        assert!(parse_script("INSERT INTO users VALUES 1, 2").is_err());
+        assert!(parse_script(" ; ").is_err());
+        assert!(parse_script(&"SELECT * FROM t;".repeat(MAX_STATEMENTS + 1)).unwrap_err().contains("at most"));
+    }
+
+    #[tokio::test]
+    async fn test_script_stops_at_first_error() {
+        let statements = parse_script("INSERT INTO users VALUES (1); INSERT INTO users VALUES ('bad'); SELECT * FROM users").unwrap();
+        let mut executed = Vec::new();
+        let outcomes = execute_statements(&statements, |statement| {
+            executed.push(statement.label());
+            let result = match statement {
+                Statement::Insert(args) if args.data.contains("bad") => Err(outcome("✖️ Data Validation Failed", "Expected: **integer**")),
+                _ => Ok(outcome("✔️ Row Inserted", "Successfully inserted 1 row")),
+            };
+            async move { result }
+        })
+        .await;
+
+        // The SELECT after the failure never runs
+        assert_eq!(executed, vec!["INSERT INTO users", "INSERT INTO users"]);
+        let report = embed_json(script_report(&statements, &outcomes));
+        assert!(report["description"].as_str().unwrap().starts_with("Statement **2** of 3 failed"));
+        assert_eq!(report["fields"].as_array().unwrap().len(), 2);
+        assert_eq!(report["fields"][1]["name"], "2. INSERT INTO users — ✖️ Data Validation Failed");
+    }
+
+    #[test]
+    fn test_script_report_success() {
+        let statements = parse_script("INSERT INTO users VALUES (1); SELECT * FROM users").unwrap();
+        let outcomes = vec![Ok(outcome("✔️ Row Inserted", "done")), Ok(outcome("🔍 SELECT Results", ""))];
+
+        let report = embed_json(script_report(&statements, &outcomes));
+        assert_eq!(report["description"], "Ran 2 statements.");
+        assert_eq!(report["fields"][1]["name"], "2. SELECT FROM users — 🔍 SELECT Results");
+    }
+
+    #[test]
+    fn test_truncate_summary_closes_code_blocks() {
+        let text = format!("Header\n```\n{}```", "row\n".repeat(200));
+        let short = truncate_summary(&text, 100);
+        assert!(short.chars().count() <= 100);
+        assert_eq!(short.matches("```").count(), 2);
+        assert!(short.ends_with('…'));
+        assert_eq!(truncate_summary("fits", 100), "fits");
+    }
+}
@@ -0,0 +1,56 @@
+// /sql sample <table> [n]
+
+use std::error::Error;
+use serenity::prelude::Context;
+use serenity::model::id::{GuildId, UserId};
+use crate::commands::options::SelectArgs;
+use crate::logging::{log_debug, log_info};
+use super::select::{OutputFormat, SelectOutput};
+
+/// Rows shown when `n` is omitted
+pub const DEFAULT_SAMPLE_ROWS: usize = 5;
+/// Most rows a sample shows
+pub const MAX_SAMPLE_ROWS: usize = 20;
+
+pub fn register() -> Result<(), Box<dyn Error>> {
+    log_debug("Registering SAMPLE command");
+    Ok(())
+}
+
+/// Show the first `n` rows of a table: `/sql select columns:* from:<table>` limited to a few rows.
+/// Schemaless tables show their positional columns, as SELECT does.
+pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, table_name: &str, n: Option<i64>) -> Result<SelectOutput, serenity::builder::CreateEmbed> {
+    log_info(&format!("SAMPLE command executed for table: {} with n: {:?}", table_name, n));
+    super::select::run(ctx, guild_id, user_id, &sample_args(table_name, n)).await
+}
+
+/// SELECT arguments for a sample of `n` rows (clamped to 1..=20, default 5)
+fn sample_args(table_name: &str, n: Option<i64>) -> SelectArgs<'_> {
+    let rows = n.map_or(DEFAULT_SAMPLE_ROWS, |n| n.clamp(1, MAX_SAMPLE_ROWS as i64) as usize);
+    SelectArgs {
+        columns: "*",
+        from: table_name,
+        distinct: None,
+        where_clause: None,
+        ephemeral: false,
+        format: OutputFormat::Table,
+        to_thread: false,
+        max_rows: Some(rows),
+        db: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_args_clamp_rows() {
+        let args = sample_args("users", None);
+        assert_eq!((args.columns, args.from, args.max_rows), ("*", "users", Some(DEFAULT_SAMPLE_ROWS)));
+        assert_eq!(sample_args("users", Some(8)).max_rows, Some(8));
+        assert_eq!(sample_args("users", Some(500)).max_rows, Some(MAX_SAMPLE_ROWS));
+        assert_eq!(sample_args("users", Some(0)).max_rows, Some(1));
+        assert_eq!(sample_args("users", Some(-3)).max_rows, Some(1));
+    }
+}
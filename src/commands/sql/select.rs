@@ -1,107 +1,73 @@
 // /sql select <columns> from <table> [distinct] [where]
 
 use std::error::Error;
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use serenity::prelude::Context;
 use serenity::model::id::{GuildId, UserId};
-use serenity::model::channel::ChannelType;
-use crate::state::CurrentDB;
-use crate::logging::log_info;
-use crate::utils::{sanitize_channel_name, create_error_embed, create_info_embed};
-use crate::sql_parser::{parse_column_definitions, ColumnDefinition, SqlValue};
+use std::time::{Duration, Instant};
+use serenity::builder::{CreateAttachment, CreateEmbed, CreateEmbedFooter, CreateMessage, CreateThread, EditInteractionResponse};
+use serenity::model::application::CommandInteraction;
+use serenity::model::channel::{AutoArchiveDuration, Message};
+use serenity::model::id::ChannelId;
+use crate::guards::{require_table, resolve_database};
+use crate::services::discord_fs::max_row_messages;
+use crate::services::shards::fetch_table_messages;
+use crate::services::BotContext;
+use crate::services::encode::decode_stored_string;
+use crate::error::SqlError;
+use crate::logging::{log_debug, log_info};
+use crate::render::fit_or_attach;
+use crate::render::pages::{paginate_code_block, EMBED_DESCRIPTION_LIMIT, MESSAGE_CHAR_LIMIT};
+use crate::render::table::{render_table, has_truncated_cells};
+use crate::commands::options::SelectArgs;
+use super::expression::{looks_like_expression, parse_expression, Expression};
+use crate::utils::{create_error_embed, create_info_embed};
+use crate::sql_parser::{format_float, format_float_for_column, parse_column_definitions, parse_temporal_value, ColumnDefinition, SqlValue};
 
 pub fn register() -> Result<(), Box<dyn Error>> {
-    log_info("Registering SELECT command");
+    log_debug("Registering SELECT command");
     Ok(())
 }
 
 /// SELECT data from a table (Discord channel)
-/// Supports column selection, DISTINCT, and enhanced WHERE filtering
+/// Supports column selection, DISTINCT, enhanced WHERE filtering and table/JSON/CSV output
 pub async fn run(
-    ctx: &Context, 
+    ctx: &impl BotContext, 
     guild_id: GuildId, 
     user_id: UserId, 
-    columns: &str, 
-    table_name: &str,
-    distinct: Option<bool>,
-    where_clause: Option<&str>
-) -> Result<serenity::builder::CreateEmbed, serenity::builder::CreateEmbed> {
+    args: &SelectArgs<'_>
+) -> Result<SelectOutput, serenity::builder::CreateEmbed> {
+    let SelectArgs { columns, from, distinct, where_clause, db, .. } = *args;
     log_info(&format!("SELECT command executed: columns={}, table={}, distinct={:?}, where={:?}", 
-                      columns, table_name, distinct, where_clause));
-    
-    // Get the current database for this user
-    let current_db_key = (guild_id, user_id);
-    let current_db = {
-        let data = ctx.data.read().await;
-        if let Some(db_store) = data.get::<CurrentDB>() {
-            let db_map = db_store.lock().await;
-            db_map.get(&current_db_key).cloned()
-        } else {
-            None
-        }
-    };
-    
-    let current_db = match current_db {
-        Some(db) => db,
-        None => {
-            return Err(create_error_embed(
-                "✖️ No Database Selected",
-                "Please select a database first using `/sql use <database_name>`"
-            ));
-        }
-    };
-    
-    // Get categories in the guild
-    let channels = match guild_id.channels(&ctx.http).await {
-        Ok(channels) => channels,
-        Err(_) => {
-            return Err(create_error_embed(
-                "✖️ Database Access Error",
-                "Could not access guild channels. Please check bot permissions."
-            ));
-        }
-    };
+                      columns, from, distinct, where_clause));
     
-    let categories = channels
-        .values()
-        .filter(|c| c.kind == ChannelType::Category)
-        .collect::<Vec<_>>();
-    
-    // Find the current database category
-    let db_category_name = format!("db_{}", current_db);
-    let category = categories
-        .iter()
-        .find(|c| c.name == db_category_name)
-        .ok_or_else(|| {
-            create_error_embed(
-                "✖️ Database Not Found",
-                &format!("Database **{}** does not exist. Please create it first or select a different database.", current_db)
-            )
-        })?;
-    
-    // Find the table channel within the category
-    let (sanitized_table_name, _) = sanitize_channel_name(table_name);
-    let table_channel_name = format!("table_{}", sanitized_table_name);
-    
-    let all_channels = match guild_id.channels(&ctx.http).await {
-        Ok(channels) => channels,
-        Err(_) => {
-            return Err(create_error_embed(
-                "✖️ Channel Access Error",
-                "Could not access guild channels. Please check bot permissions."
-            ));
-        }
-    };
-    
-    let table_channel = all_channels
-        .values()
-        .find(|c| c.name == table_channel_name && c.parent_id == Some(category.id))
-        .ok_or_else(|| {
-            create_error_embed(
-                "✖️ Table Not Found",
-                &format!("Table **{}** does not exist in database **{}**. Please create it first.", table_name, current_db)
-            )
-        })?;
+    let (db, table_name) = split_qualified_table(from, db)?;
+    let current_db = resolve_database(ctx, guild_id, user_id, db).await?;
+    let query_start = Instant::now();
+    let table = load_table(ctx, guild_id, &current_db, table_name).await?;
+    if table.stored_columns > 0 && let Some(where_condition) = where_clause {
+        check_where_columns(where_condition, &table.schema)?;
+    }
+    let selected_columns = parse_column_selection(columns, &table.schema, table.stored_columns)?;
+    Ok(select_from(table, selected_columns, args, query_start))
+}
+
+/// A table's rows as read from its channel, oldest first
+pub(crate) struct LoadedTable {
+    /// Stored columns (from the topic, or inferred from the rows of a schemaless table), then implicit ones
+    pub schema: Vec<ColumnDefinition>,
+    /// How many leading `schema` columns are stored in the rows; `*` covers these
+    pub stored_columns: usize,
+    pub rows: Vec<Vec<SqlValue>>,
+    pub messages_scanned: usize,
+    /// The scan stopped at `SQLCORD_MAX_ROWS` before reaching the oldest message
+    pub scan_truncated: bool,
+}
+
+/// Read every row of table `table_name` in database `db`, with `_rowid` and `_created_at` appended
+pub(crate) async fn load_table(ctx: &impl BotContext, guild_id: GuildId, db: &str, table_name: &str) -> Result<LoadedTable, CreateEmbed> {
+    let table_channel = require_table(ctx, guild_id, db, table_name).await?;
     
     // Get and parse table schema from channel topic
     let schema = if let Some(topic) = &table_channel.topic {
@@ -110,35 +76,65 @@ pub async fn run(
         Vec::new() // No schema defined
     };
     
-    // Parse column selection
-    let selected_columns = parse_column_selection(columns, &schema)?;
-    
-    // Fetch messages from the table channel
-    let messages = match table_channel.messages(&ctx.http, serenity::builder::GetMessages::new().limit(100)).await {
-        Ok(messages) => messages,
+    // Fetch messages from every shard of the table, paging past Discord's 100-message limit
+    let fetched = match fetch_table_messages(ctx, &table_channel, max_row_messages()).await {
+        Ok(fetched) => fetched,
         Err(_) => {
             return Err(create_error_embed(
-                "✖️ Table Access Error",
+                "Table Access Error",
                 "Could not read messages from table. Please check bot permissions."
             ));
         }
     };
     
+    // Reverse to show oldest first; schemaless tables get their columns from the stored rows
+    let messages: Vec<&Message> = fetched.messages.iter().rev().collect();
+    let contents: Vec<&str> = messages.iter().map(|message| message.content.as_str()).collect();
+    let (mut schema, indexed_rows) = indexed_rows_from_messages(&contents, schema);
+    let stored_columns = schema.len();
+    
+    // Rows also carry `_rowid` and `_created_at`, unless the table has real columns by those names
+    let implicit = implicit_columns(&schema);
+    schema.extend(implicit.iter().map(|column| column.definition()));
+    let rows = indexed_rows
+        .into_iter()
+        .map(|(index, mut row)| {
+            row.extend(implicit.iter().map(|column| column.value(messages[index])));
+            row
+        })
+        .collect();
+    
+    Ok(LoadedTable { schema, stored_columns, rows, messages_scanned: fetched.messages.len(), scan_truncated: fetched.truncated })
+}
+
+/// Filter, project and render rows that are already in memory: `where_clause`, `distinct` and the output
+/// options apply as in `/sql select`, while `args.columns` and `args.from` are not used.
+/// The footer's query time is measured from `query_start`.
+pub(crate) fn select_from(table: LoadedTable, selected_columns: Vec<SelectedColumn>, args: &SelectArgs<'_>, query_start: Instant) -> SelectOutput {
+    let SelectArgs { distinct, where_clause, .. } = *args;
+    let LoadedTable { schema, rows: table_rows, messages_scanned, scan_truncated, .. } = table;
+    let schema = &schema;
+    
+    // Repeated columns are usually a typo: show each once and say so
+    let (selected_columns, duplicates) = dedupe_selected_columns(selected_columns);
+    let mut notes = Vec::new();
+    if !duplicates.is_empty() {
+        notes.push(format!("Duplicate column(s) removed from selection: {}", duplicates.join(", ")));
+    }
+    
     // Extract and filter data
     let mut rows = Vec::new();
-    for message in messages.iter().rev() { // Reverse to show oldest first
-        if let Some(row_data) = extract_values_from_message(&message.content, &schema) {
-            // Apply WHERE filtering if specified
-            if let Some(where_condition) = where_clause {
-                if !evaluate_where_condition(&row_data, &schema, where_condition) {
-                    continue;
-                }
-            }
-            
-            // Select only requested columns
-            let selected_row = select_columns(&row_data, &schema, &selected_columns);
-            rows.push(selected_row);
+    for row_data in table_rows {
+        // Apply WHERE filtering if specified
+        if let Some(where_condition) = where_clause
+            && !evaluate_where_condition(&row_data, schema, where_condition)
+        {
+            continue;
         }
+        
+        // Select only requested columns
+        let selected_row = select_columns(&row_data, schema, &selected_columns);
+        rows.push(selected_row);
     }
     
     // Apply DISTINCT if requested
@@ -146,15 +142,88 @@ pub async fn run(
         rows = apply_distinct(rows);
     }
     
-    // Format results
-    let result_embed = format_select_results(&selected_columns, &rows, table_name, distinct.unwrap_or(false), where_clause);
-    Ok(result_embed)
+    // Format results using aliases (if any) as headers, and the selected columns' types for numbers
+    let headers = column_headers(&selected_columns);
+    let definitions: Vec<_> = selected_columns.iter().map(|col| find_column_index(schema, &col.name).map(|index| &schema[index])).collect();
+    let stats = QueryStats {
+        elapsed: query_start.elapsed(),
+        messages_scanned,
+        scan_limit_hit: scan_truncated.then_some(max_row_messages()),
+    };
+    
+    // Threads can't hang off an ephemeral response, so private queries stay in the embed
+    let mut pages = Vec::new();
+    if args.to_thread && args.ephemeral {
+        notes.push("`to_thread` is ignored for ephemeral results".to_string());
+    } else if args.to_thread && !rows.is_empty() {
+        let (language, lines) = full_result_lines(&headers, &definitions, &rows, args.format);
+        pages = paginate_code_block(language, &lines, MESSAGE_CHAR_LIMIT);
+    }
+    
+    let (embed, attachment) = format_select_results(&headers, &definitions, &rows, args, &stats, &notes);
+    SelectOutput { embed, attachment, pages }
+}
+
+/// A SELECT result: the response embed, the full output as a file when it overflowed the embed,
+/// and the full result as message pages when `to_thread` is set
+pub struct SelectOutput {
+    pub embed: CreateEmbed,
+    pub attachment: Option<CreateAttachment>,
+    pub pages: Vec<String>,
+}
+
+/// Split a `from` value into the database to read and the table name: `sales.users` is table `users` of
+/// database `sales`, whatever `db_override` or the `/sql use` selection say. Unqualified names keep `db_override`.
+pub(crate) fn split_qualified_table<'a>(from: &'a str, db_override: Option<&'a str>) -> Result<(Option<&'a str>, &'a str), SqlError> {
+    let Some((database, table)) = from.split_once('.') else {
+        return Ok((db_override, from));
+    };
+    let (database, table) = (database.trim(), table.trim());
+    if database.is_empty() || table.is_empty() || table.contains('.') {
+        return Err(SqlError::InvalidName {
+            kind: "Table",
+            reason: format!("`{}` is not a valid table reference. Use `table` or `database.table`, e.g. `sales.users`.", from),
+        });
+    }
+    Ok((Some(database), table))
+}
+
+/// Open a thread on the command's response, post the result pages there and link it from the embed.
+/// Failures are reported on the response instead of the thread.
+pub async fn post_result_thread(ctx: &Context, command: &CommandInteraction, table_name: &str, output: SelectOutput) {
+    let linked = match open_result_thread(ctx, command, table_name, &output.pages).await {
+        Ok(thread_id) => format!("🧵 Full results ({} {}): <#{}>", output.pages.len(), if output.pages.len() == 1 { "page" } else { "pages" }, thread_id),
+        Err(e) => {
+            tracing::error!("Failed to post SELECT results to a thread: {e}");
+            "⚠️ Could not post the full results to a thread. Check the bot's thread permissions.".to_string()
+        }
+    };
+    
+    let embed = output.embed.field("Thread", linked, false);
+    if let Err(e) = command.edit_response(&ctx.http, EditInteractionResponse::new().embed(embed)).await {
+        tracing::error!("Failed to edit SELECT response: {e}");
+    }
+}
+
+async fn open_result_thread(ctx: &Context, command: &CommandInteraction, table_name: &str, pages: &[String]) -> serenity::Result<ChannelId> {
+    let response = command.get_response(&ctx.http).await?;
+    // Thread names are limited to 100 characters
+    let name: String = format!("SELECT from {}", table_name).chars().take(100).collect();
+    let thread = command.channel_id
+        .create_thread_from_message(&ctx.http, response.id, CreateThread::new(name).auto_archive_duration(AutoArchiveDuration::OneDay))
+        .await?;
+    
+    for page in pages {
+        thread.id.send_message(&ctx.http, CreateMessage::new().content(page)).await?;
+    }
+    Ok(thread.id)
 }
 
 /// Parse schema from channel topic (similar to insert.rs)
-fn parse_schema_from_topic(topic: &str) -> Result<Vec<ColumnDefinition>, serenity::builder::CreateEmbed> {
+pub(crate) fn parse_schema_from_topic(topic: &str) -> Result<Vec<ColumnDefinition>, SqlError> {
     if let Some(schema_start) = topic.find("Schema: ") {
-        let schema_str = &topic[schema_start + 8..];
+        // Only the schema line: the topic may also carry `Rows:` / `Columns:` lines
+        let schema_str = topic[schema_start + 8..].lines().next().unwrap_or_default();
         
         // Handle backward compatibility: if the schema contains colons (old format),
         // convert it to the new format before parsing
@@ -164,58 +233,216 @@ fn parse_schema_from_topic(topic: &str) -> Result<Vec<ColumnDefinition>, serenit
             schema_str.to_string()
         };
         
-        match parse_column_definitions(&normalized_schema) {
-            Ok(columns) => Ok(columns),
-            Err(e) => {
-                Err(create_error_embed(
-                    "✖️ Schema Parse Error",
-                    &format!("Failed to parse table schema: {}", e)
-                ))
-            }
-        }
+        parse_column_definitions(&normalized_schema).map_err(SqlError::SchemaParse)
     } else {
         Ok(Vec::new()) // No schema in topic
     }
 }
 
-/// Parse column selection (*, column names, etc.)
-fn parse_column_selection(columns: &str, schema: &[ColumnDefinition]) -> Result<Vec<String>, serenity::builder::CreateEmbed> {
+/// A column requested in the SELECT list, with an optional display alias (`col AS alias`).
+/// A computed column (`price*quantity AS total`) keeps the expression as typed in `name`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SelectedColumn {
+    name: String,
+    alias: Option<String>,
+    expression: Option<Expression>,
+}
+
+impl SelectedColumn {
+    fn new(name: &str) -> Self {
+        SelectedColumn { name: name.to_string(), alias: None, expression: None }
+    }
+
+    /// Header shown in the result table: the alias if given, otherwise the column name
+    fn display_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Parse a single selection item, detecting `column AS alias`.
+/// Either side may be quoted with backticks or double quotes, e.g. `` `first name` AS name ``.
+fn parse_selected_column(item: &str) -> SelectedColumn {
+    let (name, rest) = split_leading_identifier(item);
+    let rest = rest.trim();
+    let alias = rest.get(..2)
+        .filter(|keyword| keyword.eq_ignore_ascii_case("AS"))
+        .map(|_| rest[2..].trim())
+        .filter(|alias| !alias.is_empty() && rest[2..].starts_with(char::is_whitespace));
+    
+    match alias {
+        Some(alias) => SelectedColumn { name: name.to_string(), alias: Some(unquote_identifier(alias).to_string()), expression: None },
+        None if rest.is_empty() => SelectedColumn::new(name),
+        // Not a recognizable alias: keep the whole item so the unknown-column error shows it
+        None => SelectedColumn::new(item.trim()),
+    }
+}
+
+/// Parse a selection item that names no column as an expression, e.g. `price * quantity AS total`.
+/// The alias is split off here, since `parse_selected_column` only finds it after a single identifier.
+fn computed_column(item: &SelectedColumn, schema: &[ColumnDefinition]) -> Result<SelectedColumn, String> {
+    let (text, alias) = match &item.alias {
+        Some(alias) => (item.name.as_str(), Some(alias.clone())),
+        None => split_trailing_alias(&item.name),
+    };
+    match parse_expression(text, schema) {
+        Ok(expression) => Ok(SelectedColumn { name: text.to_string(), alias, expression: Some(expression) }),
+        Err(reason) => Err(format!("Could not compute `{}`: {}", text, reason)),
+    }
+}
+
+/// Split a trailing `AS alias` off `text`, ignoring `AS` inside quoted identifiers and strings
+fn split_trailing_alias(text: &str) -> (&str, Option<String>) {
+    let mut quote: Option<char> = None;
+    let mut split = None;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '`' || c == '"' || c == '\'' => quote = Some(c),
+            None if c.is_whitespace() => {
+                let after = &text[i + c.len_utf8()..];
+                if after.get(..2).is_some_and(|keyword| keyword.eq_ignore_ascii_case("AS")) && after[2..].starts_with(char::is_whitespace) {
+                    split = Some((i, i + c.len_utf8() + 2));
+                }
+            }
+            None => {}
+        }
+    }
+    match split {
+        Some((end, alias_start)) if !text[alias_start..].trim().is_empty() => {
+            (text[..end].trim(), Some(unquote_identifier(&text[alias_start..]).to_string()))
+        }
+        _ => (text.trim(), None),
+    }
+}
+
+/// Split an identifier off the start of `text`, returning it (unquoted) and the remainder.
+/// Quoted identifiers may contain spaces; unquoted ones end at whitespace.
+fn split_leading_identifier(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    if let Some(quote) = text.chars().next().filter(|c| *c == '`' || *c == '"')
+        && let Some(end) = text[1..].find(quote)
+    {
+        return (&text[1..end + 1], &text[end + 2..]);
+    }
+    match text.find(char::is_whitespace) {
+        Some(end) => (&text[..end], &text[end..]),
+        None => (text, ""),
+    }
+}
+
+/// Strip one pair of surrounding backticks or double quotes from an identifier
+fn unquote_identifier(name: &str) -> &str {
+    let name = name.trim();
+    for quote in ['`', '"'] {
+        if name.len() >= 2 && name.starts_with(quote) && name.ends_with(quote) {
+            return &name[1..name.len() - 1];
+        }
+    }
+    name
+}
+
+/// Split a comma-separated column list, ignoring commas inside quoted identifiers, strings
+/// and function call parentheses (`CONCAT(first, ' ', last)`)
+fn split_column_list(list: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    let mut start = 0;
+    
+    for (i, c) in list.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '`' || c == '"' || c == '\'' => quote = Some(c),
+            None if c == '(' => depth += 1,
+            None if c == ')' => depth = depth.saturating_sub(1),
+            None if c == ',' && depth == 0 => {
+                items.push(list[start..i].trim());
+                start = i + 1;
+            }
+            None => {}
+        }
+    }
+    items.push(list[start..].trim());
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+/// Find a schema column by name, ignoring ASCII case
+pub(crate) fn find_column_index(schema: &[ColumnDefinition], name: &str) -> Option<usize> {
+    schema.iter().position(|col| col.name.eq_ignore_ascii_case(name))
+}
+
+/// Result table headers for the selected columns
+fn column_headers(selected_columns: &[SelectedColumn]) -> Vec<String> {
+    selected_columns.iter().map(|col| col.display_name().to_string()).collect()
+}
+
+/// Parse column selection (*, column names, aliases, etc.).
+/// `*` covers the first `stored_columns` of `schema`; implicit columns such as `_created_at` follow them
+/// and must be named explicitly.
+pub(crate) fn parse_column_selection(columns: &str, schema: &[ColumnDefinition], stored_columns: usize) -> Result<Vec<SelectedColumn>, SqlError> {
     let columns = columns.trim();
+    let stored_schema = &schema[..stored_columns.min(schema.len())];
     
     if columns == "*" {
-        // Select all columns
-        if schema.is_empty() {
-            return Err(create_error_embed(
-                "✖️ Schema Required",
-                "Cannot use '*' selection on tables without defined schema. Please specify column names explicitly."
+        // Select all columns (for schemaless tables, the columns inferred from stored rows)
+        Ok(stored_schema.iter().map(|col| SelectedColumn::new(&col.name)).collect())
+    } else if let Some(excepted) = parse_star_except(columns) {
+        // * EXCEPT (col, ...): every column but the listed ones
+        let excepted = excepted?;
+        for name in &excepted {
+            if find_column_index(schema, name).is_none() {
+                return Err(SqlError::UnknownColumn {
+                    column: name.clone(),
+                    available: schema.iter().map(|c| c.name.clone()).collect(),
+                    clause: Some("EXCEPT"),
+                });
+            }
+        }
+        
+        let remaining: Vec<SelectedColumn> = stored_schema
+            .iter()
+            .filter(|col| !excepted.iter().any(|name| col.name.eq_ignore_ascii_case(name)))
+            .map(|col| SelectedColumn::new(&col.name))
+            .collect();
+        if remaining.is_empty() {
+            return Err(SqlError::InvalidColumnSelection(
+                "`* EXCEPT (...)` removed every column. Leave at least one column selected.".to_string()
             ));
         }
-        Ok(schema.iter().map(|col| col.name.clone()).collect())
+        Ok(remaining)
     } else {
         // Parse specific column names
-        let requested_columns: Vec<String> = columns
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
+        let requested_columns: Vec<SelectedColumn> = split_column_list(columns)
+            .into_iter()
+            .map(parse_selected_column)
             .collect();
         
         if requested_columns.is_empty() {
-            return Err(create_error_embed(
-                "✖️ Invalid Column Selection",
-                "Please specify column names or use '*' to select all columns."
+            return Err(SqlError::InvalidColumnSelection(
+                "Please specify column names or use '*' to select all columns.".to_string()
             ));
         }
         
-        // Validate column names against schema (if schema exists)
-        if !schema.is_empty() {
-            let schema_columns: HashSet<String> = schema.iter().map(|col| col.name.clone()).collect();
-            for col in &requested_columns {
-                if !schema_columns.contains(col) {
-                    return Err(create_error_embed(
-                        "✖️ Unknown Column",
-                        &format!("Column **{}** does not exist in table schema.\n\n**Available columns:** {}", 
-                                col, schema.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", "))
-                    ));
+        // Validate the underlying column names against schema (if schema exists),
+        // resolving them to the schema's casing so headers match the stored names
+        let mut requested_columns = requested_columns;
+        if !stored_schema.is_empty() {
+            for col in &mut requested_columns {
+                match find_column_index(schema, &col.name) {
+                    Some(index) => col.name = schema[index].name.clone(),
+                    None if looks_like_expression(&col.name) => {
+                        *col = computed_column(col, schema).map_err(SqlError::InvalidExpression)?;
+                    }
+                    None => {
+                        return Err(SqlError::UnknownColumn {
+                            column: col.name.clone(),
+                            available: schema.iter().map(|c| c.name.clone()).collect(),
+                            clause: None,
+                        });
+                    }
                 }
             }
         }
@@ -224,44 +451,219 @@ fn parse_column_selection(columns: &str, schema: &[ColumnDefinition]) -> Result<
     }
 }
 
-/// Extract values from stored message (similar to insert.rs)
-fn extract_values_from_message(content: &str, schema: &[ColumnDefinition]) -> Option<Vec<SqlValue>> {
-    if let Some(data_start) = content.find("DATA:\n") {
-        let data_section = &content[data_start + 6..];
-        let mut value_map = HashMap::new();
-        
-        for line in data_section.lines() {
-            if line.starts_with("  ") && line.contains(": ") {
-                if let Some(colon_pos) = line.find(": ") {
-                    let column_name = line[2..colon_pos].trim();
-                    let value_str = line[colon_pos + 2..].trim();
-                    
-                    if let Ok(sql_value) = parse_stored_value(value_str) {
-                        value_map.insert(column_name.to_string(), sql_value);
-                    }
-                }
+/// Remove repeated selections that would produce identical result headers (ignoring case).
+/// Returns the remaining columns and the display names that were dropped.
+fn dedupe_selected_columns(selected_columns: Vec<SelectedColumn>) -> (Vec<SelectedColumn>, Vec<String>) {
+    let mut kept: Vec<SelectedColumn> = Vec::new();
+    let mut duplicates: Vec<String> = Vec::new();
+    
+    for column in selected_columns {
+        let name = column.display_name();
+        if kept.iter().any(|existing| existing.display_name().eq_ignore_ascii_case(name)) {
+            if !duplicates.iter().any(|d| d.eq_ignore_ascii_case(name)) {
+                duplicates.push(name.to_string());
             }
+        } else {
+            kept.push(column);
         }
-        
-        // If we have a schema, use it to order values
-        if !schema.is_empty() {
-            let mut ordered_values = Vec::new();
-            for column in schema {
-                if let Some(value) = value_map.get(&column.name) {
-                    ordered_values.push(value.clone());
-                } else {
-                    return None; // Missing column
-                }
+    }
+    
+    (kept, duplicates)
+}
+
+/// Parse `* EXCEPT (col, ...)`, returning the excepted column names.
+/// Returns None if the selection isn't a `* EXCEPT` form.
+fn parse_star_except(columns: &str) -> Option<Result<Vec<String>, SqlError>> {
+    let rest = columns.strip_prefix('*')?.trim_start();
+    let keyword = rest.get(..6)?;
+    if !keyword.eq_ignore_ascii_case("EXCEPT") {
+        return None;
+    }
+    
+    let list = rest[6..].trim();
+    let inner = match list.strip_prefix('(').and_then(|l| l.strip_suffix(')')) {
+        Some(inner) => inner,
+        None => {
+            return Some(Err(SqlError::InvalidColumnSelection(
+                "EXCEPT expects a parenthesized column list, e.g. `* EXCEPT (password, secret)`".to_string()
+            )));
+        }
+    };
+    
+    let names: Vec<String> = split_column_list(inner)
+        .into_iter()
+        .map(|name| unquote_identifier(name).to_string())
+        .collect();
+    if names.is_empty() {
+        return Some(Err(SqlError::InvalidColumnSelection(
+            "EXCEPT needs at least one column, e.g. `* EXCEPT (password)`".to_string()
+        )));
+    }
+    
+    Some(Ok(names))
+}
+
+/// Extract the stored `column: value` pairs from a row message, in stored order
+fn extract_stored_pairs(content: &str) -> Option<Vec<(String, SqlValue)>> {
+    let data_start = content.find("DATA:\n")?;
+    let data_section = &content[data_start + 6..];
+    let mut pairs = Vec::new();
+    
+    for line in data_section.lines() {
+        if !line.starts_with("  ") {
+            continue;
+        }
+        if let Some(colon_pos) = line.find(": ") {
+            let column_name = line[2..colon_pos].trim();
+            let value_str = line[colon_pos + 2..].trim();
+            
+            if let Ok(sql_value) = parse_stored_value(value_str) {
+                pairs.push((column_name.to_string(), sql_value));
             }
-            if ordered_values.len() == schema.len() {
-                return Some(ordered_values);
+        }
+    }
+    
+    Some(pairs)
+}
+
+/// Extract values from stored message (similar to insert.rs)
+pub(crate) fn extract_values_from_message(content: &str, schema: &[ColumnDefinition]) -> Option<Vec<SqlValue>> {
+    let pairs = extract_stored_pairs(content)?;
+    
+    if schema.is_empty() {
+        // No schema - return values in stored order
+        return Some(pairs.into_iter().map(|(_, value)| value).collect());
+    }
+    
+    // Use the schema to order values; a row missing a column doesn't belong to this schema
+    let value_map: HashMap<String, SqlValue> = pairs.into_iter().collect();
+    schema.iter().map(|column| value_map.get(&column.name).cloned()).collect()
+}
+
+/// Build table rows from message contents.
+/// Schemaless tables get a column set inferred from the union of stored keys
+/// (`column_1`, `column_2`, ...), so sparse rows are padded with NULL instead of being dropped.
+pub(crate) fn rows_from_messages(contents: &[&str], schema: Vec<ColumnDefinition>) -> (Vec<ColumnDefinition>, Vec<Vec<SqlValue>>) {
+    let (schema, rows) = indexed_rows_from_messages(contents, schema);
+    (schema, rows.into_iter().map(|(_, row)| row).collect())
+}
+
+/// Like `rows_from_messages`, with each row paired with the index of the content it came from
+pub(crate) fn indexed_rows_from_messages(contents: &[&str], schema: Vec<ColumnDefinition>) -> (Vec<ColumnDefinition>, Vec<(usize, Vec<SqlValue>)>) {
+    if !schema.is_empty() {
+        let rows = contents.iter()
+            .enumerate()
+            .filter_map(|(index, content)| Some((index, extract_values_from_message(content, &schema)?)))
+            .collect();
+        return (schema, rows);
+    }
+    
+    let stored_rows: Vec<(usize, Vec<(String, SqlValue)>)> = contents.iter()
+        .enumerate()
+        .filter_map(|(index, content)| Some((index, extract_stored_pairs(content)?)))
+        .collect();
+    
+    let mut column_names: Vec<String> = Vec::new();
+    for (_, pairs) in &stored_rows {
+        for (name, _) in pairs {
+            if !column_names.contains(name) {
+                column_names.push(name.clone());
             }
-        } else {
-            // No schema - return values in order found
-            return Some(value_map.into_values().collect());
         }
     }
-    None
+    // Positional keys sort numerically (column_2 before column_10); other keys keep first-seen order after them
+    column_names.sort_by_key(|name| {
+        let position = name.strip_prefix("column_").and_then(|n| n.parse::<usize>().ok());
+        (position.is_none(), position)
+    });
+    
+    let rows = stored_rows
+        .into_iter()
+        .map(|(index, pairs)| {
+            let value_map: HashMap<String, SqlValue> = pairs.into_iter().collect();
+            (index, column_names.iter().map(|name| value_map.get(name).cloned().unwrap_or(SqlValue::Null)).collect())
+        })
+        .collect();
+    
+    let inferred_schema = column_names.iter().map(|name| inferred_column(name)).collect();
+    (inferred_schema, rows)
+}
+
+/// Implicit column holding each row's insertion time
+pub const CREATED_AT_COLUMN: &str = "_created_at";
+/// Implicit column holding the id of the message that stores the row
+pub const ROWID_COLUMN: &str = "_rowid";
+
+/// A pseudo-column every row has without storing it, selectable by name and usable in WHERE
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ImplicitColumn {
+    RowId,
+    CreatedAt,
+}
+
+impl ImplicitColumn {
+    fn name(self) -> &'static str {
+        match self {
+            ImplicitColumn::RowId => ROWID_COLUMN,
+            ImplicitColumn::CreatedAt => CREATED_AT_COLUMN,
+        }
+    }
+
+    /// Definition with the type WHERE compares the column as
+    fn definition(self) -> ColumnDefinition {
+        let data_type = match self {
+            ImplicitColumn::RowId => "INT",
+            ImplicitColumn::CreatedAt => "DATETIME",
+        };
+        ColumnDefinition { data_type: data_type.to_string(), ..inferred_column(self.name()) }
+    }
+
+    fn value(self, message: &Message) -> SqlValue {
+        match self {
+            // Snowflakes stay below 2^63, so the id fits an INT exactly
+            ImplicitColumn::RowId => SqlValue::Integer(message.id.get() as i64),
+            ImplicitColumn::CreatedAt => created_at_value(&message.content, message.timestamp.unix_timestamp()),
+        }
+    }
+}
+
+/// The implicit columns a table gets: all of them, except those shadowed by a real column of the same name
+fn implicit_columns(schema: &[ColumnDefinition]) -> Vec<ImplicitColumn> {
+    [ImplicitColumn::RowId, ImplicitColumn::CreatedAt]
+        .into_iter()
+        .filter(|column| find_column_index(schema, column.name()).is_none())
+        .collect()
+}
+
+/// A row's insertion time as an ISO 8601 UTC datetime: the stored `TIMESTAMP:` line,
+/// or the message's own timestamp (`fallback_unix`) for rows without one
+fn created_at_value(content: &str, fallback_unix: i64) -> SqlValue {
+    let stored = content.lines()
+        .find_map(|line| line.strip_prefix("TIMESTAMP: "))
+        .and_then(|stamp| chrono::NaiveDateTime::parse_from_str(stamp.trim(), "%Y-%m-%d %H:%M:%S UTC").ok())
+        .map(|stamp| stamp.and_utc());
+    match stored.or_else(|| chrono::DateTime::from_timestamp(fallback_unix, 0)) {
+        Some(created_at) => SqlValue::String(created_at.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        None => SqlValue::Null,
+    }
+}
+
+/// Column definition for a key found in a schemaless table's stored rows
+fn inferred_column(name: &str) -> ColumnDefinition {
+    ColumnDefinition {
+        name: name.to_string(),
+        data_type: "VARCHAR".to_string(),
+        size: None,
+        scale: None,
+        unsigned: false,
+        nullable: true,
+        primary_key: false,
+        unique: false,
+        auto_increment: false,
+        default: None,
+        references: None,
+        comment: None,
+    }
 }
 
 /// Parse stored value back to SqlValue (similar to insert.rs)
@@ -282,7 +684,7 @@ fn parse_stored_value(value_str: &str) -> Result<SqlValue, String> {
     if (trimmed.starts_with('\'') && trimmed.ends_with('\'')) || 
        (trimmed.starts_with('"') && trimmed.ends_with('"')) {
         let content = &trimmed[1..trimmed.len()-1];
-        return Ok(SqlValue::String(content.to_string()));
+        return Ok(SqlValue::String(decode_stored_string(content)));
     }
     
     if let Ok(int_val) = trimmed.parse::<i64>() {
@@ -298,15 +700,17 @@ fn parse_stored_value(value_str: &str) -> Result<SqlValue, String> {
 }
 
 /// Select only requested columns from a row
-fn select_columns(row_data: &[SqlValue], schema: &[ColumnDefinition], selected_columns: &[String]) -> Vec<SqlValue> {
+fn select_columns(row_data: &[SqlValue], schema: &[ColumnDefinition], selected_columns: &[SelectedColumn]) -> Vec<SqlValue> {
     if schema.is_empty() {
         // Without schema, just return first N values
         return row_data.iter().take(selected_columns.len()).cloned().collect();
     }
     
     let mut result = Vec::new();
-    for col_name in selected_columns {
-        if let Some(index) = schema.iter().position(|col| &col.name == col_name) {
+    for selected in selected_columns {
+        if let Some(expression) = &selected.expression {
+            result.push(expression.evaluate(row_data));
+        } else if let Some(index) = find_column_index(schema, &selected.name) {
             if let Some(value) = row_data.get(index) {
                 result.push(value.clone());
             } else {
@@ -317,13 +721,13 @@ fn select_columns(row_data: &[SqlValue], schema: &[ColumnDefinition], selected_c
     result
 }
 
-/// Apply DISTINCT filtering
+/// Apply DISTINCT filtering: rows whose values are all equal keep only their first occurrence
 fn apply_distinct(rows: Vec<Vec<SqlValue>>) -> Vec<Vec<SqlValue>> {
     let mut seen = HashSet::new();
     let mut distinct_rows = Vec::new();
     
     for row in rows {
-        let row_key = format!("{:?}", row); // Simple serialization for comparison
+        let row_key: Vec<DistinctKey> = row.iter().map(DistinctKey::of).collect();
         if seen.insert(row_key) {
             distinct_rows.push(row);
         }
@@ -332,8 +736,34 @@ fn apply_distinct(rows: Vec<Vec<SqlValue>>) -> Vec<Vec<SqlValue>> {
     distinct_rows
 }
 
+/// A value as DISTINCT compares it, with the equality INSERT's duplicate checks use: `Integer(1)` and
+/// `Float(1.0)` are the same key, and floats differing only past 15 significant digits (`0.1 + 0.2`
+/// and `0.3`) are too
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum DistinctKey {
+    Null,
+    Integer(i64),
+    Float(String),
+    String(String),
+    Boolean(bool),
+}
+
+impl DistinctKey {
+    fn of(value: &SqlValue) -> Self {
+        match value {
+            SqlValue::Null => DistinctKey::Null,
+            SqlValue::Integer(i) => DistinctKey::Integer(*i),
+            // Whole floats in i64 range key as the integer they equal
+            SqlValue::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => DistinctKey::Integer(*f as i64),
+            SqlValue::Float(f) => DistinctKey::Float(format!("{:.14e}", f)),
+            SqlValue::String(s) => DistinctKey::String(s.clone()),
+            SqlValue::Boolean(b) => DistinctKey::Boolean(*b),
+        }
+    }
+}
+
 /// Enhanced WHERE condition evaluation with AND/OR and parentheses support
-fn evaluate_where_condition(
+pub(crate) fn evaluate_where_condition(
     row_data: &[SqlValue], 
     schema: &[ColumnDefinition], 
     where_condition: &str
@@ -348,13 +778,54 @@ fn evaluate_where_condition(
     parse_or_expression(row_data, schema, where_condition.trim())
 }
 
+/// Reject a WHERE clause that names columns outside `schema`, which would otherwise
+/// silently match nothing. Tables without any known columns are not checked.
+pub(crate) fn check_where_columns(where_condition: &str, schema: &[ColumnDefinition]) -> Result<(), SqlError> {
+    if schema.is_empty() {
+        return Ok(());
+    }
+    let mut unknown: Vec<String> = Vec::new();
+    for column in where_columns(where_condition.trim()) {
+        if find_column_index(schema, column).is_none() && !unknown.iter().any(|seen| seen.eq_ignore_ascii_case(column)) {
+            unknown.push(column.to_string());
+        }
+    }
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(SqlError::UnknownWhereColumn {
+            columns: unknown,
+            available: schema.iter().map(|col| col.name.clone()).collect(),
+        })
+    }
+}
+
+/// Column names referenced by the conditions of a WHERE expression, in order of appearance
+fn where_columns(expression: &str) -> Vec<&str> {
+    let mut columns = Vec::new();
+    for or_part in split_by_operator(expression, "OR") {
+        for and_part in split_by_operator(or_part.trim(), "AND") {
+            let mut expr = and_part.trim();
+            while let Some(rest) = strip_not_keyword(expr) {
+                expr = rest;
+            }
+            if is_wrapped_in_parens(expr) {
+                columns.extend(where_columns(expr[1..expr.len()-1].trim()));
+            } else if let Some((column_name, _, _)) = split_condition(expr) {
+                columns.push(column_name);
+            }
+        }
+    }
+    columns
+}
+
 /// Parse OR expression (lowest precedence)
 fn parse_or_expression(
     row_data: &[SqlValue], 
     schema: &[ColumnDefinition], 
     expression: &str
 ) -> bool {
-    let or_parts = split_by_operator(expression, " OR ");
+    let or_parts = split_by_operator(expression, "OR");
     
     for part in or_parts {
         if parse_and_expression(row_data, schema, part.trim()) {
@@ -371,7 +842,7 @@ fn parse_and_expression(
     schema: &[ColumnDefinition], 
     expression: &str
 ) -> bool {
-    let and_parts = split_by_operator(expression, " AND ");
+    let and_parts = split_by_operator(expression, "AND");
     
     for part in and_parts {
         if !parse_primary_expression(row_data, schema, part.trim()) {
@@ -389,8 +860,13 @@ fn parse_primary_expression(
     expression: &str
 ) -> bool {
     let expr = expression.trim();
-    
-    if expr.starts_with('(') && expr.ends_with(')') {
+
+    // NOT binds tighter than AND/OR, so it only negates the following condition or group
+    if let Some(rest) = strip_not_keyword(expr) {
+        return !parse_primary_expression(row_data, schema, rest);
+    }
+
+    if is_wrapped_in_parens(expr) {
         // Remove outer parentheses and evaluate inner expression
         let inner = &expr[1..expr.len()-1];
         return parse_or_expression(row_data, schema, inner);
@@ -400,43 +876,48 @@ fn parse_primary_expression(
     evaluate_single_condition(row_data, schema, expr)
 }
 
-/// Split expression by operator while respecting parentheses
-fn split_by_operator<'a>(expression: &'a str, operator: &str) -> Vec<&'a str> {
+/// Strip a leading `NOT` keyword (case-insensitive), returning the negated operand
+fn strip_not_keyword(expression: &str) -> Option<&str> {
+    let keyword = expression.get(..3)?;
+    if !keyword.eq_ignore_ascii_case("NOT") {
+        return None;
+    }
+
+    let rest = &expression[3..];
+    // Require a word boundary so columns like `notes` aren't mistaken for NOT
+    if rest.starts_with(char::is_whitespace) || rest.starts_with('(') {
+        Some(rest.trim_start())
+    } else {
+        None
+    }
+}
+
+/// Split expression at a top-level `AND`/`OR` keyword.
+/// The keyword matches in any case with any whitespace around it, but not inside parentheses
+/// or quoted literals, and not as part of a longer word (`'brand'`, `orders`).
+fn split_by_operator<'a>(expression: &'a str, keyword: &str) -> Vec<&'a str> {
+    let bytes = expression.as_bytes();
     let mut parts = Vec::new();
     let mut current_start = 0;
     let mut paren_depth = 0;
-    let chars: Vec<char> = expression.chars().collect();
-    let op_chars: Vec<char> = operator.chars().collect();
-    
-    let mut i = 0;
-    while i < chars.len() {
-        match chars[i] {
-            '(' => paren_depth += 1,
-            ')' => paren_depth -= 1,
-            _ => {
-                // Check if we're at an operator and not inside parentheses
-                if paren_depth == 0 && i + op_chars.len() <= chars.len() {
-                    let potential_op: String = chars[i..i + op_chars.len()].iter().collect();
-                    if potential_op == operator {
-                        // Found operator at top level, split here
-                        let part = &expression[current_start..i];
-                        if !part.trim().is_empty() {
-                            parts.push(part);
-                        }
-                        current_start = i + op_chars.len();
-                        i += op_chars.len() - 1; // -1 because loop will increment
-                    }
-                }
+    
+    // A doubled quote ('It''s') closes and reopens a literal, so it stays literal text
+    for i in unquoted_byte_positions(expression) {
+        match bytes[i] {
+            b'(' => paren_depth += 1,
+            b')' => paren_depth -= 1,
+            _ if i >= current_start && paren_depth == 0 && is_keyword_at(expression, i, keyword) => {
+                // Found operator at top level, split here
+                parts.push(&expression[current_start..i]);
+                current_start = i + keyword.len();
             }
+            _ => {}
         }
-        i += 1;
     }
     
     // Add the remaining part
-    let remaining = &expression[current_start..];
-    if !remaining.trim().is_empty() {
-        parts.push(remaining);
-    }
+    parts.push(&expression[current_start..]);
+    parts.retain(|part| !part.trim().is_empty());
     
     // If no splits were made, return the whole expression
     if parts.is_empty() {
@@ -446,152 +927,1235 @@ fn split_by_operator<'a>(expression: &'a str, operator: &str) -> Vec<&'a str> {
     }
 }
 
-/// Evaluate a single condition (column=value)
+/// Whether `keyword` starts at byte `i`, case-insensitively and with whitespace on both sides
+fn is_keyword_at(expression: &str, i: usize, keyword: &str) -> bool {
+    let Some(word) = expression.get(i..i + keyword.len()) else {
+        return false;
+    };
+    let before = expression[..i].chars().next_back();
+    let after = expression[i + keyword.len()..].chars().next();
+    word.eq_ignore_ascii_case(keyword)
+        && before.is_some_and(char::is_whitespace)
+        && after.is_some_and(char::is_whitespace)
+}
+
+/// Evaluate a single condition (`column=value`, `column<value`, ...)
 fn evaluate_single_condition(
     row_data: &[SqlValue], 
     schema: &[ColumnDefinition], 
     condition: &str
 ) -> bool {
-    if let Some(eq_pos) = condition.find('=') {
-        let column_name = condition[..eq_pos].trim();
-        let expected_value = condition[eq_pos + 1..].trim();
-        
-        if let Some(index) = schema.iter().position(|col| col.name == column_name) {
-            if let Some(actual_value) = row_data.get(index) {
-                return format_sql_value_for_comparison(actual_value) == expected_value;
-            }
+    let actual_value = split_condition(condition).and_then(|(column_name, comparison, expected_value)| {
+        let index = find_column_index(schema, column_name)?;
+        Some((row_data.get(index)?, &schema[index], comparison, expected_value))
+    });
+    
+    let Some((actual_value, column, comparison, expected_value)) = actual_value else {
+        // If we can't parse the condition, fail it (fail-closed for security)
+        return false;
+    };
+    // CHAR values are stored padded; trailing spaces don't count on either side
+    let (unpadded, unpadded_literal);
+    let (actual_value, expected_value) = match actual_value {
+        SqlValue::String(s) if column.data_type == "CHAR" => {
+            unpadded = SqlValue::String(s.trim_end_matches(' ').to_string());
+            unpadded_literal = trim_char_literal(expected_value);
+            (&unpadded, unpadded_literal.as_str())
         }
+        _ => (actual_value, expected_value),
+    };
+    let equal = || {
+        temporal_ordering(actual_value, &column.data_type, expected_value)
+            .map(Ordering::is_eq)
+            .unwrap_or_else(|| values_match(actual_value, expected_value))
+    };
+    match comparison {
+        Comparison::Eq => equal(),
+        // Like SQL, NULL is never unequal to anything
+        Comparison::NotEq => !matches!(actual_value, SqlValue::Null) && !equal(),
+        _ => temporal_ordering(actual_value, &column.data_type, expected_value)
+            .or_else(|| value_ordering(actual_value, expected_value))
+            .is_some_and(|ordering| comparison.accepts(ordering)),
     }
-    
-    // If we can't parse the condition, fail it (fail-closed for security)
-    false
 }
 
-/// Format SQL value for comparison in WHERE clauses
-fn format_sql_value_for_comparison(value: &SqlValue) -> String {
-    match value {
-        SqlValue::String(s) => format!("'{}'", s),
-        SqlValue::Integer(i) => i.to_string(),
-        SqlValue::Float(f) => f.to_string(),
-        SqlValue::Boolean(b) => b.to_string(),
-        SqlValue::Null => "null".to_string(),
-    }
+/// Comparison operator of a WHERE condition
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Eq,
+    NotEq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
 }
 
-/// Format SELECT results into a Discord embed
-fn format_select_results(
-    columns: &[String],
-    rows: &[Vec<SqlValue>],
-    table_name: &str,
-    distinct: bool,
-    where_clause: Option<&str>
-) -> serenity::builder::CreateEmbed {
-    let mut description = String::new();
-    
-    // Add query info
-    description.push_str(&format!("**Table:** {}\n", table_name));
-    description.push_str(&format!("**Columns:** {}\n", columns.join(", ")));
-    if distinct {
-        description.push_str("**Modifier:** DISTINCT\n");
+impl Comparison {
+    /// Operator at the start of `text` and its length in bytes
+    fn parse_prefix(text: &str) -> Option<(Comparison, usize)> {
+        [
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("<>", Comparison::NotEq),
+            ("!=", Comparison::NotEq),
+            ("=", Comparison::Eq),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+        ]
+        .into_iter()
+        .find(|(symbol, _)| text.starts_with(symbol))
+        .map(|(symbol, comparison)| (comparison, symbol.len()))
+    }
+
+    /// Whether a stored value ordered `ordering` against the literal satisfies the operator
+    fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Eq => ordering.is_eq(),
+            Comparison::NotEq => ordering.is_ne(),
+            Comparison::Lt => ordering.is_lt(),
+            Comparison::Le => ordering.is_le(),
+            Comparison::Gt => ordering.is_gt(),
+            Comparison::Ge => ordering.is_ge(),
+        }
     }
-    if let Some(where_cond) = where_clause {
-        description.push_str(&format!("**Filter:** WHERE {}\n", where_cond));
+}
+
+/// Split `column<op>value` at the first comparison operator outside quotes; a column quoted with
+/// backticks or double quotes may contain spaces or operators, and operators inside a quoted value are literal text
+fn split_condition(condition: &str) -> Option<(&str, Comparison, &str)> {
+    let condition = condition.trim_start();
+    if condition.starts_with(['`', '"']) {
+        let (column_name, rest) = split_leading_identifier(condition);
+        let rest = rest.trim_start();
+        let (comparison, len) = Comparison::parse_prefix(rest)?;
+        return Some((column_name, comparison, rest[len..].trim()));
     }
-    description.push_str(&format!("**Rows returned:** {}\n\n", rows.len()));
     
-    if rows.is_empty() {
-        description.push_str("*No rows found matching the criteria.*");
-    } else {
-        // Calculate optimal column widths
-        let mut col_widths = vec![3; columns.len() + 1]; // Start with minimum widths, +1 for Row column
-        col_widths[0] = std::cmp::max(3, "Row".len()); // Row column
-        
-        // Set minimum width based on column names
-        for (i, col) in columns.iter().enumerate() {
-            col_widths[i + 1] = std::cmp::max(col_widths[i + 1], col.len());
+    let (op_pos, (comparison, len)) = unquoted_byte_positions(condition)
+        .find_map(|i| Some((i, Comparison::parse_prefix(&condition[i..])?)))?;
+    Some((condition[..op_pos].trim(), comparison, condition[op_pos + len..].trim()))
+}
+
+/// Byte offsets of the characters in `text` that are outside `'...'`, `"..."` and `` `...` `` literals
+/// (quote characters themselves excluded). Quotes are ASCII, so offsets are always char boundaries.
+pub(crate) fn unquoted_byte_positions(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut quote: Option<u8> = None;
+    text.bytes().enumerate().filter_map(move |(i, byte)| match quote {
+        Some(open) => {
+            if byte == open {
+                quote = None;
+            }
+            None
         }
-        
-        // Calculate widths based on actual data (limit to first 20 rows for performance)
-        let display_rows = rows.iter().take(20).collect::<Vec<_>>();
-        for (row_idx, row) in display_rows.iter().enumerate() {
-            // Update width for row number column
-            let row_num_width = (row_idx + 1).to_string().len();
-            col_widths[0] = std::cmp::max(col_widths[0], row_num_width);
-            
-            // Update widths for data columns
-            for (col_idx, value) in row.iter().enumerate() {
-                let formatted = format_sql_value_for_display_table(value);
-                if col_idx + 1 < col_widths.len() {
-                    col_widths[col_idx + 1] = std::cmp::max(col_widths[col_idx + 1], formatted.len());
+        None if matches!(byte, b'\'' | b'"' | b'`') => {
+            quote = Some(byte);
+            None
+        }
+        None => Some(i),
+    })
+}
+
+/// Whether the whole expression is one parenthesized group, e.g. `(a=1 OR b=2)` but not
+/// `(a=1) AND (b=2)`. Parentheses inside quoted values don't count.
+fn is_wrapped_in_parens(expr: &str) -> bool {
+    if !expr.starts_with('(') || !expr.ends_with(')') {
+        return false;
+    }
+    let mut depth = 0;
+    for i in unquoted_byte_positions(expr) {
+        match expr.as_bytes()[i] {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                // The opening parenthesis closed before the end
+                if depth == 0 && i + 1 < expr.len() {
+                    return false;
                 }
             }
+            _ => {}
         }
-        
-        // Apply maximum width limit to prevent extremely wide tables
-        const MAX_COL_WIDTH: usize = 50;
-        for width in &mut col_widths {
-            *width = std::cmp::min(*width, MAX_COL_WIDTH);
-        }
-        
-        // Build the table
-        description.push_str("```\n");
-        
-        // Header row
-        description.push_str(&format!("{:<width$}", "Row", width = col_widths[0]));
-        for (i, col) in columns.iter().enumerate() {
-            description.push_str(&format!(" | {:<width$}", col, width = col_widths[i + 1]));
+    }
+    depth == 0
+}
+
+/// Compare a stored value with the literal from a WHERE clause.
+/// Numeric columns compare by number, so `1e3`, `'1000'` and `1000.0` all match a stored `1000`.
+/// Other values compare with the typed literal (see `parse_where_literal`): `active=true` matches a stored
+/// boolean and `name='Ann'` a stored string, while `name=100` never matches text.
+fn values_match(actual: &SqlValue, expected: &str) -> bool {
+    match (actual, parse_numeric_literal(expected)) {
+        // Both integral: compare exactly, beyond f64 precision
+        (SqlValue::Integer(a), Some(NumericLiteral::Integer(e))) => *a == e,
+        (SqlValue::Integer(a), Some(e)) => *a as f64 == e.as_f64(),
+        (SqlValue::Float(a), Some(e)) => *a == e.as_f64(),
+        _ => match (actual, parse_where_literal(expected)) {
+            (SqlValue::Null, Some(SqlValue::Null)) => true,
+            (SqlValue::String(a), Some(SqlValue::String(e))) => *a == e,
+            (SqlValue::Boolean(a), Some(SqlValue::Boolean(e))) => *a == e,
+            _ => false,
+        },
+    }
+}
+
+/// A WHERE literal as the value it stands for. Quoted literals (`'...'` or `"..."`, a doubled quote
+/// standing for one) are always strings; unquoted ones are `NULL` or `TRUE`/`FALSE` in any case, or numbers.
+/// `None` for unquoted text, which no stored value equals.
+fn parse_where_literal(literal: &str) -> Option<SqlValue> {
+    let text = strip_literal_quotes(literal);
+    if text.len() < literal.len() {
+        let quote = &literal[..1];
+        return Some(SqlValue::String(text.replace(&quote.repeat(2), quote)));
+    }
+    if text.eq_ignore_ascii_case("null") {
+        return Some(SqlValue::Null);
+    }
+    if let Ok(b) = text.to_ascii_lowercase().parse::<bool>() {
+        return Some(SqlValue::Boolean(b));
+    }
+    parse_numeric_literal(text).map(|number| match number {
+        NumericLiteral::Integer(i) => SqlValue::Integer(i),
+        NumericLiteral::Float(f) => SqlValue::Float(f),
+    })
+}
+
+/// Compare DATE/TIME/DATETIME values chronologically, so `'2025-01-15T16:30:00+02:00'` equals a stored
+/// `2025-01-15T14:30:00Z`. `None` when the column isn't temporal or either side doesn't parse.
+fn temporal_ordering(actual: &SqlValue, data_type: &str, expected: &str) -> Option<Ordering> {
+    let SqlValue::String(stored) = actual else {
+        return None;
+    };
+    let stored = parse_temporal_value(data_type, stored)?;
+    let typed = parse_temporal_value(data_type, strip_literal_quotes(expected))?;
+    Some(stored.cmp(&typed))
+}
+
+/// Order a stored value against a WHERE literal for `<`, `>` and friends: numbers by value,
+/// strings by text against a quoted literal. `None` when they can't be ordered (NULL, booleans, mixed types).
+fn value_ordering(actual: &SqlValue, expected: &str) -> Option<Ordering> {
+    match (actual, parse_numeric_literal(expected)) {
+        (SqlValue::Integer(a), Some(NumericLiteral::Integer(e))) => Some(a.cmp(&e)),
+        (SqlValue::Integer(a), Some(e)) => (*a as f64).partial_cmp(&e.as_f64()),
+        (SqlValue::Float(a), Some(e)) => a.partial_cmp(&e.as_f64()),
+        (SqlValue::String(s), _) => {
+            let text = strip_literal_quotes(expected);
+            (text.len() < expected.len()).then(|| s.as_str().cmp(text))
         }
-        description.push_str("\n");
-        
-        // Separator line
-        let total_width = col_widths.iter().sum::<usize>() + (col_widths.len() - 1) * 3; // 3 chars per separator " | "
-        description.push_str(&"-".repeat(total_width));
-        description.push_str("\n");
-        
-        // Data rows
-        for (row_idx, row) in display_rows.iter().enumerate() {
-            description.push_str(&format!("{:<width$}", row_idx + 1, width = col_widths[0]));
-            for (col_idx, value) in row.iter().enumerate() {
-                let formatted = format_sql_value_for_display_table(value);
-                let truncated = if formatted.len() > col_widths[col_idx + 1] {
-                    format!("{}...", &formatted[..col_widths[col_idx + 1].saturating_sub(3)])
-                } else {
-                    formatted
-                };
-                description.push_str(&format!(" | {:<width$}", truncated, width = col_widths[col_idx + 1]));
-            }
-            description.push_str("\n");
+        _ => None,
+    }
+}
+
+/// A WHERE literal for a CHAR column without trailing spaces inside its quotes (`'AB  '` -> `'AB'`)
+fn trim_char_literal(literal: &str) -> String {
+    let text = strip_literal_quotes(literal);
+    if text.len() == literal.len() {
+        return literal.trim_end_matches(' ').to_string();
+    }
+    let quote = &literal[..1];
+    format!("{}{}{}", quote, text.trim_end_matches(' '), quote)
+}
+
+/// The text inside a `'...'` or `"..."` WHERE literal, or the literal itself when unquoted
+fn strip_literal_quotes(literal: &str) -> &str {
+    ['\'', '"'].iter()
+        .find_map(|quote| literal.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(literal)
+}
+
+/// A number typed in a WHERE clause
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NumericLiteral {
+    Integer(i64),
+    Float(f64),
+}
+
+impl NumericLiteral {
+    fn as_f64(self) -> f64 {
+        match self {
+            NumericLiteral::Integer(i) => i as f64,
+            NumericLiteral::Float(f) => f,
         }
-        
-        if rows.len() > 20 {
-            description.push_str(&format!("... and {} more rows\n", rows.len() - 20));
+    }
+}
+
+/// Parse a WHERE literal as a number, optionally quoted (`-5`, `1e3`, `'2.50'`).
+/// `inf` and `NaN` are not numbers here.
+fn parse_numeric_literal(literal: &str) -> Option<NumericLiteral> {
+    let unquoted = strip_literal_quotes(literal).trim();
+    if let Ok(i) = unquoted.parse::<i64>() {
+        return Some(NumericLiteral::Integer(i));
+    }
+    unquoted.parse::<f64>().ok()
+        .filter(|f| f.is_finite())
+        .map(NumericLiteral::Float)
+}
+
+/// How much work a query did, shown in the result footer
+struct QueryStats {
+    /// Time spent fetching and filtering rows
+    elapsed: Duration,
+    /// Raw messages read from the table channel
+    messages_scanned: usize,
+    /// Set to the scan limit when it stopped the fetch early
+    scan_limit_hit: Option<usize>,
+}
+
+impl QueryStats {
+    fn footer_text(&self) -> String {
+        let mut text = format!(
+            "⏱️ {} ms • {} {} scanned",
+            self.elapsed.as_millis(),
+            self.messages_scanned,
+            if self.messages_scanned == 1 { "message" } else { "messages" }
+        );
+        if let Some(limit) = self.scan_limit_hit {
+            text.push_str(&format!(" • ⚠️ Scan limit reached: only the newest {} rows were read", limit));
         }
-        
-        description.push_str("```");
-        
-        // If any values were truncated, add a note
-        let has_long_values = display_rows.iter().any(|row| {
-            row.iter().any(|value| {
-                let formatted = format_sql_value_for_display_table(value);
-                formatted.len() > MAX_COL_WIDTH
-            })
-        });
-        
-        if has_long_values {
-            description.push_str("\n\n*Note: Some long values have been truncated for display. Use more specific column selection to see full values.*");
+        text
+    }
+}
+
+/// Output formats for SELECT results
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    /// Aligned text table (first 20 rows)
+    #[default]
+    Table,
+    /// JSON array of objects, one per row
+    Json,
+    /// Comma-separated values with a header line
+    Csv,
+}
+
+impl OutputFormat {
+    /// Parse the `format` option; anything unrecognized falls back to the table
+    pub fn from_option(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("json") => OutputFormat::Json,
+            Some("csv") => OutputFormat::Csv,
+            _ => OutputFormat::Table,
+        }
+    }
+}
+
+/// Rows shown in the text table unless `max_rows` or `SQLCORD_DISPLAY_ROWS` says otherwise
+const DEFAULT_DISPLAY_ROWS: usize = 20;
+/// Upper bound for `max_rows`; larger results belong in a thread (`to_thread:true`)
+pub const MAX_DISPLAY_ROWS: usize = 100;
+/// Characters of JSON/CSV output that fit in the embed next to the query summary
+const EXPORT_CHAR_BUDGET: usize = 3500;
+
+/// Rows to render in the text table: the `max_rows` option, else the
+/// `SQLCORD_DISPLAY_ROWS` environment variable, else 20; always within 1..=`MAX_DISPLAY_ROWS`
+fn display_row_limit(requested: Option<usize>) -> usize {
+    requested
+        .or_else(|| std::env::var("SQLCORD_DISPLAY_ROWS").ok().and_then(|value| value.parse::<usize>().ok()))
+        .unwrap_or(DEFAULT_DISPLAY_ROWS)
+        .clamp(1, MAX_DISPLAY_ROWS)
+}
+
+/// Room kept free in a cut-down SELECT preview for the attachment note
+const ATTACHMENT_NOTE_RESERVE: usize = 100;
+
+/// Format SELECT results into a Discord embed.
+/// When the rendered result overflows the embed, a preview stays in the embed and the full text is attached.
+fn format_select_results(
+    columns: &[String],
+    definitions: &[Option<&ColumnDefinition>],
+    rows: &[Vec<SqlValue>],
+    args: &SelectArgs<'_>,
+    stats: &QueryStats,
+    notes: &[String]
+) -> (CreateEmbed, Option<CreateAttachment>) {
+    let mut summary = String::new();
+    
+    // Add query info
+    summary.push_str(&format!("**Table:** {}\n", args.from));
+    summary.push_str(&format!("**Columns:** {}\n", columns.join(", ")));
+    if args.distinct.unwrap_or(false) {
+        summary.push_str("**Modifier:** DISTINCT\n");
+    }
+    if let Some(where_cond) = args.where_clause {
+        summary.push_str(&format!("**Filter:** WHERE {}\n", where_cond));
+    }
+    for note in notes {
+        summary.push_str(&format!("⚠️ **Note:** {}\n", note));
+    }
+    summary.push_str(&format!("**Rows returned:** {}\n\n", rows.len()));
+    
+    let full = format!("{}{}", summary, format_result_block(columns, definitions, rows, args, usize::MAX));
+    let fitted = fit_or_attach(full, || {
+        // Whatever room the query summary leaves in the embed goes to a cut-down result block
+        let budget = EMBED_DESCRIPTION_LIMIT.saturating_sub(summary.chars().count() + ATTACHMENT_NOTE_RESERVE);
+        format!("{}{}", summary, format_result_block(columns, definitions, rows, args, budget))
+    }, &format!("select_{}.txt", args.from));
+    
+    let embed = create_info_embed("📊 SELECT Results", &fitted.description)
+        .footer(CreateEmbedFooter::new(stats.footer_text()));
+    (embed, fitted.attachment)
+}
+
+/// The rows in the requested format, within `budget` characters
+fn format_result_block(columns: &[String], definitions: &[Option<&ColumnDefinition>], rows: &[Vec<SqlValue>], args: &SelectArgs<'_>, budget: usize) -> String {
+    if rows.is_empty() {
+        return "*No rows found matching the criteria.*".to_string();
+    }
+    match args.format {
+        OutputFormat::Table => format_rows_as_table(columns, definitions, rows, display_row_limit(args.max_rows), budget),
+        OutputFormat::Json => {
+            let lines = rows.iter().map(|row| row_to_json(columns, row).to_string()).collect::<Vec<_>>();
+            format_export_block("json", "[", &lines, ",", "]", budget)
+        }
+        OutputFormat::Csv => {
+            let header = columns.iter().map(|col| csv_field(col)).collect::<Vec<_>>().join(",");
+            let lines = rows.iter().map(|row| row_to_csv(row)).collect::<Vec<_>>();
+            format_export_block("csv", &header, &lines, "", "", budget)
+        }
+    }
+}
+
+/// Render up to `limit` rows as a numbered text table in a code block of at most `budget` characters.
+/// Rows that don't fit are dropped from the end and reported as omitted.
+fn format_rows_as_table(columns: &[String], definitions: &[Option<&ColumnDefinition>], rows: &[Vec<SqlValue>], limit: usize, budget: usize) -> String {
+    let mut headers = vec!["Row".to_string()];
+    headers.extend(columns.iter().cloned());
+    let display_rows = rows.iter().take(limit).enumerate()
+        .map(|(row_idx, row)| table_cells(row_idx, row, definitions))
+        .collect::<Vec<_>>();
+    
+    for shown in (1..=display_rows.len()).rev() {
+        let output = table_block(&headers, &display_rows[..shown], rows.len(), shown < display_rows.len());
+        if output.chars().count() <= budget {
+            return output;
+        }
+    }
+    "*The result is too wide to display. Select fewer columns, or use `format:csv` or `to_thread:true`.*".to_string()
+}
+
+/// The cells of one table row: its number, then each value formatted for its column
+fn table_cells(row_idx: usize, row: &[SqlValue], definitions: &[Option<&ColumnDefinition>]) -> Vec<String> {
+    let mut cells = vec![(row_idx + 1).to_string()];
+    cells.extend(row.iter().enumerate().map(|(index, value)| format_sql_value_for_display_table(value, definitions.get(index).copied().flatten())));
+    cells
+}
+
+/// A rendered table block plus its omitted-rows and truncation notes
+fn table_block(headers: &[String], display_rows: &[Vec<String>], total_rows: usize, cut_to_fit: bool) -> String {
+    let mut output = String::new();
+    output.push_str("```\n");
+    output.push_str(&render_table(headers, display_rows));
+    if total_rows > display_rows.len() {
+        output.push_str(&format!("... and {} more rows\n", total_rows - display_rows.len()));
+    }
+    output.push_str("```");
+    
+    if cut_to_fit {
+        output.push_str(&format!("\n\n*Only {} rows fit in the embed. Select fewer columns or use `to_thread:true` to see the rest.*", display_rows.len()));
+    }
+    // If any values were truncated, add a note
+    if has_truncated_cells(display_rows) {
+        output.push_str("\n\n*Note: Some long values have been truncated for display. Use more specific column selection to see full values.*");
+    }
+    output
+}
+
+/// Wrap one line per row in a code block, keeping as many rows as fit the embed.
+/// `separator` ends every row line but the last; `open`/`close` are optional surrounding lines.
+fn format_export_block(language: &str, open: &str, lines: &[String], separator: &str, close: &str, budget: usize) -> String {
+    // Leave room for the fences, surrounding lines and the "Showing the first" note
+    let char_budget = EXPORT_CHAR_BUDGET.min(budget.saturating_sub(open.len() + close.len() + 120));
+    let mut body = String::new();
+    let mut shown = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let is_last = i + 1 == lines.len();
+        let entry = format!("{}{}\n", line, if is_last { "" } else { separator });
+        if shown > 0 && body.len() + entry.len() > char_budget {
+            break;
         }
+        body.push_str(&entry);
+        shown += 1;
+    }
+    // A cut-off JSON array still needs to parse, so drop the dangling separator
+    if shown < lines.len() && !separator.is_empty() {
+        body.truncate(body.trim_end().trim_end_matches(separator).len());
+        body.push('\n');
     }
     
-    create_info_embed("📊 SELECT Results", &description)
+    let mut output = format!("```{}\n", language);
+    if !open.is_empty() {
+        output.push_str(open);
+        output.push('\n');
+    }
+    output.push_str(&body);
+    if !close.is_empty() {
+        output.push_str(close);
+        output.push('\n');
+    }
+    output.push_str("```");
+    if shown < lines.len() {
+        output.push_str(&format!("\n*Showing the first {} of {} rows; narrow the query to see the rest.*", shown, lines.len()));
+    }
+    output
+}
+
+/// Every result row as output lines in the requested format, with the code block language
+fn full_result_lines(columns: &[String], definitions: &[Option<&ColumnDefinition>], rows: &[Vec<SqlValue>], format: OutputFormat) -> (&'static str, Vec<String>) {
+    match format {
+        OutputFormat::Table => {
+            let mut headers = vec!["Row".to_string()];
+            headers.extend(columns.iter().cloned());
+            let cells = rows.iter().enumerate()
+                .map(|(row_idx, row)| table_cells(row_idx, row, definitions))
+                .collect::<Vec<_>>();
+            ("", render_table(&headers, &cells).lines().map(str::to_string).collect())
+        }
+        OutputFormat::Json => {
+            let mut lines = vec!["[".to_string()];
+            for (i, row) in rows.iter().enumerate() {
+                let separator = if i + 1 < rows.len() { "," } else { "" };
+                lines.push(format!("  {}{}", row_to_json(columns, row), separator));
+            }
+            lines.push("]".to_string());
+            ("json", lines)
+        }
+        OutputFormat::Csv => {
+            let mut lines = vec![columns.iter().map(|col| csv_field(col)).collect::<Vec<_>>().join(",")];
+            lines.extend(rows.iter().map(|row| row_to_csv(row)));
+            ("csv", lines)
+        }
+    }
+}
+
+/// Convert a SQL value to JSON: NULL is null, numbers and booleans keep their JSON types
+fn sql_value_to_json(value: &SqlValue) -> serde_json::Value {
+    match value {
+        SqlValue::String(s) => serde_json::Value::String(s.clone()),
+        SqlValue::Integer(i) => serde_json::Value::from(*i),
+        // NaN and infinity have no JSON representation
+        SqlValue::Float(f) => serde_json::Number::from_f64(*f).map_or(serde_json::Value::Null, serde_json::Value::Number),
+        SqlValue::Boolean(b) => serde_json::Value::Bool(*b),
+        SqlValue::Null => serde_json::Value::Null,
+    }
+}
+
+/// One result row as a JSON object keyed by column header, in column order
+fn row_to_json(columns: &[String], row: &[SqlValue]) -> serde_json::Value {
+    let object = columns.iter()
+        .zip(row)
+        .map(|(column, value)| (column.clone(), sql_value_to_json(value)))
+        .collect::<serde_json::Map<_, _>>();
+    serde_json::Value::Object(object)
+}
+
+/// One result row as a CSV line; NULL is an empty field, and an empty string `""` so the two stay apart
+pub(crate) fn row_to_csv(row: &[SqlValue]) -> String {
+    row.iter()
+        .map(|value| match value {
+            SqlValue::String(s) if s.is_empty() => "\"\"".to_string(),
+            SqlValue::String(s) => csv_field(s),
+            SqlValue::Null => String::new(),
+            // Exports keep every digit, unlike the table
+            SqlValue::Float(f) => format_float(*f),
+            other => format_sql_value_for_display_table(other, None),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Quote a CSV field when it contains a comma, quote or line break
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
 }
 
-/// Format SQL value for table display (similar to comparison but optimized for tables)
-fn format_sql_value_for_display_table(value: &SqlValue) -> String {
+/// Format SQL value for table display (similar to comparison but optimized for tables);
+/// floats follow `column`'s DECIMAL scale or FLOAT precision
+fn format_sql_value_for_display_table(value: &SqlValue, column: Option<&ColumnDefinition>) -> String {
     match value {
         SqlValue::String(s) => format!("'{}'", s),
         SqlValue::Integer(i) => i.to_string(),
-        SqlValue::Float(f) => f.to_string(),
+        SqlValue::Float(f) => format_float_for_column(*f, column),
         SqlValue::Boolean(b) => b.to_string(),
         SqlValue::Null => "NULL".to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_schema() -> Vec<ColumnDefinition> {
+        parse_column_definitions("name VARCHAR(50), age INT").unwrap()
+    }
+
+    fn sample_row(name: &str, age: i64) -> Vec<SqlValue> {
+        vec![SqlValue::String(name.to_string()), SqlValue::Integer(age)]
+    }
+
+    #[test]
+    fn test_split_qualified_table() {
+        assert_eq!(split_qualified_table("sales.users", None), Ok((Some("sales"), "users")));
+        // The qualified database wins over the db option
+        assert_eq!(split_qualified_table("sales.users", Some("shop")), Ok((Some("sales"), "users")));
+        assert_eq!(split_qualified_table("users", Some("shop")), Ok((Some("shop"), "users")));
+        assert_eq!(split_qualified_table("users", None), Ok((None, "users")));
+        for malformed in [".users", "sales.", "a.b.c"] {
+            assert!(matches!(split_qualified_table(malformed, None), Err(SqlError::InvalidName { kind: "Table", .. })), "{malformed}");
+        }
+    }
+
+    #[test]
+    fn test_select_star_on_schemaless_table() {
+        let contents = [
+            "TIMESTAMP: 2025-08-19 12:34:56 UTC\nDATA:\n  column_1: 'Widget'\n  column_2: 10",
+            "TIMESTAMP: 2025-08-19 12:35:00 UTC\nDATA:\n  column_1: 'Gadget'",
+            "TIMESTAMP: 2025-08-19 12:36:00 UTC\nDATA:\n  column_1: 'Gizmo'\n  column_2: 5\n  column_3: true",
+            "not a row message",
+        ];
+        
+        let (schema, rows) = rows_from_messages(&contents, Vec::new());
+        let selected = parse_column_selection("*", &schema, schema.len()).unwrap();
+        
+        assert_eq!(column_headers(&selected), vec!["column_1", "column_2", "column_3"]);
+        assert_eq!(rows.len(), 3);
+        
+        let selected_rows: Vec<Vec<SqlValue>> = rows.iter().map(|row| select_columns(row, &schema, &selected)).collect();
+        assert_eq!(selected_rows[0], vec![SqlValue::String("Widget".to_string()), SqlValue::Integer(10), SqlValue::Null]);
+        // Sparse row is padded rather than dropped
+        assert_eq!(selected_rows[1], vec![SqlValue::String("Gadget".to_string()), SqlValue::Null, SqlValue::Null]);
+        assert_eq!(selected_rows[2], vec![SqlValue::String("Gizmo".to_string()), SqlValue::Integer(5), SqlValue::Boolean(true)]);
+        
+        // Inferred columns work in WHERE clauses too
+        assert!(evaluate_where_condition(&rows[2], &schema, "column_3=true"));
+    }
+    
+    #[test]
+    fn test_multiline_string_round_trips_through_storage() {
+        let schema = parse_column_definitions("id INT, notes VARCHAR(255)").unwrap();
+        let notes = "first line\nsecond line: with colon\n  indented third";
+        let values = vec![SqlValue::Integer(1), SqlValue::String(notes.to_string())];
+        
+        let message = crate::commands::sql::insert::format_sql_values_for_storage(&values, &schema);
+        let (schema, rows) = rows_from_messages(&[message.as_str()], schema);
+        
+        assert_eq!(rows.len(), 1);
+        let selected = parse_column_selection("notes", &schema, schema.len()).unwrap();
+        assert_eq!(select_columns(&rows[0], &schema, &selected), vec![SqlValue::String(notes.to_string())]);
+    }
+    
+    #[test]
+    fn test_quoted_column_names_round_trip_through_storage() {
+        let schema = parse_column_definitions("id INT, `order date` DATE, \"select\" VARCHAR(10)").unwrap();
+        let values = vec![SqlValue::Integer(1), SqlValue::String("2025-01-15".to_string()), SqlValue::String("yes".to_string())];
+        let message = crate::commands::sql::insert::format_sql_values_for_storage(&values, &schema);
+        let (schema, rows) = rows_from_messages(&[message.as_str()], schema);
+        assert_eq!(rows, vec![values]);
+
+        let selected = parse_column_selection("`order date`, \"select\"", &schema, schema.len()).unwrap();
+        assert_eq!(
+            select_columns(&rows[0], &schema, &selected),
+            vec![SqlValue::String("2025-01-15".to_string()), SqlValue::String("yes".to_string())]
+        );
+        assert!(evaluate_where_condition(&rows[0], &schema, "`order date`='2025-01-15' AND \"select\"='yes'"));
+    }
+
+    #[test]
+    fn test_table_output_truncates_emoji_cells() {
+        let columns = vec!["note".to_string()];
+        let rows = vec![vec![SqlValue::String(format!("a{}", "🦀".repeat(80)))]];
+        let output = format_rows_as_table(&columns, &[], &rows, 10, usize::MAX);
+        let cell_line = output.lines().nth(3).unwrap();
+        assert!(cell_line.ends_with("..."));
+        assert!(cell_line.contains("'a🦀🦀"));
+    }
+
+    #[test]
+    fn test_table_output_formats_numbers_by_column() {
+        let schema = parse_column_definitions("price DECIMAL(10,2), ratio FLOAT").unwrap();
+        let columns = vec!["price".to_string(), "ratio".to_string()];
+        let definitions: Vec<Option<&ColumnDefinition>> = schema.iter().map(Some).collect();
+        let rows = vec![vec![SqlValue::Float(3.5), SqlValue::Float(0.1 + 0.2)]];
+        let output = format_rows_as_table(&columns, &definitions, &rows, 10, usize::MAX);
+        let cell_line = output.lines().nth(3).unwrap();
+        assert!(cell_line.contains("| 3.50 ") && cell_line.trim_end().ends_with("| 0.3"), "{}", cell_line);
+
+        // Exports keep the stored value
+        assert_eq!(row_to_csv(&rows[0]), "3.5,0.30000000000000004");
+        let (_, lines) = full_result_lines(&columns, &definitions, &rows, OutputFormat::Json);
+        assert!(lines[1].contains("\"price\":3.5"));
+    }
+
+    #[test]
+    fn test_null_keyword_and_null_text_round_trip() {
+        let schema = parse_column_definitions("id INT, note VARCHAR(10)").unwrap();
+        let stored: Vec<String> = ["1, NULL", "2, 'NULL'", "3, 'null'"]
+            .iter()
+            .map(|data| crate::commands::sql::insert::format_sql_values_for_storage(&crate::sql_parser::parse_sql_values(data).unwrap(), &schema))
+            .collect();
+        let contents: Vec<&str> = stored.iter().map(String::as_str).collect();
+        let (schema, rows) = rows_from_messages(&contents, schema);
+
+        let notes: Vec<&SqlValue> = rows.iter().map(|row| &row[1]).collect();
+        assert_eq!(notes, [&SqlValue::Null, &SqlValue::String("NULL".to_string()), &SqlValue::String("null".to_string())]);
+
+        let matching = |condition: &str| -> Vec<&SqlValue> {
+            rows.iter().filter(|row| evaluate_where_condition(row, &schema, condition)).map(|row| &row[0]).collect()
+        };
+        assert_eq!(matching("note=NULL"), [&SqlValue::Integer(1)]);
+        assert_eq!(matching("note=null"), [&SqlValue::Integer(1)]);
+        assert_eq!(matching("note='NULL'"), [&SqlValue::Integer(2)]);
+        assert_eq!(matching("note='null'"), [&SqlValue::Integer(3)]);
+    }
+
+    #[test]
+    fn test_query_stats_footer() {
+        let stats = QueryStats { elapsed: Duration::from_millis(42), messages_scanned: 250, scan_limit_hit: None };
+        assert_eq!(stats.footer_text(), "⏱️ 42 ms • 250 messages scanned");
+        
+        let stats = QueryStats { elapsed: Duration::from_millis(7), messages_scanned: 1000, scan_limit_hit: Some(1000) };
+        assert!(stats.footer_text().ends_with("Scan limit reached: only the newest 1000 rows were read"));
+    }
+    
+    #[test]
+    fn test_duplicate_columns_reported_and_removed() {
+        let schema = sample_schema();
+        let selected = parse_column_selection("name, age, NAME, name", &schema, schema.len()).unwrap();
+        
+        let (kept, duplicates) = dedupe_selected_columns(selected);
+        assert_eq!(column_headers(&kept), vec!["name", "age"]);
+        assert_eq!(duplicates, vec!["name"]);
+        
+        // The same column under a different alias is intentional
+        let selected = parse_column_selection("name, name AS full_name", &schema, schema.len()).unwrap();
+        let (kept, duplicates) = dedupe_selected_columns(selected);
+        assert_eq!(column_headers(&kept), vec!["name", "full_name"]);
+        assert!(duplicates.is_empty());
+    }
+    
+    #[test]
+    fn test_select_star_except() {
+        let schema = parse_column_definitions("id INT, name VARCHAR(50), password VARCHAR(50), secret VARCHAR(50)").unwrap();
+        
+        let selected = parse_column_selection("* EXCEPT (password)", &schema, schema.len()).unwrap();
+        assert_eq!(column_headers(&selected), vec!["id", "name", "secret"]);
+        
+        let selected = parse_column_selection("* except (Password, secret)", &schema, schema.len()).unwrap();
+        assert_eq!(column_headers(&selected), vec!["id", "name"]);
+    }
+    
+    #[test]
+    fn test_select_star_except_errors() {
+        let schema = parse_column_definitions("id INT, name VARCHAR(50)").unwrap();
+        
+        assert!(matches!(
+            parse_column_selection("* EXCEPT (missing)", &schema, schema.len()),
+            Err(SqlError::UnknownColumn { clause: Some("EXCEPT"), .. })
+        ));
+        assert!(matches!(parse_column_selection("* EXCEPT name", &schema, schema.len()), Err(SqlError::InvalidColumnSelection(_))));
+        assert!(matches!(parse_column_selection("* EXCEPT (id, name)", &schema, schema.len()), Err(SqlError::InvalidColumnSelection(_))));
+    }
+    
+    #[test]
+    fn test_schemaless_columns_sort_numerically() {
+        let contents = ["DATA:\n  column_10: 1\n  column_2: 2\n  column_1: 3"];
+        let (schema, _) = rows_from_messages(&contents, Vec::new());
+        let names: Vec<&str> = schema.iter().map(|col| col.name.as_str()).collect();
+        assert_eq!(names, vec!["column_1", "column_2", "column_10"]);
+    }
+
+    #[test]
+    fn test_where_not_single_condition() {
+        let schema = sample_schema();
+        let john = sample_row("John", 25);
+        let jane = sample_row("Jane", 30);
+
+        assert!(!evaluate_where_condition(&john, &schema, "NOT name='John'"));
+        assert!(evaluate_where_condition(&jane, &schema, "NOT name='John'"));
+        assert!(evaluate_where_condition(&jane, &schema, "not name='John'"));
+    }
+
+    #[test]
+    fn test_where_not_group() {
+        let schema = sample_schema();
+
+        assert!(!evaluate_where_condition(&sample_row("John", 25), &schema, "NOT (age=25 OR age=30)"));
+        assert!(!evaluate_where_condition(&sample_row("Jane", 30), &schema, "NOT (age=25 OR age=30)"));
+        assert!(evaluate_where_condition(&sample_row("Bob", 40), &schema, "NOT(age=25 OR age=30)"));
+    }
+
+    #[test]
+    fn test_where_not_precedence_and_double_negation() {
+        let schema = sample_schema();
+        let john = sample_row("John", 25);
+
+        // NOT applies only to the first condition, not the whole AND
+        assert!(evaluate_where_condition(&john, &schema, "NOT name='Jane' AND age=25"));
+        assert!(!evaluate_where_condition(&john, &schema, "NOT name='John' OR age=30"));
+
+        // Double negation cancels out
+        assert!(evaluate_where_condition(&john, &schema, "NOT NOT name='John'"));
+        assert!(!evaluate_where_condition(&john, &schema, "NOT (NOT age=30)"));
+    }
+
+    #[test]
+    fn test_column_aliases_in_headers() {
+        let schema = sample_schema();
+        let selected = parse_column_selection("name AS full_name, age as years", &schema, schema.len()).unwrap();
+
+        assert_eq!(column_headers(&selected), vec!["full_name", "years"]);
+        assert_eq!(selected[0].name, "name");
+        assert_eq!(selected[1].name, "age");
+
+        // Data is still pulled from the underlying schema columns
+        let row = sample_row("John", 25);
+        let picked = select_columns(&row, &schema, &selected);
+        assert!(matches!(picked[0], SqlValue::String(ref s) if s == "John"));
+        assert!(matches!(picked[1], SqlValue::Integer(25)));
+    }
+
+    #[test]
+    fn test_computed_columns() {
+        let schema = parse_column_definitions("item VARCHAR(20), price DECIMAL(10,2), quantity INT").unwrap();
+        let selected = parse_column_selection("item, price*quantity AS total, quantity + 1 as next, price / (quantity - 3)", &schema, schema.len()).unwrap();
+        assert_eq!(column_headers(&selected), vec!["item", "total", "next", "price / (quantity - 3)"]);
+
+        let row = vec![SqlValue::String("pen".into()), SqlValue::Float(1.5), SqlValue::Integer(3)];
+        let picked = select_columns(&row, &schema, &selected);
+        assert_eq!(picked, vec![SqlValue::String("pen".into()), SqlValue::Float(4.5), SqlValue::Integer(4), SqlValue::Null]);
+
+        // Arithmetic on text, or on columns the table lacks, is rejected up front
+        assert!(parse_column_selection("item * 2 AS twice", &schema, schema.len()).is_err());
+        assert!(parse_column_selection("cost * 2", &schema, schema.len()).is_err());
+        assert_eq!(split_trailing_alias("`a as b` + 1"), ("`a as b` + 1", None));
+        assert_eq!(split_trailing_alias("price * 2 AS \"double price\""), ("price * 2", Some("double price".to_string())));
+    }
+
+    #[test]
+    fn test_distinct_compares_values_not_representations() {
+        let rows = vec![
+            vec![SqlValue::Integer(1), SqlValue::String("a".into())],
+            vec![SqlValue::Float(1.0), SqlValue::String("a".into())],
+            vec![SqlValue::Float(0.1 + 0.2), SqlValue::Null],
+            vec![SqlValue::Float(0.3), SqlValue::Null],
+            vec![SqlValue::Float(-0.0), SqlValue::Boolean(true)],
+            vec![SqlValue::Integer(0), SqlValue::Boolean(true)],
+            vec![SqlValue::Float(1.5), SqlValue::String("a".into())],
+            vec![SqlValue::String("1".into()), SqlValue::String("a".into())],
+        ];
+        let distinct = apply_distinct(rows);
+        // The first of each equal group is kept, in order
+        assert_eq!(distinct, vec![
+            vec![SqlValue::Integer(1), SqlValue::String("a".into())],
+            vec![SqlValue::Float(0.1 + 0.2), SqlValue::Null],
+            vec![SqlValue::Float(-0.0), SqlValue::Boolean(true)],
+            vec![SqlValue::Float(1.5), SqlValue::String("a".into())],
+            vec![SqlValue::String("1".into()), SqlValue::String("a".into())],
+        ]);
+    }
+
+    #[test]
+    fn test_string_function_columns() {
+        let schema = parse_column_definitions("first_name VARCHAR(20), last_name VARCHAR(20), age INT").unwrap();
+        let selected = parse_column_selection("CONCAT(first_name, ' ', last_name) AS name, UPPER(last_name), LENGTH(first_name) AS len, age", &schema, schema.len()).unwrap();
+        assert_eq!(column_headers(&selected), vec!["name", "UPPER(last_name)", "len", "age"]);
+
+        let row = vec![SqlValue::String("Ann".into()), SqlValue::String("Lee".into()), SqlValue::Integer(31)];
+        let picked = select_columns(&row, &schema, &selected);
+        assert_eq!(picked, vec![SqlValue::String("Ann Lee".into()), SqlValue::String("LEE".into()), SqlValue::Integer(3), SqlValue::Integer(31)]);
+
+        // Commas and `AS` inside strings belong to the string
+        let selected = parse_column_selection("CONCAT(last_name, ', as ', first_name)", &schema, schema.len()).unwrap();
+        assert_eq!(select_columns(&row, &schema, &selected), vec![SqlValue::String("Lee, as Ann".into())]);
+        assert!(parse_column_selection("UPPER(age)", &schema, schema.len()).is_err());
+    }
+
+    #[test]
+    fn test_mixed_case_column_references() {
+        let schema = sample_schema();
+        let selected = parse_column_selection("Name, AGE AS Years", &schema, schema.len()).unwrap();
+
+        // Stored casing is preserved for display, aliases are kept as typed
+        assert_eq!(column_headers(&selected), vec!["name", "Years"]);
+
+        let row = sample_row("John", 25);
+        let picked = select_columns(&row, &schema, &selected);
+        assert!(matches!(picked[0], SqlValue::String(ref s) if s == "John"));
+        assert!(matches!(picked[1], SqlValue::Integer(25)));
+
+        assert!(evaluate_where_condition(&row, &schema, "NAME='John' AND Age=25"));
+        assert!(!evaluate_where_condition(&row, &schema, "nAmE='Jane'"));
+    }
+
+    #[test]
+    fn test_column_alias_requires_existing_column() {
+        let schema = sample_schema();
+        assert!(parse_column_selection("missing AS m", &schema, schema.len()).is_err());
+
+        let selected = parse_column_selection("age, name", &schema, schema.len()).unwrap();
+        assert_eq!(column_headers(&selected), vec!["age", "name"]);
+    }
+
+    #[test]
+    fn test_quoted_column_identifiers() {
+        let contents = ["DATA:\n  first name: 'Ann'\n  a=b: 1"];
+        let (schema, rows) = rows_from_messages(&contents, Vec::new());
+
+        let selected = parse_column_selection("`first name` AS name, \"a=b\"", &schema, schema.len()).unwrap();
+        assert_eq!(column_headers(&selected), vec!["name", "a=b"]);
+        assert_eq!(selected[0].name, "first name");
+        assert_eq!(select_columns(&rows[0], &schema, &selected), vec![SqlValue::String("Ann".to_string()), SqlValue::Integer(1)]);
+
+        assert!(evaluate_where_condition(&rows[0], &schema, "`first name`='Ann'"));
+        assert!(evaluate_where_condition(&rows[0], &schema, "\"a=b\" = 1 AND NOT `First Name`='Bob'"));
+        assert!(!evaluate_where_condition(&rows[0], &schema, "`a=b`=2"));
+
+        let selected = parse_column_selection("* EXCEPT (`first name`)", &schema, schema.len()).unwrap();
+        assert_eq!(column_headers(&selected), vec!["a=b"]);
+    }
+
+    #[test]
+    fn test_sql_value_to_json() {
+        assert_eq!(sql_value_to_json(&SqlValue::String("Ann".to_string())), serde_json::json!("Ann"));
+        assert_eq!(sql_value_to_json(&SqlValue::Integer(-42)), serde_json::json!(-42));
+        assert_eq!(sql_value_to_json(&SqlValue::Float(3.5)), serde_json::json!(3.5));
+        assert_eq!(sql_value_to_json(&SqlValue::Float(f64::NAN)), serde_json::Value::Null);
+        assert_eq!(sql_value_to_json(&SqlValue::Boolean(true)), serde_json::json!(true));
+        assert_eq!(sql_value_to_json(&SqlValue::Null), serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_row_to_json_keeps_column_order() {
+        let columns = vec!["name".to_string(), "age".to_string(), "email".to_string()];
+        let row = vec![SqlValue::String("Ann".to_string()), SqlValue::Integer(30), SqlValue::Null];
+        assert_eq!(row_to_json(&columns, &row).to_string(), r#"{"name":"Ann","age":30,"email":null}"#);
+    }
+
+    #[test]
+    fn test_row_to_csv() {
+        let row = vec![
+            SqlValue::String("Doe, \"JD\" John".to_string()),
+            SqlValue::Float(1.5),
+            SqlValue::Boolean(false),
+            SqlValue::Null,
+        ];
+        assert_eq!(row_to_csv(&row), r#""Doe, ""JD"" John",1.5,false,"#);
+        assert_eq!(row_to_csv(&[SqlValue::String(String::new()), SqlValue::Null]), r#""","#);
+    }
+
+    #[test]
+    fn test_export_block_stays_valid_when_cut() {
+        let lines: Vec<String> = (0..1000).map(|i| format!("{{\"id\":{}}}", i)).collect();
+        let block = format_export_block("json", "[", &lines, ",", "]", EMBED_DESCRIPTION_LIMIT);
+        assert!(block.contains("*Showing the first"));
+
+        let json = block.trim_start_matches("```json\n").split("```").next().unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert!(parsed.as_array().unwrap().len() < lines.len());
+
+        let full = format_export_block("csv", "id", &["1".to_string(), "2".to_string()], "", "", EMBED_DESCRIPTION_LIMIT);
+        assert_eq!(full, "```csv\nid\n1\n2\n```");
+    }
+
+    #[test]
+    fn test_output_format_from_option() {
+        assert_eq!(OutputFormat::from_option(None), OutputFormat::Table);
+        assert_eq!(OutputFormat::from_option(Some("JSON")), OutputFormat::Json);
+        assert_eq!(OutputFormat::from_option(Some("csv")), OutputFormat::Csv);
+        assert_eq!(OutputFormat::from_option(Some("xml")), OutputFormat::Table);
+    }
+
+    #[test]
+    fn test_full_result_lines_cover_every_row() {
+        let columns = vec!["name".to_string(), "age".to_string()];
+        let rows: Vec<Vec<SqlValue>> = (0..50).map(|i| sample_row(&format!("user{}", i), i)).collect();
+
+        let (language, lines) = full_result_lines(&columns, &[], &rows, OutputFormat::Json);
+        assert_eq!(language, "json");
+        let parsed: serde_json::Value = serde_json::from_str(&lines.join("\n")).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 50);
+
+        let (_, lines) = full_result_lines(&columns, &[], &rows, OutputFormat::Table);
+        // Header, separator and one line per row
+        assert_eq!(lines.len(), 52);
+        assert!(lines[51].starts_with("50"));
+    }
+
+    #[test]
+    fn test_where_numeric_comparison() {
+        let schema = parse_column_definitions("count INT, ratio FLOAT, label VARCHAR(10)").unwrap();
+        let row = vec![SqlValue::Integer(1000), SqlValue::Float(-0.5), SqlValue::String("1e3".to_string())];
+
+        // Scientific notation and quoting on the typed side
+        assert!(evaluate_where_condition(&row, &schema, "count=1e3"));
+        assert!(evaluate_where_condition(&row, &schema, "count='1e3'"));
+        assert!(evaluate_where_condition(&row, &schema, "count=1000.0"));
+        assert!(!evaluate_where_condition(&row, &schema, "count=1e2"));
+
+        // Negative numbers
+        assert!(evaluate_where_condition(&row, &schema, "ratio=-5e-1"));
+        assert!(evaluate_where_condition(&row, &schema, "ratio='-0.50'"));
+        assert!(!evaluate_where_condition(&row, &schema, "ratio=0.5"));
+
+        let negative = vec![SqlValue::Integer(-5), SqlValue::Float(-2e3), SqlValue::Null];
+        assert!(evaluate_where_condition(&negative, &schema, "count=-5"));
+        assert!(evaluate_where_condition(&negative, &schema, "ratio=-2000"));
+
+        // Text columns still compare as text
+        assert!(evaluate_where_condition(&row, &schema, "label='1e3'"));
+        assert!(!evaluate_where_condition(&row, &schema, "label=1000"));
+        assert!(!evaluate_where_condition(&negative, &schema, "label=0"));
+    }
+
+    #[test]
+    fn test_parse_numeric_literal() {
+        assert_eq!(parse_numeric_literal("-5"), Some(NumericLiteral::Integer(-5)));
+        assert_eq!(parse_numeric_literal("'1e3'"), Some(NumericLiteral::Float(1000.0)));
+        assert_eq!(parse_numeric_literal("\"2.5\""), Some(NumericLiteral::Float(2.5)));
+        assert_eq!(parse_numeric_literal("inf"), None);
+        assert_eq!(parse_numeric_literal("'abc'"), None);
+    }
+
+    #[test]
+    fn test_display_row_limit_is_clamped() {
+        assert_eq!(display_row_limit(Some(5)), 5);
+        assert_eq!(display_row_limit(Some(0)), 1);
+        assert_eq!(display_row_limit(Some(10_000)), MAX_DISPLAY_ROWS);
+    }
+
+    #[test]
+    fn test_large_result_fits_embed_limit() {
+        let columns: Vec<String> = (0..12).map(|i| format!("column_{}", i)).collect();
+        let rows: Vec<Vec<SqlValue>> = (0..MAX_DISPLAY_ROWS)
+            .map(|_| (0..12).map(|_| SqlValue::String("w".repeat(60))).collect())
+            .collect();
+        let args = SelectArgs {
+            columns: "*",
+            from: "wide",
+            distinct: None,
+            where_clause: None,
+            ephemeral: false,
+            format: OutputFormat::Table,
+            to_thread: false,
+            max_rows: Some(MAX_DISPLAY_ROWS),
+            db: None,
+        };
+        let stats = QueryStats { elapsed: Duration::from_millis(1), messages_scanned: rows.len(), scan_limit_hit: None };
+
+        let (embed, attachment) = format_select_results(&columns, &[], &rows, &args, &stats, &[]);
+        let embed = serde_json::to_value(embed).unwrap();
+        let description = embed["description"].as_str().unwrap();
+        assert!(description.chars().count() <= EMBED_DESCRIPTION_LIMIT);
+        assert!(description.contains("more rows"));
+        assert!(description.contains("fit in the embed"));
+
+        // The oversized table goes out in full as a text file
+        let attachment = attachment.expect("oversized result is attached");
+        assert_eq!(attachment.filename, "select_wide.txt");
+        let full = String::from_utf8(attachment.data).unwrap();
+        assert!(full.matches('\n').count() > MAX_DISPLAY_ROWS);
+        assert!(!full.contains("fit in the embed"));
+
+        // Too many columns for even one row: explain instead of overflowing
+        let block = format_rows_as_table(&columns, &[], &rows, 1, 200);
+        assert!(block.contains("too wide to display"));
+    }
+
+    #[test]
+    fn test_where_compares_datetimes_chronologically() {
+        let schema = parse_column_definitions("created_at DATETIME, note VARCHAR(40)").unwrap();
+        let row = vec![
+            SqlValue::String("2025-01-15T14:30:00Z".to_string()),
+            SqlValue::String("2025-01-15T14:30:00Z".to_string()),
+        ];
+
+        assert!(evaluate_where_condition(&row, &schema, "created_at='2025-01-15T16:30:00+02:00'"));
+        assert!(evaluate_where_condition(&row, &schema, "created_at='2025-01-15T09:30:00-05:00'"));
+        assert!(!evaluate_where_condition(&row, &schema, "created_at='2025-01-15T14:30:00+02:00'"));
+        // Text columns keep exact string matching
+        assert!(!evaluate_where_condition(&row, &schema, "note='2025-01-15T16:30:00+02:00'"));
+        assert!(evaluate_where_condition(&row, &schema, "note='2025-01-15T14:30:00Z'"));
+    }
+
+    #[test]
+    fn test_where_operators_ignore_case_and_spacing() {
+        let schema = sample_schema();
+        let john = sample_row("John", 25);
+
+        assert!(evaluate_where_condition(&john, &schema, "name='John' and age=25"));
+        assert!(!evaluate_where_condition(&john, &schema, "name='John' and age=30"));
+        assert!(evaluate_where_condition(&john, &schema, "name='Jane' or age=25"));
+        assert!(evaluate_where_condition(&john, &schema, "name='John'  AND\tage=25"));
+        assert!(evaluate_where_condition(&john, &schema, "(name='Jane'   Or name='John')   aNd age=25"));
+    }
+
+    #[test]
+    fn test_split_by_operator_skips_words_and_quotes() {
+        assert_eq!(split_by_operator("brand='brand' AND x=1", "AND"), vec!["brand='brand' ", " x=1"]);
+        assert_eq!(split_by_operator("name='salt AND pepper'", "AND"), vec!["name='salt AND pepper'"]);
+        assert_eq!(split_by_operator("orders=1 OR ranking=2", "OR"), vec!["orders=1 ", " ranking=2"]);
+        assert_eq!(split_by_operator("(a=1 OR b=2) OR c=3", "OR"), vec!["(a=1 OR b=2) ", " c=3"]);
+
+        let schema = parse_column_definitions("brand VARCHAR(20), qty INT").unwrap();
+        let row = vec![SqlValue::String("brand".to_string()), SqlValue::Integer(3)];
+        assert!(evaluate_where_condition(&row, &schema, "brand='brand' AND qty=3"));
+    }
+
+    #[test]
+    fn test_where_unquoted_literals_are_typed() {
+        let schema = parse_column_definitions("price INT, active BOOLEAN, name VARCHAR(30)").unwrap();
+        let row = vec![SqlValue::Integer(100), SqlValue::Boolean(true), SqlValue::String("100".to_string())];
+
+        // Unquoted ints and booleans compare as values
+        assert!(evaluate_where_condition(&row, &schema, "price=100"));
+        assert!(!evaluate_where_condition(&row, &schema, "price=101"));
+        assert!(evaluate_where_condition(&row, &schema, "active=true"));
+        assert!(evaluate_where_condition(&row, &schema, "active=TRUE AND price=100.0"));
+        assert!(!evaluate_where_condition(&row, &schema, "active=false"));
+        assert!(evaluate_where_condition(&row, &schema, "active<>False"));
+        // A quoted `'true'` is text, not a boolean
+        assert!(!evaluate_where_condition(&row, &schema, "active='true'"));
+
+        // Quoted literals are strings, with either quote; numbers don't equal text
+        assert!(evaluate_where_condition(&row, &schema, "name='100'"));
+        assert!(evaluate_where_condition(&row, &schema, "name=\"100\""));
+        assert!(!evaluate_where_condition(&row, &schema, "name=100"));
+        let quoted = vec![SqlValue::Null, SqlValue::Null, SqlValue::String("It's".to_string())];
+        assert!(evaluate_where_condition(&quoted, &schema, "name='It''s'"));
+        assert!(evaluate_where_condition(&quoted, &schema, "active=null AND price=NULL"));
+
+        assert_eq!(parse_where_literal("42"), Some(SqlValue::Integer(42)));
+        assert_eq!(parse_where_literal("'42'"), Some(SqlValue::String("42".to_string())));
+        assert_eq!(parse_where_literal("fAlSe"), Some(SqlValue::Boolean(false)));
+        assert_eq!(parse_where_literal("Ann"), None);
+    }
+
+    #[test]
+    fn test_where_quoted_values_are_literal() {
+        let schema = parse_column_definitions("name VARCHAR(30), note VARCHAR(30), age INT").unwrap();
+        let row = vec![
+            SqlValue::String("A and B".to_string()),
+            SqlValue::String("x=y".to_string()),
+            SqlValue::Integer(25),
+        ];
+
+        // AND/OR inside a value
+        assert!(evaluate_where_condition(&row, &schema, "name='A and B'"));
+        assert!(evaluate_where_condition(&row, &schema, "name='A and B' AND age=25"));
+        assert!(!evaluate_where_condition(&row, &schema, "name='A or B'"));
+
+        // `=` inside a value
+        assert_eq!(split_condition("note='x=y'"), Some(("note", Comparison::Eq, "'x=y'")));
+        assert!(evaluate_where_condition(&row, &schema, "note='x=y' AND name='A and B'"));
+
+        // Parentheses inside a value
+        let parens = vec![SqlValue::String("(draft)".to_string()), SqlValue::String("a)".to_string()), SqlValue::Integer(1)];
+        assert!(evaluate_where_condition(&parens, &schema, "name='(draft)'"));
+        assert!(evaluate_where_condition(&parens, &schema, "note='a)' OR age=2"));
+        assert!(evaluate_where_condition(&parens, &schema, "(note='a)' AND name='(draft)')"));
+        assert!(evaluate_where_condition(&parens, &schema, "(age=1) AND (note='a)')"));
+    }
+
+    #[test]
+    fn test_where_columns_are_checked_against_schema() {
+        let schema = sample_schema();
+        assert_eq!(where_columns("(name='A=B' OR NOT age=3) AND `name`=1"), vec!["name", "age", "name"]);
+        assert_eq!(check_where_columns("NAME='Ann' AND (age=30 OR name='Bo')", &schema), Ok(()));
+
+        let err = check_where_columns("nmae='Ann' OR (age=30 AND nmae='Bo')", &schema).unwrap_err();
+        assert_eq!(err, SqlError::UnknownWhereColumn {
+            columns: vec!["nmae".to_string()],
+            available: vec!["name".to_string(), "age".to_string()],
+        });
+
+        // Schemaless tables with no rows have nothing to check against
+        assert_eq!(check_where_columns("anything=1", &[]), Ok(()));
+    }
+
+    #[test]
+    fn test_where_comparison_operators() {
+        let schema = sample_schema();
+        let john = sample_row("John", 25);
+
+        assert!(evaluate_where_condition(&john, &schema, "age>20"));
+        assert!(evaluate_where_condition(&john, &schema, "age >= 25 AND age <= 25"));
+        assert!(!evaluate_where_condition(&john, &schema, "age<25"));
+        assert!(evaluate_where_condition(&john, &schema, "age<>30"));
+        assert!(!evaluate_where_condition(&john, &schema, "name!='John'"));
+        assert!(evaluate_where_condition(&john, &schema, "name<'Kate'"));
+        assert_eq!(split_condition("note>='a<b'"), Some(("note", Comparison::Ge, "'a<b'")));
+
+        // NULL neither orders nor differs
+        let unknown = vec![SqlValue::String("Ann".to_string()), SqlValue::Null];
+        assert!(!evaluate_where_condition(&unknown, &schema, "age>0"));
+        assert!(!evaluate_where_condition(&unknown, &schema, "age!=0"));
+    }
+
+    #[test]
+    fn test_created_at_column() {
+        let content = "TIMESTAMP: 2024-03-05 10:20:30 UTC\nDATA:\n  name: 'Ann'";
+        assert_eq!(created_at_value(content, 0), SqlValue::String("2024-03-05T10:20:30Z".to_string()));
+        // Rows without a TIMESTAMP line fall back to the message's own timestamp
+        assert_eq!(created_at_value("DATA:\n  name: 'Ann'", 86_400), SqlValue::String("1970-01-02T00:00:00Z".to_string()));
+
+        let mut schema = sample_schema();
+        schema.push(ImplicitColumn::CreatedAt.definition());
+        let row = vec![SqlValue::String("Ann".to_string()), SqlValue::Integer(30), created_at_value(content, 0)];
+        assert!(evaluate_where_condition(&row, &schema, "_created_at>'2024-01-01 00:00:00'"));
+        assert!(!evaluate_where_condition(&row, &schema, "_created_at<'2024-03-05T10:20:30Z'"));
+        assert_eq!(check_where_columns("_created_at>='2024-01-01'", &schema), Ok(()));
+
+        // Only an explicit selection shows the implicit column
+        let names = |selected: Vec<SelectedColumn>| selected.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(parse_column_selection("*", &schema, 2).unwrap()), vec!["name", "age"]);
+        assert_eq!(names(parse_column_selection("name, _created_at", &schema, 2).unwrap()), vec!["name", "_created_at"]);
+    }
+
+    #[test]
+    fn test_rowid_column() {
+        let mut schema = sample_schema();
+        assert_eq!(implicit_columns(&schema), vec![ImplicitColumn::RowId, ImplicitColumn::CreatedAt]);
+        schema.extend(implicit_columns(&schema).iter().map(|column| column.definition()));
+        let row = vec![
+            SqlValue::String("Ann".to_string()),
+            SqlValue::Integer(30),
+            SqlValue::Integer(1234567890123456789),
+            SqlValue::String("2024-03-05T10:20:30Z".to_string()),
+        ];
+
+        // Quoted or not, the id matches exactly
+        assert!(evaluate_where_condition(&row, &schema, "_rowid='1234567890123456789'"));
+        assert!(evaluate_where_condition(&row, &schema, "_rowid=1234567890123456789"));
+        assert!(!evaluate_where_condition(&row, &schema, "_rowid='1234567890123456788'"));
+
+        let selected = parse_column_selection("_rowid, name", &schema, 2).unwrap();
+        assert_eq!(select_columns(&row, &schema, &selected), vec![SqlValue::Integer(1234567890123456789), SqlValue::String("Ann".to_string())]);
+
+        // A real column of the same name wins
+        let shadowed = parse_column_definitions("_rowid VARCHAR(20), name VARCHAR(20)").unwrap();
+        assert_eq!(implicit_columns(&shadowed), vec![ImplicitColumn::CreatedAt]);
+    }
+
+    #[test]
+    fn test_where_char_ignores_padding() {
+        let schema = parse_column_definitions("code CHAR(5), name VARCHAR(5)").unwrap();
+        let row = vec![SqlValue::String("AB   ".to_string()), SqlValue::String("AB ".to_string())];
+
+        assert!(evaluate_where_condition(&row, &schema, "code='AB'"));
+        assert!(evaluate_where_condition(&row, &schema, "code='AB  '"));
+        assert!(!evaluate_where_condition(&row, &schema, "code!='AB'"));
+        assert!(!evaluate_where_condition(&row, &schema, "code='A'"));
+        // VARCHAR keeps its spaces
+        assert!(!evaluate_where_condition(&row, &schema, "name='AB'"));
+        assert_eq!(trim_char_literal("\"x \""), "\"x\"");
+    }
+
+    #[test]
+    fn test_is_wrapped_in_parens() {
+        assert!(is_wrapped_in_parens("(a=1 OR b=2)"));
+        assert!(is_wrapped_in_parens("((a=1) AND b=')')"));
+        assert!(!is_wrapped_in_parens("(a=1) AND (b=2)"));
+        assert!(!is_wrapped_in_parens("(a=')'"));
+    }
+}
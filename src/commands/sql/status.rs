@@ -0,0 +1,107 @@
+// /sql status
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Duration;
+use serenity::builder::CreateEmbed;
+use serenity::prelude::Context;
+use serenity::model::channel::GuildChannel;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use crate::state::{BotStartTime, CurrentDB};
+use crate::logging::{log_debug, log_info};
+use crate::services::{get_channels_cached, list_databases, list_tables};
+use crate::error::SqlError;
+use crate::utils::{DB_PREFIX, create_info_embed};
+
+pub fn register() -> Result<(), Box<dyn Error>> {
+    log_debug("Registering STATUS command");
+    Ok(())
+}
+
+/// Report the bot version, uptime, the guild's database/table counts and the user's selected database
+pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId) -> Result<CreateEmbed, SqlError> {
+    log_info(&format!("STATUS command executed by user: {}", user_id));
+    
+    let channels = match get_channels_cached(ctx, guild_id).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            tracing::error!("Failed to get channels: {e}");
+            return Err(SqlError::DiscordRequest { operation: "list channels" });
+        }
+    };
+    let (databases, tables) = count_databases_and_tables(&channels);
+    
+    let (started_at, selections) = {
+        let data = ctx.data.read().await;
+        (data.get::<BotStartTime>().copied(), data.get::<CurrentDB>().cloned())
+    };
+    let uptime = started_at.map_or_else(|| "unknown".to_string(), |start| format_uptime(start.elapsed()));
+    let selected = match selections {
+        Some(map) => map.lock().await.get(&(guild_id, user_id)).cloned(),
+        None => None,
+    };
+    
+    let embed = create_info_embed("📊 SQLcord Status", "Bot health and storage summary for this server")
+        .field("Version", format!("`{}`", env!("CARGO_PKG_VERSION")), true)
+        .field("Uptime", uptime, true)
+        .field("\u{200b}", "\u{200b}", true)
+        .field("Databases", databases.to_string(), true)
+        .field("Tables", tables.to_string(), true)
+        .field("Selected Database", selected.map_or_else(|| "*None* (use `/sql use`)".to_string(), |db| format!("**{}{}**", DB_PREFIX, db)), true);
+    Ok(embed)
+}
+
+/// Count `db_` categories and the table channels inside them
+fn count_databases_and_tables(channels: &HashMap<ChannelId, GuildChannel>) -> (usize, usize) {
+    let categories = list_databases(channels);
+    let tables = categories.iter()
+        .map(|category| list_tables(channels, category.id).len())
+        .sum();
+    (categories.len(), tables)
+}
+
+/// Human-readable uptime, e.g. `2d 3h 4m` or `42s`
+fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::discord_fs::test_channel;
+
+    #[test]
+    fn test_count_databases_and_tables() {
+        let channels: HashMap<ChannelId, GuildChannel> = [
+            test_channel(10, "db_shop", 4, None),
+            test_channel(11, "table_users", 0, Some(10)),
+            test_channel(12, "table_orders", 0, Some(10)),
+            test_channel(20, "db_blog", 4, None),
+            test_channel(30, "general", 4, None),
+            test_channel(31, "table_stray", 0, Some(30)),
+        ]
+        .into_iter()
+        .map(|c| (c.id, c))
+        .collect();
+
+        assert_eq!(count_databases_and_tables(&channels), (2, 2));
+    }
+
+    #[test]
+    fn test_format_uptime() {
+        assert_eq!(format_uptime(Duration::from_secs(42)), "42s");
+        assert_eq!(format_uptime(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_uptime(Duration::from_secs(3 * 3_600 + 60)), "3h 1m");
+        assert_eq!(format_uptime(Duration::from_secs(2 * 86_400 + 3_600 + 240)), "2d 1h 4m");
+    }
+}
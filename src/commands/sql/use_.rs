@@ -1,75 +1,103 @@
 // /sql use <name>
 
+use std::collections::HashMap;
 use std::error::Error;
+use serenity::builder::CreateEmbed;
 use serenity::prelude::Context;
-use serenity::model::id::{GuildId, UserId};
-use serenity::model::channel::ChannelType;
+use serenity::model::channel::GuildChannel;
+use serenity::model::id::{ChannelId, GuildId, UserId};
 use crate::state::CurrentDB;
-use crate::logging::log_info;
-use crate::utils::{sanitize_channel_name, create_success_embed, create_error_embed};
+use crate::logging::{log_debug, log_info};
+use crate::services::{find_database_category, list_tables, get_channels_cached};
+use crate::error::SqlError;
+use crate::utils::{canonical_name, DB_PREFIX, TABLE_PREFIX, create_success_embed};
 
 pub fn register() -> Result<(), Box<dyn Error>> {
-    log_info("Registering USE command");
+    log_debug("Registering USE command");
     Ok(())
 }
 
-/// Set the current DB for a user in a guild. Returns Ok(embed) or Err(embed).
-pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, db_name: &str) -> Result<serenity::builder::CreateEmbed, serenity::builder::CreateEmbed> {
+/// Set the current DB for a user in a guild. Returns Ok(embed) or Err(SqlError).
+pub async fn run(ctx: &Context, guild_id: GuildId, user_id: UserId, db_name: &str) -> Result<CreateEmbed, SqlError> {
     log_info(&format!("USE command executed for database: {} by user: {}", db_name, user_id));
     
-    // Sanitize the database name
-    let (sanitized_name, was_changed) = sanitize_channel_name(db_name);
-    
-    if sanitized_name.is_empty() {
-        let embed = create_error_embed(
-            "✖️ Invalid Database Name",
-            "Database name cannot be empty after sanitization. Please provide a valid name."
-        );
-        return Err(embed);
-    }
-    
-    // Verify the database exists
-    let db_category_name = format!("db_{}", sanitized_name);
-    match guild_id.channels(&ctx.http).await {
-        Ok(channels) => {
-            let db_exists = channels.values()
-                .any(|c| c.name == db_category_name && c.kind == ChannelType::Category);
-            
-            if !db_exists {
-                let embed = create_error_embed(
-                    "✖️ Database Not Found",
-                    &format!("Database **{}** was not found. Create it first with `/sql create db {}`", db_category_name, sanitized_name)
-                );
-                return Err(embed);
-            }
-        },
+    let channels = match get_channels_cached(ctx, guild_id).await {
+        Ok(channels) => channels,
         Err(e) => {
             tracing::error!("Failed to get channels: {e}");
-            let embed = create_error_embed(
-                "✖️ Permission Error",
-                "Failed to list channels. Please check bot permissions."
-            );
-            return Err(embed);
+            return Err(SqlError::DiscordRequest { operation: "list channels" });
         }
-    }
+    };
+    let selection = resolve_database(&channels, db_name)?;
     
-    let data_read = ctx.data.read().await;
-    if let Some(map_arc) = data_read.get::<CurrentDB>().cloned() {
-        drop(data_read);
-        let mut map = map_arc.lock().await;
-        map.insert((guild_id, user_id), sanitized_name.clone());
-        
-        let mut description = format!("Now using database **{}**", db_category_name);
-        if was_changed {
-            description.push_str(&format!("\n\n*Name sanitized from `{}` to `{}`*", db_name, sanitized_name));
-        }
-        let embed = create_success_embed("✔️ Database Selected", &description);
-        Ok(embed)
+    let map_arc = ctx.data.read().await.get::<CurrentDB>().cloned()
+        .ok_or(SqlError::Internal("Data map missing. Please try again or contact support."))?;
+    map_arc.lock().await.insert((guild_id, user_id), selection.name.clone());
+    
+    let mut description = format!("Now using database **{}**", selection.name);
+    if selection.was_changed {
+        description.push_str(&format!("\n\n*Name sanitized from `{}` to `{}`*", db_name, selection.name));
+    }
+    if selection.tables.is_empty() {
+        description.push_str("\n\n*No tables yet.*");
     } else {
-        let embed = create_error_embed(
-            "✖️ Internal Error",
-            "Data map missing. Please try again or contact support."
-        );
-        Err(embed)
+        description.push_str(&format!("\n\n**Tables:** {}", selection.tables.join(", ")));
+    }
+    Ok(create_success_embed("✔️ Database Selected", &description))
+}
+
+/// An existing database matched by `/sql use`
+#[derive(Debug, PartialEq)]
+struct DatabaseSelection {
+    /// Sanitized name, without the `db_` prefix
+    name: String,
+    was_changed: bool,
+    /// Table names in the database, without the `table_` prefix
+    tables: Vec<String>,
+}
+
+/// Canonicalize the requested name and check the database exists in the guild's channels
+fn resolve_database(channels: &HashMap<ChannelId, GuildChannel>, db_name: &str) -> Result<DatabaseSelection, SqlError> {
+    let (name, was_changed) = canonical_name(db_name, DB_PREFIX);
+    if name.is_empty() {
+        return Err(SqlError::InvalidName {
+            kind: "Database",
+            reason: "Database name cannot be empty after sanitization. Please provide a valid name.".to_string(),
+        });
+    }
+    
+    let category = find_database_category(channels, &name)
+        .ok_or_else(|| SqlError::DatabaseNotFound(name.clone()))?;
+    let tables = list_tables(channels, category.id)
+        .iter()
+        .map(|table| table.name.trim_start_matches(TABLE_PREFIX).to_string())
+        .collect();
+    Ok(DatabaseSelection { name, was_changed, tables })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::discord_fs::test_channel;
+
+    #[test]
+    fn test_resolve_database() {
+        let channels: HashMap<ChannelId, GuildChannel> = [
+            test_channel(10, "db_shop", 4, None),
+            test_channel(11, "table_users", 0, Some(10)),
+        ]
+        .into_iter()
+        .map(|c| (c.id, c))
+        .collect();
+
+        assert_eq!(resolve_database(&channels, "Shop"), Ok(DatabaseSelection {
+            name: "shop".to_string(),
+            was_changed: true,
+            tables: vec!["users".to_string()],
+        }));
+        // Typing the internal prefix selects the same database
+        assert_eq!(resolve_database(&channels, "db_shop").map(|selection| selection.name), Ok("shop".to_string()));
+        assert_eq!(resolve_database(&channels, "blog"), Err(SqlError::DatabaseNotFound("blog".to_string())));
+        assert!(matches!(resolve_database(&channels, "!!!"), Err(SqlError::InvalidName { kind: "Database", .. })));
     }
 }
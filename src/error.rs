@@ -0,0 +1,192 @@
+// Structured command errors, turned into embeds at the handler boundary
+
+use std::fmt;
+use serenity::builder::CreateEmbed;
+use serenity::model::id::RoleId;
+use crate::guards::DestructiveAction;
+use crate::utils::{create_error_embed, create_error_embed_with_code};
+
+/// Why a command failed, independent of how the failure is shown to the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlError {
+    /// The command was used outside a server
+    NotInGuild,
+    /// The user has not picked a database with `/sql use`
+    NoDatabaseSelected,
+    /// A database or table name is unusable (`kind` is "Database" or "Table")
+    InvalidName { kind: &'static str, reason: String },
+    DatabaseNotFound(String),
+    TableNotFound { table: String, database: String },
+    /// `requested` is the name as typed when it sanitized to the existing `table`
+    TableExists { table: String, database: String, requested: Option<String> },
+    /// `requested` is the name as typed when it sanitized to the existing `database`
+    DatabaseExists { database: String, requested: Option<String> },
+    /// A table schema definition could not be parsed
+    InvalidSchema(String),
+    /// Row values failed parsing or schema validation
+    InvalidData(String),
+    /// A row would break a PRIMARY KEY or UNIQUE constraint (`kind` is "Primary Key" or "Unique Constraint")
+    ConstraintViolation { kind: &'static str, detail: String },
+    /// A WHERE clause names columns the table doesn't have
+    UnknownWhereColumn { columns: Vec<String>, available: Vec<String> },
+    /// A row's stored message would be longer than Discord allows
+    RowTooLong { length: usize, limit: usize },
+    /// A selected column isn't in the table schema (`clause` is "EXCEPT" for `* EXCEPT (...)` lists)
+    UnknownColumn { column: String, available: Vec<String>, clause: Option<&'static str> },
+    /// A SELECT column list is empty or malformed
+    InvalidColumnSelection(String),
+    /// A computed SELECT column could not be parsed
+    InvalidExpression(String),
+    /// A `/sql join` condition is malformed or names columns the tables don't have
+    InvalidJoin(String),
+    /// The schema stored in a table's channel topic could not be parsed
+    SchemaParse(String),
+    /// A new table's channel didn't keep its schema and was removed again
+    SchemaNotStored(String),
+    /// A table comment can't be stored in the channel topic
+    InvalidComment(String),
+    /// An uploaded archive isn't a `/sql dump data` export that can be restored
+    InvalidArchive(String),
+    /// A table has more rows than one scan reads (`SQLCORD_MAX_ROWS`), so it can't be copied whole
+    TableTooLarge { table: String, limit: usize },
+    /// A shard channel of a sharded table is gone, so rows placed there can't be written
+    ShardMissing { channel: String },
+    /// A table was asked for fewer than one or more than `MAX_SHARDS` shards
+    InvalidShardCount(usize),
+    /// The caller lacks every role on the destructive-command allowlist
+    PermissionDenied { action: DestructiveAction, allowed_roles: Vec<RoleId> },
+    /// The command is limited to members with the Administrator permission
+    AdministratorRequired,
+    /// A Discord API call failed; `operation` completes "Failed to ..."
+    DiscordRequest { operation: &'static str },
+    /// The bot runs with `SQLCORD_READ_ONLY`, so mutating commands are refused
+    ReadOnly,
+    /// Shared bot state is missing or inconsistent
+    Internal(&'static str),
+}
+
+impl fmt::Display for SqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlError::NotInGuild => write!(f, "This command must be used in a server (guild)."),
+            SqlError::NoDatabaseSelected => write!(f, "No database selected. Use `/sql use <db_name>` first to select a database."),
+            SqlError::InvalidName { reason, .. } => write!(f, "{}", reason),
+            SqlError::DatabaseNotFound(db) => write!(f, "Database **{}** does not exist. Create it with `/sql create db {}` or select a different database.", db, db),
+            SqlError::TableNotFound { table, database } => write!(f, "Table **{}** does not exist in database **{}**. Create it with `/sql create table {}`.", table, database, table),
+            SqlError::TableExists { table, database, requested: Some(requested) } => write!(f, "The name `{}` sanitizes to **{}**, which already exists in database **{}**. Choose a name that differs after sanitization.", requested, table, database),
+            SqlError::TableExists { table, database, requested: None } => write!(f, "Table **{}** already exists in database **{}**", table, database),
+            SqlError::DatabaseExists { database, requested: Some(requested) } => write!(f, "The name `{}` sanitizes to **{}**, which already exists. Choose a name that differs after sanitization.", requested, database),
+            SqlError::DatabaseExists { database, requested: None } => write!(f, "Database **{}** already exists in this server", database),
+            SqlError::InvalidSchema(e) => write!(f, "**Schema Error:**\n{}\n\n💡 **Tip:** Use formats like `id INT`, `name VARCHAR(255)`, `active BOOLEAN`", e),
+            SqlError::InvalidData(e) => write!(f, "**Validation Error:**\n{}", e),
+            SqlError::ConstraintViolation { detail, .. } => write!(f, "{}", detail),
+            SqlError::UnknownWhereColumn { columns, available } => write!(
+                f,
+                "WHERE references unknown column(s): **{}**\n\n**Available columns:** {}",
+                columns.join("**, **"),
+                available.join(", ")
+            ),
+            SqlError::RowTooLong { length, limit } => write!(f, "This row would be stored as a message of **{}** characters, but Discord messages hold at most **{}**. Nothing was written.\n\n💡 **Tip:** Insert fewer or shorter values, or split long text across several rows.", length, limit),
+            SqlError::UnknownColumn { column, available, clause } => write!(
+                f,
+                "Column **{}**{} does not exist in table schema.\n\n**Available columns:** {}",
+                column,
+                clause.map(|clause| format!(" in {}", clause)).unwrap_or_default(),
+                available.join(", ")
+            ),
+            SqlError::InvalidColumnSelection(e) | SqlError::InvalidExpression(e) => write!(f, "{}", e),
+            SqlError::InvalidJoin(e) => write!(f, "{}", e),
+            SqlError::SchemaParse(e) => write!(f, "Failed to parse table schema: {}", e),
+            SqlError::InvalidComment(reason) => write!(f, "{}", reason),
+            SqlError::InvalidArchive(reason) => write!(f, "{}", reason),
+            SqlError::SchemaNotStored(table) => write!(f, "Discord did not store the schema of table **{}**, so the table was not created. Please try again.", table),
+            SqlError::PermissionDenied { action, allowed_roles } => {
+                let roles = allowed_roles.iter().map(|role| format!("<@&{}>", role)).collect::<Vec<_>>().join(", ");
+                write!(f, "You need one of these roles to run **{}**: {}\n\n💡 **Tip:** Server administrators can change this with `/admin perms allow <role>`.", action, roles)
+            }
+            SqlError::AdministratorRequired => write!(f, "Only server administrators can run this command."),
+            SqlError::InvalidShardCount(shards) => write!(f, "A table can't have {} shards; choose 1 to {}.", shards, crate::services::shards::MAX_SHARDS),
+            SqlError::TableTooLarge { table, limit } => write!(f, "Table **{}** has more than **{}** rows, the most one scan reads, so it can't be copied in full. Nothing was copied.\n\n💡 **Tip:** Raise `SQLCORD_MAX_ROWS` to copy larger tables.", table, limit),
+            SqlError::ShardMissing { channel } => write!(f, "This row belongs in shard channel **{}**, which no longer exists. Recreate the channel or restore the table from a dump.", channel),
+            SqlError::DiscordRequest { operation } => write!(f, "Failed to {}. Please check bot permissions or try again.", operation),
+            SqlError::ReadOnly => write!(f, "Read-only mode: operation not performed. This bot was started with `SQLCORD_READ_ONLY`, so commands that change databases, tables or rows are disabled."),
+            SqlError::Internal(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for SqlError {}
+
+impl SqlError {
+    /// Short heading for the error embed, e.g. "Table Not Found"
+    pub(crate) fn title(&self) -> String {
+        match self {
+            SqlError::NotInGuild => "Server Only".to_string(),
+            SqlError::NoDatabaseSelected => "No Database Selected".to_string(),
+            SqlError::InvalidName { kind, .. } => format!("Invalid {} Name", kind),
+            SqlError::DatabaseNotFound(_) => "Database Not Found".to_string(),
+            SqlError::TableNotFound { .. } => "Table Not Found".to_string(),
+            SqlError::TableExists { .. } => "Table Already Exists".to_string(),
+            SqlError::DatabaseExists { .. } => "Database Already Exists".to_string(),
+            SqlError::InvalidSchema(_) => "Invalid Table Schema".to_string(),
+            SqlError::InvalidData(_) => "Data Validation Failed".to_string(),
+            SqlError::ConstraintViolation { kind, .. } => format!("{} Violation", kind),
+            SqlError::UnknownWhereColumn { .. } => "Unknown Column in WHERE".to_string(),
+            SqlError::RowTooLong { .. } => "Row Too Large".to_string(),
+            SqlError::UnknownColumn { .. } => "Unknown Column".to_string(),
+            SqlError::InvalidColumnSelection(_) => "Invalid Column Selection".to_string(),
+            SqlError::InvalidExpression(_) => "Invalid Expression".to_string(),
+            SqlError::InvalidJoin(_) => "Invalid JOIN".to_string(),
+            SqlError::SchemaParse(_) => "Schema Parse Error".to_string(),
+            SqlError::SchemaNotStored(_) => "Table Creation Failed".to_string(),
+            SqlError::InvalidComment(_) => "Invalid Comment".to_string(),
+            SqlError::InvalidArchive(_) => "Invalid Archive".to_string(),
+            SqlError::PermissionDenied { .. } | SqlError::AdministratorRequired => "Permission Denied".to_string(),
+            SqlError::DiscordRequest { .. } => "Discord Request Failed".to_string(),
+            SqlError::ShardMissing { .. } => "Shard Missing".to_string(),
+            SqlError::TableTooLarge { .. } => "Table Too Large".to_string(),
+            SqlError::InvalidShardCount(_) => "Invalid Shard Count".to_string(),
+            SqlError::ReadOnly => "Read-Only Mode".to_string(),
+            SqlError::Internal(_) => "Internal Error".to_string(),
+        }
+    }
+
+    /// Machine-readable code shown in the embed footer, for errors users commonly hit
+    fn code(&self) -> Option<&'static str> {
+        match self {
+            SqlError::NoDatabaseSelected => Some("NO_DATABASE"),
+            SqlError::PermissionDenied { .. } | SqlError::AdministratorRequired => Some("PERMISSION_DENIED"),
+            SqlError::ReadOnly => Some("READ_ONLY"),
+            _ => None,
+        }
+    }
+}
+
+impl From<SqlError> for CreateEmbed {
+    fn from(error: SqlError) -> Self {
+        let description = error.to_string();
+        match error.code() {
+            Some(code) => create_error_embed_with_code(&error.title(), &description, code),
+            None => create_error_embed(&error.title(), &description),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_error_embed() {
+        let embed = serde_json::to_value(CreateEmbed::from(SqlError::TableNotFound {
+            table: "users".to_string(),
+            database: "shop".to_string(),
+        })).unwrap();
+        assert_eq!(embed["title"], "✖️ Table Not Found");
+        assert!(embed["description"].as_str().unwrap().contains("**users**"));
+        assert!(embed["footer"].is_null());
+
+        let embed = serde_json::to_value(CreateEmbed::from(SqlError::NoDatabaseSelected)).unwrap();
+        assert_eq!(embed["footer"]["text"], "Error code: NO_DATABASE");
+    }
+}
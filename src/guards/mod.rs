@@ -1,3 +1,169 @@
 pub mod safety;
+pub mod permissions;
 
-pub fn check_guards() {}
+pub use permissions::{check_permission, require_administrator, DestructiveAction};
+
+use std::collections::HashMap;
+use serenity::model::application::CommandInteraction;
+use serenity::model::channel::GuildChannel;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use crate::error::SqlError;
+use crate::state::{CurrentDB, ReadOnlyMode};
+use crate::services::{find_database_category, find_table_channel, get_channels_cached, BotContext};
+use crate::services::shards::is_shard_channel;
+use crate::utils::{canonical_name, DB_PREFIX, TABLE_PREFIX};
+
+/// Require that the command was invoked inside a server
+pub fn require_guild(command: &CommandInteraction) -> Result<GuildId, SqlError> {
+    check_guild(command.guild_id)
+}
+
+fn check_guild(guild_id: Option<GuildId>) -> Result<GuildId, SqlError> {
+    guild_id.ok_or(SqlError::NotInGuild)
+}
+
+/// Require that the user has selected a database with `/sql use`
+pub async fn require_selected_db(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId) -> Result<String, SqlError> {
+    let data = ctx.data().read().await;
+    let selected = match data.get::<CurrentDB>() {
+        Some(db_store) => lookup_selected_db(&*db_store.lock().await, guild_id, user_id),
+        None => None,
+    };
+    selected.ok_or(SqlError::NoDatabaseSelected)
+}
+
+/// Database a command runs against: `db_override` (a command's `db` option) when given and it exists,
+/// otherwise the user's `/sql use` selection. The override never changes that selection.
+pub async fn resolve_database(ctx: &impl BotContext, guild_id: GuildId, user_id: UserId, db_override: Option<&str>) -> Result<String, SqlError> {
+    let Some(db_override) = db_override else {
+        return require_selected_db(ctx, guild_id, user_id).await;
+    };
+    let channels = match get_channels_cached(ctx, guild_id).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            tracing::error!("Failed to get channels: {e}");
+            return Err(SqlError::DiscordRequest { operation: "list channels" });
+        }
+    };
+    find_database(&channels, db_override)
+}
+
+/// Canonical name of database `name` if its category exists in a guild's channel list
+fn find_database(channels: &HashMap<ChannelId, GuildChannel>, name: &str) -> Result<String, SqlError> {
+    let (db_name, _) = canonical_name(name, DB_PREFIX);
+    if db_name.is_empty() {
+        return Err(SqlError::InvalidName { kind: "Database", reason: "Database name cannot be empty after sanitization.".to_string() });
+    }
+    match find_database_category(channels, &db_name) {
+        Some(_) => Ok(db_name),
+        None => Err(SqlError::DatabaseNotFound(db_name)),
+    }
+}
+
+/// Refuse a mutating command while the bot runs in read-only mode (`SQLCORD_READ_ONLY`)
+pub async fn require_writable(ctx: &impl BotContext) -> Result<(), SqlError> {
+    let read_only = ctx.data().read().await.get::<ReadOnlyMode>().copied().unwrap_or(false);
+    check_writable(read_only)
+}
+
+fn check_writable(read_only: bool) -> Result<(), SqlError> {
+    if read_only { Err(SqlError::ReadOnly) } else { Ok(()) }
+}
+
+/// Selected database for a (guild, user) pair in the session map
+fn lookup_selected_db(sessions: &HashMap<(GuildId, UserId), String>, guild_id: GuildId, user_id: UserId) -> Option<String> {
+    sessions.get(&(guild_id, user_id)).cloned()
+}
+
+/// Require that table `name` exists in database `db`, returning its channel
+pub async fn require_table(ctx: &impl BotContext, guild_id: GuildId, db: &str, name: &str) -> Result<GuildChannel, SqlError> {
+    let channels = match get_channels_cached(ctx, guild_id).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            tracing::error!("Failed to get channels: {e}");
+            return Err(SqlError::DiscordRequest { operation: "list channels" });
+        }
+    };
+    find_table(&channels, db, name).cloned()
+}
+
+/// Locate table `name` of database `db` in a guild's channel list
+fn find_table<'a>(channels: &'a HashMap<ChannelId, GuildChannel>, db: &str, name: &str) -> Result<&'a GuildChannel, SqlError> {
+    let category = find_database_category(channels, db)
+        .ok_or_else(|| SqlError::DatabaseNotFound(db.to_string()))?;
+
+    let (sanitized_name, _) = canonical_name(name, TABLE_PREFIX);
+    find_table_channel(channels, category.id, &sanitized_name)
+        .filter(|channel| !is_shard_channel(channel))
+        .ok_or_else(|| SqlError::TableNotFound { table: name.to_string(), database: db.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::discord_fs::test_channel;
+
+    #[test]
+    fn test_check_guild() {
+        assert_eq!(check_guild(Some(GuildId::new(7))), Ok(GuildId::new(7)));
+        assert_eq!(check_guild(None), Err(SqlError::NotInGuild));
+    }
+
+    #[test]
+    fn test_check_writable() {
+        assert_eq!(check_writable(false), Ok(()));
+        assert_eq!(check_writable(true), Err(SqlError::ReadOnly));
+
+        let embed = serde_json::to_value(serenity::builder::CreateEmbed::from(check_writable(true).unwrap_err())).unwrap();
+        assert_eq!(embed["title"], "✖️ Read-Only Mode");
+        assert!(embed["description"].as_str().unwrap().starts_with("Read-only mode: operation not performed"));
+        assert_eq!(embed["footer"]["text"], "Error code: READ_ONLY");
+    }
+
+    #[test]
+    fn test_selected_db_lookup() {
+        let guild_id = GuildId::new(1);
+        let user_id = UserId::new(2);
+        let mut sessions = HashMap::new();
+        sessions.insert((guild_id, user_id), "shop".to_string());
+
+        assert_eq!(lookup_selected_db(&sessions, guild_id, user_id).as_deref(), Some("shop"));
+
+        // Selections are per guild and per user
+        assert_eq!(lookup_selected_db(&sessions, guild_id, UserId::new(3)), None);
+        assert_eq!(lookup_selected_db(&sessions, GuildId::new(4), user_id), None);
+    }
+
+    #[test]
+    fn test_find_database_override() {
+        let channels: HashMap<ChannelId, GuildChannel> = [test_channel(10, "db_sales", 4, None), test_channel(11, "table_users", 0, Some(10))]
+            .into_iter()
+            .map(|c| (c.id, c))
+            .collect();
+
+        assert_eq!(find_database(&channels, "Sales"), Ok("sales".to_string()));
+        assert_eq!(find_database(&channels, "db_sales"), Ok("sales".to_string()));
+        assert_eq!(find_database(&channels, "shop"), Err(SqlError::DatabaseNotFound("shop".to_string())));
+        // Tables are not databases
+        assert!(find_database(&channels, "table_users").is_err());
+        assert!(matches!(find_database(&channels, "!!"), Err(SqlError::InvalidName { kind: "Database", .. })));
+    }
+
+    #[test]
+    fn test_find_table_errors() {
+        let channels: HashMap<ChannelId, GuildChannel> = [
+            test_channel(10, "db_shop", 4, None),
+            test_channel(11, "table_users", 0, Some(10)),
+        ]
+        .into_iter()
+        .map(|c| (c.id, c))
+        .collect();
+
+        assert_eq!(find_table(&channels, "shop", "Users").map(|c| c.id), Ok(ChannelId::new(11)));
+        assert_eq!(find_table(&channels, "blog", "users").unwrap_err(), SqlError::DatabaseNotFound("blog".to_string()));
+        assert_eq!(
+            find_table(&channels, "shop", "orders").unwrap_err(),
+            SqlError::TableNotFound { table: "orders".to_string(), database: "shop".to_string() }
+        );
+    }
+}
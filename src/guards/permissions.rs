@@ -0,0 +1,86 @@
+// Role-based permission checks for destructive commands
+
+use std::collections::HashSet;
+use std::fmt;
+use serenity::model::guild::Member;
+use serenity::model::id::RoleId;
+use serenity::prelude::Context;
+use crate::state::DestructiveRoles;
+use crate::error::SqlError;
+
+/// Commands that destroy data and are restricted by the role allowlist
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DestructiveAction {
+    DropDatabase,
+    DropTable,
+    Delete,
+}
+
+impl fmt::Display for DestructiveAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DestructiveAction::DropDatabase => "DROP DATABASE",
+            DestructiveAction::DropTable => "DROP TABLE",
+            DestructiveAction::Delete => "DELETE",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Decide whether a member may run a destructive command.
+/// Administrators always may; otherwise an empty allowlist means unrestricted,
+/// and a configured one requires at least one matching role.
+pub fn is_action_permitted(allowed_roles: &HashSet<RoleId>, member_roles: &[RoleId], is_admin: bool) -> bool {
+    is_admin || allowed_roles.is_empty() || member_roles.iter().any(|role| allowed_roles.contains(role))
+}
+
+/// Check the caller against the guild's destructive-command allowlist
+pub async fn check_permission(ctx: &Context, member: &Member, action: DestructiveAction) -> Result<(), SqlError> {
+    let allowed_roles = {
+        let data = ctx.data.read().await;
+        match data.get::<DestructiveRoles>() {
+            Some(store) => store.lock().await.get(&member.guild_id).cloned().unwrap_or_default(),
+            None => HashSet::new(),
+        }
+    };
+
+    let is_admin = member.permissions.is_some_and(|permissions| permissions.administrator());
+    if is_action_permitted(&allowed_roles, &member.roles, is_admin) {
+        return Ok(());
+    }
+
+    let mut allowed_roles: Vec<RoleId> = allowed_roles.into_iter().collect();
+    allowed_roles.sort();
+    Err(SqlError::PermissionDenied { action, allowed_roles })
+}
+
+/// Require the Administrator permission, for server-wide debugging tools.
+/// `/admin` is already hidden from other members by default, but server owners can override that.
+pub fn require_administrator(member: &Member) -> Result<(), SqlError> {
+    if member.permissions.is_some_and(|permissions| permissions.administrator()) {
+        Ok(())
+    } else {
+        Err(SqlError::AdministratorRequired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_action_permitted() {
+        let moderator = RoleId::new(1);
+        let member = RoleId::new(2);
+        let allowed: HashSet<RoleId> = [moderator].into_iter().collect();
+
+        // No allowlist configured: everyone may
+        assert!(is_action_permitted(&HashSet::new(), &[member], false));
+        // Allowlist configured: a matching role is required
+        assert!(is_action_permitted(&allowed, &[member, moderator], false));
+        assert!(!is_action_permitted(&allowed, &[member], false));
+        assert!(!is_action_permitted(&allowed, &[], false));
+        // Administrators bypass the allowlist
+        assert!(is_action_permitted(&allowed, &[member], true));
+    }
+}
@@ -1,9 +1,15 @@
 use serenity::async_trait;
+use serenity::model::channel::Reaction;
 use serenity::model::gateway::Ready;
-use serenity::model::application::Interaction;
-use serenity::model::application::CommandDataOptionValue;
-use serenity::builder::{CreateInteractionResponse, CreateInteractionResponseMessage};
+use serenity::model::guild::Guild;
+use serenity::model::id::GuildId;
+use serenity::model::application::{CommandDataOption, CommandDataOptionValue, CommandInteraction, Interaction};
+use serenity::builder::{CreateAttachment, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage};
 use serenity::prelude::*;
+use crate::commands::options::{get_attachment_option, get_bool_option, get_integer_option, get_role_option, get_string_option, parse_count_args, parse_create_table_args, parse_insert_args, parse_join_args, parse_select_args, MissingOptions};
+use crate::error::SqlError;
+use crate::guards::{check_permission, require_administrator, require_guild, DestructiveAction};
+use crate::state::BotStartTime;
 
 pub struct Handler;
 
@@ -11,507 +17,493 @@ pub struct Handler;
 impl EventHandler for Handler {
     async fn ready(&self, _ctx: Context, ready: Ready) {
         tracing::info!("{} is connected!", ready.user.name);
+        // ready fires again after reconnects; keep the first start time
+        {
+            let mut data = _ctx.data.write().await;
+            if !data.contains_key::<BotStartTime>() {
+                data.insert::<BotStartTime>(std::time::Instant::now());
+            }
+        }
         // register global commands now that we're ready
         if let Err(e) = crate::bot::register_commands(&_ctx.http).await {
             tracing::error!("Failed to create sql command: {e}");
         }
     }
 
+    async fn guild_create(&self, ctx: Context, guild: Guild, _is_new: Option<bool>) {
+        // Per-guild mode: each guild gets the commands immediately, at startup and when the bot joins it
+        if crate::bot::command_registration_mode() == crate::bot::RegistrationMode::PerGuild
+            && let Err(e) = crate::bot::register_guild_commands(&ctx.http, guild.id).await
+        {
+            tracing::error!("Failed to create guild commands for {}: {e}", guild.id);
+        }
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        // The only reactions SQLcord reads are answers to `/sql drop db` confirmation prompts
+        crate::commands::sql::drop::db::handle_reaction(&ctx, &reaction).await;
+    }
+
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::Command(command) = interaction {
-            match command.data.name.as_str() {
-                "sql" => {
-                    // options may contain a subcommand group (create) and/or subcommands (use). Iterate to find which was used.
-                    for opt in &command.data.options {
-                        match opt.name.as_str() {
-                            "create" => {
-                                match &opt.value {
-                                    CommandDataOptionValue::SubCommandGroup(groups) => {
-                                        if let Some(sub) = groups.get(0) {
-                                            if sub.name == "db" {
-                                                if let CommandDataOptionValue::SubCommand(params) = &sub.value {
-                                                    if let Some(name_opt) = params.get(0) {
-                                                                if let CommandDataOptionValue::String(db_name) = &name_opt.value {
-                                                                    if let Some(guild_id) = command.guild_id {
-                                                                        match crate::commands::sql::create::db::run(&ctx, guild_id, db_name).await {
-                                                                            Ok(embed) => {
-                                                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                                    CreateInteractionResponseMessage::new().embed(embed)
-                                                                                )).await {
-                                                                                    tracing::error!("Failed to respond after creating db: {e}");
-                                                                                }
-                                                                            }
-                                                                            Err(embed) => {
-                                                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                                    CreateInteractionResponseMessage::new().embed(embed)
-                                                                                )).await {
-                                                                                    tracing::error!("Failed to send error response: {e}");
-                                                                                }
-                                                                            }
-                                                                        }
-                                                                    } else {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().content("This command must be used in a server (guild).")
-                                                                        )).await {
-                                                                            tracing::error!("Failed to send guild-only response: {e}");
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                }
-                                            } else if sub.name == "table" {
-                                                if let CommandDataOptionValue::SubCommand(params) = &sub.value {
-                                                    if let Some(name_opt) = params.get(0) {
-                                                        if let CommandDataOptionValue::String(table_name) = &name_opt.value {
-                                                            // Extract optional schema parameter
-                                                            let schema = params.get(1).and_then(|opt| {
-                                                                if let CommandDataOptionValue::String(schema_str) = &opt.value {
-                                                                    Some(schema_str.as_str())
-                                                                } else {
-                                                                    None
-                                                                }
-                                                            });
-                                                            
-                                                            if let Some(guild_id) = command.guild_id {
-                                                                let user_id = command.user.id;
-                                                                match crate::commands::sql::create::table::run(&ctx, guild_id, user_id, table_name, schema).await {
-                                                                    Ok(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to respond after creating table: {e}");
-                                                                        }
-                                                                    }
-                                                                    Err(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to send error response: {e}");
-                                                                        }
-                                                                    }
-                                                                }
-                                                            } else {
-                                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                    CreateInteractionResponseMessage::new().content("This command must be used in a server (guild).")
-                                                                )).await {
-                                                                    tracing::error!("Failed to send guild-only response: {e}");
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    CommandDataOptionValue::SubCommand(params) => {
-                                        // handle if create was registered as subcommand directly
-                                        if let Some(sub) = params.get(0) {
-                                            if sub.name == "db" {
-                                                if let CommandDataOptionValue::SubCommand(inner) = &sub.value {
-                                                    if let Some(name_opt) = inner.get(0) {
-                                                        if let CommandDataOptionValue::String(db_name) = &name_opt.value {
-                                                            if let Some(guild_id) = command.guild_id {
-                                                                match crate::commands::sql::create::db::run(&ctx, guild_id, db_name).await {
-                                                                    Ok(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to respond after creating db: {e}");
-                                                                        }
-                                                                    }
-                                                                    Err(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to send error response: {e}");
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            } else if sub.name == "table" {
-                                                if let CommandDataOptionValue::SubCommand(inner) = &sub.value {
-                                                    if let Some(name_opt) = inner.get(0) {
-                                                        if let CommandDataOptionValue::String(table_name) = &name_opt.value {
-                                                            // Extract optional schema parameter
-                                                            let schema = inner.get(1).and_then(|opt| {
-                                                                if let CommandDataOptionValue::String(schema_str) = &opt.value {
-                                                                    Some(schema_str.as_str())
-                                                                } else {
-                                                                    None
-                                                                }
-                                                            });
-                                                            
-                                                            if let Some(guild_id) = command.guild_id {
-                                                                let user_id = command.user.id;
-                                                                match crate::commands::sql::create::table::run(&ctx, guild_id, user_id, table_name, schema).await {
-                                                                    Ok(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to respond after creating table: {e}");
-                                                                        }
-                                                                    }
-                                                                    Err(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to send error response: {e}");
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            "drop" => {
-                                match &opt.value {
-                                    CommandDataOptionValue::SubCommandGroup(groups) => {
-                                        if let Some(sub) = groups.get(0) {
-                                            if sub.name == "db" {
-                                                if let CommandDataOptionValue::SubCommand(params) = &sub.value {
-                                                    if let Some(name_opt) = params.get(0) {
-                                                        if let CommandDataOptionValue::String(db_name) = &name_opt.value {
-                                                            if let Some(guild_id) = command.guild_id {
-                                                                match crate::commands::sql::drop::db::run(&ctx, guild_id, db_name).await {
-                                                                    Ok(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to respond after dropping db: {e}");
-                                                                        }
-                                                                    }
-                                                                    Err(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to send error response: {e}");
-                                                                        }
-                                                                    }
-                                                                }
-                                                            } else {
-                                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                    CreateInteractionResponseMessage::new().content("This command must be used in a server (guild).")
-                                                                )).await {
-                                                                    tracing::error!("Failed to send guild-only response: {e}");
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            } else if sub.name == "table" {
-                                                if let CommandDataOptionValue::SubCommand(params) = &sub.value {
-                                                    if let Some(name_opt) = params.get(0) {
-                                                        if let CommandDataOptionValue::String(table_name) = &name_opt.value {
-                                                            if let Some(guild_id) = command.guild_id {
-                                                                let user_id = command.user.id;
-                                                                match crate::commands::sql::drop::table::run(&ctx, guild_id, user_id, table_name).await {
-                                                                    Ok(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to respond after dropping table: {e}");
-                                                                        }
-                                                                    }
-                                                                    Err(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to send error response: {e}");
-                                                                        }
-                                                                    }
-                                                                }
-                                                            } else {
-                                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                    CreateInteractionResponseMessage::new().content("This command must be used in a server (guild).")
-                                                                )).await {
-                                                                    tracing::error!("Failed to send guild-only response: {e}");
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    CommandDataOptionValue::SubCommand(params) => {
-                                        // handle if drop was registered as subcommand directly
-                                        if let Some(sub) = params.get(0) {
-                                            if sub.name == "db" {
-                                                if let CommandDataOptionValue::SubCommand(inner) = &sub.value {
-                                                    if let Some(name_opt) = inner.get(0) {
-                                                        if let CommandDataOptionValue::String(db_name) = &name_opt.value {
-                                                            if let Some(guild_id) = command.guild_id {
-                                                                match crate::commands::sql::drop::db::run(&ctx, guild_id, db_name).await {
-                                                                    Ok(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to respond after dropping db: {e}");
-                                                                        }
-                                                                    }
-                                                                    Err(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to send error response: {e}");
-                                                                        }
-                                                                    }
-                                                                }
-                                                            } else {
-                                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                    CreateInteractionResponseMessage::new().content("This command must be used in a server (guild).")
-                                                                )).await {
-                                                                    tracing::error!("Failed to send guild-only response: {e}");
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            } else if sub.name == "table" {
-                                                if let CommandDataOptionValue::SubCommand(inner) = &sub.value {
-                                                    if let Some(name_opt) = inner.get(0) {
-                                                        if let CommandDataOptionValue::String(table_name) = &name_opt.value {
-                                                            if let Some(guild_id) = command.guild_id {
-                                                                let user_id = command.user.id;
-                                                                match crate::commands::sql::drop::table::run(&ctx, guild_id, user_id, table_name).await {
-                                                                    Ok(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to respond after dropping table: {e}");
-                                                                        }
-                                                                    }
-                                                                    Err(embed) => {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                                        )).await {
-                                                                            tracing::error!("Failed to send error response: {e}");
-                                                                        }
-                                                                    }
-                                                                }
-                                                            } else {
-                                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                    CreateInteractionResponseMessage::new().content("This command must be used in a server (guild).")
-                                                                )).await {
-                                                                    tracing::error!("Failed to send guild-only response: {e}");
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            "use" => {
-                                match &opt.value {
-                                    CommandDataOptionValue::SubCommand(params) => {
-                                        if let Some(name_opt) = params.get(0) {
-                                            if let CommandDataOptionValue::String(db_name) = &name_opt.value {
-                                                if let Some(guild_id) = command.guild_id {
-                                                    let user_id = command.user.id;
-                                                    match crate::commands::sql::use_::run(&ctx, guild_id, user_id, db_name).await {
-                                                        Ok(embed) => {
-                                                            if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                CreateInteractionResponseMessage::new().embed(embed)
-                                                            )).await {
-                                                                tracing::error!("Failed to respond after setting current db: {e}");
-                                                            }
-                                                        }
-                                                        Err(embed) => {
-                                                            if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                CreateInteractionResponseMessage::new().embed(embed)
-                                                            )).await {
-                                                                tracing::error!("Failed to send internal error response: {e}");
-                                                            }
-                                                        }
-                                                    }
-                                                } else {
-                                                    if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                        CreateInteractionResponseMessage::new().content("This command must be used in a server (guild).")
-                                                    )).await {
-                                                        tracing::error!("Failed to send guild-only response: {e}");
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            "explain" => {
-                                match &opt.value {
-                                    CommandDataOptionValue::SubCommand(params) => {
-                                        // operation is now optional; if missing, pass an empty string
-                                        let operation = params.get(0).and_then(|operation_opt| {
-                                            if let CommandDataOptionValue::String(op) = &operation_opt.value {
-                                                Some(op.as_str())
-                                            } else {
-                                                None
-                                            }
-                                        }).unwrap_or("");
-
-                                        match crate::commands::sql::explain::run(operation).await {
-                                            Ok(embed) => {
-                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                    CreateInteractionResponseMessage::new().embed(embed)
-                                                )).await {
-                                                    tracing::error!("Failed to respond with explanation: {e}");
-                                                }
-                                            }
-                                            Err(embed) => {
-                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                    CreateInteractionResponseMessage::new().embed(embed)
-                                                )).await {
-                                                    tracing::error!("Failed to send explain error response: {e}");
-                                                }
-                                            }
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            "select" => {
-                                match &opt.value {
-                                    CommandDataOptionValue::SubCommand(params) => {
-                                        // Extract parameters
-                                        let mut columns = None;
-                                        let mut table = None;
-                                        let mut distinct = None;
-                                        let mut where_clause = None;
-                                        
-                                        for param in params {
-                                            match param.name.as_str() {
-                                                "columns" => {
-                                                    if let CommandDataOptionValue::String(cols) = &param.value {
-                                                        columns = Some(cols.as_str());
-                                                    }
-                                                }
-                                                "from" => {
-                                                    if let CommandDataOptionValue::String(tbl) = &param.value {
-                                                        table = Some(tbl.as_str());
-                                                    }
-                                                }
-                                                "distinct" => {
-                                                    if let CommandDataOptionValue::Boolean(dist) = &param.value {
-                                                        distinct = Some(*dist);
-                                                    }
-                                                }
-                                                "where" => {
-                                                    if let CommandDataOptionValue::String(whr) = &param.value {
-                                                        where_clause = Some(whr.as_str());
-                                                    }
-                                                }
-                                                _ => {}
-                                            }
-                                        }
-                                        
-                                        if let (Some(columns), Some(table)) = (columns, table) {
-                                            if let Some(guild_id) = command.guild_id {
-                                                let user_id = command.user.id;
-                                                match crate::commands::sql::select::run(&ctx, guild_id, user_id, columns, table, distinct, where_clause).await {
-                                                    Ok(embed) => {
-                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                        )).await {
-                                                            tracing::error!("Failed to respond after selecting data: {e}");
-                                                        }
-                                                    }
-                                                    Err(embed) => {
-                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                            CreateInteractionResponseMessage::new().embed(embed)
-                                                        )).await {
-                                                            tracing::error!("Failed to send select error response: {e}");
-                                                        }
-                                                    }
-                                                }
-                                            } else {
-                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                    CreateInteractionResponseMessage::new().content("This command must be used in a server (guild).")
-                                                )).await {
-                                                    tracing::error!("Failed to send guild-only response: {e}");
-                                                }
-                                            }
-                                        } else {
-                                            if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                CreateInteractionResponseMessage::new().content("Missing required parameters: columns and table name.")
-                                            )).await {
-                                                tracing::error!("Failed to send parameter error response: {e}");
-                                            }
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            "insert" => {
-                                match &opt.value {
-                                    CommandDataOptionValue::SubCommandGroup(groups) => {
-                                        if let Some(sub) = groups.get(0) {
-                                            if sub.name == "into" {
-                                                if let CommandDataOptionValue::SubCommand(params) = &sub.value {
-                                                    if let Some(table_opt) = params.get(0) {
-                                                        if let CommandDataOptionValue::String(table_name) = &table_opt.value {
-                                                            if let Some(data_opt) = params.get(1) {
-                                                                if let CommandDataOptionValue::String(data) = &data_opt.value {
-                                                                    if let Some(guild_id) = command.guild_id {
-                                                                        let user_id = command.user.id;
-                                                                        match crate::commands::sql::insert::run(&ctx, guild_id, user_id, table_name, data).await {
-                                                                            Ok(embed) => {
-                                                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                                    CreateInteractionResponseMessage::new().embed(embed)
-                                                                                )).await {
-                                                                                    tracing::error!("Failed to respond after inserting data: {e}");
-                                                                                }
-                                                                            }
-                                                                            Err(embed) => {
-                                                                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                                    CreateInteractionResponseMessage::new().embed(embed)
-                                                                                )).await {
-                                                                                    tracing::error!("Failed to send insert error response: {e}");
-                                                                                }
-                                                                            }
-                                                                        }
-                                                                    } else {
-                                                                        if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                                                            CreateInteractionResponseMessage::new().content("This command must be used in a server (guild).")
-                                                                        )).await {
-                                                                            tracing::error!("Failed to send guild-only response: {e}");
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
+        let command = match interaction {
+            Interaction::Command(command) => command,
+            // Suggestions while typing table and column names
+            Interaction::Autocomplete(autocomplete) => {
+                if autocomplete.data.name == crate::commands::sql::sql_command_name() {
+                    crate::commands::sql::autocomplete::respond(&ctx, &autocomplete).await;
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        match command.data.name.as_str() {
+            // `/sql` unless renamed with SQLCORD_COMMAND_NAME
+            name if name == crate::commands::sql::sql_command_name() => {
+                // options may contain a subcommand group (create) and/or subcommands (use). Iterate to find which was used.
+                for opt in &command.data.options {
+                    handle_sql_option(&ctx, &command, opt).await;
+                }
+            }
+            "admin" => {
+                for opt in &command.data.options {
+                    handle_admin_option(&ctx, &command, opt).await;
+                }
+            }
+            _ => respond_with_content(&ctx, &command, "Unknown command").await,
+        }
+    }
+}
+
+/// Dispatch a single `/admin` option to its command module
+async fn handle_admin_option(ctx: &Context, command: &CommandInteraction, opt: &CommandDataOption) {
+    let Some((sub_name, params)) = nested_subcommand(&opt.value) else {
+        return;
+    };
+    match opt.name.as_str() {
+        "perms" => handle_admin_perms(ctx, command, sub_name, params).await,
+        "debug" if sub_name == "table" => {
+            let Some(table_name) = get_string_option(params, "name") else {
+                return;
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+            // Guild interactions always carry the invoking member
+            let Some(member) = command.member.as_deref() else {
+                return;
+            };
+            if let Err(error) = require_administrator(member) {
+                respond_with_embed(ctx, command, Err(error.into())).await;
+                return;
+            }
+
+            let result = crate::commands::admin::debug::run(ctx, guild_id, command.user.id, table_name).await;
+            respond_with_embed_visibility(ctx, command, result.map_err(CreateEmbed::from), true).await;
+        }
+        _ => {}
+    }
+}
+
+/// `/admin perms allow|deny <role>`
+async fn handle_admin_perms(ctx: &Context, command: &CommandInteraction, sub_name: &str, params: &[CommandDataOption]) {
+    let Some(role_id) = get_role_option(params, "role") else {
+        return;
+    };
+    let Some(guild_id) = guild_or_respond(ctx, command).await else {
+        return;
+    };
+
+    let allow = match sub_name {
+        "allow" => true,
+        "deny" => false,
+        _ => return,
+    };
+    let result = crate::commands::admin::perms::run(ctx, guild_id, allow, role_id).await;
+    respond_with_embed(ctx, command, result).await;
+}
+
+/// Dispatch a single `/sql` option (subcommand or subcommand group) to its command module
+async fn handle_sql_option(ctx: &Context, command: &CommandInteraction, opt: &CommandDataOption) {
+    match opt.name.as_str() {
+        "create" | "drop" => {
+            let Some((sub_name, params)) = nested_subcommand(&opt.value) else {
+                return;
+            };
+            let Some(name) = get_string_option(params, "name") else {
+                return;
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+            let user_id = command.user.id;
+
+            // Destructive commands are limited to the roles configured with /admin perms
+            if opt.name == "drop" {
+                // Guild interactions always carry the invoking member
+                let Some(member) = command.member.as_deref() else {
+                    return;
+                };
+                let action = if sub_name == "db" { DestructiveAction::DropDatabase } else { DestructiveAction::DropTable };
+                if let Err(error) = check_permission(ctx, member, action).await {
+                    respond_with_embed(ctx, command, Err(error.into())).await;
+                    return;
                 }
-                _ => {
-                    if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                        CreateInteractionResponseMessage::new().content("Unknown command")
-                    )).await {
-                        tracing::error!("Failed to respond to unknown command: {e}");
+            }
+
+            // Dropping a database waits for the user to confirm with a reaction
+            if (opt.name.as_str(), sub_name) == ("drop", "db") {
+                match crate::commands::sql::drop::db::confirmation_prompt(ctx, guild_id, name).await {
+                    Ok((prompt, db_name)) => {
+                        respond_with_embed(ctx, command, Ok(prompt)).await;
+                        crate::commands::sql::drop::db::request_confirmation(ctx, command, guild_id, db_name).await;
                     }
+                    Err(embed) => respond_with_embed(ctx, command, Err(embed)).await,
+                }
+                return;
+            }
+
+            let result = match (opt.name.as_str(), sub_name) {
+                ("create", "db") => crate::commands::sql::create::db::run(ctx, guild_id, user_id, name).await.map_err(CreateEmbed::from),
+                ("create", "table") => match parse_create_table_args(params) {
+                    Ok(args) => crate::commands::sql::create::table::run(ctx, guild_id, user_id, &args).await.map_err(CreateEmbed::from),
+                    Err(missing) => Err(missing.to_embed()),
+                },
+                ("drop", "table") => crate::commands::sql::drop::table::run(ctx, guild_id, user_id, name).await.map_err(CreateEmbed::from),
+                _ => return,
+            };
+            respond_with_embed(ctx, command, result).await;
+        }
+        "use" => {
+            let CommandDataOptionValue::SubCommand(params) = &opt.value else {
+                return;
+            };
+            let Some(db_name) = get_string_option(params, "name") else {
+                return;
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+
+            let result = crate::commands::sql::use_::run(ctx, guild_id, command.user.id, db_name).await;
+            respond_with_embed(ctx, command, result.map_err(CreateEmbed::from)).await;
+        }
+        "status" => {
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+
+            let result = crate::commands::sql::status::run(ctx, guild_id, command.user.id).await;
+            respond_with_embed(ctx, command, result.map_err(CreateEmbed::from)).await;
+        }
+        "explain" => {
+            let CommandDataOptionValue::SubCommand(params) = &opt.value else {
+                return;
+            };
+            // operation is optional; if missing, pass an empty string
+            let operation = get_string_option(params, "op").unwrap_or("");
+
+            let result = crate::commands::sql::explain::run(operation).await;
+            respond_with_embed(ctx, command, result).await;
+        }
+        "select" => {
+            let CommandDataOptionValue::SubCommand(params) = &opt.value else {
+                return;
+            };
+            let args = match parse_select_args(params) {
+                Ok(args) => args,
+                Err(missing) => {
+                    respond_with_embed(ctx, command, Err(missing.to_embed())).await;
+                    return;
+                }
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+
+            match crate::commands::sql::select::run(ctx, guild_id, command.user.id, &args).await {
+                Ok(output) if !output.pages.is_empty() => {
+                    respond_with_embed_files(ctx, command, Ok(output.embed.clone()), false, output.attachment.clone()).await;
+                    crate::commands::sql::select::post_result_thread(ctx, command, args.from, output).await;
+                }
+                Ok(output) => respond_with_embed_files(ctx, command, Ok(output.embed), args.ephemeral, output.attachment).await,
+                Err(embed) => respond_with_embed(ctx, command, Err(embed)).await,
+            }
+        }
+        "count" => {
+            let CommandDataOptionValue::SubCommand(params) = &opt.value else {
+                return;
+            };
+            let args = match parse_count_args(params) {
+                Ok(args) => args,
+                Err(missing) => {
+                    respond_with_embed(ctx, command, Err(missing.to_embed())).await;
+                    return;
+                }
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+
+            let result = crate::commands::sql::count::run(ctx, guild_id, command.user.id, &args).await;
+            respond_with_embed(ctx, command, result).await;
+        }
+        "copy" => {
+            let CommandDataOptionValue::SubCommand(params) = &opt.value else {
+                return;
+            };
+            let (Some(from), Some(to)) = (get_string_option(params, "from"), get_string_option(params, "to")) else {
+                let missing = ["from", "to"].into_iter().filter(|name| get_string_option(params, name).is_none()).collect();
+                respond_with_embed(ctx, command, Err(MissingOptions { command: "copy", missing }.to_embed())).await;
+                return;
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+
+            let with_data = get_bool_option(params, "with_data").unwrap_or(false);
+            let result = crate::commands::sql::copy::run(ctx, guild_id, command.user.id, from, to, with_data).await;
+            respond_with_embed(ctx, command, result.map_err(CreateEmbed::from)).await;
+        }
+        "dump" => {
+            let Some((sub_name, _)) = nested_subcommand(&opt.value) else {
+                return;
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+
+            let result = match sub_name {
+                "schema" => crate::commands::sql::dump::run_schema(ctx, guild_id, command.user.id).await,
+                "data" => crate::commands::sql::dump::run_data(ctx, guild_id, command.user.id).await,
+                _ => return,
+            };
+            match result {
+                Ok(output) => respond_with_embed_files(ctx, command, Ok(output.embed), false, output.attachment).await,
+                Err(error) => respond_with_embed(ctx, command, Err(error.into())).await,
+            }
+        }
+        "restore" => {
+            let CommandDataOptionValue::SubCommand(params) = &opt.value else {
+                return;
+            };
+            let Some(attachment) = get_attachment_option(params, "archive").and_then(|id| command.data.resolved.attachments.get(&id)) else {
+                respond_with_embed(ctx, command, Err(MissingOptions { command: "restore", missing: vec!["archive"] }.to_embed())).await;
+                return;
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+            // Overwriting drops the existing tables, so it needs the same roles as DROP TABLE
+            let overwrite = get_bool_option(params, "overwrite").unwrap_or(false);
+            if overwrite {
+                let Some(member) = command.member.as_deref() else {
+                    return;
+                };
+                if let Err(error) = check_permission(ctx, member, DestructiveAction::DropTable).await {
+                    respond_with_embed(ctx, command, Err(error.into())).await;
+                    return;
+                }
+            }
+
+            let archive = match attachment.download().await {
+                Ok(archive) => archive,
+                Err(e) => {
+                    tracing::error!("Failed to download restore archive: {e}");
+                    respond_with_embed(ctx, command, Err(SqlError::DiscordRequest { operation: "download the archive" }.into())).await;
+                    return;
                 }
+            };
+            let result = crate::commands::sql::restore::run(ctx, guild_id, command.user.id, &archive, overwrite).await;
+            respond_with_embed(ctx, command, result.map_err(CreateEmbed::from)).await;
+        }
+        "sample" => {
+            let CommandDataOptionValue::SubCommand(params) = &opt.value else {
+                return;
+            };
+            let Some(table_name) = get_string_option(params, "table") else {
+                respond_with_embed(ctx, command, Err(MissingOptions { command: "sample", missing: vec!["table"] }.to_embed())).await;
+                return;
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+
+            let result = crate::commands::sql::sample::run(ctx, guild_id, command.user.id, table_name, get_integer_option(params, "n")).await;
+            match result {
+                Ok(output) => respond_with_embed_files(ctx, command, Ok(output.embed), false, output.attachment).await,
+                Err(embed) => respond_with_embed(ctx, command, Err(embed)).await,
+            }
+        }
+        "join" => {
+            let CommandDataOptionValue::SubCommand(params) = &opt.value else {
+                return;
+            };
+            let args = match parse_join_args(params) {
+                Ok(args) => args,
+                Err(missing) => {
+                    respond_with_embed(ctx, command, Err(missing.to_embed())).await;
+                    return;
+                }
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+
+            let result = crate::commands::sql::join::run(ctx, guild_id, command.user.id, &args).await;
+            match result {
+                Ok(output) => respond_with_embed_files(ctx, command, Ok(output.embed), false, output.attachment).await,
+                Err(embed) => respond_with_embed(ctx, command, Err(embed)).await,
+            }
+        }
+        "run" => {
+            let CommandDataOptionValue::SubCommand(params) = &opt.value else {
+                return;
+            };
+            let Some(script) = get_string_option(params, "script") else {
+                respond_with_embed(ctx, command, Err(MissingOptions { command: "run", missing: vec!["script"] }.to_embed())).await;
+                return;
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+
+            let result = crate::commands::sql::run::run(ctx, guild_id, command.user.id, script).await;
+            respond_with_embed(ctx, command, result).await;
+        }
+        "insert" => {
+            let Some(("into", params)) = nested_subcommand(&opt.value) else {
+                return;
+            };
+            let args = match parse_insert_args(params) {
+                Ok(args) => args,
+                Err(missing) => {
+                    respond_with_embed(ctx, command, Err(missing.to_embed())).await;
+                    return;
+                }
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+
+            let result = crate::commands::sql::insert::run(ctx, guild_id, command.user.id, &args).await;
+            respond_with_embed(ctx, command, result).await;
+        }
+        "delete" => {
+            let CommandDataOptionValue::SubCommand(params) = &opt.value else {
+                return;
+            };
+            let Some(table_name) = get_string_option(params, "table") else {
+                return;
+            };
+            let Some(guild_id) = guild_or_respond(ctx, command).await else {
+                return;
+            };
+            // Guild interactions always carry the invoking member
+            let Some(member) = command.member.as_deref() else {
+                return;
+            };
+            if let Err(error) = check_permission(ctx, member, DestructiveAction::Delete).await {
+                respond_with_embed(ctx, command, Err(error.into())).await;
+                return;
+            }
+
+            let message = match crate::commands::sql::delete::run(ctx, guild_id, command.user.id, table_name).await {
+                Ok(message) | Err(message) => message,
+            };
+            respond_with_content(ctx, command, &message).await;
+        }
+        _ => {}
+    }
+}
+
+/// The invoking guild, or `None` after replying with an error when used outside a server
+async fn guild_or_respond(ctx: &Context, command: &CommandInteraction) -> Option<GuildId> {
+    match require_guild(command) {
+        Ok(guild_id) => Some(guild_id),
+        Err(error) => {
+            respond_with_embed(ctx, command, Err(error.into())).await;
+            None
+        }
+    }
+}
+
+/// Send a command's embed result as the interaction response.
+/// Error embeds are ephemeral so mistakes don't clutter the channel; success embeds are public.
+async fn respond_with_embed(ctx: &Context, command: &CommandInteraction, result: Result<CreateEmbed, CreateEmbed>) {
+    respond_with_embed_visibility(ctx, command, result, false).await;
+}
+
+/// Like `respond_with_embed`, but `ephemeral` also hides a successful result from the channel
+async fn respond_with_embed_visibility(ctx: &Context, command: &CommandInteraction, result: Result<CreateEmbed, CreateEmbed>, ephemeral: bool) {
+    respond_with_embed_files(ctx, command, result, ephemeral, None).await;
+}
+
+/// Like `respond_with_embed_visibility`, with an optional file attached (e.g. output too long for the embed)
+async fn respond_with_embed_files(ctx: &Context, command: &CommandInteraction, result: Result<CreateEmbed, CreateEmbed>, ephemeral: bool, attachment: Option<CreateAttachment>) {
+    let (embed, is_error) = match result {
+        Ok(embed) => (embed, false),
+        Err(embed) => (embed, true),
+    };
+
+    let mut message = CreateInteractionResponseMessage::new().embed(embed).ephemeral(is_error || ephemeral);
+    if let Some(attachment) = attachment {
+        message = message.add_file(attachment);
+    }
+    if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(message)).await {
+        if is_error {
+            tracing::error!("Failed to send error response: {e}");
+        } else {
+            tracing::error!("Failed to send response: {e}");
+        }
+    }
+}
+
+/// Send a plain text ephemeral response (used for guild-only and routing errors)
+async fn respond_with_content(ctx: &Context, command: &CommandInteraction, content: &str) {
+    if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new().content(content).ephemeral(true)
+    )).await {
+        tracing::error!("Failed to send response: {e}");
+    }
+}
+
+/// Resolve `group -> subcommand` nesting, accepting both SubCommandGroup and SubCommand registrations.
+/// Returns the inner subcommand's name and its parameters.
+fn nested_subcommand(value: &CommandDataOptionValue) -> Option<(&str, &[CommandDataOption])> {
+    match value {
+        CommandDataOptionValue::SubCommandGroup(options) | CommandDataOptionValue::SubCommand(options) => {
+            let sub = options.first()?;
+            match &sub.value {
+                CommandDataOptionValue::SubCommand(params) => Some((sub.name.as_str(), params.as_slice())),
+                _ => None,
             }
         }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build options the same way Discord sends them (CommandDataOption is non-exhaustive)
+    fn options_from_json(json: &str) -> Vec<CommandDataOption> {
+        serde_json::from_str(json).expect("valid option JSON")
+    }
+
+    #[test]
+    fn test_nested_subcommand() {
+        let options = options_from_json(r#"[
+            {"name": "create", "type": 2, "options": [
+                {"name": "table", "type": 1, "options": [
+                    {"name": "name", "type": 3, "value": "users"}
+                ]}
+            ]}
+        ]"#);
+
+        let (sub_name, params) = nested_subcommand(&options[0].value).unwrap();
+        assert_eq!(sub_name, "table");
+        assert_eq!(get_string_option(params, "name"), Some("users"));
     }
 }
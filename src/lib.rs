@@ -7,4 +7,8 @@ pub mod render;
 pub mod services;
 pub mod logging;
 pub mod utils;
+pub mod error;
+pub mod api;
 pub mod sql_parser;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
@@ -1,26 +1,226 @@
-use chrono::Utc;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::json;
+use serenity::model::id::{GuildId, UserId};
+use crate::services::BotContext;
+use crate::state::AuditLog;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt::format::{FormatEvent, FormatFields, Writer};
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+use tracing_subscriber::fmt::FmtContext;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
 
 const GREY: &str = "\x1b[38;2;114;114;114m";
 const GREEN: &str = "\x1b[32m";
 const RED: &str = "\x1b[38;2;215;89;89m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
 const WHITE: &str = "\x1b[39m";
 
+/// Install the global subscriber: colored-timestamp lines, warnings and errors on stderr, the rest on stdout.
+/// `RUST_LOG` picks the level, either globally (`warn`) or per target (`sqlcord=debug,serenity=warn`); default is `info`.
+pub fn init() {
+    let filter = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|directives| directives.parse::<Targets>().ok())
+        .unwrap_or_else(|| Targets::new().with_default(LevelFilter::INFO));
+    let writer = std::io::stderr.with_max_level(Level::WARN).or_else(std::io::stdout);
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().event_format(ColoredTimestamp).with_writer(writer))
+        .with(filter)
+        .init();
+}
+
+/// Event format for every log line: `<timestamp> <LEVEL> <message>`, with the level right-aligned
+pub struct ColoredTimestamp;
+
+impl<S, N> FormatEvent<S, N> for ColoredTimestamp
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let ts = Utc::now().format("%Y-%m-%dT%H:%M:%S%.6fZ");
+        let level = *event.metadata().level();
+        if writer.has_ansi_escapes() {
+            write!(writer, "{}{}{} {}{:>5}{} ", GREY, ts, WHITE, level_color(level), level, WHITE)?;
+        } else {
+            write!(writer, "{} {:>5} ", ts, level)?;
+        }
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+fn level_color(level: Level) -> &'static str {
+    match level {
+        Level::ERROR => RED,
+        Level::WARN => YELLOW,
+        Level::INFO => GREEN,
+        Level::DEBUG | Level::TRACE => BLUE,
+    }
+}
+
+pub fn log_debug(msg: &str) {
+    tracing::debug!("{}", msg);
+}
+
 pub fn log_info(msg: &str) {
-    let now = Utc::now();
-    let ts = now.format("%Y-%m-%dT%H:%M:%S%.6fZ");
-    println!("{}{}{}  {}INFO{} {}",
-        GREY, ts, WHITE,
-        GREEN, WHITE,
-        msg
-    );
+    tracing::info!("{}", msg);
+}
+
+pub fn log_warn(msg: &str) {
+    tracing::warn!("{}", msg);
 }
 
 pub fn log_error(msg: &str) {
-    let now = Utc::now();
-    let ts = now.format("%Y-%m-%dT%H:%M:%S%.6fZ");
-    eprintln!("{}{}{} {}ERROR{} {}",
-        GREY, ts, WHITE,
-        RED, WHITE,
-        msg
-    );
+    tracing::error!("{}", msg);
+}
+
+/// Audit log path when `SQLCORD_AUDIT_LOG` isn't set
+pub const DEFAULT_AUDIT_LOG: &str = "audit.jsonl";
+
+/// One mutating command (CREATE, DROP, INSERT, ...) as recorded in the audit log
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub guild_id: GuildId,
+    pub user_id: UserId,
+    /// Command name, e.g. `CREATE TABLE`
+    pub command: &'static str,
+    pub database: Option<String>,
+    pub table: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    pub success: bool,
+}
+
+impl AuditEntry {
+    /// Entry stamped with the current time and no target; set `database`/`table` with struct update syntax
+    pub fn new(guild_id: GuildId, user_id: UserId, command: &'static str, success: bool) -> Self {
+        AuditEntry { guild_id, user_id, command, database: None, table: None, timestamp: Utc::now(), success }
+    }
+
+    /// JSON object written as one line of the audit log. Ids are strings since they don't fit a JSON number exactly.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "timestamp": self.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+            "guild_id": self.guild_id.to_string(),
+            "user_id": self.user_id.to_string(),
+            "command": self.command,
+            "database": self.database,
+            "table": self.table,
+            "success": self.success,
+        })
+    }
+}
+
+/// Audit log path from `SQLCORD_AUDIT_LOG`, default `audit.jsonl`
+pub fn audit_log_path() -> PathBuf {
+    std::env::var("SQLCORD_AUDIT_LOG").map(PathBuf::from).unwrap_or_else(|_| PathBuf::from(DEFAULT_AUDIT_LOG))
+}
+
+/// Append `entry` to the context's `AuditLog` file, or to `audit_log_path()` when the context has none.
+/// A failed write is logged and otherwise ignored so the command still answers.
+pub async fn audit(ctx: &impl BotContext, entry: AuditEntry) {
+    let path = ctx.data().read().await.get::<AuditLog>().cloned().unwrap_or_else(audit_log_path);
+    if let Err(e) = append_line(&path, &entry.to_json().to_string()) {
+        log_error(&format!("Failed to write audit log {}: {}", path.display(), e));
+    }
+}
+
+/// Append one line in a single write, so concurrent commands don't interleave within a record
+fn append_line(path: &Path, line: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(format!("{}\n", line).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_log_functions_accept_str() {
+        let loggers: [fn(&str); 4] = [log_debug, log_info, log_warn, log_error];
+        let owned = String::from("owned message");
+        for log in loggers {
+            // No subscriber installed: every call is a no-op
+            log("plain message");
+            log(&owned);
+        }
+    }
+
+    #[test]
+    fn test_colored_timestamp_format() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&output);
+        let subscriber = tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .event_format(ColoredTimestamp)
+                    .with_ansi(false)
+                    .with_writer(move || SharedBuffer(Arc::clone(&sink))),
+            )
+            .with(Targets::new().with_default(LevelFilter::INFO));
+
+        tracing::subscriber::with_default(subscriber, || {
+            log_debug("hidden below the level");
+            log_info("Registering SELECT command");
+            log_error("Failed to send message");
+        });
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("Z  INFO Registering SELECT command"));
+        assert!(lines[1].ends_with("Z ERROR Failed to send message"));
+        assert!(!output.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_audit_entry_json() {
+        let entry = AuditEntry {
+            database: Some("shop".to_string()),
+            table: Some("users".to_string()),
+            timestamp: DateTime::parse_from_rfc3339("2025-08-19T14:30:00Z").unwrap().with_timezone(&Utc),
+            ..AuditEntry::new(GuildId::new(1234567890123456789), UserId::new(42), "INSERT", true)
+        };
+        assert_eq!(
+            entry.to_json().to_string(),
+            r#"{"timestamp":"2025-08-19T14:30:00.000000Z","guild_id":"1234567890123456789","user_id":"42","command":"INSERT","database":"shop","table":"users","success":true}"#
+        );
+
+        let entry = AuditEntry::new(GuildId::new(1), UserId::new(2), "CREATE DB", false);
+        let json = entry.to_json();
+        assert_eq!((&json["database"], &json["table"], &json["success"]), (&serde_json::Value::Null, &serde_json::Value::Null, &json!(false)));
+    }
+
+    #[test]
+    fn test_append_line() {
+        let path = std::env::temp_dir().join(format!("sqlcord-audit-test-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        append_line(&path, r#"{"n":1}"#).unwrap();
+        append_line(&path, r#"{"n":2}"#).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"n\":1}\n{\"n\":2}\n");
+        std::fs::remove_file(&path).unwrap();
+        // A path that can't be opened reports the error instead of panicking
+        assert!(append_line(&std::env::temp_dir().join("missing-dir").join("audit.jsonl"), "{}").is_err());
+    }
+
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
 }
@@ -6,8 +6,14 @@ mod guards;
 mod render;
 mod services;
 mod utils;
+mod error;
+// insert_row and query are entry points for library users; the bot itself only creates through it
+#[allow(dead_code)]
+mod api;
 mod logging;
 mod sql_parser;
+#[cfg(test)]
+mod test_util;
 
 use dotenvy::dotenv;
 
@@ -16,7 +22,7 @@ async fn main() {
     // load .env
     dotenv().ok();
 
-    tracing_subscriber::fmt::init();
+    logging::init();
 
     let mut client = match bot::create_client_from_env().await {
         Ok(c) => c,
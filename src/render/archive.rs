@@ -0,0 +1,199 @@
+// Bundle several files into one ZIP attachment, and read them back
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+
+/// A ZIP archive built in memory. Files are stored uncompressed (method 0), which every unzip tool reads
+/// and which needs nothing beyond a CRC-32. Each file is written as soon as it's added, so callers can
+/// drop their copy right away.
+pub struct ZipArchive {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+    dos_time: u16,
+    dos_date: u16,
+}
+
+impl ZipArchive {
+    /// An empty archive whose files all carry the `modified` timestamp
+    pub fn new(modified: DateTime<Utc>) -> Self {
+        // MS-DOS timestamps start in 1980 and count seconds in steps of two
+        let year = modified.year().clamp(1980, 2107) as u16;
+        let dos_date = ((year - 1980) << 9) | ((modified.month() as u16) << 5) | modified.day() as u16;
+        let dos_time = ((modified.hour() as u16) << 11) | ((modified.minute() as u16) << 5) | (modified.second() as u16 / 2);
+        ZipArchive { data: Vec::new(), central_directory: Vec::new(), entries: 0, dos_time, dos_date }
+    }
+
+    /// Append `name` holding `contents`
+    pub fn add_file(&mut self, name: &str, contents: &[u8]) {
+        let offset = self.data.len() as u32;
+        let crc = crc32(contents);
+        let size = contents.len() as u32;
+        // Bit 11: file names are UTF-8
+        let (version, flags, method) = (20u16, 0x0800u16, 0u16);
+
+        push_u32(&mut self.data, 0x0403_4b50);
+        for field in [version, flags, method, self.dos_time, self.dos_date] {
+            push_u16(&mut self.data, field);
+        }
+        for field in [crc, size, size] {
+            push_u32(&mut self.data, field);
+        }
+        push_u16(&mut self.data, name.len() as u16);
+        push_u16(&mut self.data, 0);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(contents);
+
+        let entry = &mut self.central_directory;
+        push_u32(entry, 0x0201_4b50);
+        for field in [version, version, flags, method, self.dos_time, self.dos_date] {
+            push_u16(entry, field);
+        }
+        for field in [crc, size, size] {
+            push_u32(entry, field);
+        }
+        // Name length, then no extra field, comment, disk number or internal attributes
+        for field in [name.len() as u16, 0, 0, 0, 0] {
+            push_u16(entry, field);
+        }
+        push_u32(entry, 0);
+        push_u32(entry, offset);
+        entry.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+    }
+
+    /// The finished archive: every file, then the central directory listing them
+    pub fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.data.len() as u32;
+        let directory_size = self.central_directory.len() as u32;
+        self.data.append(&mut self.central_directory);
+
+        push_u32(&mut self.data, 0x0605_4b50);
+        for field in [0, 0, self.entries, self.entries] {
+            push_u16(&mut self.data, field);
+        }
+        push_u32(&mut self.data, directory_size);
+        push_u32(&mut self.data, directory_offset);
+        push_u16(&mut self.data, 0);
+        self.data
+    }
+}
+
+/// The files of a ZIP archive, in the order its central directory lists them. Only uncompressed files
+/// (as `ZipArchive` writes) can be read; anything else is an error naming the file.
+pub fn read_zip(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let truncated = || "The archive is truncated or not a ZIP file.".to_string();
+    // The end record is the last 22 bytes, unless the archive ends with a comment
+    let end = (0..=bytes.len().saturating_sub(22)).rev()
+        .find(|&at| read_u32(bytes, at) == Some(0x0605_4b50))
+        .ok_or_else(truncated)?;
+    let entries = read_u16(bytes, end + 10).ok_or_else(truncated)?;
+    let mut at = read_u32(bytes, end + 16).ok_or_else(truncated)? as usize;
+
+    let mut files = Vec::with_capacity(entries as usize);
+    for _ in 0..entries {
+        if read_u32(bytes, at) != Some(0x0201_4b50) {
+            return Err(truncated());
+        }
+        let method = read_u16(bytes, at + 10).ok_or_else(truncated)?;
+        let size = read_u32(bytes, at + 20).ok_or_else(truncated)? as usize;
+        let name_len = read_u16(bytes, at + 28).ok_or_else(truncated)? as usize;
+        let skipped = [30, 32].iter().map(|&offset| read_u16(bytes, at + offset).map(usize::from)).sum::<Option<usize>>().ok_or_else(truncated)?;
+        let local = read_u32(bytes, at + 42).ok_or_else(truncated)? as usize;
+        let name = bytes.get(at + 46..at + 46 + name_len).ok_or_else(truncated)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        at += 46 + name_len + skipped;
+
+        if method != 0 {
+            return Err(format!("`{}` is compressed; only uncompressed archives such as `/sql dump data` makes can be read.", name));
+        }
+        let header = [26, 28].iter().map(|&offset| read_u16(bytes, local + offset).map(usize::from)).sum::<Option<usize>>().ok_or_else(truncated)?;
+        let start = local + 30 + header;
+        let contents = bytes.get(start..start + size).ok_or_else(truncated)?;
+        files.push((name, contents.to_vec()));
+    }
+    Ok(files)
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn push_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+/// CRC-32 (IEEE), as ZIP stores for every file
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_zip_layout() {
+        let modified = DateTime::parse_from_rfc3339("2024-03-05T10:20:30Z").unwrap().with_timezone(&Utc);
+        let mut archive = ZipArchive::new(modified);
+        archive.add_file("users.csv", b"id,name\n1,Ann\n");
+        archive.add_file("schema.sql", b"");
+        let zip = archive.finish();
+
+        // The first file's local header, then its contents
+        let field = |at| read_u32(&zip, at).unwrap();
+        assert_eq!(field(0), 0x0403_4b50);
+        assert_eq!((read_u16(&zip, 10), read_u16(&zip, 12)), (Some((10 << 11) | (20 << 5) | 15), Some((44 << 9) | (3 << 5) | 5)));
+        assert_eq!(field(14), crc32(b"id,name\n1,Ann\n"));
+        assert_eq!(&zip[30..39], b"users.csv");
+        assert_eq!(&zip[39..53], b"id,name\n1,Ann\n");
+
+        // The end record points at a central directory listing both files
+        let end = zip.len() - 22;
+        assert_eq!(field(end), 0x0605_4b50);
+        assert_eq!(read_u16(&zip, end + 10), Some(2));
+        let directory = field(end + 16) as usize;
+        assert_eq!(field(directory), 0x0201_4b50);
+        assert_eq!(field(directory + 42), 0);
+        assert_eq!(&zip[directory + 46..directory + 55], b"users.csv");
+        assert_eq!(directory + field(end + 12) as usize, end);
+    }
+
+    #[test]
+    fn test_read_zip_round_trip() {
+        let mut archive = ZipArchive::new(Utc::now());
+        archive.add_file("users.csv", "id,naïve\n1,Ann\n".as_bytes());
+        archive.add_file("empty.csv", b"");
+        let zip = archive.finish();
+        let files = read_zip(&zip).unwrap();
+        assert_eq!(files, vec![("users.csv".to_string(), "id,naïve\n1,Ann\n".as_bytes().to_vec()), ("empty.csv".to_string(), Vec::new())]);
+
+        assert!(read_zip(b"not a zip").is_err());
+        assert!(read_zip(&zip[..zip.len() - 30]).is_err());
+        // Method 8 (deflate) in the first central directory entry
+        let mut compressed = zip.clone();
+        let directory = read_u32(&zip, zip.len() - 6).unwrap() as usize;
+        compressed[directory + 10] = 8;
+        assert!(read_zip(&compressed).unwrap_err().contains("`users.csv` is compressed"));
+    }
+}
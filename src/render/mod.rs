@@ -1,4 +1,72 @@
 pub mod reply;
 pub mod table;
+pub mod pages;
+pub mod archive;
+
+use serenity::builder::CreateAttachment;
+use pages::EMBED_DESCRIPTION_LIMIT;
 
 pub fn default_renderer() {}
+
+/// Cut `text` to at most `width` characters, marking the cut with `...`.
+/// Counts characters rather than bytes, so emoji and accented letters are never split.
+pub fn truncate_chars(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(width.saturating_sub(3)).collect();
+    format!("{}...", kept)
+}
+
+/// An embed description that fits Discord's limit, plus the full text as a file when it didn't
+pub struct FittedOutput {
+    pub description: String,
+    pub attachment: Option<CreateAttachment>,
+}
+
+/// Use `full` as the embed description if it fits; otherwise show `summary` and attach `full` as `file_name`.
+/// The summary is only built when needed and is cut to fit as well.
+pub fn fit_or_attach(full: String, summary: impl FnOnce() -> String, file_name: &str) -> FittedOutput {
+    if full.chars().count() <= EMBED_DESCRIPTION_LIMIT {
+        return FittedOutput { description: full, attachment: None };
+    }
+
+    let note = format!("\n\n📎 The full output is attached as `{}`.", file_name);
+    let room = EMBED_DESCRIPTION_LIMIT.saturating_sub(note.chars().count());
+    let mut description: String = summary().chars().take(room).collect();
+    description.push_str(&note);
+    FittedOutput {
+        description,
+        attachment: Some(CreateAttachment::bytes(full, file_name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("short", 10), "short");
+        assert_eq!(truncate_chars("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_chars("a much longer text", 10), "a much ...");
+        // Multibyte characters count once and are kept whole
+        assert_eq!(truncate_chars("🦀é🦀é🦀é🦀é🦀é🦀", 8), "🦀é🦀é🦀...");
+        assert_eq!(truncate_chars("🦀🦀🦀🦀", 2), "...");
+    }
+
+    #[test]
+    fn test_fit_or_attach() {
+        let fitted = fit_or_attach("small".to_string(), || unreachable!("summary is only built on overflow"), "out.txt");
+        assert_eq!(fitted.description, "small");
+        assert!(fitted.attachment.is_none());
+
+        let oversized = format!("```\n{}```", "| cell ".repeat(1000));
+        let fitted = fit_or_attach(oversized.clone(), || "x".repeat(5000), "out.txt");
+        assert!(fitted.description.chars().count() <= EMBED_DESCRIPTION_LIMIT);
+        assert!(fitted.description.ends_with("attached as `out.txt`."));
+        let attachment = fitted.attachment.unwrap();
+        assert_eq!(attachment.filename, "out.txt");
+        assert_eq!(attachment.data, oversized.into_bytes());
+    }
+}
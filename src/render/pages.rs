@@ -0,0 +1,88 @@
+// Split long output into Discord-sized messages
+
+/// Discord's maximum message length
+pub const MESSAGE_CHAR_LIMIT: usize = 2000;
+
+/// Discord's maximum embed description length
+pub const EMBED_DESCRIPTION_LIMIT: usize = 4096;
+
+/// Pack lines into code-block messages of at most `limit` characters each.
+/// Lines never span two messages unless a single line is too long on its own,
+/// in which case it is cut into pieces.
+pub fn paginate_code_block(language: &str, lines: &[String], limit: usize) -> Vec<String> {
+    let open = format!("```{}\n", language);
+    let close = "```";
+    let capacity = limit.saturating_sub(open.chars().count() + close.len()).max(1);
+
+    let mut pages = Vec::new();
+    let mut body = String::new();
+    let mut body_len = 0;
+
+    for line in lines {
+        for piece in split_chars(line, capacity - 1) {
+            let piece_len = piece.chars().count() + 1; // + newline
+            if body_len + piece_len > capacity && !body.is_empty() {
+                pages.push(format!("{}{}{}", open, body, close));
+                body.clear();
+                body_len = 0;
+            }
+            body.push_str(&piece);
+            body.push('\n');
+            body_len += piece_len;
+        }
+    }
+    if !body.is_empty() {
+        pages.push(format!("{}{}{}", open, body, close));
+    }
+    pages
+}
+
+/// Cut a line into pieces of at most `max` characters (an empty line stays one piece)
+fn split_chars(line: &str, max: usize) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars.chunks(max.max(1)).map(|chunk| chunk.iter().collect()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(count: usize, width: usize) -> Vec<String> {
+        (0..count).map(|i| format!("{:0width$}", i, width = width)).collect()
+    }
+
+    #[test]
+    fn test_pages_respect_limit_and_keep_lines_whole() {
+        let rows = lines(500, 30);
+        let pages = paginate_code_block("json", &rows, MESSAGE_CHAR_LIMIT);
+
+        assert!(pages.len() > 1);
+        for page in &pages {
+            assert!(page.chars().count() <= MESSAGE_CHAR_LIMIT);
+            assert!(page.starts_with("```json\n") && page.ends_with("```"));
+        }
+
+        // Every line appears exactly once, in order
+        let joined: Vec<&str> = pages.iter()
+            .flat_map(|page| page.trim_start_matches("```json\n").trim_end_matches("```").lines())
+            .collect();
+        assert_eq!(joined, rows.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_overlong_line_is_split() {
+        let rows = vec!["x".repeat(5000)];
+        let pages = paginate_code_block("", &rows, MESSAGE_CHAR_LIMIT);
+        assert_eq!(pages.len(), 3);
+        assert!(pages.iter().all(|page| page.chars().count() <= MESSAGE_CHAR_LIMIT));
+    }
+
+    #[test]
+    fn test_small_output_is_one_page() {
+        let pages = paginate_code_block("csv", &["id".to_string(), "1".to_string()], MESSAGE_CHAR_LIMIT);
+        assert_eq!(pages, vec!["```csv\nid\n1\n```"]);
+    }
+}
@@ -1,3 +1,130 @@
-// Pretty-print rows as embeds or files
+// Pretty-print rows as fixed-width text tables
 
-pub fn render_table() {}
+use super::truncate_chars;
+
+/// Columns never render narrower than this
+const MIN_COL_WIDTH: usize = 3;
+/// Wider values are truncated with `...` to keep tables readable
+pub const DEFAULT_MAX_COL_WIDTH: usize = 50;
+
+/// Widest a column may render before values are truncated.
+/// Configurable through the `SQLCORD_MAX_COL_WIDTH` environment variable.
+pub fn max_col_width() -> usize {
+    std::env::var("SQLCORD_MAX_COL_WIDTH")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|width| *width >= MIN_COL_WIDTH)
+        .unwrap_or(DEFAULT_MAX_COL_WIDTH)
+}
+
+/// Render rows as an aligned text table:
+///
+/// ```text
+/// id  | name
+/// -----------
+/// 1   | 'Ann'
+/// ```
+///
+/// Widths are measured in characters and capped at `max_col_width()`; cells missing
+/// from a short row render empty. The caller wraps the result in a code block.
+pub fn render_table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let widths = column_widths(headers, rows);
+    let mut output = String::new();
+
+    push_row(&mut output, headers, &widths);
+
+    // Separator line: 3 chars per " | " between columns
+    let total_width = widths.iter().sum::<usize>() + widths.len().saturating_sub(1) * 3;
+    output.push_str(&"-".repeat(total_width));
+    output.push('\n');
+
+    for row in rows {
+        push_row(&mut output, row, &widths);
+    }
+    output
+}
+
+/// Whether any cell is wider than `max_col_width()` and will be truncated
+pub fn has_truncated_cells(rows: &[Vec<String>]) -> bool {
+    let max_width = max_col_width();
+    rows.iter().flatten().any(|cell| cell.chars().count() > max_width)
+}
+
+/// Width of each column: the widest of header and cells, within the min/max bounds
+fn column_widths(headers: &[String], rows: &[Vec<String>]) -> Vec<usize> {
+    let max_width = max_col_width();
+    headers.iter().enumerate()
+        .map(|(i, header)| {
+            let widest_cell = rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0);
+            header.chars().count().max(widest_cell).clamp(MIN_COL_WIDTH, max_width)
+        })
+        .collect()
+}
+
+fn push_row(output: &mut String, cells: &[String], widths: &[usize]) {
+    let line = widths.iter().enumerate()
+        .map(|(i, &width)| {
+            let cell = truncate_chars(cells.get(i).map_or("", String::as_str), width);
+            format!("{:<width$}", cell, width = width)
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+    output.push_str(line.trim_end());
+    output.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_render_table_widths() {
+        let headers = strings(&["id", "name"]);
+        let rows = vec![strings(&["1", "'Ann'"]), strings(&["10", "'Benjamin'"])];
+        let table = render_table(&headers, &rows);
+
+        assert_eq!(table, "\
+id  | name
+----------------
+1   | 'Ann'
+10  | 'Benjamin'
+");
+    }
+
+    #[test]
+    fn test_column_widths_bounds() {
+        // Short headers are padded to the minimum, wide cells are capped
+        let headers = strings(&["a", "b"]);
+        let rows = vec![vec!["x".to_string(), "y".repeat(80)]];
+        assert_eq!(column_widths(&headers, &rows), vec![MIN_COL_WIDTH, max_col_width()]);
+    }
+
+    #[test]
+    fn test_render_table_truncates_long_values() {
+        let headers = strings(&["text"]);
+        let long_value = "é".repeat(60);
+        let rows = vec![vec![long_value]];
+        let table = render_table(&headers, &rows);
+
+        let data_line = table.lines().nth(2).unwrap();
+        assert_eq!(data_line.chars().count(), max_col_width());
+        assert!(data_line.ends_with("..."));
+        assert!(has_truncated_cells(&rows));
+        assert!(!has_truncated_cells(&[strings(&["short"])]));
+    }
+
+    #[test]
+    fn test_render_table_short_rows() {
+        let headers = strings(&["a", "b"]);
+        let table = render_table(&headers, &[strings(&["1"])]);
+        assert_eq!(table.lines().nth(2), Some("1   |"));
+    }
+}
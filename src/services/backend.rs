@@ -0,0 +1,68 @@
+// The Discord operations SQLcord's storage is built on
+
+use std::collections::HashMap;
+use std::future::Future;
+use serenity::builder::{CreateChannel, CreateMessage, EditChannel, EditMessage, GetMessages};
+use serenity::http::Http;
+use serenity::model::channel::{GuildChannel, Message};
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+
+/// Channel and message storage: categories and channels hold databases and tables, messages hold rows.
+/// Implemented for serenity's `Http`; `test_util::FakeDiscord` keeps everything in memory instead.
+pub trait Backend: Send + Sync {
+    fn list_channels(&self, guild_id: GuildId) -> impl Future<Output = serenity::Result<HashMap<ChannelId, GuildChannel>>> + Send;
+
+    /// A guild channel by ID. A channel that exists but isn't a guild channel is an error.
+    fn get_channel(&self, channel_id: ChannelId) -> impl Future<Output = serenity::Result<GuildChannel>> + Send;
+
+    fn create_channel(&self, guild_id: GuildId, channel: CreateChannel<'_>) -> impl Future<Output = serenity::Result<GuildChannel>> + Send;
+
+    fn edit_channel(&self, channel_id: ChannelId, changes: EditChannel<'_>) -> impl Future<Output = serenity::Result<GuildChannel>> + Send;
+
+    fn delete_channel(&self, channel_id: ChannelId) -> impl Future<Output = serenity::Result<()>> + Send;
+
+    /// Up to `limit` messages (at most 100), newest first, optionally only those older than `before`
+    fn fetch_messages(&self, channel_id: ChannelId, limit: u8, before: Option<MessageId>) -> impl Future<Output = serenity::Result<Vec<Message>>> + Send;
+
+    fn send_message(&self, channel_id: ChannelId, message: CreateMessage) -> impl Future<Output = serenity::Result<Message>> + Send;
+
+    fn edit_message(&self, channel_id: ChannelId, message_id: MessageId, changes: EditMessage) -> impl Future<Output = serenity::Result<Message>> + Send;
+}
+
+impl Backend for Http {
+    async fn list_channels(&self, guild_id: GuildId) -> serenity::Result<HashMap<ChannelId, GuildChannel>> {
+        guild_id.channels(self).await
+    }
+
+    async fn get_channel(&self, channel_id: ChannelId) -> serenity::Result<GuildChannel> {
+        channel_id.to_channel(self).await?.guild().ok_or(serenity::Error::Other("not a guild channel"))
+    }
+
+    async fn create_channel(&self, guild_id: GuildId, channel: CreateChannel<'_>) -> serenity::Result<GuildChannel> {
+        guild_id.create_channel(self, channel).await
+    }
+
+    async fn edit_channel(&self, channel_id: ChannelId, changes: EditChannel<'_>) -> serenity::Result<GuildChannel> {
+        channel_id.edit(self, changes).await
+    }
+
+    async fn delete_channel(&self, channel_id: ChannelId) -> serenity::Result<()> {
+        channel_id.delete(self).await.map(|_| ())
+    }
+
+    async fn fetch_messages(&self, channel_id: ChannelId, limit: u8, before: Option<MessageId>) -> serenity::Result<Vec<Message>> {
+        let mut request = GetMessages::new().limit(limit);
+        if let Some(before) = before {
+            request = request.before(before);
+        }
+        channel_id.messages(self, request).await
+    }
+
+    async fn send_message(&self, channel_id: ChannelId, message: CreateMessage) -> serenity::Result<Message> {
+        channel_id.send_message(self, message).await
+    }
+
+    async fn edit_message(&self, channel_id: ChannelId, message_id: MessageId, changes: EditMessage) -> serenity::Result<Message> {
+        channel_id.edit_message(self, message_id, changes).await
+    }
+}
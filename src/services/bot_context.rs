@@ -0,0 +1,29 @@
+// What the storage engine needs from a serenity `Context`
+
+use std::sync::Arc;
+use serenity::http::Http;
+use serenity::prelude::{Context, RwLock, TypeMap};
+use super::backend::Backend;
+
+/// The Discord backend and the shared session data. Storage paths (`create table`, `insert`, `select`
+/// and what they call) take `&impl BotContext` instead of `&Context`, so tests can run them against
+/// an in-memory Discord (`test_util::TestContext`) without a gateway connection.
+pub trait BotContext: Clone + Send + Sync + 'static {
+    type Backend: Backend;
+
+    fn backend(&self) -> &Self::Backend;
+    /// The TypeMap holding `CurrentDB`, the channel cache and the other `state` entries
+    fn data(&self) -> &Arc<RwLock<TypeMap>>;
+}
+
+impl BotContext for Context {
+    type Backend = Http;
+
+    fn backend(&self) -> &Http {
+        &self.http
+    }
+
+    fn data(&self) -> &Arc<RwLock<TypeMap>> {
+        &self.data
+    }
+}
@@ -0,0 +1,105 @@
+// Short-lived per-guild cache of the channel list
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use serenity::model::channel::GuildChannel;
+use serenity::model::id::{ChannelId, GuildId};
+use super::backend::Backend;
+use super::bot_context::BotContext;
+use crate::state::GuildChannelCache;
+
+/// How long a fetched channel list is reused before asking Discord again
+pub const CHANNEL_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Channel lists keyed by guild, each stamped with the time it was fetched.
+/// Callers pass `now` so expiry can be tested without waiting.
+pub struct ChannelCache {
+    ttl: Duration,
+    entries: HashMap<GuildId, (Instant, HashMap<ChannelId, GuildChannel>)>,
+}
+
+impl ChannelCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: HashMap::new() }
+    }
+
+    /// Cached channels for a guild, or `None` if missing or older than the TTL
+    pub fn get(&self, guild_id: GuildId, now: Instant) -> Option<&HashMap<ChannelId, GuildChannel>> {
+        self.entries.get(&guild_id)
+            .filter(|(fetched_at, _)| now.saturating_duration_since(*fetched_at) < self.ttl)
+            .map(|(_, channels)| channels)
+    }
+
+    pub fn insert(&mut self, guild_id: GuildId, channels: HashMap<ChannelId, GuildChannel>, now: Instant) {
+        self.entries.insert(guild_id, (now, channels));
+    }
+
+    /// Drop a guild's cached channels, e.g. after a channel was created or deleted
+    pub fn invalidate(&mut self, guild_id: GuildId) {
+        self.entries.remove(&guild_id);
+    }
+}
+
+impl Default for ChannelCache {
+    fn default() -> Self {
+        Self::new(CHANNEL_CACHE_TTL)
+    }
+}
+
+/// The guild's channels, served from the cache while fresh and fetched from Discord otherwise
+pub async fn get_channels_cached(ctx: &impl BotContext, guild_id: GuildId) -> serenity::Result<HashMap<ChannelId, GuildChannel>> {
+    let cache = ctx.data().read().await.get::<GuildChannelCache>().cloned();
+    let Some(cache) = cache else {
+        return ctx.backend().list_channels(guild_id).await;
+    };
+
+    if let Some(channels) = cache.lock().await.get(guild_id, Instant::now()) {
+        return Ok(channels.clone());
+    }
+
+    // Fetch without holding the lock so other guilds aren't blocked on this request
+    let channels = ctx.backend().list_channels(guild_id).await?;
+    cache.lock().await.insert(guild_id, channels.clone(), Instant::now());
+    Ok(channels)
+}
+
+/// Forget the guild's cached channels; call after creating, deleting or renaming channels
+pub async fn invalidate_channels(ctx: &impl BotContext, guild_id: GuildId) {
+    let cache = ctx.data().read().await.get::<GuildChannelCache>().cloned();
+    if let Some(cache) = cache {
+        cache.lock().await.invalidate(guild_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_cache_hit_miss_expiry() {
+        let guild_id = GuildId::new(1);
+        let start = Instant::now();
+        let mut cache = ChannelCache::new(Duration::from_secs(10));
+
+        // Miss before anything was fetched
+        assert!(cache.get(guild_id, start).is_none());
+
+        cache.insert(guild_id, HashMap::new(), start);
+        assert!(cache.get(guild_id, start + Duration::from_secs(9)).is_some());
+        assert!(cache.get(GuildId::new(2), start).is_none());
+
+        // Expired once the TTL has passed
+        assert!(cache.get(guild_id, start + Duration::from_secs(10)).is_none());
+    }
+
+    #[test]
+    fn test_channel_cache_invalidate() {
+        let guild_id = GuildId::new(1);
+        let now = Instant::now();
+        let mut cache = ChannelCache::default();
+
+        cache.insert(guild_id, HashMap::new(), now);
+        cache.invalidate(guild_id);
+        assert!(cache.get(guild_id, now).is_none());
+    }
+}
@@ -1,5 +1,182 @@
 // Discord filesystem helpers: categories, channels, messages
 
-pub async fn find_category() {}
+use std::collections::HashMap;
+use serenity::model::channel::{ChannelType, GuildChannel, Message};
+use serenity::model::id::ChannelId;
+use crate::services::backend::Backend;
+use crate::services::shards::is_shard_channel;
+use crate::services::throttle::{with_retry, Throttle};
+use crate::utils::{DB_PREFIX, TABLE_PREFIX};
+
+/// Discord returns at most 100 messages per request
+const MESSAGES_PER_PAGE: u8 = 100;
+
+/// Default cap on row messages read from a table channel
+pub const DEFAULT_MAX_ROW_MESSAGES: usize = 1000;
+
+/// Messages read from a channel, newest first
+pub struct FetchedMessages {
+    pub messages: Vec<Message>,
+    /// The cap was reached before the channel was exhausted
+    pub truncated: bool,
+}
+
+/// Maximum number of row messages to read per table scan.
+/// Configurable through the `SQLCORD_MAX_ROWS` environment variable.
+pub fn max_row_messages() -> usize {
+    std::env::var("SQLCORD_MAX_ROWS")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|max| *max > 0)
+        .unwrap_or(DEFAULT_MAX_ROW_MESSAGES)
+}
+
+/// Fetch up to `max` messages from a channel, paging backwards with `before(...)` until the channel is exhausted
+pub async fn fetch_all_messages(backend: &impl Backend, channel_id: ChannelId, max: usize) -> serenity::Result<FetchedMessages> {
+    let mut messages: Vec<Message> = Vec::new();
+    if max == 0 {
+        return Ok(FetchedMessages { messages, truncated: false });
+    }
+
+    loop {
+        let remaining = max - messages.len();
+        let page_size = remaining.min(MESSAGES_PER_PAGE as usize) as u8;
+
+        let before = messages.last().map(|oldest| oldest.id);
+
+        // Large tables take many page requests; back off instead of failing the whole read on a 429
+        let page = with_retry(&Throttle::default(), || backend.fetch_messages(channel_id, page_size, before)).await?;
+        let page_len = page.len();
+        messages.extend(page);
+
+        // A short page means there is nothing older left to read
+        if page_len < page_size as usize {
+            return Ok(FetchedMessages { messages, truncated: false });
+        }
+        if messages.len() >= max {
+            return Ok(FetchedMessages { messages, truncated: true });
+        }
+    }
+}
+
+/// Find the `db_<db_name>` category among a guild's channels
+pub fn find_database_category<'a>(channels: &'a HashMap<ChannelId, GuildChannel>, db_name: &str) -> Option<&'a GuildChannel> {
+    let category_name = format!("{}{}", DB_PREFIX, db_name);
+    channels.values().find(|c| c.kind == ChannelType::Category && c.name == category_name)
+}
+
+/// Find the `table_<table_name>` channel inside a database category
+pub fn find_table_channel<'a>(channels: &'a HashMap<ChannelId, GuildChannel>, category_id: ChannelId, table_name: &str) -> Option<&'a GuildChannel> {
+    let channel_name = format!("{}{}", TABLE_PREFIX, table_name);
+    channels.values().find(|c| c.parent_id == Some(category_id) && c.name == channel_name)
+}
+
+/// `db_` database categories, sorted by name
+pub fn list_databases(channels: &HashMap<ChannelId, GuildChannel>) -> Vec<&GuildChannel> {
+    let mut databases: Vec<&GuildChannel> = channels.values()
+        .filter(|c| c.kind == ChannelType::Category && c.name.starts_with(DB_PREFIX))
+        .collect();
+    databases.sort_by(|a, b| a.name.cmp(&b.name));
+    databases
+}
+
+/// Table channels inside a database category, sorted by name. The extra channels of sharded tables aren't tables.
+pub fn list_tables(channels: &HashMap<ChannelId, GuildChannel>, category_id: ChannelId) -> Vec<&GuildChannel> {
+    let mut tables: Vec<&GuildChannel> = channels.values()
+        .filter(|c| c.parent_id == Some(category_id) && c.kind == ChannelType::Text && c.name.starts_with(TABLE_PREFIX))
+        .filter(|c| !is_shard_channel(c))
+        .collect();
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    tables
+}
 
 pub async fn create_category() {}
+
+/// Build a guild channel for tests (`kind` 0 = text, 4 = category)
+#[cfg(test)]
+pub(crate) fn test_channel(id: u64, name: &str, kind: u8, parent_id: Option<u64>) -> GuildChannel {
+    serde_json::from_value(serde_json::json!({
+        "id": id.to_string(),
+        "guild_id": "1",
+        "name": name,
+        "type": kind,
+        "position": 0,
+        "parent_id": parent_id.map(|id| id.to_string()),
+        "permission_overwrites": [],
+        "nsfw": false
+    })).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two databases, each with a `users` table, plus an unrelated text channel
+    fn sample_channels() -> HashMap<ChannelId, GuildChannel> {
+        [
+            test_channel(10, "db_shop", 4, None),
+            test_channel(11, "table_users", 0, Some(10)),
+            test_channel(12, "table_orders", 0, Some(10)),
+            test_channel(13, "general", 0, Some(10)),
+            test_channel(20, "db_blog", 4, None),
+            test_channel(21, "table_users", 0, Some(20)),
+            test_channel(30, "db_fake", 0, None),
+        ]
+        .into_iter()
+        .map(|c| (c.id, c))
+        .collect()
+    }
+
+    #[test]
+    fn test_find_database_category() {
+        let channels = sample_channels();
+        assert_eq!(find_database_category(&channels, "shop").map(|c| c.id), Some(ChannelId::new(10)));
+        // A text channel with a db_ name is not a database
+        assert!(find_database_category(&channels, "fake").is_none());
+        assert!(find_database_category(&channels, "missing").is_none());
+    }
+
+    #[test]
+    fn test_find_table_channel() {
+        let channels = sample_channels();
+        assert_eq!(find_table_channel(&channels, ChannelId::new(10), "users").map(|c| c.id), Some(ChannelId::new(11)));
+        assert_eq!(find_table_channel(&channels, ChannelId::new(20), "users").map(|c| c.id), Some(ChannelId::new(21)));
+        assert!(find_table_channel(&channels, ChannelId::new(20), "orders").is_none());
+    }
+
+    #[test]
+    fn test_list_tables() {
+        let channels = sample_channels();
+        let names: Vec<&str> = list_tables(&channels, ChannelId::new(10)).iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["table_orders", "table_users"]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_all_messages_pages_past_the_limit() {
+        use serenity::builder::{CreateChannel, CreateMessage};
+        use crate::test_util::{FakeDiscord, TEST_GUILD_ID};
+
+        let discord = FakeDiscord::default();
+        let channel = discord.create_channel(TEST_GUILD_ID, CreateChannel::new("table_logs")).await.unwrap();
+        for i in 0..250 {
+            discord.send_message(channel.id, CreateMessage::new().content(i.to_string())).await.unwrap();
+        }
+
+        // Three pages, newest first, and nothing left over
+        let fetched = fetch_all_messages(&discord, channel.id, 1000).await.unwrap();
+        assert_eq!((fetched.messages.len(), fetched.truncated), (250, false));
+        assert_eq!((fetched.messages[0].content.as_str(), fetched.messages[249].content.as_str()), ("249", "0"));
+
+        // The cap stops the scan mid-page
+        let fetched = fetch_all_messages(&discord, channel.id, 120).await.unwrap();
+        assert_eq!((fetched.messages.len(), fetched.truncated), (120, true));
+        assert_eq!(fetched.messages[119].content, "130");
+    }
+
+    #[test]
+    fn test_list_databases() {
+        let channels = sample_channels();
+        let names: Vec<&str> = list_databases(&channels).iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["db_blog", "db_shop"]);
+    }
+}
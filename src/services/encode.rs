@@ -1,5 +1,61 @@
 // Encode/decode row data into Discord messages
 
+/// Escape a string value for the line-based row format.
+/// Backslashes and line breaks are escaped so a value always stays on its `  column: value` line.
+pub fn encode_stored_string(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            '\r' => encoded.push_str("\\r"),
+            _ => encoded.push(ch),
+        }
+    }
+    encoded
+}
+
+/// Reverse `encode_stored_string`.
+/// Unknown escape sequences are kept verbatim so rows written before escaping was introduced still read back.
+pub fn decode_stored_string(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            decoded.push(ch);
+            continue;
+        }
+        match chars.peek() {
+            Some('\\') => { chars.next(); decoded.push('\\'); }
+            Some('n') => { chars.next(); decoded.push('\n'); }
+            Some('r') => { chars.next(); decoded.push('\r'); }
+            _ => decoded.push('\\'),
+        }
+    }
+    decoded
+}
+
 pub fn encode_row() {}
 
 pub fn decode_row() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_string_round_trip() {
+        for value in ["plain", "line one\nline two", "windows\r\nline", "back\\slash", "literal \\n text", ""] {
+            let encoded = encode_stored_string(value);
+            assert!(!encoded.contains('\n') && !encoded.contains('\r'));
+            assert_eq!(decode_stored_string(&encoded), value);
+        }
+    }
+
+    #[test]
+    fn test_decode_keeps_unknown_escapes() {
+        // Rows stored before escaping existed may contain raw backslashes
+        assert_eq!(decode_stored_string("C:\\path\\to"), "C:\\path\\to");
+        assert_eq!(decode_stored_string("trailing\\"), "trailing\\");
+    }
+}
@@ -1,6 +1,17 @@
 // Services re-exports
+pub mod backend;
+pub mod bot_context;
 pub mod discord_fs;
 pub mod encode;
+pub mod channel_cache;
+pub mod shards;
+pub mod throttle;
+pub mod topic_meta;
+
+pub use discord_fs::{find_database_category, find_table_channel, list_databases, list_tables};
+pub use backend::Backend;
+pub use bot_context::BotContext;
+pub use channel_cache::{get_channels_cached, invalidate_channels};
 
 pub struct Services {
     // placeholder for DI
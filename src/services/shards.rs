@@ -0,0 +1,194 @@
+// Sharded tables: rows spread over several channels so no single channel grows too slow to page
+//
+// A table created with `shards:N` keeps its own `table_<name>` channel as shard 0, with `Shards: N` in the
+// topic, plus channels `table_<name>_1` .. `table_<name>_{N-1}` whose topics only say `Shard: <index>`.
+// Each row lives in the shard its primary key hashes to (the whole row, without a primary key).
+
+use serenity::model::channel::GuildChannel;
+use crate::error::SqlError;
+use crate::sql_parser::{format_float, ColumnDefinition, SqlValue};
+use super::backend::Backend;
+use super::bot_context::BotContext;
+use super::channel_cache::get_channels_cached;
+use super::discord_fs::{fetch_all_messages, FetchedMessages};
+use super::throttle::{run_bulk, with_retry, Throttle};
+use super::topic_meta::TopicMeta;
+
+/// Most channels one table may be sharded over; a Discord category holds at most 50 channels
+pub const MAX_SHARDS: usize = 8;
+
+/// Name of shard `shard` of the table whose own channel is `table_channel`; shard 0 is the table's channel
+pub fn shard_channel_name(table_channel: &str, shard: usize) -> String {
+    if shard == 0 { table_channel.to_string() } else { format!("{}_{}", table_channel, shard) }
+}
+
+/// How many channels the table's rows are spread over (1 when it isn't sharded)
+pub fn shard_count(table: &GuildChannel) -> usize {
+    TopicMeta::parse(table.topic.as_deref()).shards.filter(|shards| *shards > 1).unwrap_or(1)
+}
+
+/// Whether `channel` is an extra shard channel of some table rather than a table itself
+pub fn is_shard_channel(channel: &GuildChannel) -> bool {
+    TopicMeta::parse(channel.topic.as_deref()).shard.is_some()
+}
+
+/// The values a row is placed by: its primary key columns, or the whole row when the table has none
+pub fn shard_key<'a>(values: &'a [SqlValue], schema: &[ColumnDefinition]) -> Vec<&'a SqlValue> {
+    let key: Vec<&SqlValue> = schema.iter()
+        .zip(values)
+        .filter(|(column, _)| column.primary_key)
+        .map(|(_, value)| value)
+        .collect();
+    if key.is_empty() { values.iter().collect() } else { key }
+}
+
+/// The shard (0-based, below `shards`) a row with shard key `key` belongs in. The hash (FNV-1a) is fixed
+/// rather than std's randomly seeded one, so a key lands in the same shard on every run and every version.
+pub fn resolve_shard(key: &[&SqlValue], shards: usize) -> usize {
+    if shards <= 1 {
+        return 0;
+    }
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for value in key {
+        // Values are coerced to their column types before this, so `5` and `5.0` never meet in one column
+        let encoded = match value {
+            SqlValue::Null => "n".to_string(),
+            SqlValue::Integer(i) => format!("i{}", i),
+            SqlValue::Float(f) => format!("f{}", format_float(*f)),
+            SqlValue::String(s) => format!("s{}", s),
+            SqlValue::Boolean(b) => format!("b{}", b),
+        };
+        // 0xFF never occurs in UTF-8, so it separates values unambiguously
+        for byte in encoded.bytes().chain([0xFF]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    (hash % shards as u64) as usize
+}
+
+/// The table's channels in shard order, starting with the table's own channel.
+/// A shard channel that has gone missing is left out with a warning.
+pub async fn table_shards(ctx: &impl BotContext, table: &GuildChannel) -> serenity::Result<Vec<GuildChannel>> {
+    let shards = shard_count(table);
+    if shards == 1 {
+        return Ok(vec![table.clone()]);
+    }
+    let channels = get_channels_cached(ctx, table.guild_id).await?;
+    let mut found = vec![table.clone()];
+    for shard in 1..shards {
+        let name = shard_channel_name(&table.name, shard);
+        match channels.values().find(|c| c.parent_id == table.parent_id && c.name == name) {
+            Some(channel) => found.push(channel.clone()),
+            None => tracing::warn!("Shard channel {} of {} is missing; its rows are skipped", name, table.name),
+        }
+    }
+    Ok(found)
+}
+
+/// The channel a row with `values` is stored in
+pub async fn shard_for_row(ctx: &impl BotContext, table: &GuildChannel, values: &[SqlValue], schema: &[ColumnDefinition]) -> Result<GuildChannel, SqlError> {
+    let shard = resolve_shard(&shard_key(values, schema), shard_count(table));
+    if shard == 0 {
+        return Ok(table.clone());
+    }
+    let channels = match get_channels_cached(ctx, table.guild_id).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            tracing::error!("Failed to get channels: {e}");
+            return Err(SqlError::DiscordRequest { operation: "list channels" });
+        }
+    };
+    let name = shard_channel_name(&table.name, shard);
+    channels.values()
+        .find(|c| c.parent_id == table.parent_id && c.name == name)
+        .cloned()
+        .ok_or(SqlError::ShardMissing { channel: name })
+}
+
+/// Delete every channel of a table: the other shards first (side by side), so a failure leaves the table
+/// itself in place
+pub async fn delete_table_channels(ctx: &impl BotContext, table: &GuildChannel) -> serenity::Result<()> {
+    let others: Vec<_> = table_shards(ctx, table).await?.iter().skip(1).map(|channel| channel.id).collect();
+    let bulk_ctx = ctx.clone();
+    let deleted = run_bulk(Throttle::default(), others, move |channel_id| {
+        let ctx = bulk_ctx.clone();
+        async move { ctx.backend().delete_channel(channel_id).await }
+    })
+    .await;
+    deleted.into_iter().collect::<serenity::Result<Vec<()>>>()?;
+    with_retry(&Throttle::default(), || ctx.backend().delete_channel(table.id)).await
+}
+
+/// Up to `max` row messages of a table across all its shards, newest first as from a single channel
+pub async fn fetch_table_messages(ctx: &impl BotContext, table: &GuildChannel, max: usize) -> serenity::Result<FetchedMessages> {
+    let shards = table_shards(ctx, table).await?;
+    if let [channel] = shards.as_slice() {
+        return fetch_all_messages(ctx.backend(), channel.id, max).await;
+    }
+
+    let mut messages = Vec::new();
+    let mut truncated = false;
+    for shard in &shards {
+        let fetched = fetch_all_messages(ctx.backend(), shard.id, max).await?;
+        truncated |= fetched.truncated;
+        messages.extend(fetched.messages);
+    }
+    // Message IDs grow with time, so this interleaves the shards back into insertion order
+    messages.sort_by_key(|message| std::cmp::Reverse(message.id));
+    if messages.len() > max {
+        messages.truncate(max);
+        truncated = true;
+    }
+    Ok(FetchedMessages { messages, truncated })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_parser::parse_column_definitions;
+
+    #[test]
+    fn test_shard_assignment_is_deterministic() {
+        let one = SqlValue::Integer(1);
+        let shard = resolve_shard(&[&one], 4);
+        for _ in 0..10 {
+            assert_eq!(resolve_shard(&[&SqlValue::Integer(1)], 4), shard);
+        }
+        // Pinned, so a change to the hash (which would strand existing rows) fails here
+        let placed: Vec<usize> = (1..=8).map(|i| resolve_shard(&[&SqlValue::Integer(i)], 4)).collect();
+        assert_eq!(placed, vec![0, 3, 2, 1, 0, 3, 2, 1]);
+        assert_eq!(resolve_shard(&[&SqlValue::String("ann".to_string())], 3), 0);
+
+        // Every key fits, and keys spread over all shards
+        let mut used = [false; 5];
+        for i in 0..200 {
+            let shard = resolve_shard(&[&SqlValue::Integer(i)], 5);
+            used[shard] = true;
+        }
+        assert!(used.iter().all(|used| *used));
+        assert_eq!(resolve_shard(&[&one], 1), 0);
+        assert_eq!(resolve_shard(&[&one], 0), 0);
+
+        // Value boundaries count: ("ab", "c") and ("a", "bc") are different keys
+        let (ab, c, a, bc) = (SqlValue::String("ab".into()), SqlValue::String("c".into()), SqlValue::String("a".into()), SqlValue::String("bc".into()));
+        assert_ne!(resolve_shard(&[&ab, &c], 1 << 20), resolve_shard(&[&a, &bc], 1 << 20));
+    }
+
+    #[test]
+    fn test_shard_key() {
+        let schema = parse_column_definitions("id INT PRIMARY KEY, name VARCHAR(10)").unwrap();
+        let row = vec![SqlValue::Integer(7), SqlValue::String("Ann".to_string())];
+        assert_eq!(shard_key(&row, &schema), vec![&row[0]]);
+        // Renaming a row doesn't move it
+        let renamed = vec![SqlValue::Integer(7), SqlValue::String("Bo".to_string())];
+        assert_eq!(resolve_shard(&shard_key(&row, &schema), 4), resolve_shard(&shard_key(&renamed, &schema), 4));
+
+        let keyless = parse_column_definitions("id INT, name VARCHAR(10)").unwrap();
+        assert_eq!(shard_key(&row, &keyless), vec![&row[0], &row[1]]);
+        assert_eq!(shard_key(&row, &[]).len(), 2);
+
+        assert_eq!(shard_channel_name("table_users", 0), "table_users");
+        assert_eq!(shard_channel_name("table_users", 2), "table_users_2");
+    }
+}
@@ -0,0 +1,224 @@
+// Rate-limit-aware execution of Discord API calls: retries on 429 and bounded concurrency for bulk work
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use serenity::http::{HttpError, StatusCode};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How bulk Discord operations are throttled and retried
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throttle {
+    /// Requests in flight at once
+    pub concurrency: usize,
+    /// Retries after a rate-limited attempt before giving up
+    pub max_retries: u32,
+    /// First backoff when Discord doesn't say how long to wait; doubles on every retry
+    pub base_delay: Duration,
+    /// Upper bound for any single wait
+    pub max_delay: Duration,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Throttle {
+            concurrency: 4,
+            max_retries: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl Throttle {
+    /// Wait before retry number `retry` (0-based): Discord's retry-after when known, else exponential backoff
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let delay = retry_after.unwrap_or_else(|| self.base_delay.saturating_mul(2u32.saturating_pow(retry)));
+        delay.min(self.max_delay)
+    }
+}
+
+/// Errors that can tell whether a request was rejected by a rate limit
+pub trait RateLimitError {
+    fn is_rate_limited(&self) -> bool;
+
+    /// How long Discord asked us to wait, if it said
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Serenity waits out a 429's `retry-after` itself and sends the request again, so a 429 only gets here
+/// when Discord gave no usable wait; serenity's `ErrorResponse` keeps neither the headers nor the body's
+/// `retry_after`. Those fall back to the `Throttle` backoff.
+impl RateLimitError for serenity::Error {
+    fn is_rate_limited(&self) -> bool {
+        matches!(
+            self,
+            serenity::Error::Http(HttpError::UnsuccessfulRequest(response)) if response.status_code == StatusCode::TOO_MANY_REQUESTS
+        )
+    }
+}
+
+/// Run `operation`, retrying it while it fails with a rate limit, up to `throttle.max_retries` times
+pub async fn with_retry<T, E, F, Fut>(throttle: &Throttle, mut operation: F) -> Result<T, E>
+where
+    E: RateLimitError,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut retry = 0;
+    loop {
+        match operation().await {
+            Err(error) if error.is_rate_limited() && retry < throttle.max_retries => {
+                let delay = throttle.delay(retry, error.retry_after());
+                tracing::warn!("Rate limited by Discord; retrying in {:?} (retry {} of {})", delay, retry + 1, throttle.max_retries);
+                tokio::time::sleep(delay).await;
+                retry += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Apply `operation` to every item with at most `throttle.concurrency` calls in flight, retrying
+/// rate-limited calls. Results are returned in item order; one failure doesn't stop the rest.
+pub async fn run_bulk<I, T, E, F, Fut>(throttle: Throttle, items: Vec<I>, operation: F) -> Vec<Result<T, E>>
+where
+    I: Clone + Send + Sync + 'static,
+    T: Send + 'static,
+    E: RateLimitError + Send + 'static,
+    F: Fn(I) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<T, E>> + Send,
+{
+    let permits = Arc::new(Semaphore::new(throttle.concurrency.max(1)));
+    let operation = Arc::new(operation);
+    let mut tasks = JoinSet::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let (permits, operation) = (Arc::clone(&permits), Arc::clone(&operation));
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await.expect("semaphore is never closed");
+            (index, with_retry(&throttle, || operation(item.clone())).await)
+        });
+    }
+
+    let mut results: Vec<Option<Result<T, E>>> = std::iter::repeat_with(|| None).take(tasks.len()).collect();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, result)) => results[index] = Some(result),
+            // Only a panicking operation gets here; re-raise it rather than drop the result silently
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+    results.into_iter().map(|result| result.expect("every task reports its result")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Simulated Discord error: rate limited (with an optional retry-after) or failed for another reason
+    #[derive(Debug, PartialEq)]
+    enum MockError {
+        RateLimited(Option<Duration>),
+        Failed,
+    }
+
+    impl RateLimitError for MockError {
+        fn is_rate_limited(&self) -> bool {
+            matches!(self, MockError::RateLimited(_))
+        }
+
+        fn retry_after(&self) -> Option<Duration> {
+            match self {
+                MockError::RateLimited(retry_after) => *retry_after,
+                MockError::Failed => None,
+            }
+        }
+    }
+
+    fn fast_throttle() -> Throttle {
+        Throttle { concurrency: 2, max_retries: 3, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(5) }
+    }
+
+    #[test]
+    fn test_throttle_delay() {
+        let throttle = Throttle { base_delay: Duration::from_millis(100), max_delay: Duration::from_secs(1), ..Throttle::default() };
+        assert_eq!(throttle.delay(0, None), Duration::from_millis(100));
+        assert_eq!(throttle.delay(2, None), Duration::from_millis(400));
+        assert_eq!(throttle.delay(10, None), Duration::from_secs(1));
+        // Discord's retry-after wins over the backoff, within the cap
+        assert_eq!(throttle.delay(3, Some(Duration::from_millis(250))), Duration::from_millis(250));
+        assert_eq!(throttle.delay(0, Some(Duration::from_secs(60))), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_recovers_from_rate_limits() {
+        let calls = AtomicU32::new(0);
+        let result = with_retry(&fast_throttle(), || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(MockError::RateLimited(Some(Duration::from_millis(1)))),
+                1 => Err(MockError::RateLimited(None)),
+                n => Ok(n),
+            }
+        })
+        .await;
+        assert_eq!(result, Ok(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_gives_up() {
+        // Retries stop after max_retries, and other errors are never retried
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = with_retry(&fast_throttle(), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(MockError::RateLimited(None))
+        })
+        .await;
+        assert_eq!(result, Err(MockError::RateLimited(None)));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = with_retry(&fast_throttle(), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(MockError::Failed)
+        })
+        .await;
+        assert_eq!(result, Err(MockError::Failed));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_bulk_keeps_order_and_bounds_concurrency() {
+        let in_flight = Arc::new(AtomicU32::new(0));
+        let peak = Arc::new(AtomicU32::new(0));
+        let limited = Arc::new(std::sync::Mutex::new(std::collections::HashSet::new()));
+        let (in_flight_op, peak_op) = (Arc::clone(&in_flight), Arc::clone(&peak));
+
+        let results = run_bulk(fast_throttle(), (0..6).collect(), move |n: u32| {
+            let (in_flight, peak, limited) = (Arc::clone(&in_flight_op), Arc::clone(&peak_op), Arc::clone(&limited));
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(2)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                // Every item is rate limited once before it goes through; item 5 then fails for good
+                if limited.lock().unwrap().insert(n) {
+                    Err(MockError::RateLimited(None))
+                } else if n == 5 {
+                    Err(MockError::Failed)
+                } else {
+                    Ok(n * 10)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(&results[..5], &[Ok(0), Ok(10), Ok(20), Ok(30), Ok(40)]);
+        assert_eq!(results[5], Err(MockError::Failed));
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+}
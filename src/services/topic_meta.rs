@@ -0,0 +1,224 @@
+// Table channel topic layout: the `Schema:` and `Comment:` lines, any other text, then SQLcord's metadata lines
+//
+//   Schema: id INT PRIMARY KEY, name VARCHAR(50)
+//   Comment: Customer records
+//   Rows: 12
+//   Modified: 2025-08-19 14:30:00 UTC
+//   Shards: 4
+//
+// The extra channels of a sharded table (`table_users_1`, ...) carry only `Shard: <index>`.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serenity::builder::EditChannel;
+use serenity::model::id::{ChannelId, GuildId};
+use super::backend::Backend;
+use super::bot_context::BotContext;
+use super::channel_cache::invalidate_channels;
+use crate::state::TopicLocks;
+
+pub const SCHEMA_PREFIX: &str = "Schema: ";
+/// Table description from `/sql create table comment:`
+pub const COMMENT_PREFIX: &str = "Comment: ";
+/// Cached row count
+pub const ROWS_PREFIX: &str = "Rows: ";
+/// Time of the last insert, update or delete
+pub const MODIFIED_PREFIX: &str = "Modified: ";
+/// Value count of a schemaless table's first row
+pub const COLUMNS_PREFIX: &str = "Columns: ";
+/// Number of channels a sharded table's rows are spread over, on the table's own channel
+pub const SHARDS_PREFIX: &str = "Shards: ";
+/// Index of an extra shard channel of a sharded table
+pub const SHARD_PREFIX: &str = "Shard: ";
+
+/// Same layout as the `TIMESTAMP:` line of stored rows
+const MODIFIED_FORMAT: &str = "%Y-%m-%d %H:%M:%S UTC";
+
+/// A table topic split into its schema and metadata
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TopicMeta {
+    /// Schema definition after `Schema: `, kept verbatim
+    pub schema: Option<String>,
+    pub comment: Option<String>,
+    /// `None` when the table has no counter (yet) and rows must be scanned
+    pub rows: Option<usize>,
+    pub modified: Option<DateTime<Utc>>,
+    pub columns: Option<usize>,
+    /// Set on a sharded table's own channel
+    pub shards: Option<usize>,
+    /// Set on the extra shard channels, which aren't tables of their own
+    pub shard: Option<usize>,
+    /// Lines SQLcord doesn't manage, kept in order
+    pub other: Vec<String>,
+}
+
+impl TopicMeta {
+    /// Split a table topic into its parts. A metadata line that doesn't parse is kept as other text.
+    pub fn parse(topic: Option<&str>) -> Self {
+        let mut meta = TopicMeta::default();
+        for line in topic.unwrap_or_default().lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(schema) = line.strip_prefix(SCHEMA_PREFIX).filter(|_| meta.schema.is_none()) {
+                meta.schema = Some(schema.to_string());
+            } else if let Some(comment) = line.strip_prefix(COMMENT_PREFIX).filter(|_| meta.comment.is_none()) {
+                meta.comment = Some(comment.to_string());
+            } else if let Some(rows) = line.strip_prefix(ROWS_PREFIX).and_then(|rows| rows.trim().parse().ok()) {
+                meta.rows = Some(rows);
+            } else if let Some(modified) = line
+                .strip_prefix(MODIFIED_PREFIX)
+                .and_then(|modified| NaiveDateTime::parse_from_str(modified.trim(), MODIFIED_FORMAT).ok())
+            {
+                meta.modified = Some(modified.and_utc());
+            } else if let Some(columns) = line.strip_prefix(COLUMNS_PREFIX).and_then(|columns| columns.trim().parse().ok()) {
+                meta.columns = Some(columns);
+            } else if let Some(shards) = line.strip_prefix(SHARDS_PREFIX).and_then(|shards| shards.trim().parse().ok()) {
+                meta.shards = Some(shards);
+            } else if let Some(shard) = line.strip_prefix(SHARD_PREFIX).and_then(|shard| shard.trim().parse().ok()) {
+                meta.shard = Some(shard);
+            } else {
+                meta.other.push(line.to_string());
+            }
+        }
+        meta
+    }
+
+    /// The topic text: schema and comment first, then other text, then the metadata lines that are set
+    pub fn serialize(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(schema) = &self.schema {
+            lines.push(format!("{}{}", SCHEMA_PREFIX, schema));
+        }
+        if let Some(comment) = &self.comment {
+            lines.push(format!("{}{}", COMMENT_PREFIX, comment));
+        }
+        lines.extend(self.other.iter().cloned());
+        if let Some(rows) = self.rows {
+            lines.push(format!("{}{}", ROWS_PREFIX, rows));
+        }
+        if let Some(modified) = self.modified {
+            lines.push(format!("{}{}", MODIFIED_PREFIX, modified.format(MODIFIED_FORMAT)));
+        }
+        if let Some(columns) = self.columns {
+            lines.push(format!("{}{}", COLUMNS_PREFIX, columns));
+        }
+        if let Some(shards) = self.shards {
+            lines.push(format!("{}{}", SHARDS_PREFIX, shards));
+        }
+        if let Some(shard) = self.shard {
+            lines.push(format!("{}{}", SHARD_PREFIX, shard));
+        }
+        lines.join("\n")
+    }
+
+    /// Record a write: move the row count by `delta` (never below zero) and stamp `now`.
+    /// A table without a counter keeps none, since its count is unknown.
+    pub fn record_change(&mut self, delta: i64, now: DateTime<Utc>) {
+        self.rows = self.rows.map(|rows| {
+            if delta < 0 { rows.saturating_sub(delta.unsigned_abs() as usize) } else { rows.saturating_add(delta as usize) }
+        });
+        self.modified = Some(now);
+    }
+}
+
+/// Apply `update` to the table's current topic and save it when it changed.
+/// The topic is read back from Discord first so the edit builds on the latest counter rather than a cached one,
+/// and updates of one channel run one at a time so none is lost. Failures are logged: a missed update leaves
+/// stale metadata, not a broken table. When the edit fails, the `Rows:` counter is dropped instead, since it
+/// would be off from now on; counts then scan the rows until the counter is rebuilt.
+pub async fn update_table_topic(ctx: &impl BotContext, guild_id: GuildId, channel_id: ChannelId, update: impl FnOnce(&mut TopicMeta)) {
+    let lock = topic_lock(ctx, channel_id).await;
+    let _guard = match &lock {
+        Some(lock) => Some(lock.lock().await),
+        None => None,
+    };
+    let current = match ctx.backend().get_channel(channel_id).await {
+        Ok(channel) => channel.topic,
+        Err(e) => {
+            tracing::warn!("Failed to read table topic for channel {channel_id}: {e}");
+            return;
+        }
+    };
+    let mut meta = TopicMeta::parse(current.as_deref());
+    update(&mut meta);
+    let topic = meta.serialize();
+    if current.as_deref().unwrap_or_default() == topic {
+        return;
+    }
+    if let Err(e) = ctx.backend().edit_channel(channel_id, EditChannel::new().topic(topic)).await {
+        tracing::warn!("Failed to update table topic for channel {channel_id}: {e}");
+        if meta.rows.take().is_none() {
+            return;
+        }
+        if let Err(e) = ctx.backend().edit_channel(channel_id, EditChannel::new().topic(meta.serialize())).await {
+            tracing::warn!("Failed to drop the row counter of channel {channel_id}: {e}");
+            return;
+        }
+    }
+    invalidate_channels(ctx, guild_id).await;
+}
+
+/// The lock serializing topic updates of `channel_id`; `None` when the bot runs without `TopicLocks`
+async fn topic_lock(ctx: &impl BotContext, channel_id: ChannelId) -> Option<std::sync::Arc<tokio::sync::Mutex<()>>> {
+    let data = ctx.data().read().await;
+    let locks = data.get::<TopicLocks>()?;
+    let mut locks = locks.lock().await;
+    Some(locks.entry(channel_id).or_default().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_topic_meta_round_trip() {
+        let topic = "Schema: id INT PRIMARY KEY, note VARCHAR(50) DEFAULT 'Rows: 3'\nComment: Customer records\nRows: 42\nModified: 2025-08-19 14:30:00 UTC";
+        let meta = TopicMeta::parse(Some(topic));
+        assert_eq!(meta, TopicMeta {
+            schema: Some("id INT PRIMARY KEY, note VARCHAR(50) DEFAULT 'Rows: 3'".to_string()),
+            comment: Some("Customer records".to_string()),
+            rows: Some(42),
+            modified: Some(at("2025-08-19T14:30:00Z")),
+            columns: None,
+            shards: None,
+            shard: None,
+            other: Vec::new(),
+        });
+        assert_eq!(meta.serialize(), topic);
+
+        // Sharded tables: the count on the table's channel, the index on each extra channel
+        let topic = "Schema: id INT PRIMARY KEY\nRows: 9\nShards: 3";
+        assert_eq!(TopicMeta::parse(Some(topic)).shards, Some(3));
+        assert_eq!(TopicMeta::parse(Some(topic)).serialize(), topic);
+        assert_eq!(TopicMeta::parse(Some("Shard: 2")).shard, Some(2));
+
+        // Schemaless tables: free text and the recorded column count survive, unparseable metadata stays as text
+        let topic = "Raw logs\nRows: many\nRows: 2\nColumns: 3";
+        let meta = TopicMeta::parse(Some(topic));
+        assert_eq!((meta.schema.as_deref(), meta.rows, meta.columns), (None, Some(2), Some(3)));
+        assert_eq!(meta.other, vec!["Raw logs".to_string(), "Rows: many".to_string()]);
+        assert_eq!(meta.serialize(), topic);
+
+        assert_eq!(TopicMeta::parse(None), TopicMeta::default());
+        assert_eq!(TopicMeta::default().serialize(), "");
+    }
+
+    #[test]
+    fn test_record_change() {
+        let now = at("2025-08-20T09:00:00Z");
+        let mut meta = TopicMeta::parse(Some("Schema: id INT\nRows: 2\nModified: 2025-08-19 14:30:00 UTC"));
+        meta.record_change(1, now);
+        assert_eq!(meta.serialize(), "Schema: id INT\nRows: 3\nModified: 2025-08-20 09:00:00 UTC");
+        meta.record_change(-2, now);
+        assert_eq!(meta.rows, Some(1));
+        // Never drops below zero, even if the counter had drifted
+        meta.record_change(-4, now);
+        assert_eq!(meta.rows, Some(0));
+
+        // No counter: the count stays unknown, but the change is still stamped
+        let mut meta = TopicMeta::parse(Some("Schema: id INT"));
+        meta.record_change(1, now);
+        assert_eq!(meta.serialize(), "Schema: id INT\nModified: 2025-08-20 09:00:00 UTC");
+    }
+}
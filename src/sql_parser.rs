@@ -1,31 +1,89 @@
 // SQL column definition parsing utilities
 
 use std::fmt;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
 
 #[derive(Debug, Clone)]
 pub struct ColumnDefinition {
     pub name: String,
     pub data_type: String,
     pub size: Option<u32>,
+    pub scale: Option<u32>,
+    /// `UNSIGNED` modifier on an integer type: the range starts at 0
+    pub unsigned: bool,
     pub nullable: bool,
     pub primary_key: bool,
+    pub unique: bool,
+    pub auto_increment: bool,
+    pub default: Option<SqlValue>,
+    /// Target of `REFERENCES table(column)`: inserted values must exist there
+    pub references: Option<ForeignKey>,
+    /// Description from `COMMENT 'text'`, kept with the schema
+    pub comment: Option<String>,
 }
 
-impl fmt::Display for ColumnDefinition {
+/// A foreign key target: a column of another table (or the same one) in the same database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKey {
+    pub table: String,
+    pub column: String,
+}
+
+impl fmt::Display for ForeignKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let size_str = if let Some(size) = self.size {
-            format!("({})", size)
-        } else {
-            String::new()
+        write!(f, "{}({})", quote_identifier(&self.table), quote_identifier(&self.column))
+    }
+}
+
+impl ColumnDefinition {
+    /// Render the data type with its size/precision, e.g. `VARCHAR(255)`, `DECIMAL(10,2)` or `INT UNSIGNED`
+    pub fn type_display(&self) -> String {
+        let base = match (self.size, self.scale) {
+            (Some(size), Some(scale)) => format!("{}({},{})", self.data_type, size, scale),
+            (Some(size), None) => format!("{}({})", self.data_type, size),
+            _ => self.data_type.clone(),
         };
+        if self.unsigned { format!("{} UNSIGNED", base) } else { base }
+    }
+
+    /// Inclusive range of values an integer column accepts; None for non-integer types
+    pub fn integer_range(&self) -> Option<(i64, i64)> {
+        let (signed, unsigned) = match self.data_type.as_str() {
+            "TINYINT" => ((i8::MIN as i64, i8::MAX as i64), (0, u8::MAX as i64)),
+            "SMALLINT" => ((i16::MIN as i64, i16::MAX as i64), (0, u16::MAX as i64)),
+            // Values are stored as i64, so INT UNSIGNED tops out at i64::MAX
+            "INT" => ((i64::MIN, i64::MAX), (0, i64::MAX)),
+            _ => return None,
+        };
+        Some(if self.unsigned { unsigned } else { signed })
+    }
+}
+
+impl fmt::Display for ColumnDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         
         let constraints = {
             let mut parts = Vec::new();
             if !self.nullable {
-                parts.push("NOT NULL");
+                parts.push("NOT NULL".to_string());
             }
             if self.primary_key {
-                parts.push("PRIMARY KEY");
+                parts.push("PRIMARY KEY".to_string());
+            }
+            if self.unique {
+                parts.push("UNIQUE".to_string());
+            }
+            if self.auto_increment {
+                parts.push("AUTO_INCREMENT".to_string());
+            }
+            if let Some(default) = &self.default {
+                parts.push(format!("DEFAULT {}", format_sql_literal(default)));
+            }
+            if let Some(references) = &self.references {
+                parts.push(format!("REFERENCES {}", references));
+            }
+            if let Some(comment) = &self.comment {
+                parts.push(format!("COMMENT {}", format_sql_literal(&SqlValue::String(comment.clone()))));
             }
             if parts.is_empty() {
                 String::new()
@@ -34,7 +92,7 @@ impl fmt::Display for ColumnDefinition {
             }
         };
         
-        write!(f, "{} {}{}{}", self.name, self.data_type, size_str, constraints)
+        write!(f, "{} {}{}", quote_identifier(&self.name), self.type_display(), constraints)
     }
 }
 
@@ -57,7 +115,7 @@ impl fmt::Display for TableSchema {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SqlValue {
     Integer(i64),
     Float(f64),
@@ -78,6 +136,41 @@ impl fmt::Display for SqlValue {
     }
 }
 
+/// Format a value as a SQL literal that `parse_sql_values` reads back unchanged
+pub fn format_sql_literal(value: &SqlValue) -> String {
+    match value {
+        SqlValue::String(s) => format!("'{}'", s.replace('\'', "''")),
+        other => other.to_string(),
+    }
+}
+
+fn invalid_comment(column: &str) -> String {
+    format!(
+        "**COMMENT** requires a quoted string on one line for column **{}**\n\n**Example:** `{} INT COMMENT 'primary identifier'`",
+        column,
+        column
+    )
+}
+
+fn invalid_references(column: &str) -> String {
+    format!(
+        "**REFERENCES** requires a table and one column for column **{}**\n\n**Example:** `{} INT REFERENCES customers(id)`",
+        column,
+        column
+    )
+}
+
+/// The `table(column)` after REFERENCES; `None` unless both names are there and exactly one column is named
+fn parse_foreign_key(target: &str) -> Option<ForeignKey> {
+    let start = target.find('(')?;
+    let inner = target[start + 1..].strip_suffix(')')?;
+    let (table, column) = (unquote_identifier(target[..start].trim()), unquote_identifier(inner.trim()));
+    if table.is_empty() || column.is_empty() || inner.contains(',') {
+        return None;
+    }
+    Some(ForeignKey { table: table.to_string(), column: column.to_string() })
+}
+
 /// Parse SQL-like column definitions
 /// Example: "PersonID int, LastName varchar(255), FirstName varchar(255), Address varchar(255), City varchar(255)"
 pub fn parse_column_definitions(schema_str: &str) -> Result<Vec<ColumnDefinition>, String> {
@@ -86,30 +179,58 @@ pub fn parse_column_definitions(schema_str: &str) -> Result<Vec<ColumnDefinition
     }
     
     let mut columns = Vec::new();
+    let mut table_primary_key: Option<Vec<String>> = None;
     
-    for column_str in schema_str.split(',') {
+    for column_str in split_top_level(schema_str, ',') {
         let column_str = column_str.trim();
         if column_str.is_empty() {
             continue;
         }
         
-        let parts: Vec<&str> = column_str.split_whitespace().collect();
+        // Table-level constraint: PRIMARY KEY (col_a, col_b)
+        if let Some(key_columns) = parse_table_primary_key(column_str)? {
+            table_primary_key = Some(key_columns);
+            continue;
+        }
+        
+        let tokens = tokenize_column_definition(column_str);
+        let parts: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
         if parts.len() < 2 {
             return Err(format!("Invalid column definition: '{}'. Expected format: 'column_name data_type'", column_str));
         }
         
-        let name = parts[0].to_string();
+        let name = parse_column_name(parts[0])?;
         let mut data_type = parts[1].to_string();
         let mut size = None;
+        let mut scale = None;
         let mut nullable = true;
         let mut primary_key = false;
+        let mut unique = false;
+        let mut auto_increment = false;
+        let mut unsigned = false;
+        let mut default_token = None;
+        let mut comment_token = None;
+        let mut references_token = None;
         
-        // Parse data type with optional size
+        // Parse data type with optional size, or precision and scale: DECIMAL(10,2)
         if let Some(start) = data_type.find('(') {
             if let Some(end) = data_type.find(')') {
-                if let Ok(parsed_size) = data_type[start + 1..end].parse::<u32>() {
-                    size = Some(parsed_size);
-                    data_type = data_type[..start].to_string();
+                let args: Vec<&str> = data_type[start + 1..end].split(',').map(|a| a.trim()).collect();
+                match args.as_slice() {
+                    [size_arg] => {
+                        if let Ok(parsed_size) = size_arg.parse::<u32>() {
+                            size = Some(parsed_size);
+                            data_type = data_type[..start].to_string();
+                        }
+                    },
+                    [precision_arg, scale_arg] => {
+                        if let (Ok(parsed_precision), Ok(parsed_scale)) = (precision_arg.parse::<u32>(), scale_arg.parse::<u32>()) {
+                            size = Some(parsed_precision);
+                            scale = Some(parsed_scale);
+                            data_type = data_type[..start].to_string();
+                        }
+                    },
+                    _ => {}
                 }
             }
         }
@@ -117,11 +238,11 @@ pub fn parse_column_definitions(schema_str: &str) -> Result<Vec<ColumnDefinition
         // Normalize and validate data type
         let normalized_type = normalize_data_type(&data_type);
         let valid_types = [
-            "INT", "VARCHAR", "CHAR", "BOOLEAN", "FLOAT", "DOUBLE", "DECIMAL", "DATE", "TIME", "DATETIME"
+            "INT", "TINYINT", "SMALLINT", "VARCHAR", "CHAR", "BOOLEAN", "FLOAT", "DOUBLE", "DECIMAL", "DATE", "TIME", "DATETIME"
         ];
         if !valid_types.contains(&normalized_type.as_str()) {
             return Err(format!(
-                "**{}** is not a valid data type for column **{}**\n\n**Supported Types:**\n• INT, VARCHAR, CHAR, BOOLEAN\n• FLOAT, DOUBLE, DECIMAL\n• DATE, TIME, DATETIME\n\n**Examples:** `id INT`, `name VARCHAR(100)`, `active BOOLEAN`",
+                "**{}** is not a valid data type for column **{}**\n\n**Supported Types:**\n• INT, TINYINT, SMALLINT (optionally UNSIGNED)\n• VARCHAR, CHAR, BOOLEAN\n• FLOAT, DOUBLE, DECIMAL\n• DATE, TIME, DATETIME\n\n**Examples:** `id INT`, `name VARCHAR(100)`, `active BOOLEAN`",
                 data_type,
                 name
             ));
@@ -175,14 +296,18 @@ pub fn parse_column_definitions(schema_str: &str) -> Result<Vec<ColumnDefinition
                     ));
                 }
             },
-            "INT" => {
+            "INT" | "TINYINT" | "SMALLINT" => {
                 if size.is_some() {
                     return Err(format!(
-                        "**INT** does not support size specification for column **{}**\n\n**Correct usage:** `{} INT`\n**Invalid usage:** `{} INT({})`\n\n**Explanation:** INT values are fixed-size 64-bit integers and don't need size limits",
+                        "**{}** does not support size specification for column **{}**\n\n**Correct usage:** `{} {}`\n**Invalid usage:** `{} {}({})`\n\n**Explanation:** {} values have a fixed range and don't need size limits",
+                        normalized_type,
                         name,
                         name,
+                        normalized_type,
                         name,
-                        size.unwrap()
+                        normalized_type,
+                        size.unwrap(),
+                        normalized_type
                     ));
                 }
             },
@@ -214,48 +339,326 @@ pub fn parse_column_definitions(schema_str: &str) -> Result<Vec<ColumnDefinition
             }
         }
 
+        // Only DECIMAL accepts a scale, and it can't exceed the precision
+        if let Some(sc) = scale {
+            if normalized_type != "DECIMAL" {
+                return Err(format!(
+                    "**{}** does not support a scale for column **{}**\n\n**Correct usage:** `{} {}({})`\n\n**Explanation:** Only DECIMAL accepts `(precision, scale)`, e.g. `{} DECIMAL(10,2)`",
+                    normalized_type,
+                    name,
+                    name,
+                    normalized_type,
+                    size.unwrap_or(1),
+                    name
+                ));
+            }
+            if sc > size.unwrap_or(0) {
+                return Err(format!(
+                    "**DECIMAL** scale {} cannot exceed precision {} for column **{}**\n\n**Example:** `{} DECIMAL(10,2)` - 10 digits total, 2 after the decimal point",
+                    sc,
+                    size.unwrap_or(0),
+                    name,
+                    name
+                ));
+            }
+        }
+
         // Check for constraints in remaining parts
-        for part in &parts[2..] {
-            let part_upper = part.to_uppercase();
-            match part_upper.as_str() {
-                "NOT" => {
-                    // Look for "NOT NULL"
-                    if parts.len() > parts.iter().position(|&p| p == *part).unwrap() + 1 {
-                        let next_part = parts[parts.iter().position(|&p| p == *part).unwrap() + 1].to_uppercase();
-                        if next_part == "NULL" {
-                            nullable = false;
+        let mut index = 2;
+        while index < parts.len() {
+            let next_part = parts.get(index + 1).map(|p| p.to_uppercase());
+            match parts[index].to_uppercase().as_str() {
+                // Look for "NOT NULL"
+                "NOT" if next_part.as_deref() == Some("NULL") => {
+                    nullable = false;
+                },
+                // Look for "PRIMARY KEY"
+                "PRIMARY" if next_part.as_deref() == Some("KEY") => {
+                    primary_key = true;
+                },
+                "UNIQUE" => {
+                    unique = true;
+                },
+                "AUTO_INCREMENT" | "AUTOINCREMENT" => {
+                    auto_increment = true;
+                },
+                "UNSIGNED" => {
+                    unsigned = true;
+                },
+                "DEFAULT" => {
+                    // The literal after DEFAULT is consumed here so it isn't read as a keyword
+                    match parts.get(index + 1) {
+                        Some(literal) => default_token = Some(literal.to_string()),
+                        None => {
+                            return Err(format!(
+                                "**DEFAULT** requires a value for column **{}**\n\n**Examples:** `{} VARCHAR(20) DEFAULT 'active'`, `{} INT DEFAULT 0`",
+                                name,
+                                name,
+                                name
+                            ));
                         }
                     }
+                    index += 1;
                 },
-                "PRIMARY" => {
-                    // Look for "PRIMARY KEY"
-                    if parts.len() > parts.iter().position(|&p| p == *part).unwrap() + 1 {
-                        let next_part = parts[parts.iter().position(|&p| p == *part).unwrap() + 1].to_uppercase();
-                        if next_part == "KEY" {
-                            primary_key = true;
+                "REFERENCES" => {
+                    // `customers(id)` is one token, `customers (id)` two
+                    let target = match (parts.get(index + 1), parts.get(index + 2)) {
+                        (Some(table), Some(column)) if !table.contains('(') && column.starts_with('(') => {
+                            index += 1;
+                            format!("{}{}", table, column)
                         }
+                        (Some(target), _) => target.to_string(),
+                        (None, _) => return Err(invalid_references(&name)),
+                    };
+                    references_token = Some(target);
+                    index += 1;
+                },
+                "COMMENT" => {
+                    match parts.get(index + 1) {
+                        Some(literal) => comment_token = Some(literal.to_string()),
+                        None => return Err(invalid_comment(&name)),
                     }
+                    index += 1;
                 },
                 _ => {}
             }
+            index += 1;
         }
 
-        columns.push(ColumnDefinition {
+        let is_integer = matches!(normalized_type.as_str(), "INT" | "TINYINT" | "SMALLINT");
+        if unsigned && !is_integer {
+            return Err(format!(
+                "**UNSIGNED** requires an integer column, but column **{}** is **{}**\n\n**Correct usage:** `{} INT UNSIGNED`, `{} TINYINT UNSIGNED`",
+                name,
+                normalized_type,
+                name,
+                name
+            ));
+        }
+
+        if auto_increment && !is_integer {
+            return Err(format!(
+                "**AUTO_INCREMENT** requires an INT column, but column **{}** is **{}**\n\n**Correct usage:** `{} INT PRIMARY KEY AUTO_INCREMENT`",
+                name,
+                normalized_type,
+                name
+            ));
+        }
+
+        let mut column = ColumnDefinition {
             name,
             data_type: normalized_type,
             size,
+            scale,
+            unsigned,
             nullable,
             primary_key,
-        });
+            unique,
+            auto_increment,
+            default: None,
+            references: None,
+            comment: None,
+        };
+
+        // Parse the DEFAULT literal and make sure it fits the column type
+        if let Some(token) = default_token {
+            let default_value = match parse_sql_values(&token) {
+                Ok(mut values) if values.len() == 1 => values.remove(0),
+                _ => {
+                    return Err(format!(
+                        "**{}** is not a valid DEFAULT value for column **{}**\n\n**Examples:** `'active'`, `0`, `true`, `NULL`",
+                        token,
+                        column.name
+                    ));
+                }
+            };
+            if let Err(e) = validate_sql_value_type(&default_value, &column, columns.len() + 1) {
+                return Err(format!("**DEFAULT** value doesn't match column **{}**\n\n{}", column.name, e));
+            }
+            column.default = Some(default_value);
+        }
+
+        if let Some(token) = references_token {
+            column.references = Some(parse_foreign_key(&token).ok_or_else(|| invalid_references(&column.name))?);
+        }
+
+        // The schema is stored on one topic line, so a comment can't span lines
+        if let Some(token) = comment_token {
+            match parse_sql_values(&token).ok().as_deref() {
+                Some([SqlValue::String(text)]) if !text.contains(['\n', '\r']) => column.comment = Some(text.clone()),
+                _ => return Err(invalid_comment(&column.name)),
+            }
+        }
+
+        columns.push(column);
+    }
+    
+    // Mark every column of a composite key; uniqueness is checked on the full tuple
+    if let Some(key_columns) = table_primary_key {
+        for key_column in key_columns {
+            match columns.iter_mut().find(|col| col.name.eq_ignore_ascii_case(&key_column)) {
+                Some(col) => col.primary_key = true,
+                None => {
+                    return Err(format!(
+                        "**PRIMARY KEY** references unknown column **{}**\n\n**Defined columns:** {}",
+                        key_column,
+                        columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+                    ));
+                }
+            }
+        }
     }
     
     Ok(columns)
 }
 
+/// Parse a table-level `PRIMARY KEY (col, ...)` constraint.
+/// Returns Ok(None) if the segment is an ordinary column definition.
+fn parse_table_primary_key(segment: &str) -> Result<Option<Vec<String>>, String> {
+    let tokens = tokenize_column_definition(segment);
+    let is_constraint = tokens.len() >= 2
+        && tokens[0].eq_ignore_ascii_case("PRIMARY")
+        && (tokens[1].eq_ignore_ascii_case("KEY") || tokens[1].to_uppercase().starts_with("KEY("));
+    if !is_constraint {
+        return Ok(None);
+    }
+    
+    let column_list = match (segment.find('('), segment.rfind(')')) {
+        (Some(start), Some(end)) if start < end => &segment[start + 1..end],
+        _ => {
+            return Err(format!(
+                "Invalid table constraint: '{}'\n\n**Expected format:** `PRIMARY KEY (col_a, col_b)`",
+                segment
+            ));
+        }
+    };
+    
+    let key_columns: Vec<String> = split_top_level(column_list, ',')
+        .into_iter()
+        .map(|name| unquote_identifier(name.trim()).to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if key_columns.is_empty() {
+        return Err("**PRIMARY KEY** constraint must list at least one column, e.g. `PRIMARY KEY (col_a, col_b)`".to_string());
+    }
+    
+    Ok(Some(key_columns))
+}
+
+/// Split a string on `separator`, ignoring separators nested inside parentheses or quotes
+fn split_top_level(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    
+    for (i, ch) in input.char_indices() {
+        if let Some(q) = quote {
+            if ch == q {
+                quote = None;
+            }
+            continue;
+        }
+        match ch {
+            '\'' | '"' | '`' => quote = Some(ch),
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if c == separator && depth == 0 => {
+                parts.push(&input[start..i]);
+                start = i + c.len_utf8();
+            },
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+/// Strip one pair of surrounding backticks or double quotes from an identifier
+fn unquote_identifier(name: &str) -> &str {
+    for quote in ['`', '"'] {
+        if name.len() >= 2 && name.starts_with(quote) && name.ends_with(quote) {
+            return &name[1..name.len() - 1];
+        }
+    }
+    name
+}
+
+/// Column name from the first token of a definition; a quoted name (`` `order date` ``, `"select"`)
+/// may hold spaces or reserved words, but not `:` or line breaks, which would break the stored rows
+fn parse_column_name(token: &str) -> Result<String, String> {
+    let name = unquote_identifier(token);
+    if name.trim().is_empty() {
+        return Err(format!("Invalid column name: {}. Quoted column names can't be empty.", token));
+    }
+    if name.contains([':', '\n', '\r']) {
+        return Err(format!("Invalid column name: {}. Column names can't contain `:` or line breaks.", token));
+    }
+    Ok(name.to_string())
+}
+
+/// A column name as written in a schema: plain names as is, others quoted with backticks
+/// (or double quotes if the name holds a backtick) so they parse back to the same name
+pub fn quote_identifier(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        name.to_string()
+    } else if name.contains('`') {
+        format!("\"{}\"", name)
+    } else {
+        format!("`{}`", name)
+    }
+}
+
+/// Split a single column definition into whitespace-separated tokens.
+/// Whitespace inside parentheses is dropped so `DECIMAL(10, 2)` stays one token,
+/// and quoted literals like `'in progress'` or identifiers like `` `order date` `` are kept whole.
+fn tokenize_column_definition(column_str: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    
+    for ch in column_str.chars() {
+        if let Some(q) = quote {
+            current.push(ch);
+            if ch == q {
+                quote = None;
+            }
+            continue;
+        }
+        match ch {
+            '\'' | '"' | '`' => {
+                quote = Some(ch);
+                current.push(ch);
+            },
+            '(' => {
+                depth += 1;
+                current.push(ch);
+            },
+            ')' => {
+                depth -= 1;
+                current.push(ch);
+            },
+            c if c.is_whitespace() => {
+                if depth == 0 && !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            },
+            _ => current.push(ch),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
 /// Normalize data type names to common SQL standards
 fn normalize_data_type(data_type: &str) -> String {
     match data_type.to_lowercase().as_str() {
         "int" | "integer" => "INT".to_string(),
+        "tinyint" => "TINYINT".to_string(),
+        "smallint" => "SMALLINT".to_string(),
         "varchar" | "string" | "text" => "VARCHAR".to_string(),
         "char" | "character" => "CHAR".to_string(),
         "bool" | "boolean" => "BOOLEAN".to_string(),
@@ -274,7 +677,8 @@ fn normalize_data_type(data_type: &str) -> String {
 pub fn parse_sql_values(values_str: &str) -> Result<Vec<SqlValue>, String> {
     let mut values = Vec::new();
     let mut current_value = String::new();
-    let mut in_string = false;
+    // The quote character that opened the current string literal, if inside one
+    let mut string_quote: Option<char> = None;
     let mut escape_next = false;
     let mut chars = values_str.chars().peekable();
     
@@ -286,22 +690,22 @@ pub fn parse_sql_values(values_str: &str) -> Result<Vec<SqlValue>, String> {
         }
         
         match ch {
-            '\\' if in_string => {
+            '\\' if string_quote.is_some() => {
                 escape_next = true;
                 continue;
             },
-            '\'' => {
-                if in_string {
-                    // Check if next char is also a quote (SQL-style escaping)
-                    if let Some(&'\'') = chars.peek() {
+            '\'' | '"' if string_quote.is_none_or(|quote| quote == ch) => {
+                if string_quote.is_some() {
+                    // Check if next char is the same quote (SQL-style escaping: '' or "")
+                    if chars.peek() == Some(&ch) {
                         // Escaped quote - consume the second quote and add a literal quote
                         chars.next(); // consume the second quote
-                        current_value.push('\'');
+                        current_value.push(ch);
                     } else {
                         // End of string value
                         values.push(SqlValue::String(current_value.clone()));
                         current_value.clear();
-                        in_string = false;
+                        string_quote = None;
                         
                         // Skip to next comma or end
                         while let Some(&next_ch) = chars.peek() {
@@ -317,12 +721,12 @@ pub fn parse_sql_values(values_str: &str) -> Result<Vec<SqlValue>, String> {
                     }
                 } else {
                     // Start of string value
-                    in_string = true;
+                    string_quote = Some(ch);
                     // Skip any leading whitespace before string
                     current_value.clear();
                 }
             },
-            ',' if !in_string => {
+            ',' if string_quote.is_none() => {
                 // End of current value
                 let trimmed = current_value.trim();
                 if !trimmed.is_empty() {
@@ -330,7 +734,7 @@ pub fn parse_sql_values(values_str: &str) -> Result<Vec<SqlValue>, String> {
                 }
                 current_value.clear();
             },
-            _ if in_string => {
+            _ if string_quote.is_some() => {
                 current_value.push(ch);
             },
             _ => {
@@ -340,8 +744,8 @@ pub fn parse_sql_values(values_str: &str) -> Result<Vec<SqlValue>, String> {
     }
     
     // Handle last value
-    if in_string {
-        return Err("❌ **Unterminated string** - Missing closing quote\n\n**Example:** `'John'` instead of `'John`".to_string());
+    if let Some(quote) = string_quote {
+        return Err(format!("❌ **Unterminated string** - Missing closing quote\n\n**Example:** `{0}John{0}` instead of `{0}John`", quote));
     }
     
     let trimmed = current_value.trim();
@@ -381,6 +785,18 @@ fn parse_single_value(value_str: &str) -> Result<SqlValue, String> {
         return Ok(SqlValue::Integer(int_val));
     }
     
+    // An integer literal too big for i64 must not silently become a FLOAT
+    let digits = trimmed.strip_prefix(['-', '+']).unwrap_or(trimmed);
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!(
+            "❌ **Integer out of range:** `{}`\n\nIntegers must be between {} and {}.\n💡 **Tip:** Quote the value (`'{}'`) to store it as text",
+            trimmed,
+            i64::MIN,
+            i64::MAX,
+            trimmed
+        ));
+    }
+    
     // Try to parse as float
     if let Ok(float_val) = trimmed.parse::<f64>() {
         return Ok(SqlValue::Float(float_val));
@@ -388,7 +804,7 @@ fn parse_single_value(value_str: &str) -> Result<SqlValue, String> {
     
     // If all else fails, it's an invalid unquoted value
     Err(format!(
-        "❌ **Invalid value:** `{}`\n\n**Valid formats:**\n• Numbers: `42`, `3.14`\n• Booleans: `true`, `false`\n• Strings: `'text'`\n• NULL: `NULL`",
+        "❌ **Invalid value:** `{}`\n\n**Valid formats:**\n• Numbers: `42`, `3.14`\n• Booleans: `true`, `false`\n• Strings: `'text'` or `\"text\"`\n• NULL: `NULL`",
         trimmed
     ))
 }
@@ -436,8 +852,8 @@ fn validate_sql_value_type(value: &SqlValue, column: &ColumnDefinition, position
     
     // Type-specific validation
     match column.data_type.as_str() {
-        "INT" => {
-            if !matches!(value, SqlValue::Integer(_)) {
+        "INT" | "TINYINT" | "SMALLINT" => {
+            let SqlValue::Integer(i) = value else {
                 return Err(format!(
                     "❌ **Type mismatch** for column **{}** (position {})\n\nExpected: **integer**\nGot: **{}**\n\n**Example:** `42` instead of `{}`",
                     column.name,
@@ -445,19 +861,38 @@ fn validate_sql_value_type(value: &SqlValue, column: &ColumnDefinition, position
                     get_sql_value_type_name(value),
                     value
                 ));
+            };
+            if let Some((min, max)) = column.integer_range()
+                && !(min..=max).contains(i)
+            {
+                return Err(format!(
+                    "❌ **Value out of range** for column **{}** (position {})\n\n📋 **Column:** {} {}\nAllowed: **{}** to **{}**\nGot: **{}**",
+                    column.name,
+                    position,
+                    column.name,
+                    column.type_display(),
+                    min,
+                    max,
+                    i
+                ));
             }
         },
         "VARCHAR" | "CHAR" => {
             if let SqlValue::String(s) = value {
+                if let Some(error) = fixed_length_error(s, column, position, char_mode()) {
+                    return Err(error);
+                }
+                // Sizes count characters, and the preview is cut on a character boundary
+                let length = s.chars().count();
                 if let Some(max_size) = column.size {
-                    if s.len() > max_size as usize {
+                    if length > max_size as usize {
                         return Err(format!(
                             "❌ **String too long** for column **{}** (position {})\n\nLength: {} characters\nMaximum: {} characters\n\n📏 **Current:** '{}...'\n💡 **Tip:** Shorten the text or increase the column size",
                             column.name,
                             position,
-                            s.len(),
+                            length,
                             max_size,
-                            &s[..std::cmp::min(20, s.len())]
+                            s.chars().take(20).collect::<String>()
                         ));
                     }
                 }
@@ -492,6 +927,9 @@ fn validate_sql_value_type(value: &SqlValue, column: &ColumnDefinition, position
                     value
                 ));
             }
+            if column.data_type == "DECIMAL" {
+                validate_decimal_digits(value, column, position)?;
+            }
         },
         "DATE" | "TIME" | "DATETIME" => {
             if let SqlValue::String(s) = value {
@@ -546,6 +984,154 @@ fn validate_sql_value_type(value: &SqlValue, column: &ColumnDefinition, position
     Ok(())
 }
 
+/// Count the (integer, fractional) significant digits of a numeric value
+fn decimal_digit_counts(value: &SqlValue) -> Option<(usize, usize)> {
+    let text = match value {
+        SqlValue::Integer(i) => i.unsigned_abs().to_string(),
+        SqlValue::Float(f) => f.abs().to_string(),
+        _ => return None,
+    };
+    
+    let (int_part, frac_part) = text.split_once('.').unwrap_or((&text, ""));
+    let int_digits = int_part.trim_start_matches('0').len();
+    Some((int_digits, frac_part.len()))
+}
+
+/// Enforce DECIMAL(precision[, scale]) digit limits on a numeric value
+fn validate_decimal_digits(value: &SqlValue, column: &ColumnDefinition, position: usize) -> Result<(), String> {
+    let (int_digits, frac_digits) = match decimal_digit_counts(value) {
+        Some(counts) => counts,
+        None => return Ok(()),
+    };
+    
+    if let (Some(precision), Some(scale)) = (column.size, column.scale) {
+        if frac_digits > scale as usize {
+            return Err(format!(
+                "❌ **Too many decimal places** for column **{}** (position {})\n\nDeclared: **{}** (at most {} digits after the decimal point)\nGot: **{}** ({} digits)\n\n💡 **Tip:** Round the value to {} decimal places",
+                column.name,
+                position,
+                column.type_display(),
+                scale,
+                value,
+                frac_digits,
+                scale
+            ));
+        }
+        let max_int_digits = (precision - scale) as usize;
+        if int_digits > max_int_digits {
+            return Err(format!(
+                "❌ **Value out of range** for column **{}** (position {})\n\nDeclared: **{}** (at most {} digits before the decimal point)\nGot: **{}**",
+                column.name,
+                position,
+                column.type_display(),
+                max_int_digits,
+                value
+            ));
+        }
+    } else if let Some(precision) = column.size
+        && int_digits + frac_digits > precision as usize
+    {
+        return Err(format!(
+            "❌ **Too many digits** for column **{}** (position {})\n\nDeclared: **{}** (at most {} digits in total)\nGot: **{}** ({} digits)",
+            column.name,
+            position,
+            column.type_display(),
+            precision,
+            value,
+            int_digits + frac_digits
+        ));
+    }
+    
+    Ok(())
+}
+
+/// How CHAR(n) columns treat values shorter than n
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CharMode {
+    /// Store short values padded with spaces to exactly n characters
+    #[default]
+    Pad,
+    /// Reject short values
+    Strict,
+}
+
+/// CHAR handling, configurable through the `SQLCORD_CHAR_MODE` environment variable (`pad` or `strict`)
+pub fn char_mode() -> CharMode {
+    match std::env::var("SQLCORD_CHAR_MODE").ok().map(|mode| mode.trim().to_ascii_lowercase()).as_deref() {
+        Some("strict") => CharMode::Strict,
+        _ => CharMode::Pad,
+    }
+}
+
+/// Error for a CHAR(n) value shorter than n in strict mode
+fn fixed_length_error(s: &str, column: &ColumnDefinition, position: usize, mode: CharMode) -> Option<String> {
+    let size = column.size? as usize;
+    let length = s.chars().count();
+    (column.data_type == "CHAR" && mode == CharMode::Strict && length < size).then(|| format!(
+        "❌ **String too short** for column **{}** (position {})\n\nLength: {} characters\nRequired: exactly {} characters\n\n💡 **Tip:** CHAR columns are fixed-length; use VARCHAR for shorter values",
+        column.name,
+        position,
+        length,
+        size
+    ))
+}
+
+/// A CHAR(n) value as stored: padded with trailing spaces to n characters. Other columns are unchanged.
+pub fn pad_char_value(value: &str, column: &ColumnDefinition) -> String {
+    match column.size {
+        Some(size) if column.data_type == "CHAR" => format!("{:<width$}", value, width = size as usize),
+        _ => value.to_string(),
+    }
+}
+
+/// Compare CHAR values the SQL way: trailing padding spaces don't count
+pub fn char_values_equal(a: &str, b: &str) -> bool {
+    a.trim_end_matches(' ') == b.trim_end_matches(' ')
+}
+
+/// Convert a validated value to the representation its column declares: an integer given for a
+/// FLOAT/DOUBLE/DECIMAL column becomes a float, so it reads back as `5.0` rather than `5`.
+/// Returns whether the value changed. Integers beyond 2^53, which a float can't hold exactly, are kept.
+pub fn coerce_value_to_column(value: &mut SqlValue, column: &ColumnDefinition) -> bool {
+    const MAX_EXACT_FLOAT_INT: u64 = 1 << 53;
+    match (&*value, column.data_type.as_str()) {
+        (SqlValue::Integer(i), "FLOAT" | "DOUBLE" | "DECIMAL") if i.unsigned_abs() <= MAX_EXACT_FLOAT_INT => {
+            *value = SqlValue::Float(*i as f64);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Coerce every value to its column (see `coerce_value_to_column`), returning the names of the columns that changed
+pub fn coerce_values_to_schema(values: &mut [SqlValue], schema: &[ColumnDefinition]) -> Vec<String> {
+    values.iter_mut()
+        .zip(schema)
+        .filter_map(|(value, column)| coerce_value_to_column(value, column).then(|| column.name.clone()))
+        .collect()
+}
+
+/// Format a float so it always reads back as one: `5.0`, not `5`
+pub fn format_float(f: f64) -> String {
+    format!("{:?}", f)
+}
+
+/// Show a float the way its column declares it: DECIMAL(p,s) with exactly `s` fractional digits, FLOAT
+/// (single precision, up to FLOAT(24)) with 7 significant digits and anything else with 15, so sums such as
+/// `0.1 + 0.2` read `0.3`. Only for display; stored values and comparisons keep `format_float`.
+pub fn format_float_for_column(f: f64, column: Option<&ColumnDefinition>) -> String {
+    let significant_digits = match column {
+        Some(column) if column.data_type == "DECIMAL" && let Some(scale) = column.scale => {
+            return format!("{:.*}", scale as usize, f);
+        }
+        Some(column) if column.data_type == "FLOAT" && column.size.is_none_or(|size| size <= 24) => 7,
+        _ => 15,
+    };
+    // Round in scientific notation, then print the shortest form of the rounded value
+    let rounded = format!("{:.*e}", significant_digits - 1, f).parse().unwrap_or(f);
+    format_float(rounded)
+}
+
 /// Get human-readable type name for SQL value
 fn get_sql_value_type_name(value: &SqlValue) -> &'static str {
     match value {
@@ -561,7 +1147,7 @@ fn get_sql_value_type_name(value: &SqlValue) -> &'static str {
 fn generate_example_values(schema: &[ColumnDefinition]) -> String {
     schema.iter().map(|col| {
         match col.data_type.as_str() {
-            "INT" => "42".to_string(),
+            "INT" | "TINYINT" | "SMALLINT" => "42".to_string(),
             "VARCHAR" | "CHAR" => "'text'".to_string(),
             "BOOLEAN" => "true".to_string(),
             "FLOAT" | "DOUBLE" | "DECIMAL" => "3.14".to_string(),
@@ -577,6 +1163,89 @@ fn generate_example_values(schema: &[ColumnDefinition]) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_integer_out_of_range() {
+        let error = parse_sql_values("12345678901234567890").unwrap_err();
+        assert!(error.contains("Integer out of range"));
+        assert!(parse_sql_values("-99999999999999999999").unwrap_err().contains("Integer out of range"));
+
+        // The i64 bounds themselves and real floats still parse
+        assert_eq!(parse_sql_values("9223372036854775807").unwrap(), vec![SqlValue::Integer(i64::MAX)]);
+        assert_eq!(parse_sql_values("-9223372036854775808").unwrap(), vec![SqlValue::Integer(i64::MIN)]);
+        assert_eq!(parse_sql_values("1e20").unwrap(), vec![SqlValue::Float(1e20)]);
+    }
+
+    #[test]
+    fn test_string_too_long_with_multibyte_characters() {
+        let schema = parse_column_definitions("note VARCHAR(10)").unwrap();
+        // Byte 20 falls inside a two-byte character
+        let value = format!("a{}", "é".repeat(30));
+        let error = validate_values_against_schema(&[SqlValue::String(value)], &schema).unwrap_err();
+        assert!(error.contains("String too long"));
+        assert!(error.contains("Length: 31 characters"));
+        assert!(error.contains(&format!("'a{}...'", "é".repeat(19))));
+
+        let error = validate_values_against_schema(&[SqlValue::String("🦀".repeat(25))], &schema).unwrap_err();
+        assert!(error.contains(&format!("'{}...'", "🦀".repeat(20))));
+
+        // The size counts characters, not bytes
+        assert!(validate_values_against_schema(&[SqlValue::String("héllo wörl".to_string())], &schema).is_ok());
+    }
+
+    #[test]
+    fn test_char_fixed_length() {
+        let schema = parse_column_definitions("code CHAR(4), name VARCHAR(4)").unwrap();
+        let too_long = validate_values_against_schema(&[SqlValue::String("ABCDE".to_string()), SqlValue::Null], &schema);
+        assert!(too_long.unwrap_err().contains("String too long"));
+
+        // Short values are padded by default and rejected in strict mode
+        assert_eq!(pad_char_value("AB", &schema[0]), "AB  ");
+        assert_eq!(pad_char_value("AB", &schema[1]), "AB");
+        assert_eq!(fixed_length_error("AB", &schema[0], 1, CharMode::Pad), None);
+        assert!(fixed_length_error("AB", &schema[0], 1, CharMode::Strict).unwrap().contains("String too short"));
+        assert_eq!(fixed_length_error("ABCD", &schema[0], 1, CharMode::Strict), None);
+        assert_eq!(fixed_length_error("AB", &schema[1], 2, CharMode::Strict), None);
+
+        assert!(char_values_equal("AB  ", "AB"));
+        assert!(!char_values_equal(" AB", "AB"));
+    }
+
+    #[test]
+    fn test_coerce_value_to_column() {
+        let schema = parse_column_definitions("price FLOAT, qty INT, total DECIMAL(10,2), name VARCHAR(10)").unwrap();
+        let mut values = vec![SqlValue::Integer(5), SqlValue::Integer(3), SqlValue::Integer(-2), SqlValue::String("a".to_string())];
+
+        assert_eq!(coerce_values_to_schema(&mut values, &schema), vec!["price", "total"]);
+        assert_eq!(values, vec![SqlValue::Float(5.0), SqlValue::Integer(3), SqlValue::Float(-2.0), SqlValue::String("a".to_string())]);
+        // Already the declared type: nothing to do
+        assert!(coerce_values_to_schema(&mut values, &schema).is_empty());
+
+        let mut huge = SqlValue::Integer(i64::MAX);
+        assert!(!coerce_value_to_column(&mut huge, &schema[0]));
+        assert_eq!(format_float(5.0), "5.0");
+        assert_eq!(format_float(-0.25), "-0.25");
+    }
+
+    #[test]
+    fn test_format_float_for_column() {
+        let schema = parse_column_definitions("total DECIMAL(10,2), whole DECIMAL(5,0), price FLOAT, wide FLOAT(53), ratio DOUBLE, amount DECIMAL(8)").unwrap();
+        let column = |index: usize| Some(&schema[index]);
+        assert_eq!(format_float_for_column(3.5, column(0)), "3.50");
+        assert_eq!(format_float_for_column(0.1 + 0.2, column(0)), "0.30");
+        assert_eq!(format_float_for_column(-2.0, column(0)), "-2.00");
+        assert_eq!(format_float_for_column(7.0, column(1)), "7");
+
+        assert_eq!(format_float_for_column(1.23456789, column(2)), "1.234568");
+        assert_eq!(format_float_for_column(0.1 + 0.2, column(2)), "0.3");
+        assert_eq!(format_float_for_column(5.0, column(2)), "5.0");
+        assert_eq!(format_float_for_column(1.23456789, column(3)), "1.23456789");
+        assert_eq!(format_float_for_column(0.1 + 0.2, column(4)), "0.3");
+        // Without a scale (or a schema), DECIMAL values only lose float noise
+        assert_eq!(format_float_for_column(0.1 + 0.2, column(5)), "0.3");
+        assert_eq!(format_float_for_column(0.1 + 0.2, None), "0.3");
+        assert_eq!(format_float_for_column(1.25, None), "1.25");
+    }
+
     #[test]
     fn test_parse_column_definitions() {
         let schema = "PersonID int, LastName varchar(255), FirstName varchar(255), Address varchar(255), City varchar(255)";
@@ -646,6 +1315,31 @@ mod tests {
         assert!(error.contains("INT") && error.contains("does not support size specification"));
     }
 
+    #[test]
+    fn test_integer_ranges() {
+        let schema = parse_column_definitions("age INT UNSIGNED, level TINYINT, delta SMALLINT, flags TINYINT UNSIGNED").unwrap();
+        assert_eq!(schema[0].to_string(), "age INT UNSIGNED");
+        assert_eq!(parse_column_definitions(&schema[3].to_string()).unwrap()[0].integer_range(), Some((0, 255)));
+        
+        let values = [SqlValue::Integer(30), SqlValue::Integer(-128), SqlValue::Integer(32767), SqlValue::Integer(255)];
+        assert!(validate_values_against_schema(&values, &schema).is_ok());
+        
+        // Negative value into UNSIGNED
+        let values = [SqlValue::Integer(-1), SqlValue::Integer(0), SqlValue::Integer(0), SqlValue::Integer(0)];
+        let error = validate_values_against_schema(&values, &schema).unwrap_err();
+        assert!(error.contains("Value out of range") && error.contains("age INT UNSIGNED"));
+        
+        // Out-of-range TINYINT
+        let values = [SqlValue::Integer(0), SqlValue::Integer(300), SqlValue::Integer(0), SqlValue::Integer(0)];
+        let error = validate_values_against_schema(&values, &schema).unwrap_err();
+        assert!(error.contains("**-128** to **127**"));
+        
+        let error = parse_column_definitions("name VARCHAR(10) UNSIGNED").unwrap_err();
+        assert!(error.contains("UNSIGNED") && error.contains("requires an integer column"));
+        assert!(parse_column_definitions("level TINYINT(4)").unwrap_err().contains("does not support size specification"));
+        assert!(parse_column_definitions("level TINYINT DEFAULT 500").unwrap_err().contains("out of range"));
+    }
+
     #[test]
     fn test_decimal_with_valid_size() {
         let schema = "price DECIMAL(10), amount FLOAT(7), total DOUBLE(15)";
@@ -674,6 +1368,194 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_decimal_precision_and_scale() {
+        let columns = parse_column_definitions("price DECIMAL(10,2), total DECIMAL(8, 3), qty INT").unwrap();
+        
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[0].size, Some(10));
+        assert_eq!(columns[0].scale, Some(2));
+        assert_eq!(columns[0].to_string(), "price DECIMAL(10,2)");
+        assert_eq!(columns[1].size, Some(8));
+        assert_eq!(columns[1].scale, Some(3));
+        assert_eq!(columns[2].name, "qty");
+        
+        // Scale must not exceed precision
+        let result = parse_column_definitions("price DECIMAL(2,5)");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("cannot exceed precision"));
+        
+        // Only DECIMAL accepts a scale
+        let result = parse_column_definitions("name VARCHAR(10,2)");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("does not support a scale"));
+    }
+
+    #[test]
+    fn test_decimal_value_digit_validation() {
+        let schema = parse_column_definitions("price DECIMAL(5,2)").unwrap();
+        
+        assert!(validate_values_against_schema(&[SqlValue::Float(123.45)], &schema).is_ok());
+        assert!(validate_values_against_schema(&[SqlValue::Integer(999)], &schema).is_ok());
+        assert!(validate_values_against_schema(&[SqlValue::Float(-0.5)], &schema).is_ok());
+        
+        // Too many fractional digits
+        let result = validate_values_against_schema(&[SqlValue::Float(1.234)], &schema);
+        assert!(result.unwrap_err().contains("Too many decimal places"));
+        
+        // Too many integer digits for DECIMAL(5,2)
+        let result = validate_values_against_schema(&[SqlValue::Integer(1000)], &schema);
+        assert!(result.unwrap_err().contains("out of range"));
+        
+        // Precision-only DECIMAL limits total digits
+        let schema = parse_column_definitions("amount DECIMAL(4)").unwrap();
+        assert!(validate_values_against_schema(&[SqlValue::Float(12.34)], &schema).is_ok());
+        let result = validate_values_against_schema(&[SqlValue::Float(123.45)], &schema);
+        assert!(result.unwrap_err().contains("Too many digits"));
+    }
+
+    #[test]
+    fn test_auto_increment_flag() {
+        let columns = parse_column_definitions("id INT PRIMARY KEY AUTO_INCREMENT, name VARCHAR(50)").unwrap();
+        
+        assert!(columns[0].auto_increment);
+        assert!(columns[0].primary_key);
+        assert!(!columns[1].auto_increment);
+        assert_eq!(columns[0].to_string(), "id INT PRIMARY KEY AUTO_INCREMENT");
+        
+        // Lowercase keyword is accepted too
+        let columns = parse_column_definitions("id int auto_increment").unwrap();
+        assert!(columns[0].auto_increment);
+        
+        // Only integer columns can auto-increment
+        let result = parse_column_definitions("name VARCHAR(10) AUTO_INCREMENT");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("requires an INT column"));
+    }
+
+    #[test]
+    fn test_unique_flag() {
+        let columns = parse_column_definitions("id INT PRIMARY KEY, email VARCHAR(100) UNIQUE NOT NULL").unwrap();
+        
+        assert!(!columns[0].unique);
+        assert!(columns[1].unique);
+        assert!(!columns[1].nullable);
+        assert_eq!(columns[1].to_string(), "email VARCHAR(100) NOT NULL UNIQUE");
+    }
+
+    #[test]
+    fn test_quoted_column_names() {
+        let columns = parse_column_definitions("`order date` DATE, \"select\" INT PRIMARY KEY, `note, long` VARCHAR(20)").unwrap();
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["order date", "select", "note, long"]);
+        assert_eq!(columns[0].data_type, "DATE");
+        assert!(columns[1].primary_key);
+        assert_eq!(columns[2].size, Some(20));
+        // Shown quoted wherever the name isn't a plain identifier, so it reads back the same
+        assert_eq!(columns[0].to_string(), "`order date` DATE");
+        assert_eq!(columns[1].to_string(), "select INT PRIMARY KEY");
+        assert_eq!(quote_identifier("it`s"), "\"it`s\"");
+
+        // Quoted names in a table-level key
+        let columns = parse_column_definitions("`order id` INT, line INT, PRIMARY KEY (`order id`, line)").unwrap();
+        assert!(columns[0].primary_key && columns[1].primary_key);
+
+        assert!(parse_column_definitions("`` INT").unwrap_err().contains("can't be empty"));
+        assert!(parse_column_definitions("`a: b` INT").unwrap_err().contains("can't contain"));
+    }
+
+    #[test]
+    fn test_composite_primary_key() {
+        let columns = parse_column_definitions("order_id INT, product_id INT, qty INT, PRIMARY KEY (order_id, Product_ID)").unwrap();
+        
+        assert_eq!(columns.len(), 3);
+        assert!(columns[0].primary_key);
+        assert!(columns[1].primary_key);
+        assert!(!columns[2].primary_key);
+        
+        // No space before the column list works too
+        let columns = parse_column_definitions("a INT, b INT, PRIMARY KEY(a,b)").unwrap();
+        assert!(columns[0].primary_key && columns[1].primary_key);
+        
+        let result = parse_column_definitions("a INT, PRIMARY KEY (a, missing)");
+        assert!(result.unwrap_err().contains("unknown column"));
+        
+        let result = parse_column_definitions("a INT, PRIMARY KEY");
+        assert!(result.unwrap_err().contains("Expected format"));
+    }
+
+    #[test]
+    fn test_default_values() {
+        let columns = parse_column_definitions(
+            "status VARCHAR(20) DEFAULT 'in progress', retries INT DEFAULT 3, active BOOLEAN DEFAULT true, note VARCHAR(10) DEFAULT 'a,b'"
+        ).unwrap();
+        
+        assert_eq!(columns.len(), 4);
+        assert!(matches!(columns[0].default, Some(SqlValue::String(ref s)) if s == "in progress"));
+        assert!(matches!(columns[1].default, Some(SqlValue::Integer(3))));
+        assert!(matches!(columns[2].default, Some(SqlValue::Boolean(true))));
+        assert!(matches!(columns[3].default, Some(SqlValue::String(ref s)) if s == "a,b"));
+        assert_eq!(columns[0].to_string(), "status VARCHAR(20) DEFAULT 'in progress'");
+        
+        // DEFAULT can be combined with other constraints in any order
+        let columns = parse_column_definitions("id INT DEFAULT 0 NOT NULL").unwrap();
+        assert!(matches!(columns[0].default, Some(SqlValue::Integer(0))));
+        assert!(!columns[0].nullable);
+    }
+
+    #[test]
+    fn test_default_type_mismatch() {
+        let result = parse_column_definitions("retries INT DEFAULT 'three'");
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(error.contains("DEFAULT") && error.contains("Type mismatch"));
+        
+        let result = parse_column_definitions("code VARCHAR(2) DEFAULT 'too long'");
+        assert!(result.unwrap_err().contains("String too long"));
+        
+        let result = parse_column_definitions("status VARCHAR(20) DEFAULT");
+        assert!(result.unwrap_err().contains("requires a value"));
+    }
+
+    #[test]
+    fn test_parse_column_comment() {
+        let columns = parse_column_definitions("id INT PRIMARY KEY COMMENT 'primary identifier', note VARCHAR(50) COMMENT 'it''s, optional' DEFAULT 'none'").unwrap();
+        assert_eq!(columns[0].comment.as_deref(), Some("primary identifier"));
+        assert!(columns[0].primary_key);
+        // Quotes and commas inside the comment don't end it, and later constraints still apply
+        assert_eq!(columns[1].comment.as_deref(), Some("it's, optional"));
+        assert_eq!(columns[1].default, Some(SqlValue::String("none".to_string())));
+        assert!(parse_column_definitions("id INT").unwrap()[0].comment.is_none());
+
+        // Display writes the comment back so the stored schema parses to the same columns
+        assert_eq!(columns[1].to_string(), "note VARCHAR(50) DEFAULT 'none' COMMENT 'it''s, optional'");
+        let reparsed = parse_column_definitions(&columns[1].to_string()).unwrap();
+        assert_eq!(reparsed[0].comment, columns[1].comment);
+
+        for schema in ["id INT COMMENT", "id INT COMMENT 42", "id INT COMMENT identifier"] {
+            assert!(parse_column_definitions(schema).unwrap_err().contains("**COMMENT** requires a quoted string"), "{}", schema);
+        }
+    }
+
+    #[test]
+    fn test_parse_column_references() {
+        let columns = parse_column_definitions("id INT PRIMARY KEY, customer_id INT REFERENCES customers(id) NOT NULL, `order` INT REFERENCES `old orders` (`order id`)").unwrap();
+        assert_eq!(columns[0].references, None);
+        assert_eq!(columns[1].references, Some(ForeignKey { table: "customers".to_string(), column: "id".to_string() }));
+        assert!(!columns[1].nullable);
+        assert_eq!(columns[2].references, Some(ForeignKey { table: "old orders".to_string(), column: "order id".to_string() }));
+
+        // Display writes the reference back so the stored schema parses to the same columns
+        assert_eq!(columns[1].to_string(), "customer_id INT NOT NULL REFERENCES customers(id)");
+        assert_eq!(columns[2].to_string(), "order INT REFERENCES `old orders`(`order id`)");
+        let reparsed = parse_column_definitions(&columns[2].to_string()).unwrap();
+        assert_eq!(reparsed[0].references, columns[2].references);
+
+        for schema in ["a INT REFERENCES", "a INT REFERENCES customers", "a INT REFERENCES customers()", "a INT REFERENCES (id)", "a INT REFERENCES customers(id, name)"] {
+            assert!(parse_column_definitions(schema).unwrap_err().contains("**REFERENCES** requires a table"), "{}", schema);
+        }
+    }
+
     #[test]
     fn test_varchar_size_validation() {
         // Test zero size
@@ -737,6 +1619,51 @@ mod tests {
         assert!(matches!(result[1], SqlValue::String(ref s) if s == "with 'escaped' quotes"));
     }
 
+    #[test]
+    fn test_parse_sql_values_double_quoted_strings() {
+        let input = r#"1, "John", true"#;
+        let result = parse_sql_values(input).unwrap();
+        
+        assert_eq!(result.len(), 3);
+        assert!(matches!(result[1], SqlValue::String(ref s) if s == "John"));
+        
+        // "" and \" both escape a double quote
+        let input = r#""say ""hi""", "back\"slash""#;
+        let result = parse_sql_values(input).unwrap();
+        
+        assert_eq!(result.len(), 2);
+        assert!(matches!(result[0], SqlValue::String(ref s) if s == r#"say "hi""#));
+        assert!(matches!(result[1], SqlValue::String(ref s) if s == r#"back"slash"#));
+    }
+
+    #[test]
+    fn test_parse_sql_values_mixed_quotes() {
+        // The other quote character is literal inside a string
+        let input = r#"'He said "yes"', "It's fine", 'plain'"#;
+        let result = parse_sql_values(input).unwrap();
+        
+        assert_eq!(result.len(), 3);
+        assert!(matches!(result[0], SqlValue::String(ref s) if s == r#"He said "yes""#));
+        assert!(matches!(result[1], SqlValue::String(ref s) if s == "It's fine"));
+        assert!(matches!(result[2], SqlValue::String(ref s) if s == "plain"));
+        
+        let result = parse_sql_values(r#"1, "unterminated"#);
+        assert!(result.unwrap_err().contains("Unterminated"));
+    }
+
+    #[test]
+    fn test_parse_sql_values_null_keyword() {
+        // Unquoted NULL is the keyword in any case; quoted it is just text
+        let result = parse_sql_values(r#"NULL, null, Null, 'NULL', "null""#).unwrap();
+        assert_eq!(result, vec![
+            SqlValue::Null,
+            SqlValue::Null,
+            SqlValue::Null,
+            SqlValue::String("NULL".to_string()),
+            SqlValue::String("null".to_string()),
+        ]);
+    }
+
     #[test]
     fn test_parse_sql_values_error_cases() {
         // Unterminated string
@@ -757,15 +1684,29 @@ mod tests {
                 name: "id".to_string(),
                 data_type: "INT".to_string(),
                 size: None,
+                scale: None,
+                unsigned: false,
                 nullable: false,
                 primary_key: true,
+                unique: false,
+                auto_increment: false,
+                default: None,
+                references: None,
+                comment: None,
             },
             ColumnDefinition {
                 name: "name".to_string(),
                 data_type: "VARCHAR".to_string(),
                 size: Some(10),
+                scale: None,
+                unsigned: false,
                 nullable: false,
                 primary_key: false,
+                unique: false,
+                auto_increment: false,
+                default: None,
+                references: None,
+                comment: None,
             },
         ];
         
@@ -844,18 +1785,11 @@ fn is_valid_iso_date(date_str: &str) -> bool {
 
 /// Validate ISO 8601 time format (HH:MM:SS[.fraction][Z|±HH:MM])
 fn is_valid_iso_time(time_str: &str) -> bool {
-    // Handle timezone suffix
-    let (time_part, _tz_part) = if time_str.ends_with('Z') {
-        (&time_str[..time_str.len()-1], Some("Z"))
-    } else if let Some(pos) = time_str.rfind('+').or_else(|| time_str.rfind('-')) {
-        if pos > 6 { // Ensure we don't split on date part
-            (&time_str[..pos], Some(&time_str[pos..]))
-        } else {
-            (time_str, None)
-        }
-    } else {
-        (time_str, None)
-    };
+    let (time_part, tz_part) = split_tz_suffix(time_str);
+    
+    if tz_part.is_some_and(|tz| !is_valid_tz_offset(tz)) {
+        return false;
+    }
     
     // Split main time components
     let main_parts: Vec<&str> = time_part.split(':').collect();
@@ -889,9 +1823,9 @@ fn is_valid_iso_time(time_str: &str) -> bool {
             _ => return false,
         };
         
-        // Validate fractional seconds (must be digits)
+        // Validate fractional seconds (1-9 digits, i.e. up to nanosecond precision)
         let fraction = sec_parts[1];
-        if fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        if fraction.is_empty() || fraction.len() > 9 || !fraction.chars().all(|c| c.is_ascii_digit()) {
             return false;
         }
     } else {
@@ -905,6 +1839,47 @@ fn is_valid_iso_time(time_str: &str) -> bool {
     true
 }
 
+/// Split a time into its clock part and optional timezone suffix (`Z` or `±HH:MM`)
+fn split_tz_suffix(time_str: &str) -> (&str, Option<&str>) {
+    if let Some(stripped) = time_str.strip_suffix('Z') {
+        (stripped, Some("Z"))
+    } else if let Some(pos) = time_str.rfind('+').or_else(|| time_str.rfind('-')) {
+        if pos > 6 { // Ensure we don't split on date part
+            (&time_str[..pos], Some(&time_str[pos..]))
+        } else {
+            (time_str, None)
+        }
+    } else {
+        (time_str, None)
+    }
+}
+
+/// Validate an ISO 8601 timezone suffix (`Z` or `±HH:MM`, hours 00-14, minutes 00-59)
+fn is_valid_tz_offset(tz_str: &str) -> bool {
+    if tz_str == "Z" {
+        return true;
+    }
+    
+    let offset = match tz_str.strip_prefix('+').or_else(|| tz_str.strip_prefix('-')) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    
+    let parts: Vec<&str> = offset.split(':').collect();
+    if parts.len() != 2 {
+        return false;
+    }
+    
+    let is_two_digits = |p: &str| p.len() == 2 && p.chars().all(|c| c.is_ascii_digit());
+    if !is_two_digits(parts[0]) || !is_two_digits(parts[1]) {
+        return false;
+    }
+    
+    let hours = parts[0].parse::<u32>().unwrap_or(u32::MAX);
+    let minutes = parts[1].parse::<u32>().unwrap_or(u32::MAX);
+    hours <= 14 && minutes <= 59
+}
+
 /// Validate ISO 8601 datetime format (YYYY-MM-DDTHH:MM:SS[.fraction][Z|±HH:MM])
 fn is_valid_iso_datetime(datetime_str: &str) -> bool {
     if !datetime_str.contains('T') {
@@ -922,10 +1897,92 @@ fn is_valid_iso_datetime(datetime_str: &str) -> bool {
     is_valid_iso_date(date_part) && is_valid_iso_time(time_part)
 }
 
+/// A DATE, TIME or DATETIME value normalized for chronological comparison.
+/// Times and datetimes with an offset are converted to UTC; values without one are taken as UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TemporalValue {
+    Date(NaiveDate),
+    /// Time of day in UTC (wraps around midnight)
+    Time(NaiveTime),
+    DateTime(NaiveDateTime),
+}
+
+/// Parse a stored or typed value of a DATE/TIME/DATETIME column.
+/// Returns `None` for other types and for values the ISO validators reject.
+/// A DATETIME may separate date and time with a space instead of `T` (`2024-01-01 00:00:00`).
+pub fn parse_temporal_value(data_type: &str, value: &str) -> Option<TemporalValue> {
+    let spaced = value.get(..10).zip(value.get(11..)).filter(|_| value.as_bytes().get(10) == Some(&b' '));
+    let value = &match spaced {
+        Some((date_part, time_part)) if data_type.eq_ignore_ascii_case("DATETIME") => format!("{}T{}", date_part, time_part),
+        _ => value.to_string(),
+    };
+    match data_type.to_uppercase().as_str() {
+        "DATE" if is_valid_iso_date(value) => {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().map(TemporalValue::Date)
+        }
+        "TIME" if is_valid_iso_time(value) => {
+            let (time, offset) = parse_time_with_offset(value)?;
+            Some(TemporalValue::Time(time - offset))
+        }
+        "DATETIME" if is_valid_iso_datetime(value) => {
+            let (date_part, time_part) = value.split_once('T')?;
+            let date = NaiveDate::parse_from_str(date_part, "%Y-%m-%d").ok()?;
+            let (time, offset) = parse_time_with_offset(time_part)?;
+            Some(TemporalValue::DateTime(date.and_time(time) - offset))
+        }
+        _ => None,
+    }
+}
+
+/// Parse an already validated ISO time into its clock time and UTC offset
+fn parse_time_with_offset(time_str: &str) -> Option<(NaiveTime, TimeDelta)> {
+    let (time_part, tz_part) = split_tz_suffix(time_str);
+    let time = NaiveTime::parse_from_str(time_part, "%H:%M:%S%.f").ok()?;
+    let offset = match tz_part {
+        None | Some("Z") => TimeDelta::zero(),
+        Some(tz) => {
+            let (hours, minutes) = tz[1..].split_once(':')?;
+            let magnitude = TimeDelta::hours(hours.parse().ok()?) + TimeDelta::minutes(minutes.parse().ok()?);
+            if tz.starts_with('-') { -magnitude } else { magnitude }
+        }
+    };
+    Some((time, offset))
+}
+
 #[cfg(test)]
 mod iso_tests {
     use super::*;
 
+    #[test]
+    fn test_temporal_values_compare_across_offsets() {
+        let utc = parse_temporal_value("DATETIME", "2025-01-15T14:30:00Z").unwrap();
+        let plus_two = parse_temporal_value("DATETIME", "2025-01-15T16:30:00+02:00").unwrap();
+        let minus_five = parse_temporal_value("DATETIME", "2025-01-15T09:30:00.000-05:00").unwrap();
+        assert_eq!(utc, plus_two);
+        assert_eq!(utc, minus_five);
+
+        // Lexically later, chronologically earlier
+        let later_text = parse_temporal_value("DATETIME", "2025-01-15T15:00:00+02:00").unwrap();
+        assert!("2025-01-15T15:00:00+02:00" > "2025-01-15T14:30:00Z");
+        assert!(later_text < utc);
+
+        // Offsets can cross a date boundary
+        assert_eq!(
+            parse_temporal_value("DATETIME", "2025-01-01T01:00:00+02:00"),
+            parse_temporal_value("DATETIME", "2024-12-31T23:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_temporal_values_dates_and_times() {
+        assert!(parse_temporal_value("DATE", "2023-01-01") < parse_temporal_value("date", "2023-01-02"));
+        assert_eq!(parse_temporal_value("TIME", "12:00:00+02:00"), parse_temporal_value("TIME", "10:00:00Z"));
+        assert_eq!(parse_temporal_value("TIME", "10:00:00"), parse_temporal_value("TIME", "10:00:00.000"));
+        assert_eq!(parse_temporal_value("DATETIME", "2024-01-01 00:00:00"), parse_temporal_value("DATETIME", "2024-01-01T00:00:00Z"));
+        assert_eq!(parse_temporal_value("DATE", "2023-02-30"), None);
+        assert_eq!(parse_temporal_value("VARCHAR", "2023-01-01"), None);
+    }
+
     #[test]
     fn test_valid_iso_dates() {
         assert!(is_valid_iso_date("2025-08-19"));
@@ -974,6 +2031,42 @@ mod iso_tests {
         assert!(!is_valid_iso_time("not-a-time")); // invalid format
     }
 
+    #[test]
+    fn test_valid_tz_offsets() {
+        assert!(is_valid_iso_time("12:00:00+02:00"));
+        assert!(is_valid_iso_time("12:00:00-05:30"));
+        assert!(is_valid_iso_time("12:00:00+14:00"));
+        assert!(is_valid_iso_time("12:00:00-00:00"));
+        assert!(is_valid_iso_datetime("2024-06-15T12:00:00-05:30"));
+    }
+
+    #[test]
+    fn test_invalid_tz_offsets() {
+        assert!(!is_valid_iso_time("12:00:00+99:00")); // hours out of range
+        assert!(!is_valid_iso_time("12:00:00+15:00")); // hours out of range
+        assert!(!is_valid_iso_time("12:00:00+02:60")); // minutes out of range
+        assert!(!is_valid_iso_time("12:00:00+99:99")); // both out of range
+        assert!(!is_valid_iso_time("12:00:00+02:5")); // one-digit minutes
+        assert!(!is_valid_iso_time("12:00:00+0200")); // missing colon
+        assert!(!is_valid_iso_datetime("2024-06-15T12:00:00+99:00"));
+    }
+
+    #[test]
+    fn test_fraction_digit_limit() {
+        assert!(is_valid_iso_time("12:00:00.1"));
+        assert!(is_valid_iso_time("12:00:00.123456789")); // nanoseconds
+        assert!(!is_valid_iso_time("12:00:00.1234567890")); // 10 digits
+        assert!(!is_valid_iso_time("12:00:00.1234567890Z"));
+    }
+
+    #[test]
+    fn test_one_digit_offset_components() {
+        assert!(!is_valid_iso_time("12:00:00-5")); // one-digit hours, no minutes
+        assert!(!is_valid_iso_time("12:00:00+5:00")); // one-digit hours
+        assert!(!is_valid_iso_time("12:00:00-05:0")); // one-digit minutes
+        assert!(!is_valid_iso_datetime("2025-08-19T12:00:00-5"));
+    }
+
     #[test]
     fn test_valid_iso_datetimes() {
         assert!(is_valid_iso_datetime("2025-08-19T14:30:00Z"));
@@ -1,3 +1,6 @@
 pub mod session_store;
 
-pub use session_store::CurrentDB;
+pub use session_store::{
+    clear_database_selections, read_only_enabled, AuditLog, BotStartTime, CurrentDB, DestructiveRoles, GuildChannelCache, PendingConfirmations,
+    PendingDrop, ReadOnlyMode, TopicLocks, CONFIRMATION_TIMEOUT,
+};
@@ -1,11 +1,121 @@
 use serenity::prelude::TypeMapKey;
-use serenity::model::id::{GuildId, UserId};
-use std::collections::HashMap;
+use serenity::model::id::{ChannelId, GuildId, MessageId, RoleId, UserId};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
+use crate::services::channel_cache::ChannelCache;
 
 pub struct CurrentDB;
 
 impl TypeMapKey for CurrentDB {
     type Value = Arc<Mutex<HashMap<(GuildId, UserId), String>>>;
 }
+
+/// Forget every user's selection of database `db_name` in a guild, e.g. after it was dropped.
+/// Returns how many selections were removed.
+pub fn clear_database_selections(selections: &mut HashMap<(GuildId, UserId), String>, guild_id: GuildId, db_name: &str) -> usize {
+    let before = selections.len();
+    selections.retain(|(guild, _), db| !(*guild == guild_id && db == db_name));
+    before - selections.len()
+}
+
+/// Per-guild allowlist of roles permitted to run destructive commands (configured with `/admin perms`)
+pub struct DestructiveRoles;
+
+impl TypeMapKey for DestructiveRoles {
+    type Value = Arc<Mutex<HashMap<GuildId, HashSet<RoleId>>>>;
+}
+
+/// File the audit log is appended to (`SQLCORD_AUDIT_LOG` in the bot)
+pub struct AuditLog;
+
+impl TypeMapKey for AuditLog {
+    type Value = PathBuf;
+}
+
+/// One lock per table channel, held while its topic is read, changed and written back, so concurrent
+/// writes don't lose each other's `Rows:` updates
+pub struct TopicLocks;
+
+impl TypeMapKey for TopicLocks {
+    type Value = Arc<Mutex<HashMap<ChannelId, Arc<Mutex<()>>>>>;
+}
+
+/// Short-lived per-guild channel lists, shared by all commands
+pub struct GuildChannelCache;
+
+impl TypeMapKey for GuildChannelCache {
+    type Value = Arc<Mutex<ChannelCache>>;
+}
+
+/// When the bot first became ready, for `/sql status` uptime
+pub struct BotStartTime;
+
+impl TypeMapKey for BotStartTime {
+    type Value = Instant;
+}
+
+/// `SQLCORD_READ_ONLY`, read once at startup: when true every mutating command is refused
+pub struct ReadOnlyMode;
+
+impl TypeMapKey for ReadOnlyMode {
+    type Value = bool;
+}
+
+/// Whether a `SQLCORD_READ_ONLY` value turns read-only mode on (`1`, `true`, `yes` or `on`)
+pub fn read_only_enabled(value: Option<&str>) -> bool {
+    value.is_some_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+}
+
+/// How long a `/sql drop db` prompt waits for its ✅ before expiring
+pub const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A `/sql drop db` waiting for the invoking user to confirm with a reaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingDrop {
+    pub guild_id: GuildId,
+    /// Canonical database name, without the `db_` prefix
+    pub db_name: String,
+    pub requested_at: Instant,
+}
+
+/// Drops awaiting confirmation, keyed by the prompt message and the user who may answer it
+pub struct PendingConfirmations;
+
+impl TypeMapKey for PendingConfirmations {
+    type Value = Arc<Mutex<HashMap<(MessageId, UserId), PendingDrop>>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clear_database_selections() {
+        let guild = GuildId::new(1);
+        let other_guild = GuildId::new(2);
+        let mut selections = HashMap::new();
+        selections.insert((guild, UserId::new(10)), "shop".to_string());
+        selections.insert((guild, UserId::new(11)), "shop".to_string());
+        selections.insert((guild, UserId::new(12)), "blog".to_string());
+        selections.insert((other_guild, UserId::new(10)), "shop".to_string());
+
+        assert_eq!(clear_database_selections(&mut selections, guild, "shop"), 2);
+        assert_eq!(selections.get(&(guild, UserId::new(12))).map(String::as_str), Some("blog"));
+        // Same database name in another guild is a different database
+        assert!(selections.contains_key(&(other_guild, UserId::new(10))));
+        assert_eq!(clear_database_selections(&mut selections, guild, "shop"), 0);
+    }
+
+    #[test]
+    fn test_read_only_enabled() {
+        for value in ["1", "true", "YES", " on "] {
+            assert!(read_only_enabled(Some(value)), "{value}");
+        }
+        for value in [None, Some(""), Some("0"), Some("false"), Some("off")] {
+            assert!(!read_only_enabled(value), "{value:?}");
+        }
+    }
+}
@@ -0,0 +1,586 @@
+// In-memory Discord for tests: channels and messages kept in a `FakeDiscord` backend
+//
+// `TestContext` implements `BotContext` on top of it, so commands run end-to-end without a connection:
+//
+//   let ctx = TestContext::new();
+//   sqlcord::api::create_database(&ctx, TEST_GUILD_ID, "shop").await?;
+//   assert!(ctx.discord.channel_named("db_shop").is_some());
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use serde_json::{json, Value};
+use serenity::builder::{CreateChannel, CreateMessage, EditChannel, EditMessage};
+use serenity::model::channel::{GuildChannel, Message};
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+use serenity::prelude::{RwLock, TypeMap};
+use crate::services::channel_cache::ChannelCache;
+use crate::services::{Backend, BotContext};
+use crate::state::{AuditLog, CurrentDB, DestructiveRoles, GuildChannelCache, PendingConfirmations, ReadOnlyMode, TopicLocks};
+
+/// The only guild the fake Discord knows
+pub const TEST_GUILD_ID: GuildId = GuildId::new(1);
+/// Author of every message the fake Discord stores, standing in for the bot user
+const BOT_USER_ID: u64 = 2;
+/// What Discord answers for a channel that doesn't exist
+const UNKNOWN_CHANNEL: serenity::Error = serenity::Error::Other("Unknown Channel");
+
+/// Channels and messages in the JSON shape Discord returns them
+#[derive(Default)]
+struct Store {
+    next_id: u64,
+    channels: Vec<Value>,
+    /// Messages per channel, oldest first
+    messages: HashMap<ChannelId, Vec<Value>>,
+    /// Channel edits still to reject, as Discord does once a channel's edit rate limit is used up
+    failing_channel_edits: usize,
+}
+
+impl Store {
+    /// Snowflakes only need to be unique and increasing: paging compares message IDs
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        1000 + self.next_id
+    }
+
+    fn channel_mut(&mut self, channel_id: ChannelId) -> Option<&mut Value> {
+        let id = channel_id.to_string();
+        self.channels.iter_mut().find(|channel| channel["id"] == id.as_str())
+    }
+}
+
+/// A `Backend` that keeps a single guild's channels and messages in memory
+#[derive(Clone, Default)]
+pub struct FakeDiscord {
+    store: Arc<Mutex<Store>>,
+}
+
+impl FakeDiscord {
+    /// The channel named `name` (`db_shop`, `table_users`), as the bot would fetch it
+    pub fn channel_named(&self, name: &str) -> Option<GuildChannel> {
+        let store = self.store.lock().unwrap();
+        let channel = store.channels.iter().find(|channel| channel["name"] == name)?;
+        serde_json::from_value(channel.clone()).ok()
+    }
+
+    /// Make the next `count` channel edits fail
+    pub fn fail_channel_edits(&self, count: usize) {
+        self.store.lock().unwrap().failing_channel_edits = count;
+    }
+
+    /// Contents of a channel's messages, oldest first
+    pub fn message_contents(&self, channel_id: ChannelId) -> Vec<String> {
+        let store = self.store.lock().unwrap();
+        store
+            .messages
+            .get(&channel_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|message| message["content"].as_str().map(str::to_string))
+            .collect()
+    }
+}
+
+impl Backend for FakeDiscord {
+    async fn list_channels(&self, _guild_id: GuildId) -> serenity::Result<HashMap<ChannelId, GuildChannel>> {
+        let channels = self.store.lock().unwrap().channels.clone();
+        let channels: Vec<GuildChannel> = serde_json::from_value(Value::Array(channels))?;
+        Ok(channels.into_iter().map(|channel| (channel.id, channel)).collect())
+    }
+
+    async fn get_channel(&self, channel_id: ChannelId) -> serenity::Result<GuildChannel> {
+        let channel = self.store.lock().unwrap().channel_mut(channel_id).ok_or(UNKNOWN_CHANNEL)?.clone();
+        // Like a real response, arrive after other tasks had a chance to run
+        tokio::task::yield_now().await;
+        Ok(serde_json::from_value(channel)?)
+    }
+
+    async fn create_channel(&self, _guild_id: GuildId, channel: CreateChannel<'_>) -> serenity::Result<GuildChannel> {
+        let body = serde_json::to_value(&channel)?;
+        let mut store = self.store.lock().unwrap();
+        let channel = json!({
+            "id": store.next_id().to_string(),
+            "guild_id": TEST_GUILD_ID.to_string(),
+            "name": body["name"],
+            "type": body.get("type").cloned().unwrap_or(json!(0)),
+            "position": 0,
+            "parent_id": body.get("parent_id").cloned().unwrap_or(Value::Null),
+            "topic": body.get("topic").cloned().unwrap_or(Value::Null),
+            "permission_overwrites": [],
+            "nsfw": false
+        });
+        store.channels.push(channel.clone());
+        Ok(serde_json::from_value(channel)?)
+    }
+
+    async fn edit_channel(&self, channel_id: ChannelId, changes: EditChannel<'_>) -> serenity::Result<GuildChannel> {
+        let body = serde_json::to_value(&changes)?;
+        let mut store = self.store.lock().unwrap();
+        if store.failing_channel_edits > 0 {
+            store.failing_channel_edits -= 1;
+            return Err(serenity::Error::Other("Rate limited"));
+        }
+        let channel = store.channel_mut(channel_id).ok_or(UNKNOWN_CHANNEL)?;
+        if let (Some(channel), Some(changes)) = (channel.as_object_mut(), body.as_object()) {
+            channel.extend(changes.iter().map(|(key, value)| (key.clone(), value.clone())));
+        }
+        Ok(serde_json::from_value(channel.clone())?)
+    }
+
+    async fn delete_channel(&self, channel_id: ChannelId) -> serenity::Result<()> {
+        let mut store = self.store.lock().unwrap();
+        store.channel_mut(channel_id).ok_or(UNKNOWN_CHANNEL)?;
+        let id = channel_id.to_string();
+        store.channels.retain(|channel| channel["id"] != id.as_str());
+        store.messages.remove(&channel_id);
+        Ok(())
+    }
+
+    async fn fetch_messages(&self, channel_id: ChannelId, limit: u8, before: Option<MessageId>) -> serenity::Result<Vec<Message>> {
+        let mut store = self.store.lock().unwrap();
+        store.channel_mut(channel_id).ok_or(UNKNOWN_CHANNEL)?;
+        let page: Vec<Value> = store
+            .messages
+            .get(&channel_id)
+            .into_iter()
+            .flatten()
+            .rev()
+            .filter(|message| {
+                let id = message["id"].as_str().and_then(|id| id.parse::<u64>().ok()).unwrap_or_default();
+                before.is_none_or(|before| id < before.get())
+            })
+            .take(limit.min(100) as usize)
+            .cloned()
+            .collect();
+        Ok(serde_json::from_value(Value::Array(page))?)
+    }
+
+    async fn send_message(&self, channel_id: ChannelId, message: CreateMessage) -> serenity::Result<Message> {
+        let body = serde_json::to_value(&message)?;
+        let mut store = self.store.lock().unwrap();
+        store.channel_mut(channel_id).ok_or(UNKNOWN_CHANNEL)?;
+        let message = json!({
+            "id": store.next_id().to_string(),
+            "channel_id": channel_id.to_string(),
+            "guild_id": TEST_GUILD_ID.to_string(),
+            "author": { "id": BOT_USER_ID.to_string(), "username": "SQLcord", "discriminator": "0000", "avatar": null, "bot": true },
+            "content": body["content"].as_str().unwrap_or_default(),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "edited_timestamp": null,
+            "tts": false,
+            "mention_everyone": false,
+            "mentions": [],
+            "mention_roles": [],
+            "attachments": [],
+            "embeds": [],
+            "pinned": false,
+            "type": 0
+        });
+        store.messages.entry(channel_id).or_default().push(message.clone());
+        Ok(serde_json::from_value(message)?)
+    }
+
+    async fn edit_message(&self, channel_id: ChannelId, message_id: MessageId, changes: EditMessage) -> serenity::Result<Message> {
+        let body = serde_json::to_value(&changes)?;
+        let mut store = self.store.lock().unwrap();
+        let id = message_id.to_string();
+        let message = store
+            .messages
+            .get_mut(&channel_id)
+            .and_then(|messages| messages.iter_mut().find(|message| message["id"] == id.as_str()))
+            .ok_or(serenity::Error::Other("Unknown Message"))?;
+        if let Some(content) = body.get("content") {
+            message["content"] = content.clone();
+        }
+        message["edited_timestamp"] = json!(chrono::Utc::now().to_rfc3339());
+        Ok(serde_json::from_value(message.clone())?)
+    }
+}
+
+/// A `BotContext` over a fresh, empty `FakeDiscord`, with the shared data the bot sets up at startup
+#[derive(Clone)]
+pub struct TestContext {
+    data: Arc<RwLock<TypeMap>>,
+    pub discord: FakeDiscord,
+    /// Where this context's audited commands are logged
+    pub audit_log: PathBuf,
+}
+
+impl TestContext {
+    pub fn new() -> Self {
+        let mut data = TypeMap::new();
+        data.insert::<CurrentDB>(Arc::new(tokio::sync::Mutex::new(HashMap::new())));
+        data.insert::<DestructiveRoles>(Arc::new(tokio::sync::Mutex::new(HashMap::new())));
+        data.insert::<GuildChannelCache>(Arc::new(tokio::sync::Mutex::new(ChannelCache::default())));
+        data.insert::<PendingConfirmations>(Arc::new(tokio::sync::Mutex::new(HashMap::new())));
+        data.insert::<TopicLocks>(Arc::new(tokio::sync::Mutex::new(HashMap::new())));
+        data.insert::<ReadOnlyMode>(false);
+        // Each context audits into its own temp file, so tests never write into the working directory
+        static CONTEXTS: AtomicUsize = AtomicUsize::new(0);
+        let audit_log = std::env::temp_dir().join(format!("sqlcord-test-audit-{}-{}.jsonl", std::process::id(), CONTEXTS.fetch_add(1, Ordering::Relaxed)));
+        let _ = std::fs::remove_file(&audit_log);
+        data.insert::<AuditLog>(audit_log.clone());
+        TestContext { data: Arc::new(RwLock::new(data)), discord: FakeDiscord::default(), audit_log }
+    }
+
+    /// Select `database` for `user_id`, as `/sql use` would
+    pub async fn use_database(&self, user_id: UserId, database: &str) {
+        let data = self.data.read().await;
+        if let Some(sessions) = data.get::<CurrentDB>() {
+            sessions.lock().await.insert((TEST_GUILD_ID, user_id), database.to_string());
+        }
+    }
+}
+
+impl Default for TestContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BotContext for TestContext {
+    type Backend = FakeDiscord;
+
+    fn backend(&self) -> &FakeDiscord {
+        &self.discord
+    }
+
+    fn data(&self) -> &Arc<RwLock<TypeMap>> {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_database;
+    use crate::commands::options::{CreateTableArgs, InsertArgs, SelectArgs};
+    use crate::commands::sql::{copy, create, dump, insert, restore, select};
+    use crate::commands::sql::insert::OnConflict;
+    use crate::commands::sql::select::OutputFormat;
+    use crate::services::topic_meta::{update_table_topic, TopicMeta};
+    use crate::sql_parser::SqlValue;
+
+    const USER_ID: UserId = UserId::new(7);
+
+    /// A fake Discord with database `shop` selected for `USER_ID` and an empty `users` table
+    async fn shop_with_users() -> TestContext {
+        let ctx = TestContext::new();
+        create_database(&ctx, TEST_GUILD_ID, "shop").await.unwrap();
+        ctx.use_database(USER_ID, "shop").await;
+        let created = create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &create_args("users", Some("id INT PRIMARY KEY, name VARCHAR(20)"), None, false)).await;
+        assert!(created.is_ok());
+        ctx
+    }
+
+    fn create_args<'a>(name: &'a str, schema: Option<&'a str>, comment: Option<&'a str>, if_not_exists: bool) -> CreateTableArgs<'a> {
+        CreateTableArgs { name, schema, comment, if_not_exists, shards: 1 }
+    }
+
+    fn insert_args(data: &str) -> InsertArgs<'_> {
+        InsertArgs { table: "users", data, columns: None, validate_only: false, on_conflict: OnConflict::Error, db: None }
+    }
+
+    fn select_args(where_clause: Option<&str>) -> SelectArgs<'_> {
+        SelectArgs {
+            columns: "*",
+            from: "users",
+            distinct: None,
+            where_clause,
+            ephemeral: false,
+            format: OutputFormat::Json,
+            to_thread: false,
+            max_rows: None,
+            db: None,
+        }
+    }
+
+    fn description(embed: &serenity::builder::CreateEmbed) -> String {
+        serde_json::to_value(embed).unwrap()["description"].as_str().unwrap_or_default().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_create_insert_select_round_trip() {
+        let ctx = shop_with_users().await;
+        let table = ctx.discord.channel_named("table_users").unwrap();
+        assert!(table.topic.unwrap().starts_with("Schema: id INT PRIMARY KEY, name VARCHAR(20)"));
+
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &insert_args("1, 'Ann'")).await.is_ok());
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &insert_args("2, 'Bo'")).await.is_ok());
+        assert_eq!(ctx.discord.message_contents(table.id).len(), 2);
+
+        let output = select::run(&ctx, TEST_GUILD_ID, USER_ID, &select_args(None)).await.unwrap();
+        let result = description(&output.embed);
+        assert!(result.contains(r#"{"id":1,"name":"Ann"}"#) && result.contains(r#"{"id":2,"name":"Bo"}"#), "{}", result);
+
+        let output = select::run(&ctx, TEST_GUILD_ID, USER_ID, &select_args(Some("id=2"))).await.unwrap();
+        let result = description(&output.embed);
+        assert!(result.contains(r#"{"id":2,"name":"Bo"}"#) && !result.contains("Ann"), "{}", result);
+    }
+
+    #[tokio::test]
+    async fn test_insert_rejects_duplicate_primary_key() {
+        let ctx = shop_with_users().await;
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &insert_args("1, 'Ann'")).await.is_ok());
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &insert_args("1, 'Bo'")).await.is_err());
+
+        let table = ctx.discord.channel_named("table_users").unwrap();
+        assert_eq!(ctx.discord.message_contents(table.id).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_insert_is_refused_and_audited() {
+        let ctx = shop_with_users().await;
+        ctx.data().write().await.insert::<ReadOnlyMode>(true);
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &insert_args("1, 'Ann'")).await.is_err());
+
+        let table = ctx.discord.channel_named("table_users").unwrap();
+        assert!(ctx.discord.message_contents(table.id).is_empty());
+        let log = std::fs::read_to_string(&ctx.audit_log).unwrap();
+        let refused = log.lines().last().unwrap();
+        assert!(refused.contains(r#""command":"INSERT""#) && refused.contains(r#""success":false"#), "{}", refused);
+    }
+
+    #[tokio::test]
+    async fn test_create_table_stores_comments() {
+        let ctx = shop_with_users().await;
+        let schema = Some("id INT PRIMARY KEY COMMENT 'primary identifier', total INT");
+        assert!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &create_args("orders", schema, Some(" Customer orders "), false)).await.is_ok());
+
+        let topic = ctx.discord.channel_named("table_orders").unwrap().topic.unwrap();
+        assert_eq!(TopicMeta::parse(Some(&topic)).comment.as_deref(), Some("Customer orders"));
+        let columns = select::parse_schema_from_topic(&topic).unwrap();
+        assert_eq!(columns[0].comment.as_deref(), Some("primary identifier"));
+        assert_eq!(columns[1].comment, None);
+
+        let multiline = create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &create_args("notes", None, Some("two\nlines"), false)).await;
+        assert!(matches!(multiline, Err(crate::error::SqlError::InvalidComment(_))));
+        assert!(ctx.discord.channel_named("table_notes").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_copy_table_with_and_without_data() {
+        let ctx = TestContext::new();
+        create_database(&ctx, TEST_GUILD_ID, "shop").await.unwrap();
+        ctx.use_database(USER_ID, "shop").await;
+        let schema = Some("id INT PRIMARY KEY, name VARCHAR(20)");
+        assert!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &create_args("users", schema, Some("Customers"), false)).await.is_ok());
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &insert_args("1, 'Ann'")).await.is_ok());
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &insert_args("2, 'Bo'")).await.is_ok());
+
+        assert!(copy::run(&ctx, TEST_GUILD_ID, USER_ID, "users", "users_empty", false).await.is_ok());
+        let empty = ctx.discord.channel_named("table_users_empty").unwrap();
+        assert!(ctx.discord.message_contents(empty.id).is_empty());
+        let meta = TopicMeta::parse(empty.topic.as_deref());
+        assert_eq!((meta.rows, meta.comment.as_deref()), (Some(0), Some("Customers")));
+        assert!(meta.schema.unwrap().starts_with("id INT PRIMARY KEY, name VARCHAR(20)"));
+
+        let embed = copy::run(&ctx, TEST_GUILD_ID, USER_ID, "users", "users_backup", true).await.unwrap();
+        assert!(description(&embed).contains("**Rows copied:** 2"));
+        let backup = ctx.discord.channel_named("table_users_backup").unwrap();
+        let source = ctx.discord.channel_named("table_users").unwrap();
+        assert_eq!(ctx.discord.message_contents(backup.id), ctx.discord.message_contents(source.id));
+        assert_eq!(TopicMeta::parse(backup.topic.as_deref()).rows, Some(2));
+
+        let existing = copy::run(&ctx, TEST_GUILD_ID, USER_ID, "users", "users_backup", true).await;
+        assert!(matches!(existing, Err(crate::error::SqlError::TableExists { .. })));
+        assert_eq!(ctx.discord.message_contents(backup.id).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_topic_updates_keep_every_change() {
+        let ctx = shop_with_users().await;
+        let users = ctx.discord.channel_named("table_users").unwrap();
+        assert_eq!(TopicMeta::parse(users.topic.as_deref()).rows, Some(0));
+
+        // Concurrent writes each read and rewrite the counter; none of them is lost
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..8 {
+            let ctx = ctx.clone();
+            tasks.spawn(async move {
+                update_table_topic(&ctx, TEST_GUILD_ID, users.id, |meta| meta.record_change(1, chrono::Utc::now())).await;
+            });
+        }
+        tasks.join_all().await;
+        let users = ctx.discord.channel_named("table_users").unwrap();
+        assert_eq!(TopicMeta::parse(users.topic.as_deref()).rows, Some(8));
+
+        // A write whose topic edit fails drops the counter rather than leave it wrong
+        ctx.discord.fail_channel_edits(1);
+        update_table_topic(&ctx, TEST_GUILD_ID, users.id, |meta| meta.record_change(1, chrono::Utc::now())).await;
+        let users = ctx.discord.channel_named("table_users").unwrap();
+        let meta = TopicMeta::parse(users.topic.as_deref());
+        assert_eq!(meta.rows, None);
+        assert!(meta.schema.is_some_and(|schema| schema.starts_with("id INT PRIMARY KEY")));
+    }
+
+    #[tokio::test]
+    async fn test_copy_refuses_tables_larger_than_one_scan() {
+        let ctx = shop_with_users().await;
+        let users = ctx.discord.channel_named("table_users").unwrap();
+        let limit = crate::services::discord_fs::max_row_messages();
+        for id in 0..=limit {
+            let row = format!("DATA:\n  id: {}\n  name: 'U{}'", id, id);
+            ctx.discord.send_message(users.id, CreateMessage::new().content(row)).await.unwrap();
+        }
+
+        // Copying only the rows one scan reads would lose the oldest ones, so nothing is created
+        let result = crate::api::copy_table(&ctx, TEST_GUILD_ID, "shop", "users", "users_backup", true).await;
+        assert_eq!(result, Err(crate::error::SqlError::TableTooLarge { table: "users".to_string(), limit }));
+        assert!(ctx.discord.channel_named("table_users_backup").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_insert_checks_foreign_keys() {
+        let ctx = shop_with_users().await;
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &insert_args("1, 'Ann'")).await.is_ok());
+        let schema = Some("id INT PRIMARY KEY, user_id INT REFERENCES users(id)");
+        assert!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &create_args("orders", schema, None, false)).await.is_ok());
+        let order = |data| InsertArgs { table: "orders", ..insert_args(data) };
+
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &order("10, 1")).await.is_ok());
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &order("11, NULL")).await.is_ok());
+        let orphan = insert::run(&ctx, TEST_GUILD_ID, USER_ID, &order("12, 2")).await.unwrap_err();
+        assert!(description(&orphan).contains("**user_id** references **users(id)**"), "{}", description(&orphan));
+
+        let orders = ctx.discord.channel_named("table_orders").unwrap();
+        assert!(select::parse_schema_from_topic(orders.topic.as_deref().unwrap()).unwrap()[1].references.is_some());
+        assert_eq!(ctx.discord.message_contents(orders.id).len(), 2);
+        assert!(crate::api::insert_row(&ctx, TEST_GUILD_ID, "shop", "orders", vec![SqlValue::Integer(13), SqlValue::Integer(2)]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dump_data_archives_every_table() {
+        let ctx = shop_with_users().await;
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &insert_args("1, 'Ann'")).await.is_ok());
+        assert!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &create_args("orders", Some("id INT"), None, false)).await.is_ok());
+
+        let output = dump::run_data(&ctx, TEST_GUILD_ID, USER_ID).await.unwrap();
+        assert!(description(&output.embed).contains("• **orders**: 0 rows\n• **users**: 1 row"));
+        let attachment = output.attachment.unwrap();
+        assert_eq!(attachment.filename, "shop_data.zip");
+        let archive = String::from_utf8_lossy(&attachment.data);
+        assert!(archive.contains("users.csvid,name\n1,Ann\n") && archive.contains("orders.csvid\n"), "{}", archive);
+        assert!(archive.contains("schema.sql-- Schema of database shop"));
+    }
+
+    #[tokio::test]
+    async fn test_restore_round_trips_a_dump() {
+        let ctx = shop_with_users().await;
+        for row in ["1, 'Doe, \"JD\"\nJr'", "2, ''", "3, NULL"] {
+            assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &insert_args(row)).await.is_ok());
+        }
+        assert!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &create_args("orders", Some("id INT, total DECIMAL(8,2), paid BOOLEAN"), None, false)).await.is_ok());
+        let orders = InsertArgs { table: "orders", ..insert_args("10, 9.5, true") };
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &orders).await.is_ok());
+        let archive = dump::run_data(&ctx, TEST_GUILD_ID, USER_ID).await.unwrap().attachment.unwrap().data;
+
+        let fresh = TestContext::new();
+        create_database(&fresh, TEST_GUILD_ID, "shop").await.unwrap();
+        fresh.use_database(USER_ID, "shop").await;
+        let embed = restore::run(&fresh, TEST_GUILD_ID, USER_ID, &archive, false).await.unwrap();
+        assert!(description(&embed).contains("• **orders**: 1 row\n• **users**: 3 rows"), "{}", description(&embed));
+        for table in ["users", "orders"] {
+            let original = crate::api::query(&ctx, TEST_GUILD_ID, "shop", table, None).await.unwrap();
+            assert_eq!(crate::api::query(&fresh, TEST_GUILD_ID, "shop", table, None).await.unwrap(), original);
+        }
+        let users = fresh.discord.channel_named("table_users").unwrap();
+        assert_eq!(TopicMeta::parse(users.topic.as_deref()).rows, Some(3));
+
+        // Existing tables are skipped unless overwritten, and overwriting doesn't double the rows
+        let embed = restore::run(&fresh, TEST_GUILD_ID, USER_ID, &archive, false).await.unwrap();
+        assert!(description(&embed).contains("already exists"));
+        assert_eq!(fresh.discord.message_contents(users.id).len(), 3);
+        let embed = restore::run(&fresh, TEST_GUILD_ID, USER_ID, &archive, true).await.unwrap();
+        assert!(description(&embed).contains("• **users**: 3 rows") && !description(&embed).contains("⚠️"));
+        let users = fresh.discord.channel_named("table_users").unwrap();
+        assert_eq!(fresh.discord.message_contents(users.id).len(), 3);
+
+        let invalid = restore::run(&fresh, TEST_GUILD_ID, USER_ID, b"not a zip", false).await;
+        assert!(matches!(invalid, Err(crate::error::SqlError::InvalidArchive(_))));
+    }
+
+    #[tokio::test]
+    async fn test_restore_overwrite_keeps_shards() {
+        let ctx = shop_with_users().await;
+        let args = CreateTableArgs { shards: 3, ..create_args("orders", Some("id INT PRIMARY KEY, item VARCHAR(20)"), None, false) };
+        assert!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &args).await.is_ok());
+        for id in 1..=6 {
+            let data = format!("{}, 'item{}'", id, id);
+            assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &InsertArgs { table: "orders", ..insert_args(&data) }).await.is_ok());
+        }
+        let original = crate::api::query(&ctx, TEST_GUILD_ID, "shop", "orders", None).await.unwrap();
+        let archive = dump::run_data(&ctx, TEST_GUILD_ID, USER_ID).await.unwrap().attachment.unwrap().data;
+
+        let embed = restore::run(&ctx, TEST_GUILD_ID, USER_ID, &archive, true).await.unwrap();
+        assert!(description(&embed).contains("• **orders**: 6 rows") && !description(&embed).contains("⚠️"), "{}", description(&embed));
+        let orders = ctx.discord.channel_named("table_orders").unwrap();
+        assert_eq!(TopicMeta::parse(orders.topic.as_deref()).shards, Some(3));
+        // Rows land in the shards their keys hash to, as they did before
+        let shard = ctx.discord.channel_named("table_orders_2").unwrap();
+        assert_eq!(ctx.discord.message_contents(shard.id).len(), 2);
+        assert_eq!(crate::api::query(&ctx, TEST_GUILD_ID, "shop", "orders", None).await.unwrap(), original);
+    }
+
+    #[tokio::test]
+    async fn test_create_table_if_not_exists() {
+        let ctx = shop_with_users().await;
+        let schema = Some("id INT PRIMARY KEY, name VARCHAR(20)");
+        assert!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &create_args("users", schema, None, false)).await.is_err());
+        assert!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &create_args("users", schema, None, true)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_sharded_table_spreads_rows_and_reads_them_back() {
+        let ctx = shop_with_users().await;
+        let args = CreateTableArgs { shards: 3, ..create_args("orders", Some("id INT PRIMARY KEY, item VARCHAR(20)"), None, false) };
+        let embed = create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &args).await.unwrap();
+        assert!(description(&embed).contains("spread over 3 channels"));
+        let shards: Vec<GuildChannel> = ["table_orders", "table_orders_1", "table_orders_2"].iter().map(|name| ctx.discord.channel_named(name).unwrap()).collect();
+        assert_eq!(TopicMeta::parse(shards[0].topic.as_deref()).shards, Some(3));
+        assert_eq!(TopicMeta::parse(shards[2].topic.as_deref()).shard, Some(2));
+
+        for id in 1..=6 {
+            let data = format!("{}, 'item{}'", id, id);
+            assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &InsertArgs { table: "orders", ..insert_args(&data) }).await.is_ok());
+        }
+        // Each row went to the shard its key hashes to
+        let ids_in = |shard: &GuildChannel| ctx.discord.message_contents(shard.id).iter().filter_map(|row| row.lines().find(|line| line.starts_with("  id: ")).map(str::to_string)).collect::<Vec<_>>().join(",");
+        assert_eq!(ids_in(&shards[0]), "  id: 3,  id: 4,  id: 6");
+        assert_eq!(ids_in(&shards[1]), "  id: 1");
+        assert_eq!(ids_in(&shards[2]), "  id: 2,  id: 5");
+
+        // Reads merge the shards back into insertion order
+        let rows = crate::api::query(&ctx, TEST_GUILD_ID, "shop", "orders", None).await.unwrap();
+        assert_eq!(rows.iter().map(|row| row[0].clone()).collect::<Vec<_>>(), (1..=6).map(SqlValue::Integer).collect::<Vec<_>>());
+        let output = select::run(&ctx, TEST_GUILD_ID, USER_ID, &SelectArgs { from: "orders", ..select_args(Some("id>4")) }).await.unwrap();
+        let result = description(&output.embed);
+        assert!(result.contains(r#"{"id":5,"item":"item5"}"#) && result.contains(r#"{"id":6,"item":"item6"}"#) && !result.contains("item4"), "{}", result);
+
+        // Keys are unique across shards, and an upsert replaces the row in its own shard
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &InsertArgs { table: "orders", ..insert_args("5, 'again'") }).await.is_err());
+        let upsert = InsertArgs { table: "orders", on_conflict: OnConflict::Update, ..insert_args("5, 'again'") };
+        assert!(insert::run(&ctx, TEST_GUILD_ID, USER_ID, &upsert).await.is_ok());
+        let stored = ctx.discord.message_contents(shards[2].id);
+        assert!(stored.len() == 2 && stored[1].contains("again"), "{:?}", stored);
+
+        // Shard channels aren't tables of their own, and their names can't be taken
+        let channels = ctx.backend().list_channels(TEST_GUILD_ID).await.unwrap();
+        let category = crate::services::find_database_category(&channels, "shop").unwrap();
+        let tables: Vec<&str> = crate::services::list_tables(&channels, category.id).iter().map(|table| table.name.as_str()).collect();
+        assert_eq!(tables, vec!["table_orders", "table_users"]);
+        assert!(crate::api::query(&ctx, TEST_GUILD_ID, "shop", "orders_1", None).await.is_err());
+        assert!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &create_args("orders_1", None, None, false)).await.is_err());
+        let taken = CreateTableArgs { shards: 2, ..create_args("users", None, None, false) };
+        assert!(matches!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &taken).await, Err(crate::error::SqlError::TableExists { .. })));
+        let too_many = CreateTableArgs { shards: 9, ..create_args("big", None, None, false) };
+        assert!(matches!(create::table::run(&ctx, TEST_GUILD_ID, USER_ID, &too_many).await, Err(crate::error::SqlError::InvalidShardCount(9))));
+
+        // A copy holds every row in one channel
+        let copied = crate::api::copy_table(&ctx, TEST_GUILD_ID, "shop", "orders", "orders_copy", true).await.unwrap();
+        assert_eq!(copied.rows, 6);
+        let copy = ctx.discord.channel_named("table_orders_copy").unwrap();
+        assert_eq!((TopicMeta::parse(copy.topic.as_deref()).shards, ctx.discord.message_contents(copy.id).len()), (None, 6));
+    }
+}
@@ -1,6 +1,6 @@
 // Small helpers
 
-use serenity::builder::CreateEmbed;
+use serenity::builder::{CreateEmbed, CreateEmbedFooter};
 use serenity::model::Color;
 
 /// Sanitize a name for Discord channel usage.
@@ -30,6 +30,19 @@ pub fn sanitize_channel_name(s: &str) -> (String, bool) {
     (sanitized, was_changed)
 }
 
+/// Sanitize a user-supplied database or table name into the logical name shown to users and
+/// stored in selections: an internal `prefix` typed by the user (`db_Shop`) is dropped.
+/// Returns the canonical name and whether it differs from the input.
+pub fn canonical_name(s: &str, prefix: &str) -> (String, bool) {
+    let (sanitized, _) = sanitize_channel_name(s);
+    let canonical = match sanitized.strip_prefix(prefix) {
+        Some(rest) if !rest.is_empty() => rest.to_string(),
+        _ => sanitized,
+    };
+    let was_changed = s.trim() != canonical;
+    (canonical, was_changed)
+}
+
 /// Create a success embed (green color)
 pub fn create_success_embed(title: &str, description: &str) -> CreateEmbed {
     CreateEmbed::new()
@@ -48,15 +61,94 @@ pub fn create_warning_embed(title: &str, description: &str) -> CreateEmbed {
         .timestamp(serenity::model::Timestamp::now())
 }
 
-/// Create an error embed (red color)
+/// Category prefix for databases
+pub const DB_PREFIX: &str = "db_";
+/// Channel prefix for tables
+pub const TABLE_PREFIX: &str = "table_";
+/// Discord's channel name length limit
+pub const MAX_CHANNEL_NAME_LEN: usize = 100;
+
+/// Why a user-supplied database/table name can't be used
+#[derive(Debug, PartialEq)]
+pub enum IdentifierError {
+    /// Nothing left after sanitization
+    Empty,
+    /// The name starts with an internal prefix (`db_`/`table_`)
+    ReservedPrefix(&'static str),
+    /// The name leaves no room for the prefix within Discord's channel name limit.
+    /// `length` and `max` count the (sanitized) name without its prefix.
+    TooLong { length: usize, max: usize },
+}
+
+impl IdentifierError {
+    /// User-facing explanation; `kind` is "Database" or "Table"
+    pub fn describe(&self, kind: &str) -> String {
+        match self {
+            IdentifierError::Empty => format!(
+                "{} name cannot be empty after sanitization. Please provide a valid name with alphanumeric characters.",
+                kind
+            ),
+            IdentifierError::ReservedPrefix(prefix) => format!(
+                "{} names cannot start with `{}` - that prefix is used internally to tell databases and tables apart.",
+                kind, prefix
+            ),
+            IdentifierError::TooLong { length, max } => format!(
+                "{} name too long, max {} characters (got {}).\n\nDiscord limits channel names to {} characters including the internal prefix.",
+                kind, max, length, MAX_CHANNEL_NAME_LEN
+            ),
+        }
+    }
+}
+
+/// Sanitize a database/table name and check it is usable behind `prefix` (`DB_PREFIX` or `TABLE_PREFIX`).
+/// Returns (sanitized_name, was_changed) like `sanitize_channel_name`.
+pub fn validate_identifier(name: &str, prefix: &str) -> Result<(String, bool), IdentifierError> {
+    let (sanitized, was_changed) = sanitize_channel_name(name);
+    
+    if sanitized.is_empty() {
+        return Err(IdentifierError::Empty);
+    }
+    
+    // Keep internal prefixes unambiguous: `db_table_foo` must not look like a table
+    for reserved in [DB_PREFIX, TABLE_PREFIX] {
+        if sanitized.starts_with(reserved) {
+            return Err(IdentifierError::ReservedPrefix(reserved));
+        }
+    }
+    
+    // Checked before any API call so Discord doesn't reject the channel with an opaque error
+    let max = MAX_CHANNEL_NAME_LEN - prefix.len();
+    if sanitized.len() > max {
+        return Err(IdentifierError::TooLong { length: sanitized.len(), max });
+    }
+    
+    Ok((sanitized, was_changed))
+}
+
+/// Prefix added to every error embed title
+pub const ERROR_TITLE_PREFIX: &str = "✖️ ";
+
+/// Error embed title with the standard prefix applied exactly once
+fn error_title(title: &str) -> String {
+    let bare = title.trim_start_matches(['✖', '\u{fe0f}', '❌']).trim_start();
+    format!("{}{}", ERROR_TITLE_PREFIX, bare)
+}
+
+/// Create an error embed (red color); the title is prefixed with `ERROR_TITLE_PREFIX`
 pub fn create_error_embed(title: &str, description: &str) -> CreateEmbed {
     CreateEmbed::new()
-        .title(title)
+        .title(error_title(title))
         .description(description)
         .color(Color::from_rgb(231, 76, 60)) // Red
         .timestamp(serenity::model::Timestamp::now())
 }
 
+/// Create an error embed with a short machine-readable error code in the footer (e.g. `NO_DATABASE`)
+pub fn create_error_embed_with_code(title: &str, description: &str, code: &str) -> CreateEmbed {
+    create_error_embed(title, description)
+        .footer(CreateEmbedFooter::new(format!("Error code: {}", code)))
+}
+
 /// Create an info embed (blue color)
 pub fn create_info_embed(title: &str, description: &str) -> CreateEmbed {
     CreateEmbed::new()
@@ -99,4 +191,67 @@ mod tests {
         // Test just numbers
         assert_eq!(sanitize_channel_name("123"), ("123".to_string(), false));
     }
+
+    #[test]
+    fn test_canonical_name() {
+        assert_eq!(canonical_name("shop", DB_PREFIX), ("shop".to_string(), false));
+        assert_eq!(canonical_name("Shop", DB_PREFIX), ("shop".to_string(), true));
+        // A typed internal prefix is dropped once, whatever its case
+        assert_eq!(canonical_name("db_shop", DB_PREFIX), ("shop".to_string(), true));
+        assert_eq!(canonical_name("DB_Shop", DB_PREFIX), ("shop".to_string(), true));
+        assert_eq!(canonical_name("db_db_shop", DB_PREFIX), ("db_shop".to_string(), true));
+        assert_eq!(canonical_name("table_users", TABLE_PREFIX), ("users".to_string(), true));
+        // Only the kind's own prefix is stripped, and a bare prefix stays as it is
+        assert_eq!(canonical_name("table_users", DB_PREFIX), ("table_users".to_string(), false));
+        assert_eq!(canonical_name("db_", DB_PREFIX), ("db".to_string(), true));
+    }
+
+    #[test]
+    fn test_validate_identifier() {
+        assert_eq!(validate_identifier("Sales Data", DB_PREFIX), Ok(("sales_data".to_string(), true)));
+        assert_eq!(validate_identifier("___", DB_PREFIX), Err(IdentifierError::Empty));
+        
+        // Internal prefixes are reserved for both databases and tables
+        assert_eq!(validate_identifier("table_foo", DB_PREFIX), Err(IdentifierError::ReservedPrefix(TABLE_PREFIX)));
+        assert_eq!(validate_identifier("DB_orders", TABLE_PREFIX), Err(IdentifierError::ReservedPrefix(DB_PREFIX)));
+        // Only a leading prefix is reserved
+        assert!(validate_identifier("my_table_foo", DB_PREFIX).is_ok());
+    }
+    
+    #[test]
+    fn test_validate_identifier_length() {
+        // 94 + "table_" = 100 characters fits exactly
+        assert!(validate_identifier(&"a".repeat(94), TABLE_PREFIX).is_ok());
+        assert_eq!(
+            validate_identifier(&"a".repeat(95), TABLE_PREFIX),
+            Err(IdentifierError::TooLong { length: 95, max: 94 })
+        );
+        assert!(validate_identifier(&"a".repeat(97), DB_PREFIX).is_ok());
+    }
+    
+    #[test]
+    fn test_overlong_name_rejected_with_friendly_message() {
+        let error = validate_identifier(&"x".repeat(200), DB_PREFIX).unwrap_err();
+        assert_eq!(error, IdentifierError::TooLong { length: 200, max: 97 });
+        assert!(error.describe("Database").starts_with("Database name too long, max 97 characters (got 200)."));
+    }
+
+    #[test]
+    fn test_error_title_prefix_applied_once() {
+        assert_eq!(error_title("Table Not Found"), "✖️ Table Not Found");
+        // Titles that already carry an error emoji aren't double-prefixed
+        assert_eq!(error_title("✖️ Table Not Found"), "✖️ Table Not Found");
+        assert_eq!(error_title("❌ Table Not Found"), "✖️ Table Not Found");
+
+        let embed = serde_json::to_value(create_error_embed("Table Not Found", "details")).unwrap();
+        let title = embed["title"].as_str().unwrap();
+        assert_eq!(title.matches(ERROR_TITLE_PREFIX).count(), 1);
+    }
+
+    #[test]
+    fn test_error_embed_with_code() {
+        let embed = serde_json::to_value(create_error_embed_with_code("No Database Selected", "details", "NO_DATABASE")).unwrap();
+        assert_eq!(embed["title"], "✖️ No Database Selected");
+        assert_eq!(embed["footer"]["text"], "Error code: NO_DATABASE");
+    }
 }